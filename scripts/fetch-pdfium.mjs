@@ -0,0 +1,146 @@
+import { existsSync, mkdirSync, mkdtempSync, copyFileSync, readFileSync, rmSync, writeFileSync } from 'fs';
+import { execFileSync } from 'child_process';
+import { createHash } from 'crypto';
+import { join, dirname } from 'path';
+import { tmpdir } from 'os';
+import { fileURLToPath } from 'url';
+
+// Downloads the PDFium build for the target platform into src-tauri/pdfium,
+// which the app bundles as a resource so PDF scanning works out of the box.
+// Builds come from https://github.com/bblanchon/pdfium-binaries.
+//
+// The release is pinned to the PDFium version pdfium-render binds to with its
+// `pdfium_latest` feature (pdfium_7543 in 0.8.37). Archives are checked
+// against scripts/pdfium-checksums.json before extracting. After bumping
+// PDFIUM_VERSION, run `node scripts/fetch-pdfium.mjs --update-checksums` and
+// review the new hashes before committing them.
+
+const __dirname = dirname(fileURLToPath(import.meta.url));
+const pdfiumDir = join(__dirname, '..', 'src-tauri', 'pdfium');
+const checksumsPath = join(__dirname, 'pdfium-checksums.json');
+// Records the installed build, so a version bump triggers a fresh download
+const versionPath = join(pdfiumDir, 'VERSION');
+
+const PDFIUM_VERSION = '7543';
+const RELEASE_URL = `https://github.com/bblanchon/pdfium-binaries/releases/download/chromium%2F${PDFIUM_VERSION}`;
+
+// Rust target triple -> pdfium-binaries archive and the library inside it
+const builds = {
+  'aarch64-apple-darwin': { archive: 'pdfium-mac-arm64.tgz', library: 'lib/libpdfium.dylib' },
+  'x86_64-apple-darwin': { archive: 'pdfium-mac-x64.tgz', library: 'lib/libpdfium.dylib' },
+  'x86_64-unknown-linux-gnu': { archive: 'pdfium-linux-x64.tgz', library: 'lib/libpdfium.so' },
+  'aarch64-unknown-linux-gnu': { archive: 'pdfium-linux-arm64.tgz', library: 'lib/libpdfium.so' },
+  'x86_64-pc-windows-msvc': { archive: 'pdfium-win-x64.tgz', library: 'bin/pdfium.dll' },
+  'aarch64-pc-windows-msvc': { archive: 'pdfium-win-arm64.tgz', library: 'bin/pdfium.dll' },
+};
+
+// Tauri passes the target when running beforeBuildCommand; otherwise use the host
+function targetTriple() {
+  const args = process.argv.slice(2);
+  const flag = args.indexOf('--target');
+  if (flag !== -1 && args[flag + 1]) return args[flag + 1];
+  if (process.env.TAURI_ENV_TARGET_TRIPLE) return process.env.TAURI_ENV_TARGET_TRIPLE;
+
+  const arch = process.arch === 'arm64' ? 'aarch64' : 'x86_64';
+  switch (process.platform) {
+    case 'darwin':
+      return `${arch}-apple-darwin`;
+    case 'win32':
+      return `${arch}-pc-windows-msvc`;
+    default:
+      return `${arch}-unknown-linux-gnu`;
+  }
+}
+
+function readChecksums() {
+  return JSON.parse(readFileSync(checksumsPath, 'utf8'));
+}
+
+function sha256(bytes) {
+  return createHash('sha256').update(bytes).digest('hex');
+}
+
+async function download(archive) {
+  const url = `${RELEASE_URL}/${archive}`;
+  console.log(`Downloading ${url}`);
+  const response = await fetch(url);
+  if (!response.ok) {
+    throw new Error(`Failed to download PDFium: ${response.status} ${response.statusText}`);
+  }
+  return Buffer.from(await response.arrayBuffer());
+}
+
+// Hash every archive of the pinned release into the checksums file
+async function updateChecksums() {
+  const sha256s = {};
+  for (const { archive } of Object.values(builds)) {
+    sha256s[archive] = sha256(await download(archive));
+  }
+  writeFileSync(checksumsPath, `${JSON.stringify({ version: PDFIUM_VERSION, sha256: sha256s }, null, 2)}\n`);
+  console.log(`Updated ${checksumsPath}; review the hashes before committing`);
+}
+
+async function main() {
+  if (process.argv.includes('--update-checksums')) {
+    await updateChecksums();
+    return;
+  }
+
+  const target = targetTriple();
+  const build = builds[target];
+  if (!build) {
+    // The app still finds a system-wide PDFium at runtime
+    console.warn(`No PDFium build for ${target}; PDF scanning will need PDFium installed`);
+    return;
+  }
+
+  const checksums = readChecksums();
+  const expected = checksums.sha256?.[build.archive];
+  if (checksums.version !== PDFIUM_VERSION || !expected) {
+    throw new Error(
+      `No checksum for ${build.archive} at PDFium ${PDFIUM_VERSION}; ` +
+        'run `node scripts/fetch-pdfium.mjs --update-checksums` and review the result'
+    );
+  }
+
+  const libraryName = build.library.split('/').pop();
+  const destination = join(pdfiumDir, libraryName);
+  const installed = `${PDFIUM_VERSION} ${build.archive} ${expected}`;
+  if (existsSync(destination) && existsSync(versionPath) && readFileSync(versionPath, 'utf8').trim() === installed) {
+    console.log(`PDFium ${PDFIUM_VERSION} already present: ${destination}`);
+    return;
+  }
+
+  // A library for another platform or version would only bloat the bundle
+  for (const other of ['libpdfium.dylib', 'libpdfium.so', 'pdfium.dll', 'LICENSE', 'VERSION']) {
+    rmSync(join(pdfiumDir, other), { force: true });
+  }
+
+  const bytes = await download(build.archive);
+  const actual = sha256(bytes);
+  if (actual !== expected) {
+    throw new Error(`Checksum mismatch for ${build.archive}: expected ${expected}, got ${actual}`);
+  }
+
+  const workDir = mkdtempSync(join(tmpdir(), 'pdfium-'));
+  try {
+    writeFileSync(join(workDir, build.archive), bytes);
+    // Relative paths: GNU tar on Windows reads `C:` as a remote host
+    execFileSync('tar', ['-xzf', build.archive], { cwd: workDir, stdio: 'inherit' });
+
+    mkdirSync(pdfiumDir, { recursive: true });
+    copyFileSync(join(workDir, build.library), destination);
+    if (existsSync(join(workDir, 'LICENSE'))) {
+      copyFileSync(join(workDir, 'LICENSE'), join(pdfiumDir, 'LICENSE'));
+    }
+    writeFileSync(versionPath, `${installed}\n`);
+    console.log(`Installed PDFium ${PDFIUM_VERSION} for ${target}: ${destination}`);
+  } finally {
+    rmSync(workDir, { recursive: true, force: true });
+  }
+}
+
+main().catch((e) => {
+  console.error(e.message);
+  process.exit(1);
+});
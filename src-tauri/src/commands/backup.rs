@@ -0,0 +1,172 @@
+use crate::db::{
+    self, BackupCheck, BackupInfo, BackupKind, DbState, IntegrityReport, RecoveryReport,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+use tauri_plugin_dialog::DialogExt;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupList {
+    pub backups: Vec<BackupInfo>,
+    pub auto_backup_error: Option<String>, // the backup due on opening couldn't be made
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    pub restored: BackupCheck,
+    pub safety_backup: BackupInfo, // the database as it was before restoring
+    pub migrated_from: i64,
+    pub migrated_to: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryResult {
+    pub report: RecoveryReport,
+    pub safety_backup: Option<String>, // copy of the damaged file, if one could be made
+}
+
+/// Back up the database now
+#[tauri::command]
+pub async fn backup_create(state: State<'_, DbState>) -> Result<BackupInfo, String> {
+    let workspace = state.workspace();
+    let dir = workspace.paths.backups.clone();
+
+    let backup = workspace
+        .clone()
+        .read(move |conn| {
+            db::create_backup(conn, &dir, BackupKind::Manual)
+                .map_err(|e| format!("Failed to create backup: {}", e))
+        })
+        .await?;
+
+    // Backups work again, so a missed automatic one is covered
+    workspace.set_auto_backup_error(None);
+    Ok(backup)
+}
+
+/// List automatic, manual and pre-restore backups, newest first, with why
+/// the automatic backup failed if it did
+#[tauri::command]
+pub async fn backup_list(state: State<'_, DbState>) -> Result<BackupList, String> {
+    let workspace = state.workspace();
+    Ok(BackupList {
+        backups: db::list_backups(&workspace.paths.backups),
+        auto_backup_error: workspace.auto_backup_error(),
+    })
+}
+
+/// Check a backup file without restoring it
+#[tauri::command]
+pub async fn backup_validate(path: String) -> Result<BackupCheck, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        db::validate_backup(Path::new(&path))
+            .map_err(|e| format!("Not a readable database file: {}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Replace the library with a backup, keeping a copy of the current state
+#[tauri::command]
+pub async fn backup_restore(
+    state: State<'_, DbState>,
+    path: String,
+) -> Result<RestoreResult, String> {
+    let workspace = state.workspace();
+    let dir = workspace.paths.backups.clone();
+
+    workspace
+        .write(move |conn| {
+            let source = Path::new(&path);
+            let check = db::validate_backup(source)
+                .map_err(|e| format!("Not a readable database file: {}", e))?;
+            if let Some(problem) = &check.problem {
+                return Err(problem.clone());
+            }
+
+            let safety_backup = db::create_backup(conn, &dir, BackupKind::PreRestore)
+                .map_err(|e| format!("Failed to back up current data: {}", e))?;
+
+            db::restore_from(conn, source)
+                .map_err(|e| format!("Failed to restore backup: {}", e))?;
+
+            // Older backups need the current schema
+            let migration =
+                db::migrate(conn).map_err(|e| format!("Failed to upgrade backup: {}", e))?;
+
+            Ok(RestoreResult {
+                restored: check,
+                safety_backup,
+                migrated_from: migration.from,
+                migrated_to: migration.to,
+            })
+        })
+        .await
+}
+
+/// Run `PRAGMA integrity_check` and report any problems
+#[tauri::command]
+pub async fn db_integrity_check(state: State<'_, DbState>) -> Result<IntegrityReport, String> {
+    state
+        .read(move |conn| {
+            db::integrity_check(conn).map_err(|e| format!("Failed to check database: {}", e))
+        })
+        .await
+}
+
+/// Repair a damaged database, saving a copy of the damaged file first
+#[tauri::command]
+pub async fn db_recover(state: State<'_, DbState>) -> Result<RecoveryResult, String> {
+    let workspace = state.workspace();
+    let dir = workspace.paths.backups.clone();
+
+    workspace
+        .write(move |conn| {
+            // The backup API may refuse badly damaged pages; fall back to a
+            // raw copy once the WAL is folded into the main file
+            let safety_backup = match db::create_backup(conn, &dir, BackupKind::PreRestore) {
+                Ok(backup) => Some(backup.path),
+                Err(_) => {
+                    conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE)").ok();
+                    conn.path()
+                        .filter(|path| !path.is_empty())
+                        .and_then(|path| {
+                            let copy = format!("{}.damaged", path);
+                            std::fs::copy(path, &copy).ok().map(|_| copy)
+                        })
+                }
+            };
+
+            let report = db::recover_database(conn)
+                .map_err(|e| format!("Failed to recover database: {}", e))?;
+
+            Ok(RecoveryResult {
+                report,
+                safety_backup,
+            })
+        })
+        .await
+}
+
+/// Show a file picker for choosing a backup to restore
+#[tauri::command]
+pub async fn pick_backup_file(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+) -> Result<Option<String>, String> {
+    let file_path = app
+        .dialog()
+        .file()
+        .set_directory(&state.workspace().paths.backups)
+        .add_filter("QR Foundry Backups", &["db", "bak"])
+        .blocking_pick_file();
+
+    match file_path {
+        Some(path) => Ok(Some(path.as_path().unwrap().to_string_lossy().to_string())),
+        None => Ok(None),
+    }
+}
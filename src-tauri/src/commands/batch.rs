@@ -1,3 +1,4 @@
+use crate::qr_type::{detect_qr_type, normalize_qr_type};
 use base64::{engine::general_purpose::STANDARD, Engine};
 use image::ImageReader;
 use rqrr::PreparedImage;
@@ -8,6 +9,8 @@ use tauri_plugin_dialog::DialogExt;
 use zip::write::SimpleFileOptions;
 use zip::ZipWriter;
 
+use super::url_risk::{analyze_url, UrlRiskReport};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BatchItem {
@@ -15,6 +18,7 @@ pub struct BatchItem {
     pub content: String,
     pub qr_type: String,
     pub label: Option<String>,
+    pub url_risk: Option<UrlRiskReport>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,21 +113,45 @@ fn parse_csv_content(content: &str) -> Result<BatchParseResult, String> {
                     continue;
                 }
 
-                let qr_type = type_idx
+                let type_value = type_idx
                     .and_then(|i| record.get(i))
-                    .map(|s| s.trim().to_lowercase())
-                    .unwrap_or_else(|| detect_qr_type(&content));
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty());
+
+                // Explicit types must be ones the app knows; blank cells are detected
+                let qr_type = match type_value {
+                    Some(value) => match normalize_qr_type(value) {
+                        Some(qr_type) => qr_type.to_string(),
+                        None => {
+                            return Ok(BatchParseResult {
+                                success: false,
+                                items: vec![],
+                                error: Some(format!(
+                                    "Error at row {}: unknown type '{}'",
+                                    row_idx + 2,
+                                    value
+                                )),
+                                total_rows: 0,
+                            });
+                        }
+                    },
+                    None => detect_qr_type(&content),
+                };
 
                 let label = label_idx
                     .and_then(|i| record.get(i))
                     .map(|s| s.trim().to_string())
                     .filter(|s| !s.is_empty());
 
+                // Labels are checked against the link so "paypal.com" can't point elsewhere
+                let url_risk = (qr_type == "url").then(|| analyze_url(&content, label.as_deref()));
+
                 items.push(BatchItem {
                     row: row_idx + 1, // 1-indexed for display
                     content,
                     qr_type,
                     label,
+                    url_risk,
                 });
             }
             Err(e) => {
@@ -183,7 +211,7 @@ fn validate_single_item(item: &BatchGenerateItem) -> BatchValidationResult {
     // Load image
     let img = match ImageReader::new(Cursor::new(&image_bytes))
         .with_guessed_format()
-        .and_then(|r| r.decode().map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)))
+        .and_then(|r| r.decode().map_err(std::io::Error::other))
     {
         Ok(img) => img,
         Err(e) => {
@@ -432,30 +460,6 @@ pub async fn batch_save_files(
     })
 }
 
-fn detect_qr_type(content: &str) -> String {
-    let lower = content.to_lowercase();
-
-    if lower.starts_with("wifi:") {
-        "wifi".to_string()
-    } else if lower.starts_with("begin:vcard") {
-        "vcard".to_string()
-    } else if lower.starts_with("mailto:") {
-        "email".to_string()
-    } else if lower.starts_with("sms:") || lower.starts_with("smsto:") {
-        "sms".to_string()
-    } else if lower.starts_with("tel:") {
-        "phone".to_string()
-    } else if lower.starts_with("geo:") {
-        "geo".to_string()
-    } else if lower.starts_with("begin:vevent") {
-        "calendar".to_string()
-    } else if lower.starts_with("http://") || lower.starts_with("https://") {
-        "url".to_string()
-    } else {
-        "text".to_string()
-    }
-}
-
 fn sanitize_filename(s: &str) -> String {
     s.chars()
         .map(|c| match c {
@@ -618,6 +622,51 @@ mod tests {
         assert_eq!(result.items[0].label, None);
     }
 
+    #[test]
+    fn test_parse_csv_content_normalizes_type() {
+        let csv = "content,type\nhttps://example.com,URL\ntel:+15551234567,tel\nhello,";
+        let result = parse_csv_content(csv).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.items[0].qr_type, "url");
+        assert_eq!(result.items[1].qr_type, "phone");
+        assert_eq!(result.items[2].qr_type, "text"); // blank cell is auto-detected
+    }
+
+    #[test]
+    fn test_parse_csv_content_unknown_type() {
+        let csv = "content,type\nhttps://example.com,url\nhello,barcode";
+        let result = parse_csv_content(csv).unwrap();
+
+        assert!(!result.success);
+        assert!(result.items.is_empty());
+        assert_eq!(
+            result.error,
+            Some("Error at row 3: unknown type 'barcode'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_content_url_risk() {
+        let csv = "content,label\nhttps://login.example.net,paypal.com\nhttps://example.com,Example\nhello,";
+        let result = parse_csv_content(csv).unwrap();
+
+        let risk = result.items[0].url_risk.as_ref().unwrap();
+        assert_eq!(risk.warnings[0].code, "labelMismatch");
+        assert_eq!(result.items[1].url_risk.as_ref().unwrap().risk, "safe");
+        assert!(result.items[2].url_risk.is_none());
+    }
+
+    #[test]
+    fn test_parse_csv_content_detects_new_schemes() {
+        let csv = "content\notpauth://totp/Acme?secret=ABC\nbitcoin:bc1qxyz\nMECARD:N:Doe,John;;";
+        let result = parse_csv_content(csv).unwrap();
+
+        assert_eq!(result.items[0].qr_type, "otp");
+        assert_eq!(result.items[1].qr_type, "crypto");
+        assert_eq!(result.items[2].qr_type, "vcard");
+    }
+
     #[test]
     fn test_parse_csv_content_row_numbers() {
         let csv = "content\nfirst\nsecond\nthird";
@@ -0,0 +1,18 @@
+use crate::db::{self, DbState};
+use tauri::State;
+
+/// Fetch a thumbnail or template preview by its ref, as a data URL
+///
+/// List results only carry `thumbnailRef` / `previewRef`, so the images are
+/// loaded on demand.
+#[tauri::command]
+pub async fn blob_get(state: State<'_, DbState>, hash: String) -> Result<Option<String>, String> {
+    state
+        .read(move |conn| {
+            let blob =
+                db::get_blob(conn, &hash).map_err(|e| format!("Failed to load image: {}", e))?;
+
+            Ok(blob.map(|blob| blob.to_data_url()))
+        })
+        .await
+}
@@ -0,0 +1,472 @@
+use crate::qr_type::detect_qr_type;
+use image::ImageReader;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use tauri_plugin_dialog::DialogExt;
+
+use super::preprocess::{decode_all_with_pipeline, decode_oriented};
+
+/// Image extensions picked up by a folder scan
+const SCAN_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "tif", "tiff", "gif", "bmp"];
+
+/// One report line: a decoded code, or a file that yielded none
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkScanRow {
+    pub file: String,
+    pub index: Option<usize>, // 1-based position of the code within the file
+    pub content: Option<String>,
+    pub qr_type: Option<String>,
+    pub version: Option<usize>,
+    pub ecc: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkScanResult {
+    pub success: bool,
+    pub report_path: Option<String>,
+    pub files_scanned: usize,
+    pub codes_found: usize,
+    pub files_failed: usize,
+    pub files_skipped: usize, // entries that couldn't be read, like a locked subfolder
+    pub error: Option<String>,
+}
+
+/// Report rows for a folder, with the unreadable entries last
+#[derive(Debug, Clone)]
+pub struct FolderScan {
+    pub rows: Vec<BulkScanRow>,
+    pub files_skipped: usize,
+}
+
+impl FolderScan {
+    /// Rows for the images that were scanned, leaving out skipped entries
+    fn scanned(&self) -> &[BulkScanRow] {
+        &self.rows[..self.rows.len() - self.files_skipped]
+    }
+}
+
+/// Open a folder picker for bulk scanning
+#[tauri::command]
+pub async fn pick_scan_folder(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let dir_path = app.dialog().file().blocking_pick_folder();
+
+    match dir_path {
+        Some(path) => Ok(Some(path.as_path().unwrap().to_string_lossy().to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Recursively scan a folder of images and save a CSV or JSON report
+#[tauri::command]
+pub async fn bulk_scan_folder(
+    app: tauri::AppHandle,
+    folder_path: String,
+    format: String, // "csv" or "json"
+) -> Result<BulkScanResult, String> {
+    let extension = if format == "json" { "json" } else { "csv" };
+
+    let file_path = app
+        .dialog()
+        .file()
+        .set_file_name(format!("scan-report.{}", extension))
+        .add_filter("Scan Report", &[extension])
+        .blocking_save_file();
+
+    let report_path = match file_path {
+        Some(path) => path.as_path().unwrap().to_path_buf(),
+        None => {
+            return Ok(BulkScanResult {
+                success: false,
+                report_path: None,
+                files_scanned: 0,
+                codes_found: 0,
+                files_failed: 0,
+                files_skipped: 0,
+                error: Some("Save cancelled by user".to_string()),
+            });
+        }
+    };
+
+    let folder = PathBuf::from(folder_path);
+    let scan = tauri::async_runtime::spawn_blocking(move || scan_folder(&folder))
+        .await
+        .map_err(|e| format!("Failed to scan folder: {}", e))??;
+
+    write_report(&scan.rows, &report_path, extension)?;
+
+    let (files_scanned, codes_found, files_failed) = scan_counts(scan.scanned());
+
+    Ok(BulkScanResult {
+        success: true,
+        report_path: Some(report_path.to_string_lossy().to_string()),
+        files_scanned,
+        codes_found,
+        files_failed,
+        files_skipped: scan.files_skipped,
+        error: None,
+    })
+}
+
+/// Files scanned, codes found and files without a code, from scanned rows
+fn scan_counts(rows: &[BulkScanRow]) -> (usize, usize, usize) {
+    let files_scanned = rows
+        .iter()
+        .filter(|row| row.index.is_none_or(|i| i == 1))
+        .count();
+    let codes_found = rows.iter().filter(|row| row.content.is_some()).count();
+    let files_failed = rows.iter().filter(|row| row.index.is_none()).count();
+    (files_scanned, codes_found, files_failed)
+}
+
+/// Decode every image under `folder`, ordered by path
+///
+/// Entries that can't be read, like a subfolder without permission, get an
+/// error row after the images instead of failing the whole scan.
+pub fn scan_folder(folder: &Path) -> Result<FolderScan, String> {
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    collect_images(folder, &mut files, &mut skipped)?;
+    files.sort();
+    skipped.sort();
+
+    let relative_name = |path: &Path| {
+        path.strip_prefix(folder)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+
+    let results: Vec<Mutex<Vec<BulkScanRow>>> = files.iter().map(|_| Mutex::default()).collect();
+    let next = AtomicUsize::new(0);
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(files.len().max(1));
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = files.get(i) else { break };
+
+                *results[i].lock().unwrap() = scan_file(path, &relative_name(path));
+            });
+        }
+    });
+
+    let files_skipped = skipped.len();
+    let unreadable = skipped.into_iter().map(|(path, error)| BulkScanRow {
+        file: relative_name(&path),
+        index: None,
+        content: None,
+        qr_type: None,
+        version: None,
+        ecc: None,
+        error: Some(error),
+    });
+
+    let rows = results
+        .into_iter()
+        .flat_map(|rows| rows.into_inner().unwrap())
+        .chain(unreadable)
+        .collect();
+
+    Ok(FolderScan {
+        rows,
+        files_skipped,
+    })
+}
+
+/// Add the images under `dir` to `files`
+///
+/// Only an unreadable `dir` itself is an error; entries below it that can't
+/// be read go to `skipped` with the reason, and the walk carries on.
+fn collect_images(
+    dir: &Path,
+    files: &mut Vec<PathBuf>,
+    skipped: &mut Vec<(PathBuf, String)>,
+) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read folder {}: {}", dir.display(), e))?;
+
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                skipped.push((
+                    dir.to_path_buf(),
+                    format!("Failed to read folder entry: {}", e),
+                ));
+                continue;
+            }
+        };
+        let path = entry.path();
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(e) => {
+                skipped.push((path, format!("Failed to read file type: {}", e)));
+                continue;
+            }
+        };
+
+        // Symlinked folders are skipped so cycles can't recurse forever
+        if file_type.is_dir() {
+            if let Err(e) = collect_images(&path, files, skipped) {
+                skipped.push((path, e));
+            }
+        } else if is_scannable(&path) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn is_scannable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SCAN_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+fn scan_file(path: &Path, name: &str) -> Vec<BulkScanRow> {
+    let failure = |error: String| {
+        vec![BulkScanRow {
+            file: name.to_string(),
+            index: None,
+            content: None,
+            qr_type: None,
+            version: None,
+            ecc: None,
+            error: Some(error),
+        }]
+    };
+
+    let reader = match ImageReader::open(path) {
+        Ok(reader) => reader,
+        Err(e) => return failure(format!("Failed to open image: {}", e)),
+    };
+    let img = match decode_oriented(reader) {
+        Ok(img) => img,
+        Err(e) => return failure(e),
+    };
+
+    match decode_all_with_pipeline(&img) {
+        Ok(codes) => codes
+            .into_iter()
+            .enumerate()
+            .map(|(i, code)| BulkScanRow {
+                file: name.to_string(),
+                index: Some(i + 1),
+                qr_type: Some(detect_qr_type(&code.content)),
+                content: Some(code.content),
+                version: Some(code.version),
+                ecc: Some(code.ecc_level.to_string()),
+                error: None,
+            })
+            .collect(),
+        Err(e) => failure(e),
+    }
+}
+
+/// Write rows as CSV or pretty-printed JSON
+pub fn write_report(rows: &[BulkScanRow], path: &Path, format: &str) -> Result<(), String> {
+    if format == "json" {
+        let json = serde_json::to_string_pretty(rows)
+            .map_err(|e| format!("Failed to serialize report: {}", e))?;
+        return fs::write(path, json).map_err(|e| format!("Failed to write report: {}", e));
+    }
+
+    let file = File::create(path).map_err(|e| format!("Failed to create report: {}", e))?;
+    let mut writer = csv::Writer::from_writer(file);
+
+    writer
+        .write_record([
+            "file", "index", "content", "type", "version", "ecc", "error",
+        ])
+        .map_err(|e| format!("Failed to write report: {}", e))?;
+
+    for row in rows {
+        writer
+            .write_record([
+                row.file.clone(),
+                row.index.map(|i| i.to_string()).unwrap_or_default(),
+                row.content.clone().unwrap_or_default(),
+                row.qr_type.clone().unwrap_or_default(),
+                row.version.map(|v| v.to_string()).unwrap_or_default(),
+                row.ecc.clone().unwrap_or_default(),
+                row.error.clone().unwrap_or_default(),
+            ])
+            .map_err(|e| format!("Failed to write report: {}", e))?;
+    }
+
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write report: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+    use qrcode::{Color, QrCode};
+
+    fn write_qr(path: &Path, content: &str) {
+        let code = QrCode::new(content.as_bytes()).unwrap();
+        let modules = code.width() as u32;
+        let colors = code.to_colors();
+        let img = GrayImage::from_fn((modules + 8) * 4, (modules + 8) * 4, |x, y| {
+            let (mx, my) = (x / 4, y / 4);
+            let dark = (4..modules + 4).contains(&mx)
+                && (4..modules + 4).contains(&my)
+                && colors[((my - 4) * modules + (mx - 4)) as usize] == Color::Dark;
+            Luma([if dark { 0 } else { 255 }])
+        });
+        img.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_scan_folder_recursive() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("nested")).unwrap();
+        write_qr(&dir.path().join("a.png"), "https://example.com");
+        write_qr(&dir.path().join("nested/b.bmp"), "hello");
+        GrayImage::from_pixel(32, 32, Luma([255]))
+            .save(dir.path().join("blank.png"))
+            .unwrap();
+        fs::write(dir.path().join("notes.txt"), "not an image").unwrap();
+
+        let rows = scan_folder(dir.path()).unwrap().rows;
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].file, "a.png");
+        assert_eq!(rows[0].index, Some(1));
+        assert_eq!(rows[0].content, Some("https://example.com".to_string()));
+        assert_eq!(rows[0].qr_type, Some("url".to_string()));
+        assert_eq!(rows[0].version, Some(2));
+        assert_eq!(rows[0].ecc, Some("M".to_string()));
+
+        assert_eq!(rows[1].file, "blank.png");
+        assert_eq!(rows[1].index, None);
+        assert!(rows[1].error.is_some());
+
+        assert_eq!(rows[2].file, "nested/b.bmp");
+        assert_eq!(rows[2].content, Some("hello".to_string()));
+        assert_eq!(scan_counts(&rows), (3, 2, 1));
+    }
+
+    #[test]
+    fn test_scan_folder_unreadable_image() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("broken.jpg"), b"not really a jpeg").unwrap();
+
+        let rows = scan_folder(dir.path()).unwrap().rows;
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].error.is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_folder_skips_unreadable_subfolder() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let locked = dir.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        write_qr(&locked.join("hidden.png"), "hidden");
+        write_qr(&dir.path().join("z.png"), "visible");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Root can read the folder anyway; nothing to test then
+        if fs::read_dir(&locked).is_ok() {
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let scan = scan_folder(dir.path());
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        let scan = scan.unwrap();
+        let rows = &scan.rows;
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].file, "z.png");
+        assert_eq!(rows[0].content, Some("visible".to_string()));
+        assert_eq!(rows[1].file, "locked");
+        assert_eq!(rows[1].index, None);
+        assert!(rows[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("Failed to read folder"));
+
+        // The locked folder is neither a scanned nor a failed file
+        assert_eq!(scan.files_skipped, 1);
+        assert_eq!(scan_counts(scan.scanned()), (1, 1, 0));
+    }
+
+    #[test]
+    fn test_scan_folder_missing() {
+        assert!(scan_folder(Path::new("/definitely/not/here")).is_err());
+    }
+
+    #[test]
+    fn test_is_scannable() {
+        assert!(is_scannable(Path::new("photo.JPG")));
+        assert!(is_scannable(Path::new("scan.tiff")));
+        assert!(is_scannable(Path::new("anim.gif")));
+        assert!(!is_scannable(Path::new("doc.pdf")));
+        assert!(!is_scannable(Path::new("README")));
+    }
+
+    #[test]
+    fn test_write_report_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.csv");
+        let rows = vec![BulkScanRow {
+            file: "a.png".to_string(),
+            index: Some(1),
+            content: Some("hello, world".to_string()),
+            qr_type: Some("text".to_string()),
+            version: Some(1),
+            ecc: Some("L".to_string()),
+            error: None,
+        }];
+
+        write_report(&rows, &path, "csv").unwrap();
+        let csv = fs::read_to_string(&path).unwrap();
+
+        assert_eq!(
+            csv,
+            "file,index,content,type,version,ecc,error\na.png,1,\"hello, world\",text,1,L,\n"
+        );
+    }
+
+    #[test]
+    fn test_write_report_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.json");
+        let rows = vec![BulkScanRow {
+            file: "a.png".to_string(),
+            index: None,
+            content: None,
+            qr_type: None,
+            version: None,
+            ecc: None,
+            error: Some("No QR code found in image".to_string()),
+        }];
+
+        write_report(&rows, &path, "json").unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+        assert_eq!(json[0]["file"], "a.png");
+        assert_eq!(json[0]["qrType"], serde_json::Value::Null);
+        assert_eq!(json[0]["error"], "No QR code found in image");
+    }
+}
@@ -0,0 +1,145 @@
+use crate::db::{self, DbState, EncryptionScope, EncryptionStatus, KeyMaterial};
+use tauri::State;
+
+/// Whether history encryption is on, and unlocked for this session
+#[tauri::command]
+pub async fn encryption_status(state: State<'_, DbState>) -> Result<EncryptionStatus, String> {
+    let workspace = state.workspace();
+    let unlocked = workspace.key().is_some();
+    let error = workspace.encryption_error();
+    workspace
+        .read(move |conn| {
+            let status = db::encryption_status(conn, unlocked)
+                .map_err(|e| format!("Failed to read encryption status: {}", e))?;
+            Ok(EncryptionStatus { error, ..status })
+        })
+        .await
+}
+
+/// Encrypt sensitive history and scans, or all of them, at rest
+///
+/// Without a passphrase the key goes in a keyfile next to the database and
+/// unlocks automatically on launch. With one, the key is derived from it and
+/// must be unlocked each session.
+///
+/// Existing backups and pre-migration copies hold the secrets in the clear,
+/// so they are deleted and replaced by a fresh backup of the encrypted
+/// database.
+#[tauri::command]
+pub async fn encryption_enable(
+    state: State<'_, DbState>,
+    scope: EncryptionScope,
+    passphrase: Option<String>,
+) -> Result<EncryptionStatus, String> {
+    let workspace = state.workspace();
+    let keyfile = workspace.paths.keyfile.clone();
+    let backups = workspace.paths.backups.clone();
+    let (key, status) = workspace
+        .clone()
+        .write(move |conn| {
+            let material = match &passphrase {
+                Some(passphrase) => KeyMaterial::Passphrase(passphrase),
+                None => KeyMaterial::Keyfile(&keyfile),
+            };
+            let key = db::enable_encryption(conn, scope, material)
+                .map_err(|e| format!("Failed to enable encryption: {}", e))?;
+
+            db::scrub_plaintext_copies(conn, &backups)
+                .map_err(|e| format!("Failed to remove plaintext copies: {}", e))?;
+
+            let status = db::encryption_status(conn, true)
+                .map_err(|e| format!("Failed to read encryption status: {}", e))?;
+            Ok((key, status))
+        })
+        .await?;
+
+    workspace.set_key(Some(key));
+    workspace.set_encryption_error(None);
+    Ok(status)
+}
+
+/// Unlock encrypted history for this session
+///
+/// Rows saved or migrated while locked are encrypted now, and plaintext
+/// copies of them removed as on enabling. Copies left behind by a failure
+/// when the workspace opened are removed too.
+#[tauri::command]
+pub async fn encryption_unlock(
+    state: State<'_, DbState>,
+    passphrase: Option<String>,
+) -> Result<EncryptionStatus, String> {
+    let workspace = state.workspace();
+    let keyfile = workspace.paths.keyfile.clone();
+    let backups = workspace.paths.backups.clone();
+    let had_error = workspace.encryption_error().is_some();
+    let (key, status) = workspace
+        .clone()
+        .write(move |conn| {
+            let material = match &passphrase {
+                Some(passphrase) => KeyMaterial::Passphrase(passphrase),
+                None => KeyMaterial::Keyfile(&keyfile),
+            };
+            let key = db::unlock_encryption(conn, material)
+                .map_err(|e| format!("Failed to unlock: {}", e))?;
+
+            let sealed = db::seal_unencrypted_rows(conn, &key)
+                .map_err(|e| format!("Failed to encrypt saved rows: {}", e))?;
+            if sealed > 0 || had_error {
+                db::scrub_plaintext_copies(conn, &backups)
+                    .map_err(|e| format!("Failed to remove plaintext copies: {}", e))?;
+            }
+
+            let status = db::encryption_status(conn, true)
+                .map_err(|e| format!("Failed to read encryption status: {}", e))?;
+            Ok((key, status))
+        })
+        .await?;
+
+    workspace.set_key(Some(key));
+    workspace.set_encryption_error(None);
+    Ok(status)
+}
+
+/// Forget the key until the next unlock
+#[tauri::command]
+pub async fn encryption_lock(state: State<'_, DbState>) -> Result<EncryptionStatus, String> {
+    let workspace = state.workspace();
+    workspace.set_key(None);
+    let error = workspace.encryption_error();
+    workspace
+        .read(move |conn| {
+            let status = db::encryption_status(conn, false)
+                .map_err(|e| format!("Failed to read encryption status: {}", e))?;
+            Ok(EncryptionStatus { error, ..status })
+        })
+        .await
+}
+
+/// Decrypt all history and turn encryption off; needs the key unlocked
+///
+/// The keyfile, if one was used, is deleted afterwards.
+#[tauri::command]
+pub async fn encryption_disable(state: State<'_, DbState>) -> Result<EncryptionStatus, String> {
+    let workspace = state.workspace();
+    let key = workspace
+        .key()
+        .ok_or_else(|| "Unlock encrypted history before turning encryption off".to_string())?;
+
+    let status = workspace
+        .clone()
+        .write(move |conn| {
+            db::disable_encryption(conn, &key)
+                .map_err(|e| format!("Failed to disable encryption: {}", e))?;
+            db::encryption_status(conn, false)
+                .map_err(|e| format!("Failed to read encryption status: {}", e))
+        })
+        .await?;
+
+    workspace.set_key(None);
+    workspace.set_encryption_error(None); // nothing is meant to be encrypted now
+    if workspace.paths.keyfile.is_file() {
+        std::fs::remove_file(&workspace.paths.keyfile)
+            .map_err(|e| format!("Failed to remove keyfile: {}", e))?;
+    }
+    Ok(status)
+}
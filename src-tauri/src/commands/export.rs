@@ -18,6 +18,7 @@ pub async fn export_png(
     app: tauri::AppHandle,
     image_data: String,
     suggested_name: Option<String>,
+    directory: Option<String>,
 ) -> Result<ExportResult, String> {
     let base64_data = strip_data_url_prefix(&image_data);
 
@@ -27,11 +28,12 @@ pub async fn export_png(
 
     let file_name = suggested_name.unwrap_or_else(|| "qr-code.png".to_string());
 
-    // Show save dialog
-    let file_path = app
-        .dialog()
-        .file()
-        .set_file_name(&file_name)
+    // Show save dialog, opening in the configured export directory
+    let mut dialog = app.dialog().file().set_file_name(&file_name);
+    if let Some(dir) = directory {
+        dialog = dialog.set_directory(dir);
+    }
+    let file_path = dialog
         .add_filter("PNG Image", &["png"])
         .blocking_save_file();
 
@@ -61,14 +63,16 @@ pub async fn export_svg(
     app: tauri::AppHandle,
     svg_data: String,
     suggested_name: Option<String>,
+    directory: Option<String>,
 ) -> Result<ExportResult, String> {
     let file_name = suggested_name.unwrap_or_else(|| "qr-code.svg".to_string());
 
-    // Show save dialog
-    let file_path = app
-        .dialog()
-        .file()
-        .set_file_name(&file_name)
+    // Show save dialog, opening in the configured export directory
+    let mut dialog = app.dialog().file().set_file_name(&file_name);
+    if let Some(dir) = directory {
+        dialog = dialog.set_directory(dir);
+    }
+    let file_path = dialog
         .add_filter("SVG Image", &["svg"])
         .blocking_save_file();
 
@@ -0,0 +1,118 @@
+use crate::db::{self, DbState, Folder};
+use tauri::State;
+
+/// List all folders; the tree is rebuilt from `parentId`
+#[tauri::command]
+pub async fn folder_list(state: State<'_, DbState>) -> Result<Vec<Folder>, String> {
+    state
+        .read(move |conn| {
+            db::list_folders(conn).map_err(|e| format!("Failed to list folders: {}", e))
+        })
+        .await
+}
+
+/// Create a folder, optionally nested in another
+#[tauri::command]
+pub async fn folder_create(
+    state: State<'_, DbState>,
+    name: String,
+    parent_id: Option<i64>,
+) -> Result<i64, String> {
+    let name = folder_name(&name)?;
+    state
+        .write(move |conn| {
+            if let Some(parent_id) = parent_id {
+                db::get_folder(conn, parent_id)
+                    .map_err(|e| format!("Failed to create folder: {}", e))?
+                    .ok_or_else(|| format!("Folder {} not found", parent_id))?;
+            }
+
+            db::create_folder(conn, &name, parent_id)
+                .map_err(|e| format!("Failed to create folder: {}", e))
+        })
+        .await
+}
+
+/// Rename a folder
+#[tauri::command]
+pub async fn folder_rename(
+    state: State<'_, DbState>,
+    id: i64,
+    name: String,
+) -> Result<bool, String> {
+    let name = folder_name(&name)?;
+    state
+        .write(move |conn| {
+            db::rename_folder(conn, id, &name)
+                .map_err(|e| format!("Failed to rename folder: {}", e))
+        })
+        .await
+}
+
+/// Move a folder under another, or to the top level with no parent
+#[tauri::command]
+pub async fn folder_move(
+    state: State<'_, DbState>,
+    id: i64,
+    parent_id: Option<i64>,
+) -> Result<bool, String> {
+    state
+        .write(move |conn| {
+            if let Some(parent_id) = parent_id {
+                db::get_folder(conn, parent_id)
+                    .map_err(|e| format!("Failed to move folder: {}", e))?
+                    .ok_or_else(|| format!("Folder {} not found", parent_id))?;
+
+                let cycle = db::is_within_folder(conn, id, parent_id)
+                    .map_err(|e| format!("Failed to move folder: {}", e))?;
+                if cycle {
+                    return Err(
+                        "A folder cannot be moved into itself or its subfolders".to_string()
+                    );
+                }
+            }
+
+            db::move_folder(conn, id, parent_id)
+                .map_err(|e| format!("Failed to move folder: {}", e))
+        })
+        .await
+}
+
+/// Delete a folder; its items and subfolders move up a level
+#[tauri::command]
+pub async fn folder_delete(state: State<'_, DbState>, id: i64) -> Result<bool, String> {
+    state
+        .write(move |conn| {
+            db::delete_folder(conn, id).map_err(|e| format!("Failed to delete folder: {}", e))
+        })
+        .await
+}
+
+/// Move history items into a folder, or out of folders with no `folderId`
+#[tauri::command]
+pub async fn history_move_to_folder(
+    state: State<'_, DbState>,
+    ids: Vec<i64>,
+    folder_id: Option<i64>,
+) -> Result<i64, String> {
+    state
+        .write(move |conn| {
+            if let Some(folder_id) = folder_id {
+                db::get_folder(conn, folder_id)
+                    .map_err(|e| format!("Failed to move items: {}", e))?
+                    .ok_or_else(|| format!("Folder {} not found", folder_id))?;
+            }
+
+            db::move_history_to_folder(conn, &ids, folder_id)
+                .map_err(|e| format!("Failed to move items: {}", e))
+        })
+        .await
+}
+
+fn folder_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Folder name cannot be empty".to_string());
+    }
+    Ok(name.to_string())
+}
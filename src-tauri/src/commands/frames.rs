@@ -0,0 +1,435 @@
+use crate::qr_type::detect_qr_type;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, DynamicImage, ImageFormat, ImageReader};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use super::bulk_scan::is_scannable;
+use super::preprocess::{decode_all_with_pipeline, decode_oriented};
+
+/// Lazily decoded frames of an image file or sequence
+pub type FrameIter = Box<dyn Iterator<Item = Result<DynamicImage, String>>>;
+
+/// Undecodable frames in a row after which the rest of the stream is dropped
+const MAX_CONSECUTIVE_FRAME_ERRORS: usize = 8;
+
+/// A distinct code and every frame it appeared in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameCode {
+    pub content: String,
+    pub qr_type: String,
+    pub frames: Vec<usize>, // 0-based frame indices
+}
+
+/// A frame that couldn't be decoded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameError {
+    pub frame: usize, // 0-based frame index
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameScanResult {
+    pub success: bool,
+    pub frame_count: usize,
+    pub codes: Vec<FrameCode>,
+    pub combined: Option<String>, // payloads joined in frame order
+    pub failed_frames: Vec<FrameError>,
+    pub error: Option<String>,
+}
+
+/// Decode the codes in every frame of an animated GIF, APNG or WebP
+#[tauri::command]
+pub async fn scan_qr_frames(file_path: String) -> Result<FrameScanResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let frames = animation_frames(Path::new(&file_path))?;
+        Ok(scan_frames(frames))
+    })
+    .await
+    .map_err(|e| format!("Failed to scan frames: {}", e))?
+}
+
+/// Decode the codes in a folder of numbered images (frame_001.png, frame_002.png, ...)
+#[tauri::command]
+pub async fn scan_qr_sequence(folder_path: String) -> Result<FrameScanResult, String> {
+    tauri::async_runtime::spawn_blocking(move || scan_sequence(Path::new(&folder_path)))
+        .await
+        .map_err(|e| format!("Failed to scan sequence: {}", e))?
+}
+
+fn scan_sequence(folder: &Path) -> Result<FrameScanResult, String> {
+    let files = sequence_files(folder)?;
+    if files.is_empty() {
+        return Ok(FrameScanResult {
+            success: false,
+            frame_count: 0,
+            codes: vec![],
+            combined: None,
+            failed_frames: vec![],
+            error: Some("No numbered images found in folder".to_string()),
+        });
+    }
+
+    let frames = files.into_iter().map(|path| {
+        let reader =
+            ImageReader::open(&path).map_err(|e| format!("Failed to open image: {}", e))?;
+        decode_oriented(reader)
+    });
+    Ok(scan_frames(Box::new(frames)))
+}
+
+/// Every frame of an animated image; still images yield a single frame
+pub fn animation_frames(path: &Path) -> Result<FrameIter, String> {
+    let format = ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|e| format!("Failed to open image: {}", e))?
+        .format();
+    let open = || {
+        File::open(path)
+            .map(BufReader::new)
+            .map_err(|e| format!("Failed to open image: {}", e))
+    };
+    let decode_error = |e: image::ImageError| format!("Failed to decode image: {}", e);
+
+    let frames = match format {
+        Some(ImageFormat::Gif) => GifDecoder::new(open()?)
+            .map_err(decode_error)?
+            .into_frames(),
+        Some(ImageFormat::Png) => {
+            let decoder = PngDecoder::new(open()?).map_err(decode_error)?;
+            if !decoder.is_apng().map_err(decode_error)? {
+                return still_frame(path);
+            }
+            decoder.apng().map_err(decode_error)?.into_frames()
+        }
+        Some(ImageFormat::WebP) => {
+            let decoder = WebPDecoder::new(open()?).map_err(decode_error)?;
+            if !decoder.has_animation() {
+                return still_frame(path);
+            }
+            decoder.into_frames()
+        }
+        _ => return still_frame(path),
+    };
+
+    Ok(Box::new(frames.map(move |frame| {
+        frame
+            .map(|frame| DynamicImage::ImageRgba8(frame.into_buffer()))
+            .map_err(decode_error)
+    })))
+}
+
+fn still_frame(path: &Path) -> Result<FrameIter, String> {
+    let reader = ImageReader::open(path).map_err(|e| format!("Failed to open image: {}", e))?;
+    let img = decode_oriented(reader)?;
+    Ok(Box::new(std::iter::once(Ok(img))))
+}
+
+/// The frames that decode, giving up after too many failures in a row
+pub fn decoded_frames(frames: FrameIter) -> impl Iterator<Item = DynamicImage> {
+    let mut consecutive_errors = 0;
+    frames
+        .map_while(move |frame| match frame {
+            Ok(frame) => {
+                consecutive_errors = 0;
+                Some(Some(frame))
+            }
+            Err(_) => {
+                // A broken decoder may keep failing without ever ending
+                consecutive_errors += 1;
+                (consecutive_errors < MAX_CONSECUTIVE_FRAME_ERRORS).then_some(None)
+            }
+        })
+        .flatten()
+}
+
+/// Images in `folder` whose names carry a frame number, in numeric order
+pub fn sequence_files(folder: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(folder)
+        .map_err(|e| format!("Failed to read folder {}: {}", folder.display(), e))?;
+
+    let mut numbered = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("Failed to read folder entry: {}", e))?
+            .path();
+        if !path.is_file() || !is_scannable(&path) {
+            continue;
+        }
+
+        let stem = path.file_stem().map(|s| s.to_string_lossy().to_string());
+        if let Some(number) = stem.as_deref().and_then(frame_number) {
+            numbered.push((number, path));
+        }
+    }
+
+    numbered.sort();
+    Ok(numbered.into_iter().map(|(_, path)| path).collect())
+}
+
+/// The last run of digits in a file stem ("shot-02_frame0012" -> 12)
+fn frame_number(stem: &str) -> Option<u64> {
+    let end = stem.rfind(|c: char| c.is_ascii_digit())? + 1;
+    let start = stem[..end]
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |i| i + 1);
+    stem[start..end].parse().ok()
+}
+
+/// Decode each frame and merge repeated codes, keeping first-seen order
+///
+/// `combined` keeps every part of a multi-part sequence in frame order, so a
+/// chunk that recurs later is repeated. Only a code held on screen across
+/// consecutive frames (blank frames in between included) counts once.
+/// Frames that fail to decode are listed in `failed_frames` and skipped.
+pub fn scan_frames(frames: FrameIter) -> FrameScanResult {
+    let mut codes: Vec<FrameCode> = Vec::new();
+    let mut frame_count = 0;
+    let mut failed_frames = Vec::new();
+    let mut consecutive_errors = 0;
+    let mut parts: Vec<String> = Vec::new();
+    let mut held: Vec<String> = Vec::new(); // codes in the last frame that had any
+    let mut previous: Option<(DynamicImage, Vec<String>)> = None;
+
+    for (index, frame) in frames.enumerate() {
+        frame_count += 1;
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(error) => {
+                failed_frames.push(FrameError {
+                    frame: index,
+                    error,
+                });
+                // A broken decoder may keep failing without ever ending
+                consecutive_errors += 1;
+                if consecutive_errors >= MAX_CONSECUTIVE_FRAME_ERRORS {
+                    break;
+                }
+                continue;
+            }
+        };
+        consecutive_errors = 0;
+
+        // Animations often hold a frame for several ticks; skip re-decoding it
+        let contents = match &previous {
+            Some((image, contents)) if image.as_bytes() == frame.as_bytes() => contents.clone(),
+            _ => decode_all_with_pipeline(&frame)
+                .map(|found| found.into_iter().map(|code| code.content).collect())
+                .unwrap_or_default(),
+        };
+
+        for content in &contents {
+            match codes.iter_mut().find(|code| &code.content == content) {
+                Some(code) => code.frames.push(index),
+                None => codes.push(FrameCode {
+                    content: content.clone(),
+                    qr_type: detect_qr_type(content),
+                    frames: vec![index],
+                }),
+            }
+        }
+
+        if !contents.is_empty() && contents != held {
+            parts.extend(contents.iter().cloned());
+            held = contents.clone();
+        }
+
+        previous = Some((frame, contents));
+    }
+
+    let combined = (parts.len() > 1).then(|| parts.concat());
+
+    FrameScanResult {
+        success: !codes.is_empty(),
+        frame_count,
+        error: codes
+            .is_empty()
+            .then(|| "No QR code found in any frame".to_string()),
+        codes,
+        combined,
+        failed_frames,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, Frame, GrayImage, Luma, RgbaImage};
+    use qrcode::{Color, QrCode};
+
+    fn render_qr(content: &str) -> GrayImage {
+        let code = QrCode::new(content.as_bytes()).unwrap();
+        let modules = code.width() as u32;
+        let colors = code.to_colors();
+        // Fixed canvas so every frame of an animation has the same size
+        GrayImage::from_fn(160, 160, |x, y| {
+            let (mx, my) = (x / 4, y / 4);
+            let dark = (4..modules + 4).contains(&mx)
+                && (4..modules + 4).contains(&my)
+                && colors[((my - 4) * modules + (mx - 4)) as usize] == Color::Dark;
+            Luma([if dark { 0 } else { 255 }])
+        })
+    }
+
+    fn rgba(gray: &GrayImage) -> RgbaImage {
+        DynamicImage::ImageLuma8(gray.clone()).to_rgba8()
+    }
+
+    #[test]
+    fn test_scan_animated_gif() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("anim.gif");
+
+        let blank = GrayImage::from_pixel(160, 160, Luma([255]));
+        let frames = [
+            render_qr("part-1;"),
+            render_qr("part-1;"),
+            blank,
+            render_qr("part-2"),
+        ];
+        {
+            let mut encoder = GifEncoder::new(File::create(&path).unwrap());
+            for frame in &frames {
+                let delay = Delay::from_numer_denom_ms(100, 1);
+                encoder
+                    .encode_frame(Frame::from_parts(rgba(frame), 0, 0, delay))
+                    .unwrap();
+            }
+        }
+
+        let result = scan_frames(animation_frames(&path).unwrap());
+
+        assert!(result.success);
+        assert_eq!(result.frame_count, 4);
+        assert_eq!(result.codes.len(), 2);
+        assert_eq!(result.codes[0].content, "part-1;");
+        assert_eq!(result.codes[0].frames, vec![0, 1]);
+        assert_eq!(result.codes[1].content, "part-2");
+        assert_eq!(result.codes[1].frames, vec![3]);
+        assert_eq!(result.combined, Some("part-1;part-2".to_string()));
+    }
+
+    #[test]
+    fn test_still_image_is_one_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("still.png");
+        render_qr("still").save(&path).unwrap();
+
+        let result = scan_frames(animation_frames(&path).unwrap());
+
+        assert_eq!(result.frame_count, 1);
+        assert_eq!(result.codes[0].content, "still");
+        assert_eq!(result.combined, None);
+    }
+
+    #[test]
+    fn test_scan_sequence_in_numeric_order() {
+        let dir = tempfile::tempdir().unwrap();
+        render_qr("second")
+            .save(dir.path().join("frame_10.png"))
+            .unwrap();
+        render_qr("first")
+            .save(dir.path().join("frame_9.png"))
+            .unwrap();
+        render_qr("ignored")
+            .save(dir.path().join("cover.png"))
+            .unwrap();
+
+        let files = sequence_files(dir.path()).unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["frame_9.png", "frame_10.png"]);
+
+        let frames = files
+            .into_iter()
+            .map(|path| decode_oriented(ImageReader::open(path).map_err(|e| e.to_string())?));
+        let result = scan_frames(Box::new(frames));
+
+        assert_eq!(result.codes[0].content, "first");
+        assert_eq!(result.codes[0].frames, vec![0]);
+        assert_eq!(result.codes[1].frames, vec![1]);
+    }
+
+    fn frame(content: &str) -> Result<DynamicImage, String> {
+        Ok(DynamicImage::ImageLuma8(render_qr(content)))
+    }
+
+    #[test]
+    fn test_combined_keeps_repeated_parts() {
+        let frames = vec![frame("AB"), frame("CD"), frame("CD"), frame("AB")];
+        let result = scan_frames(Box::new(frames.into_iter()));
+
+        assert_eq!(result.codes.len(), 2);
+        assert_eq!(result.codes[0].frames, vec![0, 3]);
+        assert_eq!(result.combined, Some("ABCDAB".to_string()));
+    }
+
+    #[test]
+    fn test_bad_frames_are_recorded_and_skipped() {
+        let frames = vec![
+            frame("part-1;"),
+            Err("Failed to decode image: truncated".to_string()),
+            frame("part-2"),
+        ];
+        let result = scan_frames(Box::new(frames.into_iter()));
+
+        assert!(result.success);
+        assert_eq!(result.frame_count, 3);
+        assert_eq!(
+            result.failed_frames,
+            vec![FrameError {
+                frame: 1,
+                error: "Failed to decode image: truncated".to_string(),
+            }]
+        );
+        assert_eq!(result.codes[1].frames, vec![2]);
+        assert_eq!(result.combined, Some("part-1;part-2".to_string()));
+    }
+
+    #[test]
+    fn test_endless_frame_errors_stop_the_scan() {
+        let frames = std::iter::repeat_with(|| Err("Failed to decode image".to_string()));
+        let result = scan_frames(Box::new(frames));
+
+        assert!(!result.success);
+        assert_eq!(result.failed_frames.len(), MAX_CONSECUTIVE_FRAME_ERRORS);
+    }
+
+    #[test]
+    fn test_decoded_frames_skip_errors_and_stop_on_endless_ones() {
+        let frames = vec![frame("a"), Err("bad".to_string()), frame("b")]
+            .into_iter()
+            .chain(std::iter::repeat_with(|| Err("bad".to_string())));
+
+        assert_eq!(decoded_frames(Box::new(frames)).count(), 2);
+    }
+
+    #[test]
+    fn test_scan_frames_without_codes() {
+        let blank = DynamicImage::ImageLuma8(GrayImage::from_pixel(64, 64, Luma([255])));
+        let result = scan_frames(Box::new(std::iter::repeat_n(Ok(blank), 3)));
+
+        assert!(!result.success);
+        assert_eq!(result.frame_count, 3);
+        assert!(result.error.is_some());
+    }
+
+    #[test]
+    fn test_frame_number() {
+        assert_eq!(frame_number("frame_0012"), Some(12));
+        assert_eq!(frame_number("shot-02_frame7"), Some(7));
+        assert_eq!(frame_number("42"), Some(42));
+        assert_eq!(frame_number("v2-final"), Some(2));
+        assert_eq!(frame_number("cover"), None);
+    }
+}
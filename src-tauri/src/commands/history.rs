@@ -1,4 +1,7 @@
-use crate::db::{self, DbState, HistoryItem, NewHistoryItem};
+use crate::db::{
+    self, DbState, DuplicateGroup, HistoryCursor, HistoryFilter, HistoryItem, HistorySearchHit,
+    HistorySort, NewHistoryItem,
+};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -8,57 +11,238 @@ pub struct HistoryListResult {
     pub items: Vec<HistoryItem>,
     pub total: i64,
     pub has_more: bool,
+    pub next_cursor: Option<HistoryCursor>,
 }
 
-/// List history items with pagination
+/// List history items with filters, sorting and pagination
+///
+/// `total` counts every row matching the filter. Pass `nextCursor` back as
+/// `cursor` to fetch the following page without skipping or repeating rows
+/// when new items are saved in between.
 #[tauri::command]
 pub async fn history_list(
     state: State<'_, DbState>,
     limit: Option<i64>,
     offset: Option<i64>,
     search: Option<String>,
+    filter: Option<HistoryFilter>,
+    sort: Option<HistorySort>,
+    cursor: Option<HistoryCursor>,
 ) -> Result<HistoryListResult, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let limit = limit.unwrap_or(50);
-    let offset = offset.unwrap_or(0);
+    state
+        .read(move |conn| {
+            let limit = limit.unwrap_or(50);
+            let offset = offset.unwrap_or(0);
+            let mut filter = filter.unwrap_or_default();
+            if filter.search.is_none() {
+                filter.search = search;
+            }
 
-    let items = db::list_history(&conn, limit, offset, search.as_deref())
-        .map_err(|e| format!("Failed to list history: {}", e))?;
+            let page = db::list_history_page(
+                conn,
+                &filter,
+                sort.unwrap_or_default(),
+                cursor.as_ref(),
+                limit,
+                offset,
+            )
+            .map_err(|e| format!("Failed to list history: {}", e))?;
 
-    let total = db::count_history(&conn).map_err(|e| format!("Failed to count history: {}", e))?;
+            let total = db::count_filtered_history(conn, &filter)
+                .map_err(|e| format!("Failed to count history: {}", e))?;
 
-    let has_more = (offset + items.len() as i64) < total;
+            Ok(HistoryListResult {
+                items: page.items,
+                total,
+                has_more: page.next_cursor.is_some(),
+                next_cursor: page.next_cursor,
+            })
+        })
+        .await
+}
 
-    Ok(HistoryListResult {
-        items,
-        total,
-        has_more,
-    })
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySearchResult {
+    pub hits: Vec<HistorySearchHit>,
+    pub total: i64,
+    pub has_more: bool,
 }
 
-/// Save a history item
+/// Ranked full-text search over history with highlighted snippets
+///
+/// Supports prefixes (`offi`), phrases (`"front desk"`) and field filters
+/// (`type:wifi label:office`).
 #[tauri::command]
-pub async fn history_save(
+pub async fn history_search(
     state: State<'_, DbState>,
-    item: NewHistoryItem,
+    query: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<HistorySearchResult, String> {
+    state
+        .read(move |conn| {
+            let limit = limit.unwrap_or(50);
+            let offset = offset.unwrap_or(0);
+
+            let hits = db::search_history(conn, &query, limit, offset)
+                .map_err(|e| format!("Failed to search history: {}", e))?;
+
+            let total = db::count_history_search(conn, &query)
+                .map_err(|e| format!("Failed to count search results: {}", e))?;
+
+            let has_more = (offset + hits.len() as i64) < total;
+
+            Ok(HistorySearchResult {
+                hits,
+                total,
+                has_more,
+            })
+        })
+        .await
+}
+
+/// Save a history item, encrypting its content if encryption covers it
+///
+/// With the `upsertHistory` setting on, saving an item identical to a live
+/// one bumps that item and returns its ID instead of adding a row.
+#[tauri::command]
+pub async fn history_save(state: State<'_, DbState>, item: NewHistoryItem) -> Result<i64, String> {
+    let workspace = state.workspace();
+    let key = workspace.key();
+    workspace
+        .write(move |conn| {
+            let settings =
+                db::get_settings(conn).map_err(|e| format!("Failed to read settings: {}", e))?;
+
+            let id = if settings.upsert_history {
+                db::upsert_history(conn, &item, key.as_ref()).map(|outcome| outcome.id)
+            } else {
+                db::save_history_encrypted(conn, &item, key.as_ref())
+            }
+            .map_err(|e| format!("Failed to save history: {}", e))?;
+
+            db::enforce_retention(conn, &settings.retention_policy)
+                .map_err(|e| format!("Failed to apply retention: {}", e))?;
+
+            Ok(id)
+        })
+        .await
+}
+
+/// Full content of a history item, which lists show redacted when sensitive
+#[tauri::command]
+pub async fn history_reveal(state: State<'_, DbState>, id: i64) -> Result<String, String> {
+    let workspace = state.workspace();
+    let key = workspace.key();
+    workspace
+        .read(move |conn| {
+            db::reveal_history_content(conn, id, key.as_ref())
+                .map_err(|e| format!("Failed to read history: {}", e))?
+                .ok_or_else(|| format!("History item {} not found", id))
+        })
+        .await
+}
+
+/// Groups of live history items with the same type, content and style
+#[tauri::command]
+pub async fn history_duplicates(state: State<'_, DbState>) -> Result<Vec<DuplicateGroup>, String> {
+    let workspace = state.workspace();
+    let key = workspace.key();
+    workspace
+        .read(move |conn| {
+            db::find_duplicate_history(conn, key.as_ref())
+                .map_err(|e| format!("Failed to find duplicates: {}", e))
+        })
+        .await
+}
+
+/// Merge duplicate history items, moving the extra copies to the trash
+///
+/// Merges every group, or only those keeping one of `keepIds`. Returns how
+/// many items were merged away.
+#[tauri::command]
+pub async fn history_merge_duplicates(
+    state: State<'_, DbState>,
+    keep_ids: Option<Vec<i64>>,
 ) -> Result<i64, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let workspace = state.workspace();
+    let key = workspace.key();
+    workspace
+        .write(move |conn| {
+            let mut groups = db::find_duplicate_history(conn, key.as_ref())
+                .map_err(|e| format!("Failed to find duplicates: {}", e))?;
+            if let Some(keep_ids) = keep_ids {
+                groups.retain(|group| keep_ids.contains(&group.keep.id));
+            }
 
-    db::save_history(&conn, &item).map_err(|e| format!("Failed to save history: {}", e))
+            db::merge_duplicate_history(conn, &groups)
+                .map_err(|e| format!("Failed to merge duplicates: {}", e))
+        })
+        .await
 }
 
-/// Delete a history item
+/// Pin or unpin history items
 #[tauri::command]
-pub async fn history_delete(state: State<'_, DbState>, id: i64) -> Result<bool, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+pub async fn history_set_pinned(
+    state: State<'_, DbState>,
+    ids: Vec<i64>,
+    pinned: bool,
+) -> Result<i64, String> {
+    state
+        .write(move |conn| {
+            db::set_history_pinned(conn, &ids, pinned)
+                .map_err(|e| format!("Failed to update pinned items: {}", e))
+        })
+        .await
+}
 
-    db::delete_history(&conn, id).map_err(|e| format!("Failed to delete history: {}", e))
+/// Move a history item to the trash
+#[tauri::command]
+pub async fn history_delete(state: State<'_, DbState>, id: i64) -> Result<bool, String> {
+    state
+        .write(move |conn| {
+            db::delete_history(conn, id).map_err(|e| format!("Failed to delete history: {}", e))
+        })
+        .await
 }
 
-/// Clear all history
+/// Move all history to the trash
 #[tauri::command]
 pub async fn history_clear(state: State<'_, DbState>) -> Result<i64, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    state
+        .write(move |conn| {
+            db::clear_history(conn).map_err(|e| format!("Failed to clear history: {}", e))
+        })
+        .await
+}
+
+/// Restore history items from the trash
+#[tauri::command]
+pub async fn history_restore(state: State<'_, DbState>, ids: Vec<i64>) -> Result<i64, String> {
+    state
+        .write(move |conn| {
+            db::restore_history(conn, &ids).map_err(|e| format!("Failed to restore history: {}", e))
+        })
+        .await
+}
 
-    db::clear_history(&conn).map_err(|e| format!("Failed to clear history: {}", e))
+/// Permanently delete history items that are in the trash
+#[tauri::command]
+pub async fn history_purge(state: State<'_, DbState>, ids: Vec<i64>) -> Result<i64, String> {
+    state
+        .write(move |conn| {
+            db::purge_history(conn, &ids).map_err(|e| format!("Failed to purge history: {}", e))
+        })
+        .await
+}
+
+/// Permanently delete everything in the history trash
+#[tauri::command]
+pub async fn history_empty_trash(state: State<'_, DbState>) -> Result<i64, String> {
+    state
+        .write(move |conn| {
+            db::empty_history_trash(conn).map_err(|e| format!("Failed to empty trash: {}", e))
+        })
+        .await
 }
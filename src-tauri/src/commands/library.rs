@@ -0,0 +1,590 @@
+use crate::db::{
+    self, blobs::parse_data_url, ConflictStrategy, DbState, ImportReport, Library,
+    LibraryHistoryItem, LibrarySelection, LibraryTemplate,
+};
+use crate::qr_type::detect_qr_type;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use tauri::State;
+use tauri_plugin_dialog::DialogExt;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use super::export::ExportResult;
+
+/// The library document inside a `.qrfoundry` bundle
+const BUNDLE_MANIFEST: &str = "library.json";
+
+/// Folder inside a bundle holding thumbnails, previews and logos
+const BUNDLE_IMAGES: &str = "images/";
+
+const CSV_HEADERS: [&str; 11] = [
+    "kind",
+    "label",
+    "content",
+    "qrType",
+    "styleJson",
+    "tags",
+    "pinned",
+    "isDefault",
+    "createdAt",
+    "updatedAt",
+    "redacted",
+];
+
+/// Separates tags within the CSV `tags` column
+const CSV_TAG_SEPARATOR: char = ';';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LibraryFileFormat {
+    Json,
+    /// Flat rows without images
+    Csv,
+    /// ZIP bundle with the JSON document and image files
+    Qrfoundry,
+}
+
+impl LibraryFileFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            LibraryFileFormat::Json => "json",
+            LibraryFileFormat::Csv => "csv",
+            LibraryFileFormat::Qrfoundry => "qrfoundry",
+        }
+    }
+
+    /// Guess from the extension, falling back to sniffing the contents
+    fn detect(path: &Path, bytes: &[u8]) -> Self {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "qrfoundry" | "zip" => LibraryFileFormat::Qrfoundry,
+            "csv" => LibraryFileFormat::Csv,
+            "json" => LibraryFileFormat::Json,
+            _ if bytes.starts_with(b"PK") => LibraryFileFormat::Qrfoundry,
+            _ if bytes.trim_ascii_start().starts_with(b"{") => LibraryFileFormat::Json,
+            _ => LibraryFileFormat::Csv,
+        }
+    }
+}
+
+fn library_to_json(library: &Library) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(library).map_err(|e| format!("Failed to serialize library: {}", e))
+}
+
+fn library_from_json(bytes: &[u8]) -> Result<Library, String> {
+    let library: Library =
+        serde_json::from_slice(bytes).map_err(|e| format!("Invalid library file: {}", e))?;
+    library.validate()?;
+    Ok(library)
+}
+
+fn library_to_csv(library: &Library) -> Result<Vec<u8>, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let write_error = |e: csv::Error| format!("Failed to write CSV: {}", e);
+
+    writer.write_record(CSV_HEADERS).map_err(write_error)?;
+    for item in &library.history {
+        writer
+            .write_record([
+                "history",
+                item.label.as_deref().unwrap_or_default(),
+                &item.content,
+                &item.qr_type,
+                &item.style_json,
+                &item.tags.join(&CSV_TAG_SEPARATOR.to_string()),
+                if item.pinned { "true" } else { "false" },
+                "",
+                item.created_at.as_deref().unwrap_or_default(),
+                item.updated_at.as_deref().unwrap_or_default(),
+                if item.redacted { "true" } else { "false" },
+            ])
+            .map_err(write_error)?;
+    }
+    for template in &library.templates {
+        writer
+            .write_record([
+                "template",
+                &template.name,
+                "",
+                "",
+                &template.style_json,
+                "",
+                "",
+                if template.is_default { "true" } else { "false" },
+                template.created_at.as_deref().unwrap_or_default(),
+                "",
+                "",
+            ])
+            .map_err(write_error)?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| format!("Failed to write CSV: {}", e))
+}
+
+/// Read CSV rows written by `library_to_csv`, or a hand-made subset
+///
+/// Only `content` is required; rows default to history, and a missing type
+/// is detected from the content.
+fn library_from_csv(bytes: &[u8]) -> Result<Library, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::Headers)
+        .from_reader(bytes);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Failed to read CSV: {}", e))?
+        .clone();
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let columns: HashMap<&str, usize> = CSV_HEADERS
+        .iter()
+        .filter_map(|name| column(name).map(|i| (*name, i)))
+        .collect();
+    if !columns.contains_key("content") && !columns.contains_key("kind") {
+        return Err("CSV must have a 'content' column".to_string());
+    }
+
+    let mut history = Vec::new();
+    let mut templates = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Failed to read CSV: {}", e))?;
+        let field = |name: &str| {
+            columns
+                .get(name)
+                .and_then(|&i| record.get(i))
+                .unwrap_or_default()
+        };
+        let optional = |name: &str| Some(field(name).to_string()).filter(|v| !v.is_empty());
+        let flag = |name: &str| matches!(field(name).to_lowercase().as_str(), "true" | "1" | "yes");
+        let style_json = optional("styleJson").unwrap_or_else(|| "{}".to_string());
+
+        if field("kind").eq_ignore_ascii_case("template") {
+            let Some(name) = optional("label") else {
+                continue;
+            };
+            templates.push(LibraryTemplate {
+                name,
+                style_json,
+                preview: None,
+                is_default: flag("isDefault"),
+                created_at: optional("createdAt"),
+            });
+            continue;
+        }
+
+        let content = field("content");
+        if content.is_empty() {
+            continue;
+        }
+        history.push(LibraryHistoryItem {
+            content: content.to_string(),
+            qr_type: optional("qrType").unwrap_or_else(|| detect_qr_type(content)),
+            label: optional("label"),
+            style_json,
+            thumbnail: None,
+            pinned: flag("pinned"),
+            tags: field("tags")
+                .split(CSV_TAG_SEPARATOR)
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+            created_at: optional("createdAt"),
+            updated_at: optional("updatedAt"),
+            redacted: flag("redacted"),
+        });
+    }
+
+    Ok(Library::new(history, templates))
+}
+
+fn image_extension(mime: &str) -> &'static str {
+    match mime {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        _ => "bin",
+    }
+}
+
+fn image_mime(name: &str) -> &'static str {
+    match name.rsplit('.').next().unwrap_or_default() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Images pulled out of a library while bundling, keyed by archive path
+#[derive(Default)]
+struct BundleImages {
+    files: Vec<(String, Vec<u8>)>,
+    paths: HashMap<String, String>, // data URL -> archive path
+}
+
+impl BundleImages {
+    /// Archive path for a data URL, or `None` if it isn't one
+    fn add(&mut self, data_url: &str) -> Option<String> {
+        if let Some(path) = self.paths.get(data_url) {
+            return Some(path.clone());
+        }
+        let (mime, bytes) = parse_data_url(data_url)?;
+
+        let digest = Sha256::digest(&bytes);
+        let name: String = digest[..12].iter().map(|b| format!("{:02x}", b)).collect();
+        let path = format!("{}{}.{}", BUNDLE_IMAGES, name, image_extension(mime));
+
+        self.paths.insert(data_url.to_string(), path.clone());
+        self.files.push((path.clone(), bytes));
+        Some(path)
+    }
+
+    /// Swap an image for its archive path, leaving other values alone
+    fn externalize(&mut self, image: &mut Option<String>) {
+        if let Some(path) = image.as_deref().and_then(|url| self.add(url)) {
+            *image = Some(path);
+        }
+    }
+
+    /// Swap a style's inline logo for its archive path
+    ///
+    /// Edits the JSON text rather than re-serializing it, so the style
+    /// string comes back byte-for-byte and still dedupes on import.
+    fn externalize_logo(&mut self, style_json: &mut String) {
+        let logo = serde_json::from_str::<serde_json::Value>(style_json)
+            .ok()
+            .and_then(|style| style.get("logo")?.as_str().map(str::to_string));
+        if let Some(logo) = logo {
+            if let Some(path) = self.add(&logo) {
+                *style_json = style_json.replace(&logo, &path);
+            }
+        }
+    }
+}
+
+fn library_to_bundle(library: &Library) -> Result<Vec<u8>, String> {
+    let mut library = library.clone();
+    let mut images = BundleImages::default();
+    for item in &mut library.history {
+        images.externalize(&mut item.thumbnail);
+        images.externalize_logo(&mut item.style_json);
+    }
+    for template in &mut library.templates {
+        images.externalize(&mut template.preview);
+        images.externalize_logo(&mut template.style_json);
+    }
+
+    let write_error = |e: zip::result::ZipError| format!("Failed to write bundle: {}", e);
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+    zip.start_file(
+        BUNDLE_MANIFEST,
+        SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated),
+    )
+    .map_err(write_error)?;
+    zip.write_all(&library_to_json(&library)?)
+        .map_err(|e| format!("Failed to write bundle: {}", e))?;
+
+    // Images are already compressed
+    let stored = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    for (path, bytes) in &images.files {
+        zip.start_file(path.as_str(), stored).map_err(write_error)?;
+        zip.write_all(bytes)
+            .map_err(|e| format!("Failed to write bundle: {}", e))?;
+    }
+
+    Ok(zip.finish().map_err(write_error)?.into_inner())
+}
+
+fn library_from_bundle(bytes: &[u8]) -> Result<Library, String> {
+    let mut archive =
+        ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Invalid bundle: {}", e))?;
+
+    let mut read_entry = |name: &str| -> Option<Vec<u8>> {
+        let mut entry = archive.by_name(name).ok()?;
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).ok()?;
+        Some(data)
+    };
+
+    let manifest =
+        read_entry(BUNDLE_MANIFEST).ok_or_else(|| format!("Bundle has no {}", BUNDLE_MANIFEST))?;
+    let mut library = library_from_json(&manifest)?;
+
+    let mut data_urls: HashMap<String, Option<String>> = HashMap::new();
+    let mut inline = |path: &str| -> Option<String> {
+        data_urls
+            .entry(path.to_string())
+            .or_insert_with(|| {
+                read_entry(path).map(|data| {
+                    format!("data:{};base64,{}", image_mime(path), STANDARD.encode(data))
+                })
+            })
+            .clone()
+    };
+    let mut internalize = |image: &mut Option<String>| {
+        if let Some(path) = image.clone().filter(|p| p.starts_with(BUNDLE_IMAGES)) {
+            // A missing image just leaves the item without one
+            *image = inline(&path);
+        }
+    };
+    for item in &mut library.history {
+        internalize(&mut item.thumbnail);
+    }
+    for template in &mut library.templates {
+        internalize(&mut template.preview);
+    }
+
+    // Logos are referenced from inside the style JSON
+    let mut restore_logo = |style_json: &mut String| {
+        let logo = serde_json::from_str::<serde_json::Value>(style_json)
+            .ok()
+            .and_then(|style| style.get("logo")?.as_str().map(str::to_string))
+            .filter(|logo| logo.starts_with(BUNDLE_IMAGES));
+        if let Some(logo) = logo {
+            if let Some(data_url) = inline(&logo) {
+                *style_json = style_json.replace(&logo, &data_url);
+            }
+        }
+    };
+    for item in &mut library.history {
+        restore_logo(&mut item.style_json);
+    }
+    for template in &mut library.templates {
+        restore_logo(&mut template.style_json);
+    }
+
+    Ok(library)
+}
+
+fn write_library(library: &Library, format: LibraryFileFormat) -> Result<Vec<u8>, String> {
+    match format {
+        LibraryFileFormat::Json => library_to_json(library),
+        LibraryFileFormat::Csv => library_to_csv(library),
+        LibraryFileFormat::Qrfoundry => library_to_bundle(library),
+    }
+}
+
+fn read_library(path: &Path) -> Result<Library, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    match LibraryFileFormat::detect(path, &bytes) {
+        LibraryFileFormat::Json => library_from_json(&bytes),
+        LibraryFileFormat::Csv => library_from_csv(&bytes),
+        LibraryFileFormat::Qrfoundry => library_from_bundle(&bytes),
+    }
+}
+
+/// Export history and templates (all, or a selection) to a file
+///
+/// WiFi passwords and OTP secrets are redacted unless `includeSecrets`;
+/// encrypted history must be unlocked to include them. Redacted items are
+/// flagged so an import leaves them out.
+#[tauri::command]
+pub async fn library_export(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+    format: LibraryFileFormat,
+    selection: Option<LibrarySelection>,
+    include_secrets: Option<bool>,
+) -> Result<ExportResult, String> {
+    let workspace = state.workspace();
+    let key = workspace.key();
+    let library = workspace
+        .read(move |conn| {
+            db::export_library(
+                conn,
+                &selection.unwrap_or_default(),
+                include_secrets.unwrap_or(false),
+                key.as_ref(),
+            )
+            .map_err(|e| format!("Failed to export library: {}", e))
+        })
+        .await?;
+    let bytes = write_library(&library, format)?;
+
+    let extension = format.extension();
+    let file_path = app
+        .dialog()
+        .file()
+        .set_file_name(format!("qr-foundry-library.{}", extension))
+        .add_filter("QR Foundry Library", &[extension])
+        .blocking_save_file();
+
+    match file_path {
+        Some(path) => {
+            let path_buf = path.as_path().unwrap().to_path_buf();
+            std::fs::write(&path_buf, bytes)
+                .map_err(|e| format!("Failed to write export: {}", e))?;
+
+            Ok(ExportResult {
+                success: true,
+                path: Some(path_buf.to_string_lossy().to_string()),
+                error: None,
+            })
+        }
+        None => Ok(ExportResult {
+            success: false,
+            path: None,
+            error: Some("Save cancelled by user".to_string()),
+        }),
+    }
+}
+
+/// Import a library file, skipping items already present
+#[tauri::command]
+pub async fn library_import(
+    state: State<'_, DbState>,
+    path: String,
+    conflict: Option<ConflictStrategy>,
+) -> Result<ImportReport, String> {
+    let library = tauri::async_runtime::spawn_blocking(move || read_library(Path::new(&path)))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let workspace = state.workspace();
+    let key = workspace.key();
+    workspace
+        .write(move |conn| {
+            db::import_library(conn, &library, conflict.unwrap_or_default(), key.as_ref())
+                .map_err(|e| format!("Failed to import library: {}", e))
+        })
+        .await
+}
+
+/// Show a file picker for library files
+#[tauri::command]
+pub async fn pick_library_file(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let file_path = app
+        .dialog()
+        .file()
+        .add_filter("QR Foundry Library", &["qrfoundry", "json", "csv"])
+        .blocking_pick_file();
+
+    match file_path {
+        Some(path) => Ok(Some(path.as_path().unwrap().to_string_lossy().to_string())),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &str = "data:image/png;base64,iVBORw0KGgo=";
+    const LOGO: &str = "data:image/jpeg;base64,/9j/4AAQ";
+
+    fn sample_library() -> Library {
+        Library::new(
+            vec![LibraryHistoryItem {
+                content: "https://example.com".to_string(),
+                qr_type: "url".to_string(),
+                label: Some("Site, \"quoted\"".to_string()),
+                style_json: format!(r#"{{"dotStyle":"dots","logo":"{}"}}"#, LOGO),
+                thumbnail: Some(PNG.to_string()),
+                pinned: true,
+                tags: vec!["promo".to_string(), "print".to_string()],
+                created_at: Some("2026-01-02T03:04:05Z".to_string()),
+                updated_at: Some("2026-01-03T03:04:05Z".to_string()),
+                redacted: false,
+            }],
+            vec![LibraryTemplate {
+                name: "Brand".to_string(),
+                style_json: r#"{"foreground":"black"}"#.to_string(),
+                preview: Some(PNG.to_string()),
+                is_default: true,
+                created_at: Some("2026-01-01T00:00:00Z".to_string()),
+            }],
+        )
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let library = sample_library();
+        let parsed = library_from_json(&library_to_json(&library).unwrap()).unwrap();
+        assert_eq!(parsed.history, library.history);
+        assert_eq!(parsed.templates, library.templates);
+
+        assert!(library_from_json(br#"{"format":"other","version":1,"exportedAt":""}"#).is_err());
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let library = sample_library();
+        let parsed = library_from_csv(&library_to_csv(&library).unwrap()).unwrap();
+
+        let mut expected = library.history[0].clone();
+        expected.thumbnail = None; // CSV carries no images
+        assert_eq!(parsed.history, vec![expected]);
+
+        let mut expected = library.templates[0].clone();
+        expected.preview = None;
+        assert_eq!(parsed.templates, vec![expected]);
+    }
+
+    #[test]
+    fn test_csv_minimal_columns() {
+        let parsed = library_from_csv(b"Content,Label\nWIFI:S:Cafe;;,Cafe\n,skipped\n").unwrap();
+        assert_eq!(parsed.history.len(), 1);
+        assert_eq!(parsed.history[0].qr_type, "wifi");
+        assert_eq!(parsed.history[0].label.as_deref(), Some("Cafe"));
+        assert_eq!(parsed.history[0].style_json, "{}");
+
+        assert!(library_from_csv(b"label\nonly\n").is_err());
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let library = sample_library();
+        let bytes = library_to_bundle(&library).unwrap();
+
+        // Images are files in the archive, stored once each
+        let mut archive = ZipArchive::new(Cursor::new(&bytes)).unwrap();
+        let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        assert_eq!(names.len(), 3);
+        assert!(names
+            .iter()
+            .any(|n| n.starts_with("images/") && n.ends_with(".jpg")));
+        let mut manifest = String::new();
+        archive
+            .by_name(BUNDLE_MANIFEST)
+            .unwrap()
+            .read_to_string(&mut manifest)
+            .unwrap();
+        assert!(!manifest.contains("base64"));
+
+        let parsed = library_from_bundle(&bytes).unwrap();
+        assert_eq!(parsed.history, library.history);
+        assert_eq!(parsed.templates, library.templates);
+    }
+
+    #[test]
+    fn test_detects_format() {
+        let zip = library_to_bundle(&sample_library()).unwrap();
+        assert_eq!(
+            LibraryFileFormat::detect(Path::new("export"), &zip),
+            LibraryFileFormat::Qrfoundry
+        );
+        assert_eq!(
+            LibraryFileFormat::detect(Path::new("export"), b"  {\"format\":"),
+            LibraryFileFormat::Json
+        );
+        assert_eq!(
+            LibraryFileFormat::detect(Path::new("export.CSV"), b"{"),
+            LibraryFileFormat::Csv
+        );
+    }
+}
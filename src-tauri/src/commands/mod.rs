@@ -1,11 +1,47 @@
+pub mod backup;
 pub mod batch;
+pub mod blobs;
+pub mod bulk_scan;
+pub mod encryption;
 pub mod export;
+pub mod folders;
+pub mod frames;
 pub mod history;
+pub mod library;
+pub mod payload;
+pub mod pdf_scan;
+pub mod preprocess;
+pub mod print_size;
+pub mod scans;
+pub mod settings;
+pub mod storage;
+pub mod sync;
+pub mod tags;
 pub mod templates;
+pub mod trash;
+pub mod url_risk;
 pub mod validate;
+pub mod workspaces;
 
+pub use backup::*;
 pub use batch::*;
+pub use blobs::*;
+pub use bulk_scan::*;
+pub use encryption::*;
 pub use export::*;
+pub use folders::*;
+pub use frames::*;
 pub use history::*;
+pub use library::*;
+pub use pdf_scan::*;
+pub use print_size::*;
+pub use scans::*;
+pub use settings::*;
+pub use storage::*;
+pub use sync::*;
+pub use tags::*;
 pub use templates::*;
+pub use trash::*;
+pub use url_risk::*;
 pub use validate::*;
+pub use workspaces::*;
@@ -0,0 +1,974 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+/// Structured form of a decoded payload, shaped to prefill the generator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ParsedPayload {
+    Wifi(WifiPayload),
+    Vcard(Box<ContactPayload>),
+    Email(EmailPayload),
+    Sms(SmsPayload),
+    Phone(PhonePayload),
+    Geo(GeoPayload),
+    Calendar(EventPayload),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WifiPayload {
+    pub ssid: String,
+    pub password: Option<String>,
+    pub encryption: String, // "WPA" | "WEP" | "nopass" | other raw value
+    pub hidden: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactPayload {
+    pub format: String, // "vcard" | "mecard"
+    pub version: Option<String>,
+    pub first_name: String,
+    pub last_name: String,
+    pub full_name: Option<String>,
+    pub organization: Option<String>,
+    pub title: Option<String>,
+    pub phones: Vec<TypedValue>,
+    pub emails: Vec<TypedValue>,
+    pub url: Option<String>,
+    pub address: Option<ContactAddress>,
+    pub birthday: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedValue {
+    pub value: String,
+    pub types: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactAddress {
+    pub street: String,
+    pub city: String,
+    pub state: String,
+    pub zip: String,
+    pub country: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailPayload {
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub bcc: Vec<String>,
+    pub subject: Option<String>,
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmsPayload {
+    pub phone: String,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhonePayload {
+    pub phone: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoPayload {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<f64>,
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventPayload {
+    pub summary: Option<String>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub start: Option<String>, // ISO 8601; trailing "Z" when UTC
+    pub end: Option<String>,
+    pub all_day: bool,
+    pub timezone: Option<String>, // TZID for local times
+}
+
+/// Parse decoded QR content into structured data, if it's a known payload
+pub fn parse_payload(content: &str) -> Option<ParsedPayload> {
+    let trimmed = content.trim();
+    let lower = trimmed.to_lowercase();
+
+    if lower.starts_with("wifi:") {
+        parse_wifi(&trimmed[5..]).map(ParsedPayload::Wifi)
+    } else if lower.starts_with("begin:vcard") {
+        Some(ParsedPayload::Vcard(Box::new(parse_vcard(trimmed))))
+    } else if lower.starts_with("mecard:") {
+        Some(ParsedPayload::Vcard(Box::new(parse_mecard(&trimmed[7..]))))
+    } else if lower.starts_with("begin:vevent") || lower.starts_with("begin:vcalendar") {
+        Some(ParsedPayload::Calendar(parse_vevent(trimmed)))
+    } else if lower.starts_with("mailto:") {
+        Some(ParsedPayload::Email(parse_mailto(&trimmed[7..])))
+    } else if lower.starts_with("matmsg:") {
+        Some(ParsedPayload::Email(parse_matmsg(&trimmed[7..])))
+    } else if lower.starts_with("smsto:") {
+        Some(ParsedPayload::Sms(parse_smsto(&trimmed[6..])))
+    } else if lower.starts_with("sms:") {
+        Some(ParsedPayload::Sms(parse_sms(&trimmed[4..])))
+    } else if lower.starts_with("tel:") {
+        Some(ParsedPayload::Phone(PhonePayload {
+            phone: percent_decode(&trimmed[4..]),
+        }))
+    } else if lower.starts_with("geo:") {
+        parse_geo(&trimmed[4..]).map(ParsedPayload::Geo)
+    } else {
+        None
+    }
+}
+
+// --- WiFi and MECARD-style "KEY:value;" lists ---
+
+/// Split on `sep` where it isn't escaped with a backslash, keeping escapes intact
+fn split_unescaped(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == sep {
+            parts.push(&s[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Drop backslashes used to escape `\ ; , : "`
+fn unescape_field(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next) = chars.next() {
+                out.push(next);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Parse `KEY:value;KEY:value;;` into upper-cased keys and raw values
+fn parse_fields(body: &str) -> Vec<(String, &str)> {
+    split_unescaped(body, ';')
+        .into_iter()
+        .filter_map(|field| {
+            let (key, value) = field.split_once(':')?;
+            Some((key.trim().to_uppercase(), value))
+        })
+        .collect()
+}
+
+fn parse_wifi(body: &str) -> Option<WifiPayload> {
+    let mut ssid = None;
+    let mut password = None;
+    let mut encryption = String::new();
+    let mut hidden = false;
+
+    for (key, raw) in parse_fields(body) {
+        match key.as_str() {
+            "S" => ssid = Some(unquote_wifi(raw)),
+            "P" => password = Some(unquote_wifi(raw)),
+            "T" => encryption = unescape_field(raw),
+            "H" => hidden = matches!(raw.to_lowercase().as_str(), "true" | "1"),
+            _ => {}
+        }
+    }
+
+    let encryption = match encryption.to_uppercase().as_str() {
+        "" | "NOPASS" => "nopass".to_string(),
+        "WPA" | "WPA2" | "WPA3" | "SAE" => "WPA".to_string(),
+        "WEP" => "WEP".to_string(),
+        other => other.to_string(),
+    };
+
+    Some(WifiPayload {
+        ssid: ssid?,
+        password: password.filter(|p| !p.is_empty()),
+        encryption,
+        hidden,
+    })
+}
+
+/// Values that look like hex may be wrapped in double quotes
+fn unquote_wifi(raw: &str) -> String {
+    let quoted =
+        raw.len() >= 2 && raw.starts_with('"') && raw.ends_with('"') && !raw.ends_with("\\\"");
+    if quoted {
+        unescape_field(&raw[1..raw.len() - 1])
+    } else {
+        unescape_field(raw)
+    }
+}
+
+fn parse_mecard(body: &str) -> ContactPayload {
+    let mut contact = ContactPayload {
+        format: "mecard".to_string(),
+        ..Default::default()
+    };
+
+    for (key, raw) in parse_fields(body) {
+        let value = unescape_field(raw);
+        if value.is_empty() {
+            continue;
+        }
+
+        match key.as_str() {
+            "N" => {
+                // "Last,First"
+                let parts: Vec<String> = split_unescaped(raw, ',')
+                    .into_iter()
+                    .map(unescape_field)
+                    .collect();
+                contact.last_name = parts.first().cloned().unwrap_or_default();
+                contact.first_name = parts.get(1).cloned().unwrap_or_default();
+            }
+            "TEL" | "TEL-AV" => contact.phones.push(TypedValue {
+                value,
+                types: vec![],
+            }),
+            "EMAIL" => contact.emails.push(TypedValue {
+                value,
+                types: vec![],
+            }),
+            "ORG" => contact.organization = Some(value),
+            "TITLE" => contact.title = Some(value),
+            "URL" => contact.url = Some(value),
+            "NOTE" => contact.note = Some(value),
+            "BDAY" => contact.birthday = Some(format_date(&value).unwrap_or(value)),
+            "ADR" => {
+                // PO box, extended, street, city, state, zip, country
+                let parts: Vec<String> = split_unescaped(raw, ',')
+                    .into_iter()
+                    .map(|p| unescape_field(p).trim().to_string())
+                    .collect();
+                contact.address = Some(address_from_parts(&parts));
+            }
+            _ => {}
+        }
+    }
+
+    if contact.full_name.is_none() {
+        contact.full_name = join_name(&contact.first_name, &contact.last_name);
+    }
+
+    contact
+}
+
+// --- vCard / iCalendar content lines ---
+
+/// A single `NAME;PARAM=x:value` content line
+struct ContentLine {
+    name: String,
+    types: Vec<String>,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Normalize line endings and unfold continuation lines
+///
+/// Handles both RFC folding (leading whitespace) and vCard 2.1
+/// quoted-printable soft line breaks (trailing `=`).
+fn unfold_lines(content: &str) -> Vec<String> {
+    let normalized = content.replace("\r\n", "\n").replace('\r', "\n");
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in normalized.split('\n') {
+        if let Some(last) = lines.last_mut() {
+            let soft_break =
+                last.ends_with('=') && last.to_uppercase().contains("QUOTED-PRINTABLE");
+            if soft_break {
+                last.pop();
+                last.push_str(line.trim_start());
+                continue;
+            }
+            if line.starts_with(' ') || line.starts_with('\t') {
+                last.push_str(&line[1..]);
+                continue;
+            }
+        }
+        if !line.trim().is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+
+    lines
+}
+
+fn parse_content_line(line: &str) -> Option<ContentLine> {
+    // The first colon outside a quoted parameter value separates the value
+    let mut in_quotes = false;
+    let split = line.char_indices().find(|&(_, c)| {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        c == ':' && !in_quotes
+    })?;
+    let (head, value) = (&line[..split.0], &line[split.0 + 1..]);
+
+    let mut segments = head.split(';');
+    let name = segments.next()?.trim();
+    // Drop "item1." style group prefixes
+    let name = name.rsplit('.').next().unwrap_or(name).to_uppercase();
+
+    let mut types = Vec::new();
+    let mut params = Vec::new();
+    let mut quoted_printable = false;
+
+    for segment in segments {
+        match segment.split_once('=') {
+            Some((key, val)) => {
+                let key = key.trim().to_uppercase();
+                let val = val.trim().trim_matches('"');
+                if key == "TYPE" {
+                    types.extend(val.split(',').map(|t| t.trim().to_lowercase()));
+                } else if key == "ENCODING" && val.eq_ignore_ascii_case("QUOTED-PRINTABLE") {
+                    quoted_printable = true;
+                } else {
+                    params.push((key, val.to_string()));
+                }
+            }
+            // vCard 2.1 allows bare parameters such as "TEL;WORK;VOICE"
+            None if segment.eq_ignore_ascii_case("QUOTED-PRINTABLE") => quoted_printable = true,
+            None => types.push(segment.trim().to_lowercase()),
+        }
+    }
+
+    let value = if quoted_printable {
+        decode_quoted_printable(value)
+    } else {
+        value.to_string()
+    };
+
+    Some(ContentLine {
+        name,
+        types,
+        params,
+        value,
+    })
+}
+
+fn decode_quoted_printable(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'=' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Unescape vCard 3.0/4.0 and iCalendar text values
+fn unescape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(next) => out.push(next),
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Split a structured value (N, ADR) into unescaped components
+fn structured_parts(value: &str) -> Vec<String> {
+    split_unescaped(value, ';')
+        .into_iter()
+        .map(|p| unescape_text(p).trim().to_string())
+        .collect()
+}
+
+fn parse_vcard(content: &str) -> ContactPayload {
+    let mut contact = ContactPayload {
+        format: "vcard".to_string(),
+        ..Default::default()
+    };
+
+    for line in unfold_lines(content) {
+        let Some(line) = parse_content_line(&line) else {
+            continue;
+        };
+        if line.name == "END" {
+            break;
+        }
+
+        let text = unescape_text(&line.value).trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+
+        match line.name.as_str() {
+            "VERSION" => contact.version = Some(text),
+            "FN" => contact.full_name = Some(text),
+            "N" => {
+                // Family; Given; Additional; Prefix; Suffix
+                let parts = structured_parts(&line.value);
+                contact.last_name = parts.first().cloned().unwrap_or_default();
+                contact.first_name = parts.get(1).cloned().unwrap_or_default();
+            }
+            "ORG" => {
+                let parts = structured_parts(&line.value);
+                let org: Vec<String> = parts.into_iter().filter(|p| !p.is_empty()).collect();
+                contact.organization = Some(org.join(", "));
+            }
+            "TITLE" => contact.title = Some(text),
+            "TEL" => contact.phones.push(TypedValue {
+                value: strip_uri_scheme(&text, "tel:"),
+                types: line.types,
+            }),
+            "EMAIL" => contact.emails.push(TypedValue {
+                value: strip_uri_scheme(&text, "mailto:"),
+                types: line.types,
+            }),
+            // Keep the first URL and address when several are listed
+            "URL" if contact.url.is_none() => contact.url = Some(text),
+            "ADR" if contact.address.is_none() => {
+                contact.address = Some(address_from_parts(&structured_parts(&line.value)));
+            }
+            "BDAY" => contact.birthday = Some(format_date(&text).unwrap_or(text)),
+            "NOTE" => contact.note = Some(text),
+            _ => {}
+        }
+    }
+
+    if contact.full_name.is_none() {
+        contact.full_name = join_name(&contact.first_name, &contact.last_name);
+    }
+
+    contact
+}
+
+fn parse_vevent(content: &str) -> EventPayload {
+    let mut event = EventPayload::default();
+    let mut in_event = !content.to_lowercase().starts_with("begin:vcalendar");
+
+    for line in unfold_lines(content) {
+        let Some(line) = parse_content_line(&line) else {
+            continue;
+        };
+        let text = unescape_text(&line.value).trim().to_string();
+
+        match line.name.as_str() {
+            "BEGIN" if text.eq_ignore_ascii_case("VEVENT") => in_event = true,
+            "END" if text.eq_ignore_ascii_case("VEVENT") => break,
+            _ if !in_event || text.is_empty() => {}
+            "SUMMARY" => event.summary = Some(text),
+            "LOCATION" => event.location = Some(text),
+            "DESCRIPTION" => event.description = Some(text),
+            "DTSTART" | "DTEND" => {
+                let parsed = parse_ical_datetime(&text);
+                if line.name == "DTSTART" {
+                    event.all_day = parsed.as_ref().is_some_and(|(_, all_day)| *all_day);
+                    event.timezone = line.param("TZID").map(str::to_string);
+                    event.start = parsed.map(|(value, _)| value);
+                } else {
+                    event.end = parsed.map(|(value, _)| value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    event
+}
+
+/// Convert iCalendar DATE / DATE-TIME values to ISO 8601
+///
+/// Returns the formatted value and whether it was a date-only (all-day) value.
+fn parse_ical_datetime(value: &str) -> Option<(String, bool)> {
+    if let Some(date) = format_date(value) {
+        return Some((date, true));
+    }
+
+    let (local, utc) = match value.strip_suffix('Z') {
+        Some(local) => (local, true),
+        None => (value, false),
+    };
+
+    let datetime = NaiveDateTime::parse_from_str(local, "%Y%m%dT%H%M%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(local, "%Y%m%dT%H%M"))
+        .ok()?;
+    let formatted = datetime.format("%Y-%m-%dT%H:%M:%S").to_string();
+
+    Some((if utc { formatted + "Z" } else { formatted }, false))
+}
+
+/// Normalize "YYYYMMDD" or "YYYY-MM-DD" to "YYYY-MM-DD"
+fn format_date(value: &str) -> Option<String> {
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+        .ok()
+        .map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+/// Map PO box, extended, street, city, region, postal code, country components
+fn address_from_parts(parts: &[String]) -> ContactAddress {
+    let get = |i: usize| parts.get(i).cloned().unwrap_or_default();
+
+    // Fold PO box and extended address into the street line
+    let street: Vec<String> = [get(0), get(1), get(2)]
+        .into_iter()
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    ContactAddress {
+        street: street.join(", "),
+        city: get(3),
+        state: get(4),
+        zip: get(5),
+        country: get(6),
+    }
+}
+
+fn join_name(first: &str, last: &str) -> Option<String> {
+    let name = [first, last]
+        .iter()
+        .filter(|p| !p.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!name.is_empty()).then_some(name)
+}
+
+fn strip_uri_scheme(value: &str, scheme: &str) -> String {
+    if value.to_lowercase().starts_with(scheme) {
+        value[scheme.len()..].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+// --- URI schemes ---
+
+fn percent_decode(s: &str) -> String {
+    urlencoding::decode(s)
+        .map(|decoded| decoded.into_owned())
+        .unwrap_or_else(|_| s.to_string())
+}
+
+/// Split a URI body into its path and decoded `key=value` query pairs
+fn split_query(rest: &str) -> (&str, Vec<(String, String)>) {
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let pairs = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key.to_lowercase(), percent_decode(value))
+        })
+        .collect();
+    (path, pairs)
+}
+
+fn split_addresses(s: &str) -> Vec<String> {
+    percent_decode(s)
+        .split(',')
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
+fn parse_mailto(rest: &str) -> EmailPayload {
+    let (path, query) = split_query(rest);
+    let mut email = EmailPayload {
+        to: split_addresses(path),
+        ..Default::default()
+    };
+
+    for (key, value) in query {
+        match key.as_str() {
+            "to" => email.to.extend(split_addresses(&value)),
+            "cc" => email.cc.extend(split_addresses(&value)),
+            "bcc" => email.bcc.extend(split_addresses(&value)),
+            "subject" => email.subject = Some(value),
+            "body" => email.body = Some(value),
+            _ => {}
+        }
+    }
+
+    email
+}
+
+fn parse_matmsg(body: &str) -> EmailPayload {
+    let mut email = EmailPayload::default();
+
+    for (key, raw) in parse_fields(body) {
+        let value = unescape_field(raw);
+        match key.as_str() {
+            "TO" => email.to.extend(split_addresses(&value)),
+            "SUB" => email.subject = Some(value),
+            "BODY" => email.body = Some(value),
+            _ => {}
+        }
+    }
+
+    email
+}
+
+fn parse_sms(rest: &str) -> SmsPayload {
+    let (path, query) = split_query(rest);
+    let message = query
+        .into_iter()
+        .find(|(key, _)| key == "body")
+        .map(|(_, value)| value);
+
+    SmsPayload {
+        phone: percent_decode(path.trim_end_matches(';')),
+        message,
+    }
+}
+
+/// `SMSTO:number:message`
+fn parse_smsto(rest: &str) -> SmsPayload {
+    let (phone, message) = match rest.split_once(':') {
+        Some((phone, message)) => (phone, Some(message.to_string())),
+        None => (rest, None),
+    };
+
+    SmsPayload {
+        phone: phone.to_string(),
+        message: message.filter(|m| !m.is_empty()),
+    }
+}
+
+/// `geo:lat,lng[,alt][;params][?q=query]`
+fn parse_geo(rest: &str) -> Option<GeoPayload> {
+    let (path, query) = split_query(rest);
+    let coords = path.split(';').next().unwrap_or(path);
+    let mut parts = coords.split(',').map(|p| p.trim().parse::<f64>());
+
+    let latitude = parts.next()?.ok()?;
+    let longitude = parts.next()?.ok()?;
+    let altitude = parts.next().and_then(|a| a.ok());
+
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return None;
+    }
+
+    let query = query
+        .into_iter()
+        .find(|(key, _)| key == "q")
+        .map(|(_, value)| value.replace('+', " "))
+        .filter(|q| !q.is_empty());
+
+    Some(GeoPayload {
+        latitude,
+        longitude,
+        altitude,
+        query,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wifi(content: &str) -> WifiPayload {
+        match parse_payload(content) {
+            Some(ParsedPayload::Wifi(w)) => w,
+            other => panic!("expected wifi, got {:?}", other),
+        }
+    }
+
+    fn contact(content: &str) -> ContactPayload {
+        match parse_payload(content) {
+            Some(ParsedPayload::Vcard(c)) => *c,
+            other => panic!("expected contact, got {:?}", other),
+        }
+    }
+
+    fn event(content: &str) -> EventPayload {
+        match parse_payload(content) {
+            Some(ParsedPayload::Calendar(e)) => e,
+            other => panic!("expected event, got {:?}", other),
+        }
+    }
+
+    fn email(content: &str) -> EmailPayload {
+        match parse_payload(content) {
+            Some(ParsedPayload::Email(e)) => e,
+            other => panic!("expected email, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_wifi_basic() {
+        let w = wifi("WIFI:T:WPA;S:HomeNet;P:secret123;;");
+        assert_eq!(w.ssid, "HomeNet");
+        assert_eq!(w.password, Some("secret123".to_string()));
+        assert_eq!(w.encryption, "WPA");
+        assert!(!w.hidden);
+    }
+
+    #[test]
+    fn test_parse_wifi_escaping_and_order() {
+        let w = wifi(r#"WIFI:S:My\;Net\:work;H:true;P:pa\\ss\,word\";T:WEP;;"#);
+        assert_eq!(w.ssid, "My;Net:work");
+        assert_eq!(w.password, Some(r#"pa\ss,word""#.to_string()));
+        assert_eq!(w.encryption, "WEP");
+        assert!(w.hidden);
+    }
+
+    #[test]
+    fn test_parse_wifi_open_and_quoted() {
+        let w = wifi(r#"wifi:T:nopass;S:"CAFE0123";;"#);
+        assert_eq!(w.ssid, "CAFE0123");
+        assert_eq!(w.password, None);
+        assert_eq!(w.encryption, "nopass");
+
+        let w = wifi("WIFI:S:Guest;;");
+        assert_eq!(w.encryption, "nopass");
+    }
+
+    #[test]
+    fn test_parse_wifi_requires_ssid() {
+        assert!(parse_payload("WIFI:T:WPA;P:secret;;").is_none());
+    }
+
+    #[test]
+    fn test_parse_vcard_30() {
+        let c = contact(
+            "BEGIN:VCARD\nVERSION:3.0\nN:Doe;John;;;\nFN:John Doe\nORG:Acme\\, Inc.;Sales\n\
+             TITLE:Manager\nTEL;TYPE=work,voice:+15551234567\nEMAIL:john@example.com\n\
+             URL:https://example.com\nADR;TYPE=home:;;123 Main St;Springfield;IL;62701;USA\n\
+             NOTE:Line one\\nLine two\nEND:VCARD",
+        );
+
+        assert_eq!(c.format, "vcard");
+        assert_eq!(c.version, Some("3.0".to_string()));
+        assert_eq!(c.first_name, "John");
+        assert_eq!(c.last_name, "Doe");
+        assert_eq!(c.full_name, Some("John Doe".to_string()));
+        assert_eq!(c.organization, Some("Acme, Inc., Sales".to_string()));
+        assert_eq!(c.title, Some("Manager".to_string()));
+        assert_eq!(c.phones[0].value, "+15551234567");
+        assert_eq!(c.phones[0].types, vec!["work", "voice"]);
+        assert_eq!(c.emails[0].value, "john@example.com");
+        assert_eq!(c.url, Some("https://example.com".to_string()));
+        let address = c.address.unwrap();
+        assert_eq!(address.street, "123 Main St");
+        assert_eq!(address.city, "Springfield");
+        assert_eq!(address.zip, "62701");
+        assert_eq!(c.note, Some("Line one\nLine two".to_string()));
+    }
+
+    #[test]
+    fn test_parse_vcard_21_quoted_printable() {
+        let c = contact(
+            "BEGIN:VCARD\r\nVERSION:2.1\r\nN;CHARSET=UTF-8;ENCODING=QUOTED-PRINTABLE:M=C3=BCller;J=\r\n\
+             =C3=BCrgen\r\nTEL;WORK;VOICE:555-1234\r\nEND:VCARD",
+        );
+
+        assert_eq!(c.version, Some("2.1".to_string()));
+        assert_eq!(c.last_name, "Müller");
+        assert_eq!(c.first_name, "Jürgen");
+        assert_eq!(c.full_name, Some("Jürgen Müller".to_string()));
+        assert_eq!(c.phones[0].types, vec!["work", "voice"]);
+    }
+
+    #[test]
+    fn test_parse_vcard_40_uri_values_and_folding() {
+        let c = contact(
+            "BEGIN:VCARD\nVERSION:4.0\nFN:Jane\n  Smith\nTEL;VALUE=uri;TYPE=\"cell,text\":tel:+1-555-0100\n\
+             item1.EMAIL:jane@example.com\nBDAY:19850412\nEND:VCARD",
+        );
+
+        assert_eq!(c.full_name, Some("Jane Smith".to_string()));
+        assert_eq!(c.phones[0].value, "+1-555-0100");
+        assert_eq!(c.phones[0].types, vec!["cell", "text"]);
+        assert_eq!(c.emails[0].value, "jane@example.com");
+        assert_eq!(c.birthday, Some("1985-04-12".to_string()));
+    }
+
+    #[test]
+    fn test_parse_mecard() {
+        let c = contact(
+            "MECARD:N:Doe,John;TEL:+15551234567;EMAIL:john@example.com;ORG:Acme;\
+             ADR:,,1 Main St,Town,CA,90210,USA;NOTE:Hi\\; there;;",
+        );
+
+        assert_eq!(c.format, "mecard");
+        assert_eq!(c.last_name, "Doe");
+        assert_eq!(c.first_name, "John");
+        assert_eq!(c.full_name, Some("John Doe".to_string()));
+        assert_eq!(c.phones[0].value, "+15551234567");
+        assert_eq!(c.organization, Some("Acme".to_string()));
+        assert_eq!(c.address.unwrap().state, "CA");
+        assert_eq!(c.note, Some("Hi; there".to_string()));
+    }
+
+    #[test]
+    fn test_parse_vevent_utc() {
+        let e = event(
+            "BEGIN:VEVENT\nSUMMARY:Team Sync\nLOCATION:Room 4\\, HQ\nDTSTART:20240115T090000Z\n\
+             DTEND:20240115T100000Z\nEND:VEVENT",
+        );
+
+        assert_eq!(e.summary, Some("Team Sync".to_string()));
+        assert_eq!(e.location, Some("Room 4, HQ".to_string()));
+        assert_eq!(e.start, Some("2024-01-15T09:00:00Z".to_string()));
+        assert_eq!(e.end, Some("2024-01-15T10:00:00Z".to_string()));
+        assert!(!e.all_day);
+        assert!(e.timezone.is_none());
+    }
+
+    #[test]
+    fn test_parse_vevent_all_day_and_tzid() {
+        let e = event("BEGIN:VEVENT\nDTSTART;VALUE=DATE:20240301\nEND:VEVENT");
+        assert_eq!(e.start, Some("2024-03-01".to_string()));
+        assert!(e.all_day);
+
+        let e = event("BEGIN:VEVENT\nDTSTART;TZID=Europe/Berlin:20240301T143000\nEND:VEVENT");
+        assert_eq!(e.start, Some("2024-03-01T14:30:00".to_string()));
+        assert_eq!(e.timezone, Some("Europe/Berlin".to_string()));
+    }
+
+    #[test]
+    fn test_parse_vevent_inside_vcalendar() {
+        let e = event(
+            "BEGIN:VCALENDAR\nVERSION:2.0\nSUMMARY:Not the event\nBEGIN:VEVENT\nSUMMARY:Launch\n\
+             END:VEVENT\nEND:VCALENDAR",
+        );
+        assert_eq!(e.summary, Some("Launch".to_string()));
+    }
+
+    #[test]
+    fn test_parse_vevent_invalid_date() {
+        let e = event("BEGIN:VEVENT\nDTSTART:tomorrow\nEND:VEVENT");
+        assert!(e.start.is_none());
+    }
+
+    #[test]
+    fn test_parse_mailto() {
+        let e = email("mailto:a@example.com,b@example.com?subject=Hello%20there&body=Line%0A2&cc=c@example.com");
+        assert_eq!(e.to, vec!["a@example.com", "b@example.com"]);
+        assert_eq!(e.cc, vec!["c@example.com"]);
+        assert_eq!(e.subject, Some("Hello there".to_string()));
+        assert_eq!(e.body, Some("Line\n2".to_string()));
+    }
+
+    #[test]
+    fn test_parse_matmsg() {
+        let e = email("MATMSG:TO:a@example.com;SUB:Hi;BODY:See you\\; soon;;");
+        assert_eq!(e.to, vec!["a@example.com"]);
+        assert_eq!(e.subject, Some("Hi".to_string()));
+        assert_eq!(e.body, Some("See you; soon".to_string()));
+    }
+
+    #[test]
+    fn test_parse_sms() {
+        let expected = ParsedPayload::Sms(SmsPayload {
+            phone: "+15551234567".to_string(),
+            message: Some("Hello world".to_string()),
+        });
+
+        assert_eq!(
+            parse_payload("sms:+15551234567?body=Hello%20world"),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            parse_payload("sms:+15551234567;?body=Hello%20world"),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            parse_payload("SMSTO:+15551234567:Hello world"),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_parse_tel() {
+        assert_eq!(
+            parse_payload("tel:+1%20555%20123"),
+            Some(ParsedPayload::Phone(PhonePayload {
+                phone: "+1 555 123".to_string()
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_geo() {
+        match parse_payload("geo:37.7749,-122.4194,12;u=35?q=Golden+Gate") {
+            Some(ParsedPayload::Geo(g)) => {
+                assert_eq!(g.latitude, 37.7749);
+                assert_eq!(g.longitude, -122.4194);
+                assert_eq!(g.altitude, Some(12.0));
+                assert_eq!(g.query, Some("Golden Gate".to_string()));
+            }
+            other => panic!("expected geo, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_geo_invalid() {
+        assert!(parse_payload("geo:95,0").is_none());
+        assert!(parse_payload("geo:abc,def").is_none());
+        assert!(parse_payload("geo:10").is_none());
+    }
+
+    #[test]
+    fn test_parse_unstructured() {
+        assert!(parse_payload("https://example.com").is_none());
+        assert!(parse_payload("Hello World").is_none());
+    }
+
+    #[test]
+    fn test_parsed_payload_serialization() {
+        let payload = parse_payload("WIFI:T:WPA;S:Net;P:pw;H:true;;").unwrap();
+        let json = serde_json::to_value(&payload).unwrap();
+
+        assert_eq!(json["type"], "wifi");
+        assert_eq!(json["ssid"], "Net");
+        assert_eq!(json["hidden"], true);
+    }
+}
@@ -0,0 +1,271 @@
+use crate::qr_type::detect_qr_type;
+use image::DynamicImage;
+use pdfium_render::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+use tauri_plugin_dialog::DialogExt;
+
+use super::preprocess::decode_all_with_pipeline;
+
+/// Default rasterization density; enough for codes printed at 10mm and up
+const DEFAULT_PDF_DPI: u32 = 200;
+const MIN_PDF_DPI: u32 = 72;
+const MAX_PDF_DPI: u32 = 600;
+
+const POINTS_PER_INCH: f64 = 72.0;
+const MM_PER_INCH: f64 = 25.4;
+
+/// A code found on a page, positioned in millimetres from the page's top-left corner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfCode {
+    pub index: usize, // 1-based position of the code on the page
+    pub content: String,
+    pub qr_type: String,
+    pub version: usize,
+    pub ecc: String,
+    pub x_mm: f64,
+    pub y_mm: f64,
+    pub width_mm: f64,
+    pub height_mm: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfPageResult {
+    pub page: usize, // 1-based
+    pub width_mm: f64,
+    pub height_mm: f64,
+    pub codes: Vec<PdfCode>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfScanResult {
+    pub success: bool,
+    pub dpi: u32,
+    pub page_count: usize,
+    pub pages: Vec<PdfPageResult>,
+    pub error: Option<String>,
+}
+
+/// Open a file picker to select a PDF for scanning
+#[tauri::command]
+pub async fn pick_pdf_file(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let file_path = app
+        .dialog()
+        .file()
+        .add_filter("PDF Documents", &["pdf"])
+        .blocking_pick_file();
+
+    match file_path {
+        Some(path) => Ok(Some(path.as_path().unwrap().to_string_lossy().to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Rasterize every page of a PDF and decode the QR codes on it
+#[tauri::command]
+pub async fn scan_qr_from_pdf(
+    app: tauri::AppHandle,
+    file_path: String,
+    dpi: Option<u32>,
+    password: Option<String>,
+) -> Result<PdfScanResult, String> {
+    let dpi = dpi
+        .unwrap_or(DEFAULT_PDF_DPI)
+        .clamp(MIN_PDF_DPI, MAX_PDF_DPI);
+    let search_dirs = library_search_dirs(&app);
+
+    let scanned = tauri::async_runtime::spawn_blocking(move || {
+        let pdfium = load_pdfium(&search_dirs)?;
+        scan_document(&pdfium, Path::new(&file_path), dpi, password.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Failed to scan PDF: {}", e))?;
+
+    match scanned {
+        Ok(pages) => Ok(PdfScanResult {
+            success: true,
+            dpi,
+            page_count: pages.len(),
+            pages,
+            error: None,
+        }),
+        Err(e) => Ok(PdfScanResult {
+            success: false,
+            dpi,
+            page_count: 0,
+            pages: vec![],
+            error: Some(e),
+        }),
+    }
+}
+
+/// Folders checked for a bundled PDFium before falling back to the system copy
+fn library_search_dirs(app: &tauri::AppHandle) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Ok(resources) = app.path().resource_dir() {
+        dirs.push(resources.join("pdfium"));
+        dirs.push(resources);
+    }
+    if let Some(exe_dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        dirs.push(exe_dir);
+    }
+
+    dirs
+}
+
+fn load_pdfium(search_dirs: &[PathBuf]) -> Result<Pdfium, String> {
+    for dir in search_dirs {
+        let library = Pdfium::pdfium_platform_library_name_at_path(dir);
+        if library.exists() {
+            return Pdfium::bind_to_library(&library)
+                .map(Pdfium::new)
+                .map_err(|e| format!("Failed to load PDF renderer: {}", e));
+        }
+    }
+
+    Pdfium::bind_to_system_library()
+        .map(Pdfium::new)
+        .map_err(|e| format!("PDF renderer (PDFium) is not installed: {}", e))
+}
+
+fn scan_document(
+    pdfium: &Pdfium,
+    path: &Path,
+    dpi: u32,
+    password: Option<&str>,
+) -> Result<Vec<PdfPageResult>, String> {
+    let document = pdfium
+        .load_pdf_from_file(path, password)
+        .map_err(|e| format!("Failed to open PDF: {}", e))?;
+
+    let config = PdfRenderConfig::new().scale_page_by_factor(dpi as f32 / POINTS_PER_INCH as f32);
+
+    let pages = document
+        .pages()
+        .iter()
+        .enumerate()
+        .map(|(i, page)| {
+            let width_mm = points_to_mm(page.width().value as f64);
+            let height_mm = points_to_mm(page.height().value as f64);
+
+            let (codes, error) = match page.render_with_config(&config) {
+                Ok(bitmap) => match page_codes(&bitmap.as_image(), dpi) {
+                    Ok(codes) => (codes, None),
+                    Err(e) => (vec![], Some(e)),
+                },
+                Err(e) => (vec![], Some(format!("Failed to render page: {}", e))),
+            };
+
+            PdfPageResult {
+                page: i + 1,
+                width_mm,
+                height_mm,
+                codes,
+                error,
+            }
+        })
+        .collect();
+
+    Ok(pages)
+}
+
+/// Decode every code on a rendered page and convert its position to millimetres
+fn page_codes(img: &DynamicImage, dpi: u32) -> Result<Vec<PdfCode>, String> {
+    let px_to_mm = |px: u32| round_mm(px as f64 / dpi as f64 * MM_PER_INCH);
+
+    let codes = decode_all_with_pipeline(img)?;
+    Ok(codes
+        .into_iter()
+        .enumerate()
+        .map(|(i, code)| PdfCode {
+            index: i + 1,
+            qr_type: detect_qr_type(&code.content),
+            content: code.content,
+            version: code.version,
+            ecc: code.ecc_level.to_string(),
+            x_mm: px_to_mm(code.bounds.x),
+            y_mm: px_to_mm(code.bounds.y),
+            width_mm: px_to_mm(code.bounds.width),
+            height_mm: px_to_mm(code.bounds.height),
+        })
+        .collect())
+}
+
+fn points_to_mm(points: f64) -> f64 {
+    round_mm(points / POINTS_PER_INCH * MM_PER_INCH)
+}
+
+fn round_mm(mm: f64) -> f64 {
+    (mm * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{imageops, GrayImage, Luma};
+    use qrcode::{Color, QrCode};
+
+    fn render_qr(content: &str, scale: u32) -> GrayImage {
+        let code = QrCode::new(content.as_bytes()).unwrap();
+        let modules = code.width() as u32;
+        let colors = code.to_colors();
+        GrayImage::from_fn((modules + 8) * scale, (modules + 8) * scale, |x, y| {
+            let (mx, my) = (x / scale, y / scale);
+            let dark = (4..modules + 4).contains(&mx)
+                && (4..modules + 4).contains(&my)
+                && colors[((my - 4) * modules + (mx - 4)) as usize] == Color::Dark;
+            Luma([if dark { 0 } else { 255 }])
+        })
+    }
+
+    #[test]
+    fn test_page_codes_position_in_mm() {
+        // A4 at 100 DPI with a code placed 200px (50.8mm) from the top-left
+        let mut page = GrayImage::from_pixel(827, 1169, Luma([255]));
+        let code = render_qr("https://example.com/proof", 4);
+        imageops::replace(&mut page, &code, 200, 200);
+
+        let codes = page_codes(&DynamicImage::ImageLuma8(page), 100).unwrap();
+
+        assert_eq!(codes.len(), 1);
+        assert_eq!(codes[0].index, 1);
+        assert_eq!(codes[0].content, "https://example.com/proof");
+        assert_eq!(codes[0].qr_type, "url");
+        // The symbol starts after the 16px quiet zone
+        let expected_mm = 216.0 / 100.0 * MM_PER_INCH;
+        assert!((codes[0].x_mm - expected_mm).abs() < 1.5);
+        assert!((codes[0].y_mm - expected_mm).abs() < 1.5);
+        assert!(codes[0].width_mm > 20.0);
+    }
+
+    #[test]
+    fn test_page_codes_blank_page() {
+        let page = GrayImage::from_pixel(200, 300, Luma([255]));
+        assert!(page_codes(&DynamicImage::ImageLuma8(page), 100).is_err());
+    }
+
+    #[test]
+    fn test_points_to_mm() {
+        // US Letter and A4 widths
+        assert_eq!(points_to_mm(612.0), 215.9);
+        assert_eq!(points_to_mm(595.0), 209.9);
+    }
+
+    #[test]
+    fn test_load_pdfium_reports_missing_library() {
+        let dir = tempfile::tempdir().unwrap();
+        // Either a system copy exists or the error explains what's missing
+        if let Err(e) = load_pdfium(&[dir.path().to_path_buf()]) {
+            assert!(e.contains("PDFium"));
+        }
+    }
+}
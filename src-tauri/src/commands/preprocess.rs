@@ -0,0 +1,484 @@
+use image::{imageops, DynamicImage, GrayImage, ImageDecoder, ImageReader, Luma};
+use rqrr::{MetaData, Point, PreparedImage};
+use std::io::{BufRead, Seek};
+
+/// Images larger than this on their longest side are scanned downscaled first
+const MAX_SCAN_DIMENSION: u32 = 1600;
+
+/// Fraction of pixels clipped at each end when stretching contrast
+const CONTRAST_CLIP: f64 = 0.01;
+
+/// Offset below the local mean a pixel must fall to count as dark
+const ADAPTIVE_THRESHOLD_OFFSET: i64 = 10;
+
+/// Lazily builds the image for one preprocessing pass
+type PassBuilder<'a> = Box<dyn Fn() -> GrayImage + 'a>;
+
+/// Decode outcome for one detected grid, with the grid's corner points
+type GridDecode = (Result<(MetaData, String), String>, [Point; 4]);
+
+/// A successful decode and the preprocessing pass that produced it
+#[derive(Debug, Clone)]
+pub struct PipelineMatch {
+    pub pass: &'static str,
+    pub content: String,
+}
+
+/// One code found by `decode_all_with_pipeline`
+#[derive(Debug, Clone)]
+pub struct DecodedCode {
+    pub content: String,
+    pub version: usize,
+    pub ecc_level: &'static str,
+    pub bounds: CodeBounds,
+}
+
+/// Axis-aligned box around a code, in pixels of the original image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CodeBounds {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Decode an image honouring its EXIF orientation tag
+pub fn decode_oriented<R: BufRead + Seek>(reader: ImageReader<R>) -> Result<DynamicImage, String> {
+    let mut decoder = reader
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read image format: {}", e))?
+        .into_decoder()
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+
+    let orientation = decoder
+        .orientation()
+        .map_err(|e| format!("Failed to read orientation: {}", e))?;
+
+    let mut img = DynamicImage::from_decoder(decoder)
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    img.apply_orientation(orientation);
+
+    Ok(img)
+}
+
+/// Try progressively heavier preprocessing until a QR code decodes
+///
+/// Returns the first successful pass, or the most informative error seen.
+pub fn decode_with_pipeline(img: &DynamicImage) -> Result<PipelineMatch, String> {
+    run_pipeline(img, |pass, gray| {
+        decode_gray(gray).map(|found| found.map(|(_, content)| PipelineMatch { pass, content }))
+    })
+}
+
+/// Decode every QR code in the image using the first pass that reads any of them
+pub fn decode_all_with_pipeline(img: &DynamicImage) -> Result<Vec<DecodedCode>, String> {
+    run_pipeline(img, |pass, gray| {
+        // Map pass coordinates back onto the original image
+        let scale = img.width() as f64 / gray.width() as f64;
+        let mirror_width = pass.starts_with("mirrored").then_some(gray.width());
+
+        let mut codes = Vec::new();
+        let mut error = None;
+
+        for (result, corners) in decode_grids(gray) {
+            match result {
+                Ok((meta, content)) => {
+                    // The same symbol can be detected twice from overlapping finder patterns
+                    if !codes.iter().any(|c: &DecodedCode| c.content == content) {
+                        codes.push(DecodedCode {
+                            content,
+                            version: meta.version.0,
+                            ecc_level: ecc_level_name(meta.ecc_level),
+                            bounds: bounding_box(&corners, scale, mirror_width),
+                        });
+                    }
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+
+        match error {
+            Some(e) if codes.is_empty() => Err(e),
+            _ => Ok((!codes.is_empty()).then_some(codes)),
+        }
+    })
+}
+
+/// Run `attempt` on each preprocessing pass until one returns a value
+fn run_pipeline<T>(
+    img: &DynamicImage,
+    mut attempt: impl FnMut(&'static str, GrayImage) -> Result<Option<T>, String>,
+) -> Result<T, String> {
+    let full = img.to_luma8();
+    let downscaled = downscale(&full);
+    let base = downscaled.as_ref().unwrap_or(&full);
+    let base_pass = if downscaled.is_some() {
+        "downscaled"
+    } else {
+        "original"
+    };
+
+    let mut passes: Vec<(&'static str, PassBuilder)> = vec![
+        (base_pass, Box::new(|| base.clone())),
+        ("contrastStretch", Box::new(|| stretch_contrast(base))),
+        ("adaptiveThreshold", Box::new(|| adaptive_threshold(base))),
+        ("sharpened", Box::new(|| imageops::unsharpen(base, 1.5, 8))),
+        ("inverted", Box::new(|| inverted(base))),
+        ("mirrored", Box::new(|| imageops::flip_horizontal(base))),
+        (
+            "mirroredInverted",
+            Box::new(|| inverted(&imageops::flip_horizontal(base))),
+        ),
+        (
+            "adaptiveThresholdInverted",
+            Box::new(|| inverted(&adaptive_threshold(base))),
+        ),
+    ];
+
+    if downscaled.is_some() {
+        passes.push(("fullResolution", Box::new(|| full.clone())));
+    }
+
+    let mut last_error = "No QR code found in image".to_string();
+
+    for (pass, build) in passes {
+        match attempt(pass, build()) {
+            Ok(Some(found)) => return Ok(found),
+            Ok(None) => {}
+            Err(e) => last_error = e,
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Decode the first readable grid; `Ok(None)` means no grid was found
+fn decode_gray(gray: GrayImage) -> Result<Option<(MetaData, String)>, String> {
+    let mut error = None;
+    for (result, _) in decode_grids(gray) {
+        match result {
+            Ok(decoded) => return Ok(Some(decoded)),
+            Err(e) => error = Some(e),
+        }
+    }
+
+    error.map_or(Ok(None), Err)
+}
+
+/// Decode every grid detected in the image, alongside its corner points
+fn decode_grids(gray: GrayImage) -> Vec<GridDecode> {
+    let mut prepared = PreparedImage::prepare(gray);
+    prepared
+        .detect_grids()
+        .iter()
+        .map(|grid| {
+            let decoded = grid
+                .decode()
+                .map_err(|e| format!("Failed to decode QR: {:?}", e));
+            (decoded, grid.bounds)
+        })
+        .collect()
+}
+
+fn bounding_box(corners: &[Point; 4], scale: f64, mirror_width: Option<u32>) -> CodeBounds {
+    let xs = corners.iter().map(|p| match mirror_width {
+        Some(width) => width as f64 - p.x as f64,
+        None => p.x as f64,
+    });
+    let ys = corners.iter().map(|p| p.y as f64);
+
+    let (min_x, max_x) = xs.fold((f64::MAX, f64::MIN), |(lo, hi), x| (lo.min(x), hi.max(x)));
+    let (min_y, max_y) = ys.fold((f64::MAX, f64::MIN), |(lo, hi), y| (lo.min(y), hi.max(y)));
+
+    CodeBounds {
+        x: (min_x.max(0.0) * scale).round() as u32,
+        y: (min_y.max(0.0) * scale).round() as u32,
+        width: ((max_x - min_x) * scale).round() as u32,
+        height: ((max_y - min_y) * scale).round() as u32,
+    }
+}
+
+/// Error correction level from the format bits (L=01, M=00, Q=11, H=10)
+pub fn ecc_level_name(level: u16) -> &'static str {
+    match level {
+        0 => "M",
+        1 => "L",
+        2 => "H",
+        _ => "Q",
+    }
+}
+
+/// Shrink images whose longest side exceeds `MAX_SCAN_DIMENSION`
+fn downscale(gray: &GrayImage) -> Option<GrayImage> {
+    let (width, height) = gray.dimensions();
+    let longest = width.max(height);
+    if longest <= MAX_SCAN_DIMENSION {
+        return None;
+    }
+
+    let scale = MAX_SCAN_DIMENSION as f64 / longest as f64;
+    let new_width = ((width as f64 * scale).round() as u32).max(1);
+    let new_height = ((height as f64 * scale).round() as u32).max(1);
+
+    Some(imageops::resize(
+        gray,
+        new_width,
+        new_height,
+        imageops::FilterType::Triangle,
+    ))
+}
+
+/// Linearly stretch intensities so the darkest and brightest percentiles span 0-255
+fn stretch_contrast(gray: &GrayImage) -> GrayImage {
+    let mut histogram = [0u64; 256];
+    for pixel in gray.pixels() {
+        histogram[pixel[0] as usize] += 1;
+    }
+
+    let total: u64 = histogram.iter().sum();
+    let clip = (total as f64 * CONTRAST_CLIP) as u64;
+    let percentile = |target: u64| {
+        let mut seen = 0;
+        histogram
+            .iter()
+            .position(|&count| {
+                seen += count;
+                seen > target
+            })
+            .unwrap_or(255) as u8
+    };
+
+    let low = percentile(clip);
+    let high = percentile(total.saturating_sub(clip + 1));
+    if high <= low {
+        return gray.clone();
+    }
+
+    let range = (high - low) as f64;
+    let mut out = gray.clone();
+    for pixel in out.pixels_mut() {
+        let value = (pixel[0].saturating_sub(low) as f64 / range * 255.0).min(255.0);
+        *pixel = Luma([value as u8]);
+    }
+    out
+}
+
+/// Binarize against the mean of each pixel's neighbourhood (Bradley-Roth)
+///
+/// Handles glare and uneven lighting that defeat a single global threshold.
+fn adaptive_threshold(gray: &GrayImage) -> GrayImage {
+    let (width, height) = gray.dimensions();
+    let (w, h) = (width as usize, height as usize);
+    let radius = (width.min(height) as usize / 16).max(4);
+
+    // Summed-area table with a zero row and column for simpler lookups
+    let mut integral = vec![0i64; (w + 1) * (h + 1)];
+    for y in 0..h {
+        let mut row_sum = 0i64;
+        for x in 0..w {
+            row_sum += gray.get_pixel(x as u32, y as u32)[0] as i64;
+            integral[(y + 1) * (w + 1) + x + 1] = integral[y * (w + 1) + x + 1] + row_sum;
+        }
+    }
+
+    let mut out = GrayImage::new(width, height);
+    for y in 0..h {
+        let y0 = y.saturating_sub(radius);
+        let y1 = (y + radius + 1).min(h);
+        for x in 0..w {
+            let x0 = x.saturating_sub(radius);
+            let x1 = (x + radius + 1).min(w);
+
+            let sum = integral[y1 * (w + 1) + x1]
+                - integral[y0 * (w + 1) + x1]
+                - integral[y1 * (w + 1) + x0]
+                + integral[y0 * (w + 1) + x0];
+            let count = ((x1 - x0) * (y1 - y0)) as i64;
+
+            let value = gray.get_pixel(x as u32, y as u32)[0] as i64;
+            let dark = value * count < sum - ADAPTIVE_THRESHOLD_OFFSET * count;
+            out.put_pixel(x as u32, y as u32, Luma([if dark { 0 } else { 255 }]));
+        }
+    }
+    out
+}
+
+fn inverted(gray: &GrayImage) -> GrayImage {
+    let mut out = gray.clone();
+    imageops::invert(&mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qrcode::{Color, QrCode};
+
+    /// Render a QR code as a grayscale image with a 4-module quiet zone
+    fn render_qr(content: &str, scale: u32) -> GrayImage {
+        render_code(&QrCode::new(content.as_bytes()).unwrap(), scale)
+    }
+
+    fn render_code(code: &QrCode, scale: u32) -> GrayImage {
+        let modules = code.width() as u32;
+        let colors = code.to_colors();
+        let size = (modules + 8) * scale;
+
+        GrayImage::from_fn(size, size, |x, y| {
+            let (mx, my) = (x / scale, y / scale);
+            let dark = (4..modules + 4).contains(&mx)
+                && (4..modules + 4).contains(&my)
+                && colors[((my - 4) * modules + (mx - 4)) as usize] == Color::Dark;
+            Luma([if dark { 0 } else { 255 }])
+        })
+    }
+
+    #[test]
+    fn test_pipeline_original() {
+        let img = DynamicImage::ImageLuma8(render_qr("https://example.com", 4));
+        let result = decode_with_pipeline(&img).unwrap();
+
+        assert_eq!(result.pass, "original");
+        assert_eq!(result.content, "https://example.com");
+    }
+
+    #[test]
+    fn test_pipeline_inverted() {
+        let img = DynamicImage::ImageLuma8(inverted(&render_qr("inverted", 4)));
+        let result = decode_with_pipeline(&img).unwrap();
+
+        assert_eq!(result.pass, "inverted");
+        assert_eq!(result.content, "inverted");
+    }
+
+    #[test]
+    fn test_pipeline_mirrored() {
+        let img = DynamicImage::ImageLuma8(imageops::flip_horizontal(&render_qr("mirror", 4)));
+        let result = decode_with_pipeline(&img).unwrap();
+
+        assert_eq!(result.pass, "mirrored");
+        assert_eq!(result.content, "mirror");
+    }
+
+    #[test]
+    fn test_pipeline_low_contrast() {
+        let mut gray = render_qr("faded", 4);
+        for pixel in gray.pixels_mut() {
+            *pixel = Luma([if pixel[0] == 0 { 120 } else { 136 }]);
+        }
+
+        let result = decode_with_pipeline(&DynamicImage::ImageLuma8(gray)).unwrap();
+        assert_eq!(result.content, "faded");
+        assert_ne!(result.pass, "original");
+    }
+
+    #[test]
+    fn test_pipeline_large_image_is_downscaled() {
+        let img = DynamicImage::ImageLuma8(render_qr("large", 80));
+        assert!(img.width() > MAX_SCAN_DIMENSION);
+
+        let result = decode_with_pipeline(&img).unwrap();
+        assert_eq!(result.pass, "downscaled");
+        assert_eq!(result.content, "large");
+    }
+
+    #[test]
+    fn test_pipeline_no_code() {
+        let img = DynamicImage::ImageLuma8(GrayImage::from_pixel(64, 64, Luma([255])));
+        assert!(decode_with_pipeline(&img).is_err());
+    }
+
+    #[test]
+    fn test_pipeline_decodes_every_code() {
+        let left = render_qr("left", 4);
+        let right = render_qr("right", 4);
+        let mut canvas = GrayImage::from_pixel(left.width() * 2, left.height(), Luma([255]));
+        imageops::replace(&mut canvas, &left, 0, 0);
+        imageops::replace(&mut canvas, &right, left.width() as i64, 0);
+
+        let codes = decode_all_with_pipeline(&DynamicImage::ImageLuma8(canvas)).unwrap();
+        let mut contents: Vec<&str> = codes.iter().map(|c| c.content.as_str()).collect();
+        contents.sort();
+
+        assert_eq!(contents, vec!["left", "right"]);
+        assert_eq!(codes[0].version, 1);
+
+        // Version 1 is 21 modules at 4px, offset by the 4-module quiet zone
+        let right_code = codes.iter().find(|c| c.content == "right").unwrap();
+        let expected_x = left.width() + 16;
+        assert!(right_code.bounds.x.abs_diff(expected_x) <= 4);
+        assert!(right_code.bounds.y.abs_diff(16) <= 4);
+        assert!(right_code.bounds.width.abs_diff(84) <= 8);
+    }
+
+    #[test]
+    fn test_pipeline_bounds_in_original_coordinates() {
+        // Code in the right half of a wide, mirrored, oversized image
+        let code = render_qr("mirrored bounds", 20);
+        let mut canvas = GrayImage::from_pixel(code.width() * 3, code.height(), Luma([255]));
+        imageops::replace(&mut canvas, &code, (code.width() * 2) as i64, 0);
+        let canvas = imageops::flip_horizontal(&canvas);
+        assert!(canvas.width() > MAX_SCAN_DIMENSION);
+
+        let codes = decode_all_with_pipeline(&DynamicImage::ImageLuma8(canvas)).unwrap();
+
+        // After flipping, the symbol sits in the left third
+        let bounds = codes[0].bounds;
+        assert!(bounds.x < code.width());
+        assert!(bounds.x + bounds.width <= code.width() + 20);
+    }
+
+    #[test]
+    fn test_ecc_level_names() {
+        for (level, name) in [
+            (qrcode::EcLevel::L, "L"),
+            (qrcode::EcLevel::M, "M"),
+            (qrcode::EcLevel::Q, "Q"),
+            (qrcode::EcLevel::H, "H"),
+        ] {
+            let code = QrCode::with_error_correction_level("ecc", level).unwrap();
+            let gray = render_code(&code, 4);
+
+            let codes = decode_all_with_pipeline(&DynamicImage::ImageLuma8(gray)).unwrap();
+            assert_eq!(codes[0].ecc_level, name);
+        }
+    }
+
+    #[test]
+    fn test_downscale_small_image_untouched() {
+        assert!(downscale(&GrayImage::new(100, 100)).is_none());
+    }
+
+    #[test]
+    fn test_downscale_preserves_aspect() {
+        let scaled = downscale(&GrayImage::new(3200, 1600)).unwrap();
+        assert_eq!(
+            scaled.dimensions(),
+            (MAX_SCAN_DIMENSION, MAX_SCAN_DIMENSION / 2)
+        );
+    }
+
+    #[test]
+    fn test_stretch_contrast_expands_range() {
+        let gray = GrayImage::from_fn(10, 10, |x, _| Luma([if x < 5 { 100 } else { 150 }]));
+        let stretched = stretch_contrast(&gray);
+
+        assert_eq!(stretched.get_pixel(0, 0)[0], 0);
+        assert_eq!(stretched.get_pixel(9, 0)[0], 255);
+    }
+
+    #[test]
+    fn test_adaptive_threshold_handles_gradient() {
+        // Dark square on a background that brightens from left to right
+        let gray = GrayImage::from_fn(64, 64, |x, y| {
+            let background = 80 + (x * 2) as u8;
+            let inside = (24..40).contains(&x) && (24..40).contains(&y);
+            Luma([if inside { background - 60 } else { background }])
+        });
+        let binary = adaptive_threshold(&gray);
+
+        assert_eq!(binary.get_pixel(26, 32)[0], 0);
+        assert_eq!(binary.get_pixel(2, 2)[0], 255);
+        assert_eq!(binary.get_pixel(60, 60)[0], 255);
+    }
+}
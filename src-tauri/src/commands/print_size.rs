@@ -0,0 +1,384 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{imageops::FilterType, DynamicImage, ImageReader};
+use rqrr::{BitGrid, PreparedImage};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+use super::strip_data_url_prefix;
+
+const MM_PER_INCH: f64 = 25.4;
+
+/// Common rule of thumb: a code scans from about ten times its own width
+const DISTANCE_RATIO: f64 = 10.0;
+
+/// Smallest module (X-dimension) phone cameras read reliably
+const MIN_MODULE_MM: f64 = 0.33;
+
+/// Smallest printed symbol recommended for consumer scanning (2 x 2 cm)
+const MIN_SYMBOL_MM: f64 = 20.0;
+
+/// Printer dots needed per module to keep module edges crisp
+const MIN_DOTS_PER_MODULE: f64 = 3.0;
+
+const DEFAULT_DPI: u32 = 300;
+
+/// Width in pixels of a typical phone camera scanning preview
+const CAMERA_FRAME_PX: f64 = 1280.0;
+
+/// Horizontal field of view of a typical phone main camera
+const CAMERA_FOV_DEG: f64 = 65.0;
+
+/// Upper bound for simulated rasters so large prints don't exhaust memory
+const MAX_SIMULATION_PX: u32 = 4096;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintSizeCheck {
+    pub name: String,
+    pub state: String, // "pass" | "warn" | "fail"
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintSizeEstimate {
+    pub state: String, // "pass" | "warn" | "fail"
+    pub modules: usize,
+    pub version: Option<usize>,
+    pub print_width_mm: f64,
+    pub symbol_width_mm: f64,
+    pub module_size_mm: f64,
+    pub dpi: u32,
+    pub dots_per_module: f64,
+    pub max_scan_distance_mm: f64,
+    pub checks: Vec<PrintSizeCheck>,
+}
+
+/// Measured layout of a detected code within its source image
+#[derive(Debug, Clone, Copy)]
+struct CodeGeometry {
+    image_width_px: f64,
+    symbol_width_px: f64,
+    modules: usize,
+}
+
+impl CodeGeometry {
+    fn module_px(&self) -> f64 {
+        self.symbol_width_px / self.modules as f64
+    }
+}
+
+/// Estimate print size and scan distance for a QR code image
+///
+/// Takes either the width the whole image will be printed at, or the distance
+/// it must scan from (in which case the smallest suitable print width is
+/// returned). Decoding is re-run on rasters simulating the print and a phone
+/// camera at that distance.
+#[tauri::command]
+pub async fn estimate_print_size(
+    image_data: String,
+    print_width_mm: Option<f64>,
+    scan_distance_mm: Option<f64>,
+    dpi: Option<u32>,
+) -> Result<PrintSizeEstimate, String> {
+    let image_bytes = STANDARD
+        .decode(strip_data_url_prefix(&image_data))
+        .map_err(|e| format!("Failed to decode base64: {}", e))?;
+
+    let img = ImageReader::new(Cursor::new(&image_bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read image format: {}", e))?
+        .decode()
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+
+    let mut prepared = PreparedImage::prepare(img.to_luma8());
+    let grids = prepared.detect_grids();
+    let grid = grids.first().ok_or("No QR code detected in image")?;
+
+    let decoded = grid.decode().ok();
+    let geometry = CodeGeometry {
+        image_width_px: img.width() as f64,
+        symbol_width_px: symbol_width_px(&grid.bounds),
+        modules: grid.grid.size(),
+    };
+
+    let mut estimate = estimate_from_geometry(
+        geometry,
+        print_width_mm,
+        scan_distance_mm,
+        dpi.unwrap_or(DEFAULT_DPI),
+    )?;
+    estimate.version = decoded.as_ref().map(|(meta, _)| meta.version.0);
+
+    let expected = decoded.map(|(_, content)| content);
+
+    // Printed raster at the requested DPI
+    let print_px = estimate.print_width_mm / MM_PER_INCH * estimate.dpi as f64;
+    let print_ok = decodes_at_width(&img, print_px, expected.as_deref());
+    estimate.checks.push(simulation_check(
+        "printSimulation",
+        print_ok,
+        format!("Decoding at {} DPI", estimate.dpi),
+    ));
+
+    // Camera frame at the target distance, or the estimated maximum
+    let distance = scan_distance_mm.unwrap_or(estimate.max_scan_distance_mm);
+    let camera_px = estimate.print_width_mm * camera_px_per_mm(distance);
+    let camera_ok = decodes_at_width(&img, camera_px, expected.as_deref());
+    estimate.checks.push(simulation_check(
+        "scanSimulation",
+        camera_ok,
+        format!("Decoding from {:.0} cm", distance / 10.0),
+    ));
+
+    estimate.state = overall_state(&estimate.checks);
+
+    Ok(estimate)
+}
+
+fn estimate_from_geometry(
+    geometry: CodeGeometry,
+    print_width_mm: Option<f64>,
+    scan_distance_mm: Option<f64>,
+    dpi: u32,
+) -> Result<PrintSizeEstimate, String> {
+    if geometry.modules == 0 || geometry.symbol_width_px <= 0.0 {
+        return Err("Could not measure the QR code in image".to_string());
+    }
+
+    let module_size_mm = match (print_width_mm, scan_distance_mm) {
+        (Some(width), _) if width > 0.0 => width * geometry.module_px() / geometry.image_width_px,
+        (None, Some(distance)) if distance > 0.0 => {
+            let required_symbol_mm = distance / DISTANCE_RATIO;
+            (required_symbol_mm / geometry.modules as f64).max(MIN_MODULE_MM)
+        }
+        _ => return Err("Provide a positive print width or scan distance".to_string()),
+    };
+
+    let print_width_mm = module_size_mm / geometry.module_px() * geometry.image_width_px;
+    let symbol_width_mm = module_size_mm * geometry.modules as f64;
+    let max_scan_distance_mm = symbol_width_mm * DISTANCE_RATIO;
+    let dots_per_module = module_size_mm / MM_PER_INCH * dpi as f64;
+
+    let mut checks = vec![
+        PrintSizeCheck {
+            name: "moduleSize".to_string(),
+            state: grade(module_size_mm >= MIN_MODULE_MM, "fail"),
+            message: format!(
+                "Module size {:.2} mm (minimum {:.2} mm)",
+                module_size_mm, MIN_MODULE_MM
+            ),
+        },
+        PrintSizeCheck {
+            name: "symbolSize".to_string(),
+            state: grade(symbol_width_mm >= MIN_SYMBOL_MM, "warn"),
+            message: format!(
+                "Code width {:.1} mm (recommended {:.0} mm or more)",
+                symbol_width_mm, MIN_SYMBOL_MM
+            ),
+        },
+        PrintSizeCheck {
+            name: "printResolution".to_string(),
+            state: grade(dots_per_module >= MIN_DOTS_PER_MODULE, "warn"),
+            message: format!(
+                "{:.1} printer dots per module at {} DPI",
+                dots_per_module, dpi
+            ),
+        },
+    ];
+
+    if let Some(distance) = scan_distance_mm {
+        checks.push(PrintSizeCheck {
+            name: "scanDistance".to_string(),
+            state: grade(max_scan_distance_mm >= distance, "fail"),
+            message: format!(
+                "Scans from up to {:.0} cm (target {:.0} cm)",
+                max_scan_distance_mm / 10.0,
+                distance / 10.0
+            ),
+        });
+    }
+
+    Ok(PrintSizeEstimate {
+        state: overall_state(&checks),
+        modules: geometry.modules,
+        version: None,
+        print_width_mm,
+        symbol_width_mm,
+        module_size_mm,
+        dpi,
+        dots_per_module,
+        max_scan_distance_mm,
+        checks,
+    })
+}
+
+/// Average width of the detected symbol from its corner points
+fn symbol_width_px(bounds: &[rqrr::Point; 4]) -> f64 {
+    let dist = |a: &rqrr::Point, b: &rqrr::Point| {
+        (((a.x - b.x) as f64).powi(2) + ((a.y - b.y) as f64).powi(2)).sqrt()
+    };
+
+    // [top-left, top-right, bottom-right, bottom-left]
+    (dist(&bounds[0], &bounds[1]) + dist(&bounds[3], &bounds[2])) / 2.0
+}
+
+/// Camera pixels covering one millimetre of print at the given distance
+fn camera_px_per_mm(distance_mm: f64) -> f64 {
+    let half_fov = (CAMERA_FOV_DEG / 2.0).to_radians();
+    let view_width_mm = 2.0 * distance_mm * half_fov.tan();
+    CAMERA_FRAME_PX / view_width_mm
+}
+
+/// Resample the image to the given width and check it still decodes
+fn decodes_at_width(img: &DynamicImage, width_px: f64, expected: Option<&str>) -> bool {
+    let width = (width_px.round() as u32).clamp(1, MAX_SIMULATION_PX);
+    let height = ((img.height() as f64 * width as f64 / img.width() as f64).round() as u32).max(1);
+
+    let resized = img.resize_exact(width, height, FilterType::Triangle);
+    let mut prepared = PreparedImage::prepare(resized.to_luma8());
+    let grids = prepared.detect_grids();
+
+    match grids.first().map(|g| g.decode()) {
+        Some(Ok((_, content))) => expected.is_none_or(|e| e == content),
+        _ => false,
+    }
+}
+
+fn simulation_check(name: &str, passed: bool, context: String) -> PrintSizeCheck {
+    PrintSizeCheck {
+        name: name.to_string(),
+        state: grade(passed, "fail"),
+        message: if passed {
+            format!("{} succeeded", context)
+        } else {
+            format!("{} failed", context)
+        },
+    }
+}
+
+/// "pass" when the guideline is met, otherwise the given severity
+fn grade(passed: bool, severity: &str) -> String {
+    if passed { "pass" } else { severity }.to_string()
+}
+
+fn overall_state(checks: &[PrintSizeCheck]) -> String {
+    if checks.iter().any(|c| c.state == "fail") {
+        "fail".to_string()
+    } else if checks.iter().any(|c| c.state == "warn") {
+        "warn".to_string()
+    } else {
+        "pass".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 25-module (version 2) code filling 250 of 300 pixels
+    fn test_geometry() -> CodeGeometry {
+        CodeGeometry {
+            image_width_px: 300.0,
+            symbol_width_px: 250.0,
+            modules: 25,
+        }
+    }
+
+    fn check_state<'a>(estimate: &'a PrintSizeEstimate, name: &str) -> &'a str {
+        &estimate
+            .checks
+            .iter()
+            .find(|c| c.name == name)
+            .unwrap()
+            .state
+    }
+
+    #[test]
+    fn test_estimate_from_print_width() {
+        let estimate = estimate_from_geometry(test_geometry(), Some(30.0), None, 300).unwrap();
+
+        // 10 px per module, 300 px printed at 30 mm => 1 mm modules
+        assert!((estimate.module_size_mm - 1.0).abs() < 1e-9);
+        assert!((estimate.symbol_width_mm - 25.0).abs() < 1e-9);
+        assert!((estimate.max_scan_distance_mm - 250.0).abs() < 1e-9);
+        assert_eq!(estimate.state, "pass");
+    }
+
+    #[test]
+    fn test_estimate_from_scan_distance() {
+        let estimate = estimate_from_geometry(test_geometry(), None, Some(1000.0), 300).unwrap();
+
+        // 1 m needs a 100 mm symbol => 4 mm modules, 120 mm print
+        assert!((estimate.module_size_mm - 4.0).abs() < 1e-9);
+        assert!((estimate.print_width_mm - 120.0).abs() < 1e-9);
+        assert_eq!(check_state(&estimate, "scanDistance"), "pass");
+    }
+
+    #[test]
+    fn test_estimate_scan_distance_respects_minimum_module() {
+        let estimate = estimate_from_geometry(test_geometry(), None, Some(10.0), 300).unwrap();
+        assert!((estimate.module_size_mm - MIN_MODULE_MM).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_too_small_fails() {
+        let estimate = estimate_from_geometry(test_geometry(), Some(6.0), None, 300).unwrap();
+
+        assert!(estimate.module_size_mm < MIN_MODULE_MM);
+        assert_eq!(check_state(&estimate, "moduleSize"), "fail");
+        assert_eq!(check_state(&estimate, "symbolSize"), "warn");
+        assert_eq!(estimate.state, "fail");
+    }
+
+    #[test]
+    fn test_estimate_low_dpi_warns() {
+        let estimate = estimate_from_geometry(test_geometry(), Some(30.0), None, 72).unwrap();
+
+        assert_eq!(check_state(&estimate, "printResolution"), "warn");
+        assert_eq!(estimate.state, "warn");
+    }
+
+    #[test]
+    fn test_estimate_unreachable_distance_fails() {
+        let estimate =
+            estimate_from_geometry(test_geometry(), Some(30.0), Some(2000.0), 300).unwrap();
+        assert_eq!(check_state(&estimate, "scanDistance"), "fail");
+    }
+
+    #[test]
+    fn test_estimate_requires_input() {
+        assert!(estimate_from_geometry(test_geometry(), None, None, 300).is_err());
+        assert!(estimate_from_geometry(test_geometry(), Some(0.0), None, 300).is_err());
+    }
+
+    #[test]
+    fn test_symbol_width_px() {
+        let bounds = [
+            rqrr::Point { x: 10, y: 10 },
+            rqrr::Point { x: 110, y: 10 },
+            rqrr::Point { x: 110, y: 110 },
+            rqrr::Point { x: 10, y: 110 },
+        ];
+        assert!((symbol_width_px(&bounds) - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_camera_px_per_mm_decreases_with_distance() {
+        assert!(camera_px_per_mm(200.0) > camera_px_per_mm(400.0));
+        assert!((camera_px_per_mm(200.0) / camera_px_per_mm(400.0) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_overall_state() {
+        let check = |state: &str| PrintSizeCheck {
+            name: "x".to_string(),
+            state: state.to_string(),
+            message: String::new(),
+        };
+
+        assert_eq!(overall_state(&[check("pass")]), "pass");
+        assert_eq!(overall_state(&[check("pass"), check("warn")]), "warn");
+        assert_eq!(overall_state(&[check("warn"), check("fail")]), "fail");
+    }
+}
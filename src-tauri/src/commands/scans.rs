@@ -0,0 +1,349 @@
+use crate::db::{self, DbState, NewScanRecord, ScanRecord};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::Path;
+use tauri::State;
+use tauri_plugin_dialog::DialogExt;
+
+use super::export::ExportResult;
+use super::payload::ParsedPayload;
+use super::url_risk::UrlRiskReport;
+use super::validate::ScanResult;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanListResult {
+    pub items: Vec<ScanRecord>,
+    pub total: i64,
+    pub has_more: bool,
+}
+
+/// Decoder details kept alongside a scan
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanMetadata<'a> {
+    subtype: &'a Option<String>,
+    confidence: &'a Option<f32>,
+    pass: &'a Option<String>,
+    parsed: &'a Option<ParsedPayload>,
+    url_risk: &'a Option<UrlRiskReport>,
+}
+
+/// List scans with pagination
+#[tauri::command]
+pub async fn scan_list(
+    state: State<'_, DbState>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    search: Option<String>,
+) -> Result<ScanListResult, String> {
+    state
+        .read(move |conn| {
+            let limit = limit.unwrap_or(50);
+            let offset = offset.unwrap_or(0);
+
+            let items = db::list_scans(conn, limit, offset, search.as_deref())
+                .map_err(|e| format!("Failed to list scans: {}", e))?;
+
+            let total = db::count_scans(conn, search.as_deref())
+                .map_err(|e| format!("Failed to count scans: {}", e))?;
+
+            let has_more = (offset + items.len() as i64) < total;
+
+            Ok(ScanListResult {
+                items,
+                total,
+                has_more,
+            })
+        })
+        .await
+}
+
+/// Record a successful scan
+#[tauri::command]
+pub async fn scan_save(
+    state: State<'_, DbState>,
+    source: String,
+    source_path: Option<String>,
+    result: ScanResult,
+    thumbnail: Option<String>,
+) -> Result<i64, String> {
+    let record = scan_record(source, source_path, &result, thumbnail)?;
+    let workspace = state.workspace();
+    let key = workspace.key();
+    workspace
+        .write(move |conn| {
+            db::save_scan(conn, &record, key.as_ref())
+                .map_err(|e| format!("Failed to save scan: {}", e))
+        })
+        .await
+}
+
+/// A scan with its content, metadata and thumbnail, which lists leave out
+/// or redact when sensitive
+#[tauri::command]
+pub async fn scan_reveal(state: State<'_, DbState>, id: i64) -> Result<ScanRecord, String> {
+    let workspace = state.workspace();
+    let key = workspace.key();
+    workspace
+        .read(move |conn| {
+            db::reveal_scan(conn, id, key.as_ref())
+                .map_err(|e| format!("Failed to read scan: {}", e))?
+                .ok_or_else(|| format!("Scan {} not found", id))
+        })
+        .await
+}
+
+/// Delete a scan
+#[tauri::command]
+pub async fn scan_delete(state: State<'_, DbState>, id: i64) -> Result<bool, String> {
+    state
+        .write(move |conn| {
+            db::delete_scan(conn, id).map_err(|e| format!("Failed to delete scan: {}", e))
+        })
+        .await
+}
+
+/// Clear all scans
+#[tauri::command]
+pub async fn scan_clear(state: State<'_, DbState>) -> Result<i64, String> {
+    state
+        .write(move |conn| {
+            db::clear_scans(conn).map_err(|e| format!("Failed to clear scans: {}", e))
+        })
+        .await
+}
+
+/// Copy a scan into generator history for re-styling
+#[tauri::command]
+pub async fn scan_promote(
+    state: State<'_, DbState>,
+    id: i64,
+    style_json: Option<String>,
+    label: Option<String>,
+) -> Result<i64, String> {
+    let workspace = state.workspace();
+    let key = workspace.key();
+    workspace
+        .write(move |conn| {
+            let style_json = style_json.unwrap_or_else(|| "{}".to_string());
+
+            db::promote_scan(conn, id, &style_json, label, key.as_ref())
+                .map_err(|e| format!("Failed to promote scan: {}", e))?
+                .ok_or_else(|| format!("Scan {} not found", id))
+        })
+        .await
+}
+
+/// Export scans (all, or the given IDs) as JSON or CSV
+///
+/// WiFi passwords and OTP secrets are redacted unless `includeSecrets`,
+/// which needs encrypted scans unlocked.
+#[tauri::command]
+pub async fn scan_export(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+    format: String, // "json" or "csv"
+    ids: Option<Vec<i64>>,
+    include_secrets: Option<bool>,
+) -> Result<ExportResult, String> {
+    let workspace = state.workspace();
+    let key = workspace.key();
+    let scans = workspace
+        .read(move |conn| {
+            let scans = db::get_scans(conn, ids.as_deref().unwrap_or_default())
+                .map_err(|e| format!("Failed to load scans: {}", e))?;
+            if !include_secrets.unwrap_or(false) {
+                return Ok(scans);
+            }
+
+            // Listed scans come redacted; reveal the sensitive ones
+            scans
+                .into_iter()
+                .map(|scan| {
+                    if !scan.sensitive && !scan.encrypted {
+                        return Ok(scan);
+                    }
+                    db::reveal_scan(conn, scan.id, key.as_ref())
+                        .map_err(|e| format!("Failed to read scan: {}", e))
+                        .map(|revealed| revealed.unwrap_or(scan))
+                })
+                .collect()
+        })
+        .await?;
+
+    let extension = if format == "csv" { "csv" } else { "json" };
+    let file_path = app
+        .dialog()
+        .file()
+        .set_file_name(format!("scans.{}", extension))
+        .add_filter("Scan Export", &[extension])
+        .blocking_save_file();
+
+    match file_path {
+        Some(path) => {
+            let path_buf = path.as_path().unwrap().to_path_buf();
+            write_scans(&scans, &path_buf, extension)?;
+
+            Ok(ExportResult {
+                success: true,
+                path: Some(path_buf.to_string_lossy().to_string()),
+                error: None,
+            })
+        }
+        None => Ok(ExportResult {
+            success: false,
+            path: None,
+            error: Some("Save cancelled by user".to_string()),
+        }),
+    }
+}
+
+/// Build a scan record from a decoder result
+fn scan_record(
+    source: String,
+    source_path: Option<String>,
+    result: &ScanResult,
+    thumbnail: Option<String>,
+) -> Result<NewScanRecord, String> {
+    let content = result
+        .content
+        .clone()
+        .filter(|_| result.success)
+        .ok_or("Only successful scans can be saved")?;
+
+    let metadata = ScanMetadata {
+        subtype: &result.subtype,
+        confidence: &result.confidence,
+        pass: &result.pass,
+        parsed: &result.parsed,
+        url_risk: &result.url_risk,
+    };
+    let metadata_json = serde_json::to_string(&metadata)
+        .map_err(|e| format!("Failed to serialize scan metadata: {}", e))?;
+
+    Ok(NewScanRecord {
+        source,
+        source_path,
+        qr_type: result.qr_type.clone().unwrap_or_else(|| "text".to_string()),
+        content,
+        metadata_json: Some(metadata_json),
+        thumbnail,
+    })
+}
+
+fn write_scans(scans: &[ScanRecord], path: &Path, format: &str) -> Result<(), String> {
+    if format == "json" {
+        let json = serde_json::to_string_pretty(scans)
+            .map_err(|e| format!("Failed to serialize scans: {}", e))?;
+        return fs::write(path, json).map_err(|e| format!("Failed to write export: {}", e));
+    }
+
+    let file = File::create(path).map_err(|e| format!("Failed to create export: {}", e))?;
+    let mut writer = csv::Writer::from_writer(file);
+
+    writer
+        .write_record([
+            "id",
+            "createdAt",
+            "source",
+            "sourcePath",
+            "type",
+            "content",
+            "metadata",
+        ])
+        .map_err(|e| format!("Failed to write export: {}", e))?;
+
+    for scan in scans {
+        writer
+            .write_record([
+                scan.id.to_string(),
+                scan.created_at.clone(),
+                scan.source.clone(),
+                scan.source_path.clone().unwrap_or_default(),
+                scan.qr_type.clone(),
+                scan.content.clone(),
+                scan.metadata_json.clone().unwrap_or_default(),
+            ])
+            .map_err(|e| format!("Failed to write export: {}", e))?;
+    }
+
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write export: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan_result(content: Option<&str>) -> ScanResult {
+        ScanResult {
+            success: content.is_some(),
+            content: content.map(str::to_string),
+            qr_type: content.map(|_| "url".to_string()),
+            subtype: None,
+            confidence: Some(1.0),
+            pass: Some("original".to_string()),
+            parsed: None,
+            url_risk: None,
+            error: content.is_none().then(|| "No QR code found".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_scan_record_from_result() {
+        let record = scan_record(
+            "clipboard".to_string(),
+            None,
+            &scan_result(Some("https://example.com")),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(record.source, "clipboard");
+        assert_eq!(record.content, "https://example.com");
+        assert_eq!(record.qr_type, "url");
+
+        let metadata: serde_json::Value =
+            serde_json::from_str(record.metadata_json.as_deref().unwrap()).unwrap();
+        assert_eq!(metadata["pass"], "original");
+        assert_eq!(metadata["confidence"], 1.0);
+    }
+
+    #[test]
+    fn test_scan_record_rejects_failed_scan() {
+        let result = scan_record("file".to_string(), None, &scan_result(None), None);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_write_scans_csv_and_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let scans = vec![ScanRecord {
+            id: 7,
+            source: "file".to_string(),
+            source_path: Some("/tmp/a.png".to_string()),
+            content: "hello, world".to_string(),
+            qr_type: "text".to_string(),
+            metadata_json: None,
+            thumbnail: Some("data:image/png;base64,abc".to_string()),
+            created_at: "2026-01-01 10:00:00".to_string(),
+            sensitive: false,
+            encrypted: false,
+        }];
+
+        let csv_path = dir.path().join("scans.csv");
+        write_scans(&scans, &csv_path, "csv").unwrap();
+        let csv = fs::read_to_string(&csv_path).unwrap();
+        assert!(csv.starts_with("id,createdAt,source,sourcePath,type,content,metadata\n"));
+        assert!(csv.contains("7,2026-01-01 10:00:00,file,/tmp/a.png,text,\"hello, world\","));
+
+        let json_path = dir.path().join("scans.json");
+        write_scans(&scans, &json_path, "json").unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(json[0]["sourcePath"], "/tmp/a.png");
+        assert_eq!(json[0]["thumbnail"], "data:image/png;base64,abc");
+    }
+}
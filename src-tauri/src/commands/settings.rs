@@ -0,0 +1,93 @@
+use crate::db::{self, DbState, Settings, SettingsChange};
+use rusqlite::Connection;
+use serde_json::{Map, Value};
+use tauri::{Emitter, State};
+
+/// Event emitted with a `SettingsChange` whenever a setting changes
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// Store a partial update; pass the result to `notify_settings_changed`
+pub(crate) fn save_settings(
+    conn: &Connection,
+    patch: &Map<String, Value>,
+) -> Result<SettingsChange, String> {
+    db::update_settings(conn, patch).map_err(|e| match e {
+        db::SettingsError::Sqlite(e) => format!("Failed to save settings: {}", e),
+        e => e.to_string(),
+    })
+}
+
+/// Let every window know what changed
+pub(crate) fn notify_settings_changed(app: &tauri::AppHandle, change: &SettingsChange) {
+    if change.changed.is_empty() {
+        return;
+    }
+    if let Err(e) = app.emit(SETTINGS_CHANGED_EVENT, change) {
+        eprintln!("Failed to emit settings change: {}", e);
+    }
+}
+
+/// Purge trash or prune history when their retention settings changed
+fn apply_change(conn: &Connection, change: &SettingsChange) -> Result<(), String> {
+    if change.has("trashRetentionDays") {
+        db::purge_expired_trash(conn, change.settings.trash_retention_days)
+            .map_err(|e| format!("Failed to purge trash: {}", e))?;
+    }
+    if change.has("retentionPolicy") {
+        db::enforce_retention(conn, &change.settings.retention_policy)
+            .map_err(|e| format!("Failed to apply retention: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Get all app settings
+#[tauri::command]
+pub async fn settings_get(state: State<'_, DbState>) -> Result<Settings, String> {
+    state
+        .read(move |conn| {
+            db::get_settings(conn).map_err(|e| format!("Failed to read settings: {}", e))
+        })
+        .await
+}
+
+/// Update some settings, keyed by their camelCase names
+#[tauri::command]
+pub async fn settings_update(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+    patch: Map<String, Value>,
+) -> Result<SettingsChange, String> {
+    let change = state
+        .write(move |conn| {
+            let change = save_settings(conn, &patch)?;
+            apply_change(conn, &change)?;
+            Ok(change)
+        })
+        .await?;
+
+    notify_settings_changed(&app, &change);
+    Ok(change)
+}
+
+/// Restore the given settings, or all of them, to their defaults
+#[tauri::command]
+pub async fn settings_reset(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+    keys: Option<Vec<String>>,
+) -> Result<SettingsChange, String> {
+    let change = state
+        .write(move |conn| {
+            let change =
+                db::reset_settings(conn, &keys.unwrap_or_default()).map_err(|e| match e {
+                    db::SettingsError::Sqlite(e) => format!("Failed to reset settings: {}", e),
+                    e => e.to_string(),
+                })?;
+            apply_change(conn, &change)?;
+            Ok(change)
+        })
+        .await?;
+
+    notify_settings_changed(&app, &change);
+    Ok(change)
+}
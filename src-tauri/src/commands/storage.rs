@@ -0,0 +1,76 @@
+use crate::db::{self, DbState, RetentionPolicy, RetentionReport, StorageReport, VacuumResult};
+use serde_json::Map;
+use tauri::State;
+
+use super::settings::{notify_settings_changed, save_settings};
+
+/// Get the history retention policy
+#[tauri::command]
+pub async fn retention_get_policy(state: State<'_, DbState>) -> Result<RetentionPolicy, String> {
+    state
+        .read(move |conn| {
+            let settings = db::get_settings(conn)
+                .map_err(|e| format!("Failed to read retention policy: {}", e))?;
+
+            Ok(settings.retention_policy)
+        })
+        .await
+}
+
+/// Save the history retention policy and apply it right away
+#[tauri::command]
+pub async fn retention_set_policy(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+    policy: RetentionPolicy,
+) -> Result<RetentionReport, String> {
+    let mut patch = Map::new();
+    let value = serde_json::to_value(&policy).map_err(|e| e.to_string())?;
+    patch.insert("retentionPolicy".to_string(), value);
+
+    let (change, report) = state
+        .write(move |conn| {
+            let change = save_settings(conn, &patch)?;
+            let report = db::enforce_retention(conn, &policy)
+                .map_err(|e| format!("Failed to apply retention: {}", e))?;
+            Ok((change, report))
+        })
+        .await?;
+
+    notify_settings_changed(&app, &change);
+    Ok(report)
+}
+
+/// Apply the retention policy now
+#[tauri::command]
+pub async fn retention_enforce(state: State<'_, DbState>) -> Result<RetentionReport, String> {
+    state
+        .write(move |conn| {
+            let settings = db::get_settings(conn)
+                .map_err(|e| format!("Failed to read retention policy: {}", e))?;
+
+            db::enforce_retention(conn, &settings.retention_policy)
+                .map_err(|e| format!("Failed to apply retention: {}", e))
+        })
+        .await
+}
+
+/// Report storage use by table
+#[tauri::command]
+pub async fn storage_report(state: State<'_, DbState>) -> Result<StorageReport, String> {
+    state
+        .read(move |conn| {
+            db::storage_report(conn).map_err(|e| format!("Failed to read storage usage: {}", e))
+        })
+        .await
+}
+
+/// Compact the database file
+#[tauri::command]
+pub async fn storage_vacuum(state: State<'_, DbState>) -> Result<VacuumResult, String> {
+    state
+        .write(move |conn| {
+            db::vacuum_database(conn).map_err(|e| format!("Failed to compact database: {}", e))
+        })
+        .await
+}
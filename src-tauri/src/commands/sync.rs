@@ -0,0 +1,59 @@
+use crate::db::{self, DbState, SyncReport, SyncStatus};
+use std::path::PathBuf;
+use tauri::State;
+use tauri_plugin_dialog::DialogExt;
+
+/// This device's sync folder, pending changes and the devices seen there
+#[tauri::command]
+pub async fn sync_status(state: State<'_, DbState>) -> Result<SyncStatus, String> {
+    state
+        .read(|conn| {
+            db::sync_status(conn).map_err(|e| format!("Failed to read sync status: {}", e))
+        })
+        .await
+}
+
+/// Choose the shared folder to sync through, or stop syncing with `null`
+///
+/// WiFi passwords and OTP secrets only go into the folder with
+/// `includeSecrets`; otherwise other devices get them redacted.
+#[tauri::command]
+pub async fn sync_configure(
+    state: State<'_, DbState>,
+    folder: Option<String>,
+    include_secrets: Option<bool>,
+) -> Result<SyncStatus, String> {
+    state
+        .write(move |conn| {
+            db::configure_sync(
+                conn,
+                folder.map(PathBuf::from).as_deref(),
+                include_secrets.unwrap_or(false),
+            )
+            .map_err(|e| format!("Failed to configure sync: {}", e))
+        })
+        .await
+}
+
+/// Merge other devices' changes from the sync folder and publish ours
+#[tauri::command]
+pub async fn sync_now(state: State<'_, DbState>) -> Result<SyncReport, String> {
+    let workspace = state.workspace();
+    let key = workspace.key();
+    workspace
+        .write(move |conn| {
+            db::sync_now(conn, key.as_ref()).map_err(|e| format!("Sync failed: {}", e))
+        })
+        .await
+}
+
+/// Show a folder picker for the sync folder
+#[tauri::command]
+pub async fn pick_sync_folder(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let dir_path = app.dialog().file().blocking_pick_folder();
+
+    match dir_path {
+        Some(path) => Ok(Some(path.as_path().unwrap().to_string_lossy().to_string())),
+        None => Ok(None),
+    }
+}
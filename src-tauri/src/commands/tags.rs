@@ -0,0 +1,109 @@
+use crate::db::{self, DbState, Tag};
+use tauri::State;
+
+/// List all tags with item counts
+#[tauri::command]
+pub async fn tag_list(state: State<'_, DbState>) -> Result<Vec<Tag>, String> {
+    state
+        .read(move |conn| db::list_tags(conn).map_err(|e| format!("Failed to list tags: {}", e)))
+        .await
+}
+
+/// Replace the tags on a history item
+#[tauri::command]
+pub async fn history_set_tags(
+    state: State<'_, DbState>,
+    id: i64,
+    tags: Vec<String>,
+) -> Result<(), String> {
+    state
+        .write(move |conn| {
+            db::set_history_tags(conn, id, &tags).map_err(|e| format!("Failed to set tags: {}", e))
+        })
+        .await
+}
+
+/// Add tags to history items, creating tags that don't exist yet
+#[tauri::command]
+pub async fn tag_assign(
+    state: State<'_, DbState>,
+    ids: Vec<i64>,
+    tags: Vec<String>,
+) -> Result<i64, String> {
+    state
+        .write(move |conn| {
+            db::add_history_tags(conn, &ids, &tags)
+                .map_err(|e| format!("Failed to assign tags: {}", e))
+        })
+        .await
+}
+
+/// Remove tags from history items
+#[tauri::command]
+pub async fn tag_unassign(
+    state: State<'_, DbState>,
+    ids: Vec<i64>,
+    tags: Vec<String>,
+) -> Result<i64, String> {
+    state
+        .write(move |conn| {
+            db::remove_history_tags(conn, &ids, &tags)
+                .map_err(|e| format!("Failed to remove tags: {}", e))
+        })
+        .await
+}
+
+/// Rename a tag
+#[tauri::command]
+pub async fn tag_rename(state: State<'_, DbState>, id: i64, name: String) -> Result<bool, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Tag name cannot be empty".to_string());
+    }
+
+    state
+        .write(move |conn| {
+            let existing =
+                db::find_tag(conn, &name).map_err(|e| format!("Failed to rename tag: {}", e))?;
+            if existing.is_some_and(|existing| existing != id) {
+                return Err(format!(
+                    "A tag named \"{}\" already exists; merge the tags instead",
+                    name
+                ));
+            }
+
+            db::rename_tag(conn, id, &name).map_err(|e| format!("Failed to rename tag: {}", e))
+        })
+        .await
+}
+
+/// Merge tags into one, keeping `target_id`
+#[tauri::command]
+pub async fn tag_merge(
+    state: State<'_, DbState>,
+    source_ids: Vec<i64>,
+    target_id: i64,
+) -> Result<i64, String> {
+    state
+        .write(move |conn| {
+            if !db::tag_exists(conn, target_id)
+                .map_err(|e| format!("Failed to merge tags: {}", e))?
+            {
+                return Err(format!("Tag {} not found", target_id));
+            }
+
+            db::merge_tags(conn, &source_ids, target_id)
+                .map_err(|e| format!("Failed to merge tags: {}", e))
+        })
+        .await
+}
+
+/// Delete a tag and remove it from every item
+#[tauri::command]
+pub async fn tag_delete(state: State<'_, DbState>, id: i64) -> Result<bool, String> {
+    state
+        .write(move |conn| {
+            db::delete_tag(conn, id).map_err(|e| format!("Failed to delete tag: {}", e))
+        })
+        .await
+}
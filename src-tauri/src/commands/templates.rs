@@ -4,17 +4,21 @@ use tauri::State;
 /// List all templates
 #[tauri::command]
 pub async fn template_list(state: State<'_, DbState>) -> Result<Vec<Template>, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-
-    db::list_templates(&conn).map_err(|e| format!("Failed to list templates: {}", e))
+    state
+        .read(move |conn| {
+            db::list_templates(conn).map_err(|e| format!("Failed to list templates: {}", e))
+        })
+        .await
 }
 
 /// Get a template by ID
 #[tauri::command]
 pub async fn template_get(state: State<'_, DbState>, id: i64) -> Result<Option<Template>, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-
-    db::get_template(&conn, id).map_err(|e| format!("Failed to get template: {}", e))
+    state
+        .read(move |conn| {
+            db::get_template(conn, id).map_err(|e| format!("Failed to get template: {}", e))
+        })
+        .await
 }
 
 /// Save a new template
@@ -23,9 +27,12 @@ pub async fn template_save(
     state: State<'_, DbState>,
     template: NewTemplate,
 ) -> Result<i64, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-
-    db::save_template(&conn, &template).map_err(|e| format!("Failed to save template: {}", e))
+    state
+        .write(move |conn| {
+            db::save_template(conn, &template)
+                .map_err(|e| format!("Failed to save template: {}", e))
+        })
+        .await
 }
 
 /// Update a template
@@ -35,25 +42,61 @@ pub async fn template_update(
     id: i64,
     template: NewTemplate,
 ) -> Result<bool, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-
-    db::update_template(&conn, id, &template)
-        .map_err(|e| format!("Failed to update template: {}", e))
+    state
+        .write(move |conn| {
+            db::update_template(conn, id, &template)
+                .map_err(|e| format!("Failed to update template: {}", e))
+        })
+        .await
 }
 
-/// Delete a template
+/// Move a template to the trash
 #[tauri::command]
 pub async fn template_delete(state: State<'_, DbState>, id: i64) -> Result<bool, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    state
+        .write(move |conn| {
+            db::delete_template(conn, id).map_err(|e| format!("Failed to delete template: {}", e))
+        })
+        .await
+}
 
-    db::delete_template(&conn, id).map_err(|e| format!("Failed to delete template: {}", e))
+/// List templates in the trash
+#[tauri::command]
+pub async fn template_trash_list(state: State<'_, DbState>) -> Result<Vec<Template>, String> {
+    state
+        .read(move |conn| {
+            db::list_template_trash(conn).map_err(|e| format!("Failed to list trash: {}", e))
+        })
+        .await
+}
+
+/// Restore a template from the trash
+#[tauri::command]
+pub async fn template_restore(state: State<'_, DbState>, id: i64) -> Result<bool, String> {
+    state
+        .write(move |conn| {
+            db::restore_template(conn, id).map_err(|e| format!("Failed to restore template: {}", e))
+        })
+        .await
+}
+
+/// Permanently delete a template that is in the trash
+#[tauri::command]
+pub async fn template_purge(state: State<'_, DbState>, id: i64) -> Result<bool, String> {
+    state
+        .write(move |conn| {
+            db::purge_template(conn, id).map_err(|e| format!("Failed to purge template: {}", e))
+        })
+        .await
 }
 
 /// Set a template as default
 #[tauri::command]
 pub async fn template_set_default(state: State<'_, DbState>, id: i64) -> Result<bool, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-
-    db::set_default_template(&conn, id)
-        .map_err(|e| format!("Failed to set default template: {}", e))
+    state
+        .write(move |conn| {
+            db::set_default_template(conn, id)
+                .map_err(|e| format!("Failed to set default template: {}", e))
+        })
+        .await
 }
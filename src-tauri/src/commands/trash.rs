@@ -0,0 +1,55 @@
+use crate::db::{self, DbState, TrashPurgeReport};
+use serde_json::{Map, Value};
+use tauri::State;
+
+use super::settings::{notify_settings_changed, save_settings};
+
+/// Days trashed items are kept before automatic purging (0 = never)
+#[tauri::command]
+pub async fn trash_get_retention(state: State<'_, DbState>) -> Result<i64, String> {
+    state
+        .read(move |conn| {
+            let settings = db::get_settings(conn)
+                .map_err(|e| format!("Failed to read trash settings: {}", e))?;
+
+            Ok(settings.trash_retention_days)
+        })
+        .await
+}
+
+/// Change the trash retention period and purge anything now past it
+#[tauri::command]
+pub async fn trash_set_retention(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+    days: i64,
+) -> Result<TrashPurgeReport, String> {
+    let mut patch = Map::new();
+    patch.insert("trashRetentionDays".to_string(), Value::from(days));
+
+    let (change, report) = state
+        .write(move |conn| {
+            let change = save_settings(conn, &patch)?;
+            let report = db::purge_expired_trash(conn, days)
+                .map_err(|e| format!("Failed to purge trash: {}", e))?;
+            Ok((change, report))
+        })
+        .await?;
+
+    notify_settings_changed(&app, &change);
+    Ok(report)
+}
+
+/// Purge history and templates trashed longer than the retention period
+#[tauri::command]
+pub async fn trash_purge_expired(state: State<'_, DbState>) -> Result<TrashPurgeReport, String> {
+    state
+        .write(move |conn| {
+            let settings = db::get_settings(conn)
+                .map_err(|e| format!("Failed to read trash settings: {}", e))?;
+
+            db::purge_expired_trash(conn, settings.trash_retention_days)
+                .map_err(|e| format!("Failed to purge trash: {}", e))
+        })
+        .await
+}
@@ -0,0 +1,626 @@
+use serde::{Deserialize, Serialize};
+use url::{Host, Url};
+
+/// Link shortening services that hide the real destination
+const URL_SHORTENERS: &[&str] = &[
+    "bit.ly",
+    "bl.ink",
+    "buff.ly",
+    "cutt.ly",
+    "goo.gl",
+    "is.gd",
+    "lnkd.in",
+    "ow.ly",
+    "qrco.de",
+    "rb.gy",
+    "rebrand.ly",
+    "s.id",
+    "shorturl.at",
+    "t.co",
+    "t.ly",
+    "tiny.cc",
+    "tinyurl.com",
+    "v.gd",
+];
+
+/// Schemes that run code or embed content instead of navigating
+const DANGEROUS_SCHEMES: &[&str] = &["javascript", "vbscript", "data", "file"];
+
+/// Query keys commonly used to forward the visitor somewhere else
+const REDIRECT_KEYS: &[&str] = &[
+    "continue",
+    "dest",
+    "destination",
+    "forward",
+    "goto",
+    "next",
+    "out",
+    "redir",
+    "redirect",
+    "redirect_uri",
+    "redirect_url",
+    "return",
+    "return_to",
+    "returnurl",
+    "target",
+    "u",
+    "url",
+];
+
+const TRACKING_KEYS: &[&str] = &[
+    "_hsenc",
+    "_hsmi",
+    "dclid",
+    "fbclid",
+    "gbraid",
+    "gclid",
+    "igshid",
+    "li_fat_id",
+    "mc_cid",
+    "mc_eid",
+    "msclkid",
+    "ttclid",
+    "twclid",
+    "wbraid",
+    "yclid",
+];
+
+/// Second-level labels under which registrations happen one level deeper
+const COMPOUND_SUFFIXES: &[&str] = &["ac", "co", "com", "edu", "gov", "net", "org"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlWarning {
+    pub code: String,
+    pub severity: String, // "info" | "low" | "medium" | "high"
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlRiskReport {
+    pub url: String,
+    pub host: Option<String>,
+    pub unicode_host: Option<String>,
+    pub risk: String, // "safe" | "low" | "medium" | "high"
+    pub warnings: Vec<UrlWarning>,
+}
+
+/// Analyze a URL for phishing and privacy risks without any network access
+#[tauri::command]
+pub async fn analyze_url_risk(url: String, label: Option<String>) -> Result<UrlRiskReport, String> {
+    Ok(analyze_url(&url, label.as_deref()))
+}
+
+/// Whether content should get a URL risk report when scanned
+pub fn is_linkable(content: &str) -> bool {
+    let lower = content.trim().to_lowercase();
+    lower.starts_with("http://")
+        || lower.starts_with("https://")
+        || DANGEROUS_SCHEMES
+            .iter()
+            .any(|scheme| lower.starts_with(&format!("{}:", scheme)))
+}
+
+/// Build a risk report for a URL, optionally checking it against a display label
+pub fn analyze_url(raw: &str, label: Option<&str>) -> UrlRiskReport {
+    let raw = raw.trim();
+    let mut warnings = Vec::new();
+
+    let url = match Url::parse(raw) {
+        Ok(url) => url,
+        Err(e) => {
+            warnings.push(warning(
+                "malformed",
+                "medium",
+                format!("URL could not be parsed: {}", e),
+            ));
+            return report(raw, None, None, warnings);
+        }
+    };
+
+    if DANGEROUS_SCHEMES.contains(&url.scheme()) {
+        warnings.push(warning(
+            "dangerousScheme",
+            "high",
+            format!("'{}:' links can run code or hide content", url.scheme()),
+        ));
+        return report(raw, None, None, warnings);
+    }
+
+    if url.scheme() == "http" {
+        warnings.push(warning(
+            "insecure",
+            "low",
+            "Link does not use HTTPS".to_string(),
+        ));
+    }
+
+    if !url.username().is_empty() || url.password().is_some() {
+        warnings.push(warning(
+            "credentialsInUrl",
+            "high",
+            "Text before '@' can disguise the real destination".to_string(),
+        ));
+    }
+
+    let (host, unicode_host) = match url.host() {
+        Some(Host::Domain(domain)) => {
+            let domain = domain.to_lowercase();
+            let (unicode, _) = idna::domain_to_unicode(&domain);
+            check_domain(&domain, &unicode, &mut warnings);
+            (Some(domain), Some(unicode))
+        }
+        Some(Host::Ipv4(ip)) => {
+            warnings.push(ip_warning(&ip.to_string()));
+            (Some(ip.to_string()), None)
+        }
+        Some(Host::Ipv6(ip)) => {
+            warnings.push(ip_warning(&ip.to_string()));
+            (Some(ip.to_string()), None)
+        }
+        None => (None, None),
+    };
+
+    check_query(&url, 0, &mut warnings);
+
+    if let (Some(label), Some(host)) = (label, host.as_deref()) {
+        check_label(label, host, &mut warnings);
+    }
+
+    report(raw, host, unicode_host, warnings)
+}
+
+fn warning(code: &str, severity: &str, message: String) -> UrlWarning {
+    UrlWarning {
+        code: code.to_string(),
+        severity: severity.to_string(),
+        message,
+    }
+}
+
+fn ip_warning(ip: &str) -> UrlWarning {
+    warning(
+        "ipHost",
+        "medium",
+        format!("Link points to a bare IP address ({})", ip),
+    )
+}
+
+fn report(
+    url: &str,
+    host: Option<String>,
+    unicode_host: Option<String>,
+    warnings: Vec<UrlWarning>,
+) -> UrlRiskReport {
+    let rank = |severity: &str| match severity {
+        "high" => 3,
+        "medium" => 2,
+        "low" => 1,
+        _ => 0,
+    };
+    let risk = match warnings.iter().map(|w| rank(&w.severity)).max() {
+        Some(3) => "high",
+        Some(2) => "medium",
+        Some(1) => "low",
+        _ => "safe",
+    };
+
+    UrlRiskReport {
+        url: url.to_string(),
+        host,
+        unicode_host,
+        risk: risk.to_string(),
+        warnings,
+    }
+}
+
+fn check_domain(domain: &str, unicode: &str, warnings: &mut Vec<UrlWarning>) {
+    if URL_SHORTENERS
+        .iter()
+        .any(|s| domain == *s || domain.ends_with(&format!(".{}", s)))
+    {
+        warnings.push(warning(
+            "shortener",
+            "medium",
+            format!("{} is a link shortener that hides the destination", domain),
+        ));
+    }
+
+    if !domain.split('.').any(|label| label.starts_with("xn--")) {
+        return;
+    }
+
+    warnings.push(warning(
+        "punycode",
+        "low",
+        format!("Internationalized domain shown as {}", unicode),
+    ));
+
+    for label in unicode.split('.') {
+        let scripts = label_scripts(label);
+        if scripts.len() > 1 && !is_allowed_script_mix(&scripts) {
+            warnings.push(warning(
+                "mixedScripts",
+                "high",
+                format!("'{}' mixes {} characters", label, scripts.join(" and ")),
+            ));
+        }
+
+        if let Some(lookalike) = latin_skeleton(label) {
+            warnings.push(warning(
+                "homograph",
+                "high",
+                format!(
+                    "'{}' imitates '{}' with look-alike letters",
+                    label, lookalike
+                ),
+            ));
+        }
+    }
+}
+
+/// Flag URLs carried in query parameters, recursing into nested ones
+fn check_query(url: &Url, depth: usize, warnings: &mut Vec<UrlWarning>) {
+    let mut tracking = Vec::new();
+    let mut embedded = Vec::new();
+
+    for (key, value) in url.query_pairs() {
+        let key = key.to_lowercase();
+        if key.starts_with("utm_") || TRACKING_KEYS.contains(&key.as_str()) {
+            tracking.push(key.clone());
+        }
+
+        if let Ok(target) = Url::parse(&value) {
+            if matches!(target.scheme(), "http" | "https") {
+                embedded.push((key, target));
+            }
+        }
+    }
+
+    if depth == 0 && !tracking.is_empty() {
+        warnings.push(warning(
+            "tracking",
+            "info",
+            format!("Tracking parameters: {}", tracking.join(", ")),
+        ));
+    }
+
+    for (key, target) in &embedded {
+        if REDIRECT_KEYS.contains(&key.as_str()) {
+            warnings.push(warning(
+                "redirectParameter",
+                "medium",
+                format!(
+                    "'{}' forwards to {}",
+                    key,
+                    target.host_str().unwrap_or("another site")
+                ),
+            ));
+        }
+        check_query(target, depth + 1, warnings);
+    }
+
+    if depth == 0 && (embedded.len() > 1 || warnings.iter().any(|w| w.code == "nestedRedirect")) {
+        warnings.push(warning(
+            "excessiveRedirects",
+            "high",
+            "Link chains through several embedded URLs".to_string(),
+        ));
+    } else if depth > 0 && !embedded.is_empty() {
+        warnings.push(warning(
+            "nestedRedirect",
+            "medium",
+            "An embedded URL carries another URL".to_string(),
+        ));
+    }
+}
+
+/// Warn when a label names a domain the URL doesn't actually go to
+fn check_label(label: &str, host: &str, warnings: &mut Vec<UrlWarning>) {
+    let target = registrable_domain(host);
+
+    let named: Vec<String> = label
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | '(' | ')' | '/' | '|'))
+        .map(|token| {
+            token
+                .trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .map(|token| {
+            token
+                .strip_prefix("www.")
+                .map(str::to_string)
+                .unwrap_or(token)
+        })
+        .filter(|token| looks_like_domain(token))
+        .collect();
+
+    if named.is_empty() {
+        return;
+    }
+
+    if !named.iter().any(|d| registrable_domain(d) == target) {
+        warnings.push(warning(
+            "labelMismatch",
+            "medium",
+            format!(
+                "Label mentions {} but the link goes to {}",
+                named.join(", "),
+                target
+            ),
+        ));
+    }
+}
+
+fn looks_like_domain(token: &str) -> bool {
+    let labels: Vec<&str> = token.split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|l| !l.is_empty())
+        && labels
+            .last()
+            .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_alphabetic()))
+}
+
+/// Approximate the registrable domain ("example.co.uk", "example.com")
+fn registrable_domain(host: &str) -> String {
+    let labels: Vec<&str> = host.trim_end_matches('.').split('.').collect();
+    let keep = if labels.len() >= 3
+        && labels[labels.len() - 1].len() == 2
+        && COMPOUND_SUFFIXES.contains(&labels[labels.len() - 2])
+    {
+        3
+    } else {
+        2
+    };
+    labels[labels.len().saturating_sub(keep)..].join(".")
+}
+
+/// Coarse Unicode script of a letter; digits and punctuation return `None`
+fn script_of(c: char) -> Option<&'static str> {
+    if c.is_ascii_alphabetic() {
+        return Some("Latin");
+    }
+    if !c.is_alphabetic() {
+        return None;
+    }
+
+    Some(match c as u32 {
+        0x00C0..=0x024F | 0x1E00..=0x1EFF => "Latin",
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => "Greek",
+        0x0400..=0x052F => "Cyrillic",
+        0x0530..=0x058F => "Armenian",
+        0x0590..=0x05FF => "Hebrew",
+        0x0600..=0x06FF | 0x0750..=0x077F => "Arabic",
+        0x0900..=0x097F => "Devanagari",
+        0x0E00..=0x0E7F => "Thai",
+        0x3040..=0x309F => "Hiragana",
+        0x30A0..=0x30FF => "Katakana",
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF => "Han",
+        0xAC00..=0xD7AF => "Hangul",
+        _ => "Other",
+    })
+}
+
+fn label_scripts(label: &str) -> Vec<&'static str> {
+    let mut scripts = Vec::new();
+    for script in label.chars().filter_map(script_of) {
+        if !scripts.contains(&script) {
+            scripts.push(script);
+        }
+    }
+    scripts
+}
+
+/// Japanese and Korean names legitimately combine scripts
+fn is_allowed_script_mix(scripts: &[&str]) -> bool {
+    let japanese = ["Han", "Hiragana", "Katakana", "Latin"];
+    let korean = ["Han", "Hangul", "Latin"];
+    let han_based = scripts.iter().any(|s| *s != "Latin");
+
+    han_based
+        && (scripts.iter().all(|s| japanese.contains(s))
+            || scripts.iter().all(|s| korean.contains(s)))
+}
+
+/// Latin letters that Cyrillic and Greek characters are commonly mistaken for
+fn confusable(c: char) -> Option<char> {
+    Some(match c {
+        'а' | 'α' => 'a',
+        'с' | 'ϲ' => 'c',
+        'ԁ' => 'd',
+        'е' | 'ε' => 'e',
+        'һ' => 'h',
+        'і' | 'ι' => 'i',
+        'ј' => 'j',
+        'κ' | 'к' => 'k',
+        'ӏ' => 'l',
+        'ո' => 'n',
+        'о' | 'ο' => 'o',
+        'р' | 'ρ' => 'p',
+        'ԛ' => 'q',
+        'ѕ' => 's',
+        'τ' => 't',
+        'υ' => 'u',
+        'ν' => 'v',
+        'ԝ' | 'ω' => 'w',
+        'х' | 'χ' => 'x',
+        'у' | 'γ' => 'y',
+        _ => return None,
+    })
+}
+
+/// If every non-ASCII letter in the label has a Latin look-alike, return the
+/// ASCII label it impersonates
+fn latin_skeleton(label: &str) -> Option<String> {
+    if label.is_ascii() {
+        return None;
+    }
+
+    label
+        .chars()
+        .map(|c| if c.is_ascii() { Some(c) } else { confusable(c) })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(report: &UrlRiskReport) -> Vec<&str> {
+        report.warnings.iter().map(|w| w.code.as_str()).collect()
+    }
+
+    #[test]
+    fn test_analyze_safe_url() {
+        let report = analyze_url("https://example.com/page", None);
+        assert_eq!(report.risk, "safe");
+        assert!(report.warnings.is_empty());
+        assert_eq!(report.host, Some("example.com".to_string()));
+    }
+
+    #[test]
+    fn test_analyze_dangerous_schemes() {
+        for url in [
+            "javascript:alert(1)",
+            "data:text/html;base64,PHNjcmlwdD4=",
+            "VBScript:msgbox",
+        ] {
+            let report = analyze_url(url, None);
+            assert_eq!(codes(&report), vec!["dangerousScheme"], "{}", url);
+            assert_eq!(report.risk, "high");
+        }
+    }
+
+    #[test]
+    fn test_analyze_ip_hosts() {
+        assert!(codes(&analyze_url("https://192.168.1.10/login", None)).contains(&"ipHost"));
+        assert!(codes(&analyze_url("https://[::1]/", None)).contains(&"ipHost"));
+        // Integer-encoded IPv4 is normalized by the parser
+        let report = analyze_url("http://3232235777/", None);
+        assert_eq!(report.host, Some("192.168.1.1".to_string()));
+        assert!(codes(&report).contains(&"ipHost"));
+    }
+
+    #[test]
+    fn test_analyze_shortener() {
+        let report = analyze_url("https://bit.ly/3abcDEF", None);
+        assert_eq!(codes(&report), vec!["shortener"]);
+        assert_eq!(report.risk, "medium");
+    }
+
+    #[test]
+    fn test_analyze_cyrillic_homograph() {
+        // "аррӏе.com" spelled with Cyrillic letters
+        let report = analyze_url("https://аррӏе.com/", None);
+
+        assert_eq!(report.host, Some("xn--80ak6aa92e.com".to_string()));
+        assert_eq!(report.unicode_host, Some("аррӏе.com".to_string()));
+        assert!(codes(&report).contains(&"punycode"));
+        assert!(codes(&report).contains(&"homograph"));
+        assert_eq!(report.risk, "high");
+    }
+
+    #[test]
+    fn test_analyze_mixed_scripts() {
+        // Latin "p" and "ypal" with a Cyrillic "а"
+        let report = analyze_url("https://pаypal.com/", None);
+        assert!(codes(&report).contains(&"mixedScripts"));
+        assert!(codes(&report).contains(&"homograph"));
+    }
+
+    #[test]
+    fn test_analyze_legitimate_idn() {
+        let report = analyze_url("https://münchen.de/", None);
+        assert_eq!(codes(&report), vec!["punycode"]);
+        assert_eq!(report.risk, "low");
+
+        let report = analyze_url("https://日本語ドメイン.jp/", None);
+        assert_eq!(codes(&report), vec!["punycode"]);
+    }
+
+    #[test]
+    fn test_analyze_credentials_in_url() {
+        let report = analyze_url("https://paypal.com@evil.example/", None);
+        assert!(codes(&report).contains(&"credentialsInUrl"));
+        assert_eq!(report.host, Some("evil.example".to_string()));
+    }
+
+    #[test]
+    fn test_analyze_insecure() {
+        assert_eq!(
+            codes(&analyze_url("http://example.com", None)),
+            vec!["insecure"]
+        );
+    }
+
+    #[test]
+    fn test_analyze_tracking_parameters() {
+        let report = analyze_url(
+            "https://example.com/?utm_source=qr&utm_medium=print&fbclid=abc&id=1",
+            None,
+        );
+        assert_eq!(codes(&report), vec!["tracking"]);
+        assert_eq!(report.risk, "safe");
+        assert!(report.warnings[0].message.contains("utm_source"));
+        assert!(report.warnings[0].message.contains("fbclid"));
+    }
+
+    #[test]
+    fn test_analyze_redirect_parameter() {
+        let report = analyze_url(
+            "https://example.com/login?next=https%3A%2F%2Fevil.example%2F",
+            None,
+        );
+        assert_eq!(codes(&report), vec!["redirectParameter"]);
+        assert!(report.warnings[0].message.contains("evil.example"));
+    }
+
+    #[test]
+    fn test_analyze_excessive_redirects() {
+        let nested = urlencoding::encode("https://b.example/?url=https://c.example/");
+        let report = analyze_url(&format!("https://a.example/?redirect={}", nested), None);
+        assert!(codes(&report).contains(&"nestedRedirect"));
+        assert!(codes(&report).contains(&"excessiveRedirects"));
+        assert_eq!(report.risk, "high");
+    }
+
+    #[test]
+    fn test_analyze_malformed() {
+        let report = analyze_url("https://", None);
+        assert_eq!(codes(&report), vec!["malformed"]);
+    }
+
+    #[test]
+    fn test_label_mismatch() {
+        let report = analyze_url("https://secure-login.example/", Some("PayPal.com login"));
+        assert_eq!(codes(&report), vec!["labelMismatch"]);
+
+        let report = analyze_url("https://www.paypal.com/signin", Some("paypal.com"));
+        assert!(report.warnings.is_empty());
+
+        let report = analyze_url("https://shop.example.co.uk/", Some("Visit example.co.uk"));
+        assert!(report.warnings.is_empty());
+
+        // Labels without a domain aren't compared
+        let report = analyze_url("https://example.com/", Some("Spring campaign"));
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_registrable_domain() {
+        assert_eq!(registrable_domain("www.example.com"), "example.com");
+        assert_eq!(registrable_domain("a.b.example.co.uk"), "example.co.uk");
+        assert_eq!(registrable_domain("example.com"), "example.com");
+        assert_eq!(registrable_domain("localhost"), "localhost");
+    }
+
+    #[test]
+    fn test_is_linkable() {
+        assert!(is_linkable("https://example.com"));
+        assert!(is_linkable("JavaScript:alert(1)"));
+        assert!(is_linkable("data:text/plain,hi"));
+        assert!(!is_linkable("WIFI:T:WPA;S:x;;"));
+        assert!(!is_linkable("hello"));
+    }
+}
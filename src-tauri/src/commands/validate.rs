@@ -1,8 +1,15 @@
+use crate::qr_type::classify_qr_type;
 use base64::{engine::general_purpose::STANDARD, Engine};
-use image::{DynamicImage, ImageReader};
+use image::{DynamicImage, ImageReader, RgbaImage};
 use rqrr::PreparedImage;
 use serde::{Deserialize, Serialize};
 use std::io::Cursor;
+use std::path::Path;
+
+use super::frames::{animation_frames, decoded_frames};
+use super::payload::{parse_payload, ParsedPayload};
+use super::preprocess::{decode_oriented, decode_with_pipeline};
+use super::url_risk::{analyze_url, is_linkable, UrlRiskReport};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -83,7 +90,7 @@ pub async fn validate_qr(
                     state: "warn".to_string(),
                     decoded_content: Some(decoded_str.clone()),
                     content_match: false,
-                    message: format!("Decoded content differs from expected"),
+                    message: "Decoded content differs from expected".to_string(),
                     suggestions: vec![
                         "Verify the QR content is correct".to_string(),
                     ],
@@ -110,10 +117,32 @@ pub async fn validate_qr(
 /// Decode a QR code from an image file path
 #[tauri::command]
 pub async fn scan_qr_from_file(file_path: String) -> Result<ScanResult, String> {
-    let img = image::open(&file_path)
-        .map_err(|e| format!("Failed to open image: {}", e))?;
+    tauri::async_runtime::spawn_blocking(move || scan_file(Path::new(&file_path)))
+        .await
+        .map_err(|e| format!("Failed to scan image: {}", e))?
+}
 
-    decode_qr_from_image(img)
+fn scan_file(path: &Path) -> Result<ScanResult, String> {
+    let reader = ImageReader::open(path).map_err(|e| format!("Failed to open image: {}", e))?;
+    let img = decode_oriented(reader)?;
+
+    let result = decode_qr_from_image(img)?;
+    if result.success {
+        return Ok(result);
+    }
+
+    // Animated images may only show the code on a later frame
+    if let Ok(mut frames) = animation_frames(path) {
+        frames.next(); // already decoded above
+        for frame in decoded_frames(frames) {
+            let found = decode_qr_from_image(frame)?;
+            if found.success {
+                return Ok(found);
+            }
+        }
+    }
+
+    Ok(result)
 }
 
 /// Decode a QR code from base64 image data
@@ -130,47 +159,75 @@ pub async fn scan_qr_from_data(image_data: String) -> Result<ScanResult, String>
         .decode(base64_data)
         .map_err(|e| format!("Failed to decode base64: {}", e))?;
 
-    let img = ImageReader::new(Cursor::new(&image_bytes))
-        .with_guessed_format()
-        .map_err(|e| format!("Failed to read image format: {}", e))?
-        .decode()
-        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    let img = decode_oriented(ImageReader::new(Cursor::new(&image_bytes)))?;
+
+    decode_qr_from_image(img)
+}
+
+/// Decode a QR code from the image currently on the clipboard
+#[tauri::command]
+pub async fn scan_qr_from_clipboard(app: tauri::AppHandle) -> Result<ScanResult, String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let image = match app.clipboard().read_image() {
+        Ok(image) => image,
+        Err(e) => {
+            return Ok(ScanResult {
+                success: false,
+                content: None,
+                qr_type: None,
+                subtype: None,
+                confidence: None,
+                pass: None,
+                parsed: None,
+                url_risk: None,
+                error: Some(format!("Clipboard does not contain an image: {}", e)),
+            });
+        }
+    };
+
+    let img = image_from_rgba(image.rgba().to_vec(), image.width(), image.height())?;
 
     decode_qr_from_image(img)
 }
 
+/// Wrap raw RGBA pixels (as provided by the clipboard) in a `DynamicImage`
+fn image_from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<DynamicImage, String> {
+    RgbaImage::from_raw(width, height, rgba)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| format!("Invalid clipboard image data for {}x{} pixels", width, height))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ScanResult {
     pub success: bool,
     pub content: Option<String>,
     pub qr_type: Option<String>,
+    pub subtype: Option<String>,
+    pub confidence: Option<f32>,
+    pub pass: Option<String>, // preprocessing pass that decoded the code
+    pub parsed: Option<ParsedPayload>,
+    pub url_risk: Option<UrlRiskReport>,
     pub error: Option<String>,
 }
 
 fn decode_qr_from_image(img: DynamicImage) -> Result<ScanResult, String> {
-    let gray_img = img.to_luma8();
-    let mut prepared = PreparedImage::prepare(gray_img);
-    let grids = prepared.detect_grids();
-
-    if grids.is_empty() {
-        return Ok(ScanResult {
-            success: false,
-            content: None,
-            qr_type: None,
-            error: Some("No QR code found in image".to_string()),
-        });
-    }
-
-    match grids[0].decode() {
-        Ok((_, content)) => {
-            let content_str = content.to_string();
-            let qr_type = detect_qr_type(&content_str);
+    match decode_with_pipeline(&img) {
+        Ok(found) => {
+            let detection = classify_qr_type(&found.content);
+            let parsed = parse_payload(&found.content);
+            let url_risk = is_linkable(&found.content).then(|| analyze_url(&found.content, None));
 
             Ok(ScanResult {
                 success: true,
-                content: Some(content_str),
-                qr_type: Some(qr_type),
+                content: Some(found.content),
+                qr_type: Some(detection.qr_type),
+                subtype: detection.subtype,
+                confidence: Some(detection.confidence),
+                pass: Some(found.pass.to_string()),
+                parsed,
+                url_risk,
                 error: None,
             })
         }
@@ -178,38 +235,20 @@ fn decode_qr_from_image(img: DynamicImage) -> Result<ScanResult, String> {
             success: false,
             content: None,
             qr_type: None,
-            error: Some(format!("Failed to decode QR: {:?}", e)),
+            subtype: None,
+            confidence: None,
+            pass: None,
+            parsed: None,
+            url_risk: None,
+            error: Some(e),
         }),
     }
 }
 
-fn detect_qr_type(content: &str) -> String {
-    let lower = content.to_lowercase();
-
-    if lower.starts_with("wifi:") {
-        "wifi".to_string()
-    } else if lower.starts_with("begin:vcard") {
-        "vcard".to_string()
-    } else if lower.starts_with("mailto:") {
-        "email".to_string()
-    } else if lower.starts_with("sms:") || lower.starts_with("smsto:") {
-        "sms".to_string()
-    } else if lower.starts_with("tel:") {
-        "phone".to_string()
-    } else if lower.starts_with("geo:") {
-        "geo".to_string()
-    } else if lower.starts_with("begin:vevent") {
-        "calendar".to_string()
-    } else if lower.starts_with("http://") || lower.starts_with("https://") {
-        "url".to_string()
-    } else {
-        "text".to_string()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::qr_type::detect_qr_type;
 
     #[test]
     fn test_detect_qr_type_all_types() {
@@ -270,24 +309,44 @@ mod tests {
         assert!(result.suggestions.is_empty());
     }
 
+    #[test]
+    fn test_image_from_rgba() {
+        let img = image_from_rgba(vec![255; 4 * 6], 3, 2).unwrap();
+        assert_eq!((img.width(), img.height()), (3, 2));
+
+        // Buffer too small for the claimed dimensions
+        assert!(image_from_rgba(vec![0; 10], 3, 2).is_err());
+    }
+
     #[test]
     fn test_scan_result_structure() {
         let success_result = ScanResult {
             success: true,
             content: Some("https://example.com".to_string()),
             qr_type: Some("url".to_string()),
+            subtype: None,
+            confidence: Some(1.0),
+            pass: Some("original".to_string()),
+            parsed: None,
+            url_risk: None,
             error: None,
         };
 
         assert!(success_result.success);
         assert_eq!(success_result.content, Some("https://example.com".to_string()));
         assert_eq!(success_result.qr_type, Some("url".to_string()));
+        assert_eq!(success_result.pass, Some("original".to_string()));
         assert!(success_result.error.is_none());
 
         let failure_result = ScanResult {
             success: false,
             content: None,
             qr_type: None,
+            subtype: None,
+            confidence: None,
+            pass: None,
+            parsed: None,
+            url_risk: None,
             error: Some("No QR code found".to_string()),
         };
 
@@ -0,0 +1,182 @@
+use crate::db::{self, DbState, Workspace, WorkspaceCopyReport, WorkspaceInfo, DEFAULT_WORKSPACE};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{Emitter, State};
+use tauri_plugin_dialog::DialogExt;
+
+/// Event emitted with a `WorkspaceList` after switching workspace or data dir
+pub const WORKSPACE_CHANGED_EVENT: &str = "workspace-changed";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceList {
+    pub active: String,
+    pub data_dir: String,
+    pub portable: bool, // data kept next to the executable
+    pub workspaces: Vec<WorkspaceInfo>,
+    pub data_dir_error: Option<String>, // the configured data dir couldn't be opened
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDirResult {
+    pub workspaces: WorkspaceList,
+    pub copy: Option<WorkspaceCopyReport>,
+}
+
+fn current_workspaces(state: &DbState) -> WorkspaceList {
+    let workspace = state.workspace();
+
+    WorkspaceList {
+        active: workspace.name.clone(),
+        data_dir: workspace.data_dir.to_string_lossy().to_string(),
+        portable: db::is_portable(),
+        workspaces: db::list_workspaces(&workspace.data_dir),
+        data_dir_error: state.data_dir_error(),
+    }
+}
+
+/// Open a workspace off the async runtime; migrations can take a while
+async fn open_workspace(data_dir: PathBuf, name: String) -> Result<Workspace, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        Workspace::open(&data_dir, &name).map_err(|e| format!("Failed to open workspace: {}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Make `workspace` current and tell the frontend to reload its data
+fn activate(
+    app: &tauri::AppHandle,
+    state: &DbState,
+    workspace: Workspace,
+) -> Result<WorkspaceList, String> {
+    state
+        .activate(workspace)
+        .map_err(|e| format!("Failed to save workspace choice: {}", e))?;
+
+    let list = current_workspaces(state);
+    if let Err(e) = app.emit(WORKSPACE_CHANGED_EVENT, &list) {
+        eprintln!("Failed to emit workspace change: {}", e);
+    }
+    Ok(list)
+}
+
+/// List workspaces in the current data directory
+#[tauri::command]
+pub async fn workspace_list(state: State<'_, DbState>) -> Result<WorkspaceList, String> {
+    Ok(current_workspaces(&state))
+}
+
+/// Create an empty workspace without switching to it
+#[tauri::command]
+pub async fn workspace_create(
+    state: State<'_, DbState>,
+    name: String,
+) -> Result<WorkspaceInfo, String> {
+    let name = db::validate_workspace_name(&name)?;
+    let data_dir = state.workspace().data_dir.clone();
+    if name == DEFAULT_WORKSPACE || db::workspace_paths(&data_dir, &name).db.exists() {
+        return Err(format!("A workspace named \"{}\" already exists", name));
+    }
+
+    open_workspace(data_dir.clone(), name.clone()).await?;
+
+    db::list_workspaces(&data_dir)
+        .into_iter()
+        .find(|workspace| workspace.name == name)
+        .ok_or_else(|| format!("Failed to create workspace \"{}\"", name))
+}
+
+/// Switch to another workspace in the current data directory
+#[tauri::command]
+pub async fn workspace_switch(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+    name: String,
+) -> Result<WorkspaceList, String> {
+    let name = db::validate_workspace_name(&name)?;
+    let data_dir = state.workspace().data_dir.clone();
+    if !db::workspace_paths(&data_dir, &name).db.is_file() && name != DEFAULT_WORKSPACE {
+        return Err(format!("Workspace \"{}\" not found", name));
+    }
+
+    let workspace = open_workspace(data_dir, name).await?;
+    activate(&app, &state, workspace)
+}
+
+/// Delete a workspace and its backups
+#[tauri::command]
+pub async fn workspace_delete(state: State<'_, DbState>, name: String) -> Result<bool, String> {
+    let name = db::validate_workspace_name(&name)?;
+    let current = state.workspace();
+    if name == current.name {
+        return Err("Switch to another workspace before deleting this one".to_string());
+    }
+    if !db::workspace_paths(&current.data_dir, &name).db.is_file() {
+        return Ok(false);
+    }
+
+    db::delete_workspace(&current.data_dir, &name)
+        .map_err(|e| format!("Failed to delete workspace: {}", e))?;
+    Ok(true)
+}
+
+/// Move to another data directory, or back to the default one with `None`
+///
+/// With `copyExisting`, workspaces are copied over first; ones already in
+/// the target are kept. The current workspace stays active if the target
+/// has it, otherwise the default workspace opens.
+#[tauri::command]
+pub async fn data_dir_set(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+    path: Option<String>,
+    copy_existing: bool,
+) -> Result<DataDirResult, String> {
+    let target = match path {
+        Some(path) if !Path::new(&path).is_absolute() => {
+            return Err("Data directory must be an absolute path".to_string());
+        }
+        Some(path) => PathBuf::from(path),
+        None => state.home().to_path_buf(),
+    };
+    std::fs::create_dir_all(&target)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+
+    let current = state.workspace();
+    let copy = if copy_existing && target != current.data_dir {
+        let (from, to) = (current.data_dir.clone(), target.clone());
+        let report = tauri::async_runtime::spawn_blocking(move || {
+            db::copy_workspaces(&from, &to).map_err(|e| format!("Failed to copy data: {}", e))
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        Some(report)
+    } else {
+        None
+    };
+
+    let name = if db::workspace_paths(&target, &current.name).db.is_file() {
+        current.name.clone()
+    } else {
+        DEFAULT_WORKSPACE.to_string()
+    };
+    let workspace = open_workspace(target, name).await?;
+
+    Ok(DataDirResult {
+        workspaces: activate(&app, &state, workspace)?,
+        copy,
+    })
+}
+
+/// Show a folder picker for choosing a data directory
+#[tauri::command]
+pub async fn pick_data_dir(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let dir_path = app.dialog().file().blocking_pick_folder();
+
+    match dir_path {
+        Some(path) => Ok(Some(path.as_path().unwrap().to_string_lossy().to_string())),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,665 @@
+use chrono::{Local, NaiveDateTime, TimeZone};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::migrations::{latest_version, schema_version};
+
+/// Automatic backups kept before the oldest are deleted
+pub const DEFAULT_BACKUP_KEEP: usize = 7;
+
+/// Minimum time between automatic backups
+pub const AUTO_BACKUP_INTERVAL_HOURS: i64 = 24;
+
+const BACKUP_PREFIX: &str = "qr-foundry-";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackupKind {
+    Auto,
+    Manual,
+    PreRestore, // taken automatically before a restore or recovery
+}
+
+impl BackupKind {
+    fn suffix(self) -> &'static str {
+        match self {
+            BackupKind::Auto => "auto",
+            BackupKind::Manual => "manual",
+            BackupKind::PreRestore => "pre-restore",
+        }
+    }
+
+    fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "auto" => Some(BackupKind::Auto),
+            "manual" => Some(BackupKind::Manual),
+            "pre-restore" => Some(BackupKind::PreRestore),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub path: String,
+    pub file_name: String,
+    pub kind: BackupKind,
+    pub created_at: String,
+    pub bytes: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub ok: bool,
+    pub errors: Vec<String>, // empty when `ok`
+}
+
+/// What a backup file holds, and whether it can be restored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupCheck {
+    pub schema_version: i64,
+    pub history_items: i64,
+    pub templates: i64,
+    pub integrity: IntegrityReport,
+    pub problem: Option<String>, // why the backup can't be restored
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryReport {
+    pub actions: Vec<String>,
+    pub salvage_errors: Vec<String>, // tables that could not be copied in full
+    pub integrity: IntegrityReport,
+}
+
+/// Copy a live database to `dest` with SQLite's online backup API
+pub fn backup_to(conn: &Connection, dest: &Path) -> Result<(), rusqlite::Error> {
+    let mut target = Connection::open(dest)?;
+    Backup::new(conn, &mut target)?.run_to_completion(256, Duration::from_millis(5), None)?;
+
+    // The copy inherits WAL mode from a live database; switch back so the
+    // backup stays a single self-contained file
+    target.pragma_update_and_check(None, "journal_mode", "DELETE", |row| {
+        row.get::<_, String>(0)
+    })?;
+    Ok(())
+}
+
+/// Write a timestamped backup into `dir`
+pub fn create_backup(
+    conn: &Connection,
+    dir: &Path,
+    kind: BackupKind,
+) -> Result<BackupInfo, rusqlite::Error> {
+    std::fs::create_dir_all(dir).map_err(|e| io_error(dir, e))?;
+
+    let now = Local::now();
+    let mut path = dir.join(backup_file_name(&now.naive_local(), kind));
+    // Two backups within the same second: keep both
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!(
+            "{}{}-{}-{}.db",
+            BACKUP_PREFIX,
+            now.format(TIMESTAMP_FORMAT),
+            kind.suffix(),
+            n
+        ));
+    }
+
+    backup_to(conn, &path)?;
+    backup_info(&path).ok_or_else(|| io_error(&path, std::io::ErrorKind::NotFound.into()))
+}
+
+/// Take an automatic backup when the last one is older than the interval,
+/// then delete automatic backups beyond `keep`
+pub fn create_automatic_backup_if_due(
+    conn: &Connection,
+    dir: &Path,
+    keep: usize,
+) -> Result<Option<BackupInfo>, rusqlite::Error> {
+    let latest = list_backups(dir)
+        .into_iter()
+        .find(|backup| backup.kind == BackupKind::Auto);
+
+    let due = match latest.and_then(|backup| parse_timestamp(&backup.created_at)) {
+        Some(created) => {
+            Local::now().naive_local() - created
+                >= chrono::Duration::hours(AUTO_BACKUP_INTERVAL_HOURS)
+        }
+        None => true,
+    };
+    if !due {
+        return Ok(None);
+    }
+
+    let backup = create_backup(conn, dir, BackupKind::Auto)?;
+    rotate_backups(dir, keep);
+    Ok(Some(backup))
+}
+
+/// Delete automatic backups beyond the newest `keep`; returns how many went
+pub fn rotate_backups(dir: &Path, keep: usize) -> usize {
+    list_backups(dir)
+        .into_iter()
+        .filter(|backup| backup.kind == BackupKind::Auto)
+        .skip(keep)
+        .filter(|backup| std::fs::remove_file(&backup.path).is_ok())
+        .count()
+}
+
+/// Delete every backup in `dir`; returns how many went
+pub fn purge_backups(dir: &Path) -> usize {
+    list_backups(dir)
+        .into_iter()
+        .filter(|backup| std::fs::remove_file(&backup.path).is_ok())
+        .count()
+}
+
+/// List backups in `dir`, newest first
+pub fn list_backups(dir: &Path) -> Vec<BackupInfo> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<BackupInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| backup_info(&entry.path()))
+        .collect();
+    backups.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| b.file_name.cmp(&a.file_name))
+    });
+    backups
+}
+
+fn backup_file_name(timestamp: &NaiveDateTime, kind: BackupKind) -> String {
+    format!(
+        "{}{}-{}.db",
+        BACKUP_PREFIX,
+        timestamp.format(TIMESTAMP_FORMAT),
+        kind.suffix()
+    )
+}
+
+/// Parse `qr-foundry-<timestamp>-<kind>[-n].db`
+fn backup_info(path: &Path) -> Option<BackupInfo> {
+    let file_name = path.file_name()?.to_str()?.to_string();
+    let stem = file_name.strip_prefix(BACKUP_PREFIX)?.strip_suffix(".db")?;
+
+    // Timestamp is fixed width: YYYYMMDD-HHMMSS
+    let (timestamp, rest) = stem.split_at_checked(15)?;
+    let timestamp = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+    let rest = rest.strip_prefix('-')?;
+    let suffix = rest
+        .rsplit_once('-')
+        .filter(|(_, n)| n.parse::<u32>().is_ok())
+        .map(|(suffix, _)| suffix)
+        .unwrap_or(rest);
+    let kind = BackupKind::from_suffix(suffix)?;
+
+    let bytes = std::fs::metadata(path).ok()?.len() as i64;
+    let created_at = Local
+        .from_local_datetime(&timestamp)
+        .earliest()?
+        .to_rfc3339();
+
+    Some(BackupInfo {
+        path: path.to_string_lossy().to_string(),
+        file_name,
+        kind,
+        created_at,
+        bytes,
+    })
+}
+
+fn parse_timestamp(created_at: &str) -> Option<NaiveDateTime> {
+    chrono::DateTime::parse_from_rfc3339(created_at)
+        .ok()
+        .map(|dt| dt.with_timezone(&Local).naive_local())
+}
+
+pub(super) fn io_error(path: &Path, e: std::io::Error) -> rusqlite::Error {
+    rusqlite::Error::InvalidPath(PathBuf::from(format!("{}: {}", path.display(), e)))
+}
+
+/// Run `PRAGMA integrity_check` plus a consistency check of the search index
+pub fn integrity_check(conn: &Connection) -> Result<IntegrityReport, rusqlite::Error> {
+    let lines = conn
+        .prepare("PRAGMA integrity_check(100)")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()
+        });
+    let mut errors: Vec<String> = match lines {
+        Ok(lines) => lines.into_iter().filter(|line| line != "ok").collect(),
+        // Badly damaged files can fail the check itself
+        Err(rusqlite::Error::SqliteFailure(e, message))
+            if e.code == rusqlite::ErrorCode::DatabaseCorrupt
+                || e.code == rusqlite::ErrorCode::NotADatabase =>
+        {
+            vec![message.unwrap_or_else(|| e.to_string())]
+        }
+        Err(e) => return Err(e),
+    };
+
+    let has_fts: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'history_fts')",
+        [],
+        |row| row.get(0),
+    )?;
+    if has_fts && errors.is_empty() {
+        let unindexed: i64 = conn.query_row(
+            r#"
+            SELECT (SELECT COUNT(*) FROM history h
+                    WHERE NOT EXISTS (SELECT 1 FROM history_fts f WHERE f.rowid = h.id))
+                 + (SELECT COUNT(*) FROM history_fts f
+                    WHERE NOT EXISTS (SELECT 1 FROM history h WHERE h.id = f.rowid))
+            "#,
+            [],
+            |row| row.get(0),
+        )?;
+        if unindexed > 0 {
+            errors.push(format!(
+                "Search index is out of sync with history ({} rows differ)",
+                unindexed
+            ));
+        }
+    }
+
+    Ok(IntegrityReport {
+        ok: errors.is_empty(),
+        errors,
+    })
+}
+
+/// Open a backup read-only and check it can be restored
+pub fn validate_backup(path: &Path) -> Result<BackupCheck, rusqlite::Error> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let integrity = integrity_check(&conn)?;
+    let schema_version = schema_version(&conn)?;
+    let has_tables: bool = conn.query_row(
+        r#"
+        SELECT COUNT(*) = 2 FROM sqlite_master
+        WHERE type = 'table' AND name IN ('history', 'templates')
+        "#,
+        [],
+        |row| row.get(0),
+    )?;
+
+    let count = |table: &str| -> Result<i64, rusqlite::Error> {
+        if !has_tables {
+            return Ok(0);
+        }
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get(0)
+        })
+    };
+    let history_items = count("history")?;
+    let templates = count("templates")?;
+
+    let problem = if !has_tables {
+        Some("File is not a QR Foundry database".to_string())
+    } else if schema_version > latest_version() {
+        Some(format!(
+            "Backup was made by a newer version of QR Foundry (schema {})",
+            schema_version
+        ))
+    } else if !integrity.ok {
+        Some(format!(
+            "Backup is damaged: {}",
+            integrity.errors.join("; ")
+        ))
+    } else {
+        None
+    };
+
+    Ok(BackupCheck {
+        schema_version,
+        history_items,
+        templates,
+        integrity,
+        problem,
+    })
+}
+
+/// Replace the contents of `conn` with the database at `source`
+///
+/// Callers validate the file first and migrate afterwards, since a backup may
+/// predate the current schema.
+pub fn restore_from(conn: &mut Connection, source: &Path) -> Result<(), rusqlite::Error> {
+    let source = Connection::open_with_flags(source, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let backup = Backup::new(&source, conn)?;
+    backup.run_to_completion(256, Duration::from_millis(5), None)
+}
+
+/// Repair a damaged database in place
+///
+/// Rebuilds indexes and the search index first; if that isn't enough, copies
+/// every readable row into a fresh database and swaps it in. Take a backup
+/// before calling this.
+pub fn recover_database(conn: &mut Connection) -> Result<RecoveryReport, rusqlite::Error> {
+    let mut actions = Vec::new();
+    let mut salvage_errors = Vec::new();
+
+    if conn.execute_batch("REINDEX").is_ok() {
+        actions.push("Rebuilt indexes".to_string());
+    }
+    if rebuild_search_index(conn).is_ok() {
+        actions.push("Rebuilt search index".to_string());
+    }
+
+    let mut integrity = integrity_check(conn)?;
+    if !integrity.ok {
+        salvage_errors = salvage(conn)?;
+        actions.push("Copied readable rows into a fresh database".to_string());
+        integrity = integrity_check(conn)?;
+    }
+
+    Ok(RecoveryReport {
+        actions,
+        salvage_errors,
+        integrity,
+    })
+}
+
+fn rebuild_search_index(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(
+        r#"
+        DELETE FROM history_fts;
+        INSERT INTO history_fts (rowid, content, label, qr_type, tags)
+        SELECT h.id, h.content, coalesce(h.label, ''), h.qr_type,
+               coalesce((SELECT group_concat(t.name, ' ') FROM history_tags ht
+                         JOIN tags t ON t.id = ht.tag_id WHERE ht.history_id = h.id), '')
+        FROM history h;
+        "#,
+    )?;
+    tx.commit()
+}
+
+/// Copy what can be read into a new database file, then restore it over `conn`
+fn salvage(conn: &mut Connection) -> Result<Vec<String>, rusqlite::Error> {
+    let damaged = conn
+        .path()
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .ok_or_else(|| rusqlite::Error::InvalidPath(PathBuf::from(":memory:")))?;
+    let fresh_path = PathBuf::from(format!("{}.salvage", damaged.display()));
+    std::fs::remove_file(&fresh_path).ok();
+
+    let mut errors = Vec::new();
+    {
+        let fresh = Connection::open(&fresh_path)?;
+        fresh.execute(
+            "ATTACH DATABASE ?1 AS damaged",
+            [damaged.to_string_lossy().to_string()],
+        )?;
+
+        // Recreate the schema in its original order; FTS shadow tables come
+        // with their virtual table, and triggers refill the search index
+        let schema: Vec<String> = fresh
+            .prepare(
+                r#"
+                SELECT sql FROM damaged.sqlite_master
+                WHERE sql IS NOT NULL AND name NOT GLOB 'sqlite_*'
+                  AND NOT (type = 'table' AND name GLOB 'history_fts_*')
+                ORDER BY rowid
+                "#,
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        for sql in schema {
+            fresh.execute_batch(&sql)?;
+        }
+        let version = fresh.query_row("PRAGMA damaged.user_version", [], |row| {
+            row.get::<_, i64>(0)
+        })?;
+        fresh.pragma_update(None, "user_version", version)?;
+
+        let tables: Vec<String> = fresh
+            .prepare(
+                r#"
+                SELECT name FROM main.sqlite_master
+                WHERE type = 'table' AND sql NOT LIKE 'CREATE VIRTUAL%'
+                  AND name NOT GLOB 'sqlite_*' AND name NOT GLOB 'history_fts*'
+                "#,
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+
+        for table in tables {
+            // The WHERE clause stops SQLite's transfer optimization, which
+            // would copy the damaged indexes along with the rows
+            let copied = fresh.execute(
+                &format!(
+                    "INSERT OR IGNORE INTO main.{} SELECT * FROM damaged.{} WHERE 1",
+                    table, table
+                ),
+                [],
+            );
+            if let Err(e) = copied {
+                errors.push(format!("{}: {}", table, e));
+            }
+        }
+        fresh.execute("DETACH DATABASE damaged", [])?;
+    }
+
+    restore_from(conn, &fresh_path)?;
+    std::fs::remove_file(&fresh_path).ok();
+
+    Ok(errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{init_schema, save_history, ConnectionPool, NewHistoryItem};
+
+    fn file_db(path: &Path) -> Connection {
+        let conn = Connection::open(path).unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn save(conn: &Connection, content: &str) -> i64 {
+        save_history(
+            conn,
+            &NewHistoryItem {
+                content: content.to_string(),
+                qr_type: "text".to_string(),
+                label: None,
+                style_json: "{}".to_string(),
+                thumbnail: None,
+            },
+        )
+        .unwrap()
+    }
+
+    fn history_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_backup_and_restore_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = file_db(&dir.path().join("qr-foundry.db"));
+        save(&conn, "kept");
+
+        let backup = create_backup(&conn, &dir.path().join("backups"), BackupKind::Manual).unwrap();
+        assert_eq!(backup.kind, BackupKind::Manual);
+        assert!(backup.file_name.ends_with("-manual.db"));
+
+        save(&conn, "added later");
+        assert_eq!(history_count(&conn), 2);
+
+        let check = validate_backup(Path::new(&backup.path)).unwrap();
+        assert!(check.problem.is_none());
+        assert_eq!(check.history_items, 1);
+
+        restore_from(&mut conn, Path::new(&backup.path)).unwrap();
+        assert_eq!(history_count(&conn), 1);
+    }
+
+    #[test]
+    fn test_backup_and_restore_with_wal_pool() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = ConnectionPool::open(&dir.path().join("qr-foundry.db"), 2).unwrap();
+        init_schema(&pool.writer()).unwrap();
+        save(&pool.writer(), "kept");
+
+        // Backing up from a reader leaves the writer free
+        let backups = dir.path().join("backups");
+        let backup = create_backup(&pool.reader().unwrap(), &backups, BackupKind::Manual).unwrap();
+        save(&pool.writer(), "added later");
+
+        let backup_path = Path::new(&backup.path);
+        let mode: String = Connection::open(backup_path)
+            .unwrap()
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "delete");
+        assert!(validate_backup(backup_path).unwrap().problem.is_none());
+
+        let reader = pool.reader().unwrap();
+        assert_eq!(history_count(&reader), 2);
+        restore_from(&mut pool.writer(), backup_path).unwrap();
+        assert_eq!(history_count(&reader), 1);
+        assert_eq!(list_backups(&backups).len(), 1);
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_files() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let garbage = dir.path().join("garbage.db");
+        std::fs::write(&garbage, b"definitely not sqlite, just some text").unwrap();
+        assert!(validate_backup(&garbage).is_err());
+
+        let other = dir.path().join("other.db");
+        Connection::open(&other)
+            .unwrap()
+            .execute("CREATE TABLE notes (body TEXT)", [])
+            .unwrap();
+        let check = validate_backup(&other).unwrap();
+        assert_eq!(
+            check.problem.as_deref(),
+            Some("File is not a QR Foundry database")
+        );
+
+        let newer = dir.path().join("newer.db");
+        let conn = file_db(&newer);
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .unwrap();
+        drop(conn);
+        let check = validate_backup(&newer).unwrap();
+        assert!(check.problem.unwrap().contains("newer version"));
+    }
+
+    #[test]
+    fn test_list_and_rotate_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = file_db(&dir.path().join("qr-foundry.db"));
+        let backups = dir.path().join("backups");
+
+        // Backfill a week of automatic backups
+        std::fs::create_dir_all(&backups).unwrap();
+        for day in 1..=9 {
+            let timestamp = NaiveDateTime::parse_from_str(
+                &format!("2026-01-{:02} 03:00:00", day),
+                "%Y-%m-%d %H:%M:%S",
+            )
+            .unwrap();
+            backup_to(
+                &conn,
+                &backups.join(backup_file_name(&timestamp, BackupKind::Auto)),
+            )
+            .unwrap();
+        }
+        std::fs::write(backups.join("notes.txt"), "ignored").unwrap();
+
+        let listed = list_backups(&backups);
+        assert_eq!(listed.len(), 9);
+        assert!(listed[0].file_name.starts_with("qr-foundry-20260109"));
+
+        // The latest is long overdue, so a new one is taken and old ones rotate out
+        let created = create_automatic_backup_if_due(&conn, &backups, 3)
+            .unwrap()
+            .unwrap();
+        let listed = list_backups(&backups);
+        assert_eq!(listed.len(), 3);
+        assert_eq!(listed[0].file_name, created.file_name);
+
+        // Not due again straight away
+        assert!(create_automatic_backup_if_due(&conn, &backups, 3)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_integrity_check_and_search_index_recovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = file_db(&dir.path().join("qr-foundry.db"));
+        save(&conn, "alpha");
+        save(&conn, "beta");
+        assert!(integrity_check(&conn).unwrap().ok);
+
+        conn.execute("DELETE FROM history_fts WHERE rowid = 1", [])
+            .unwrap();
+        let report = integrity_check(&conn).unwrap();
+        assert!(!report.ok);
+        assert!(report.errors[0].contains("Search index"));
+
+        let recovery = recover_database(&mut conn).unwrap();
+        assert!(recovery.integrity.ok);
+        assert!(recovery.salvage_errors.is_empty());
+        assert_eq!(crate::db::count_history_search(&conn, "alpha").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_recovers_corrupted_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("qr-foundry.db");
+        let conn = file_db(&path);
+        for i in 0..200 {
+            save(&conn, &format!("https://example.com/{}", i));
+        }
+        let (page_size, root): (i64, i64) = conn
+            .query_row(
+                r#"
+                SELECT (SELECT page_size FROM pragma_page_size()), rootpage
+                FROM sqlite_master WHERE name = 'idx_history_created_at'
+                "#,
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        drop(conn);
+
+        // Scribble over the created_at index
+        let mut bytes = std::fs::read(&path).unwrap();
+        let start = ((root - 1) * page_size) as usize;
+        for byte in &mut bytes[start + 8..start + page_size as usize] {
+            *byte = 0xAB;
+        }
+        std::fs::write(&path, bytes).unwrap();
+
+        let mut conn = Connection::open(&path).unwrap();
+        assert!(!integrity_check(&conn).map(|r| r.ok).unwrap_or(false));
+
+        let recovery = recover_database(&mut conn).unwrap();
+        assert!(recovery.integrity.ok, "{:?}", recovery);
+        assert_eq!(history_count(&conn), 200, "{:?}", recovery);
+    }
+}
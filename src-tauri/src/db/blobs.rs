@@ -0,0 +1,265 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+
+/// Mime type for images that weren't base64 data URLs; stored verbatim
+const RAW_MIME: &str = "";
+
+/// An image stored once and referenced by its hash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blob {
+    pub hash: String,
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+impl Blob {
+    /// Rebuild the data URL the image was saved from
+    pub fn to_data_url(&self) -> String {
+        if self.mime == RAW_MIME {
+            return String::from_utf8_lossy(&self.data).to_string();
+        }
+        format!("data:{};base64,{}", self.mime, STANDARD.encode(&self.data))
+    }
+}
+
+/// Split a `data:<mime>;base64,<payload>` URL into its type and bytes
+pub(crate) fn parse_data_url(data_url: &str) -> Option<(&str, Vec<u8>)> {
+    let (header, payload) = data_url.strip_prefix("data:")?.split_once(',')?;
+    let mime = header.strip_suffix(";base64")?;
+    let bytes = STANDARD.decode(payload).ok()?;
+    // Only accept canonical encodings so the URL round-trips exactly
+    (STANDARD.encode(&bytes) == payload).then_some((mime, bytes))
+}
+
+/// Store an image (usually a data URL) and return its hash
+///
+/// Identical images are stored once.
+pub fn put_blob(conn: &Connection, data_url: &str) -> Result<String, rusqlite::Error> {
+    let (mime, data) =
+        parse_data_url(data_url).unwrap_or_else(|| (RAW_MIME, data_url.as_bytes().to_vec()));
+
+    let mut hasher = Sha256::new();
+    hasher.update(mime.as_bytes());
+    hasher.update([0]);
+    hasher.update(&data);
+    let hash = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+
+    conn.execute(
+        "INSERT OR IGNORE INTO blobs (hash, mime, data) VALUES (?1, ?2, ?3)",
+        params![hash, mime, data],
+    )?;
+
+    Ok(hash)
+}
+
+/// Store an optional image, passing `None` through
+pub fn put_optional_blob(
+    conn: &Connection,
+    data_url: Option<&str>,
+) -> Result<Option<String>, rusqlite::Error> {
+    data_url
+        .map(|data_url| put_blob(conn, data_url))
+        .transpose()
+}
+
+/// Get a blob by hash
+pub fn get_blob(conn: &Connection, hash: &str) -> Result<Option<Blob>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT hash, mime, data FROM blobs WHERE hash = ?1",
+        params![hash],
+        |row| {
+            Ok(Blob {
+                hash: row.get(0)?,
+                mime: row.get(1)?,
+                data: row.get(2)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Move inline `history.thumbnail` and `templates.preview` images into blobs
+///
+/// Runs inside the caller's migration transaction. Returns how many rows
+/// were migrated.
+pub fn migrate_inline_images(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    let mut migrated = 0;
+
+    let targets = [
+        ("history", "thumbnail", "thumbnail_hash"),
+        ("templates", "preview", "preview_hash"),
+    ];
+    for (table, inline, hash_column) in targets {
+        let rows = conn
+            .prepare(&format!(
+                "SELECT id, {} FROM {} WHERE {} IS NOT NULL",
+                inline, table, inline
+            ))?
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (id, data_url) in rows {
+            let hash = put_blob(conn, &data_url)?;
+            conn.execute(
+                &format!(
+                    "UPDATE {} SET {} = ?1, {} = NULL WHERE id = ?2",
+                    table, hash_column, inline
+                ),
+                params![hash, id],
+            )?;
+            migrated += 1;
+        }
+    }
+
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{delete_history, init_schema, purge_history, save_history, NewHistoryItem};
+
+    const PNG_URL: &str = "data:image/png;base64,iVBORw0KGgo=";
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn blob_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM blobs", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    fn save(conn: &Connection, thumbnail: &str) -> i64 {
+        save_history(
+            conn,
+            &NewHistoryItem {
+                content: "https://example.com".to_string(),
+                qr_type: "url".to_string(),
+                label: None,
+                style_json: "{}".to_string(),
+                thumbnail: Some(thumbnail.to_string()),
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_put_blob_decodes_and_dedupes() {
+        let conn = setup_test_db();
+        let a = put_blob(&conn, PNG_URL).unwrap();
+        let b = put_blob(&conn, PNG_URL).unwrap();
+
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 64);
+        assert_eq!(blob_count(&conn), 1);
+
+        let blob = get_blob(&conn, &a).unwrap().unwrap();
+        assert_eq!(blob.mime, "image/png");
+        assert_eq!(&blob.data[..4], b"\x89PNG");
+        assert_eq!(blob.to_data_url(), PNG_URL);
+    }
+
+    #[test]
+    fn test_non_data_urls_round_trip() {
+        let conn = setup_test_db();
+        let hash = put_blob(&conn, "data:image/png;base64,abc").unwrap();
+
+        let blob = get_blob(&conn, &hash).unwrap().unwrap();
+        assert_eq!(blob.to_data_url(), "data:image/png;base64,abc");
+        assert!(get_blob(&conn, "missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_blob_released_with_last_reference() {
+        let conn = setup_test_db();
+        let a = save(&conn, PNG_URL);
+        let b = save(&conn, PNG_URL);
+        assert_eq!(blob_count(&conn), 1);
+
+        delete_history(&conn, a).unwrap();
+        purge_history(&conn, &[a]).unwrap();
+        assert_eq!(blob_count(&conn), 1);
+
+        delete_history(&conn, b).unwrap();
+        purge_history(&conn, &[b]).unwrap();
+        assert_eq!(blob_count(&conn), 0);
+    }
+
+    #[test]
+    fn test_migrates_inline_images() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                content TEXT NOT NULL,
+                qr_type TEXT NOT NULL,
+                label TEXT,
+                style_json TEXT NOT NULL,
+                thumbnail TEXT,
+                created_at TEXT DEFAULT (datetime('now')),
+                updated_at TEXT DEFAULT (datetime('now'))
+            );
+            CREATE TABLE templates (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                style_json TEXT NOT NULL,
+                preview TEXT,
+                is_default INTEGER DEFAULT 0,
+                created_at TEXT DEFAULT (datetime('now'))
+            );
+            INSERT INTO history (content, qr_type, style_json, thumbnail)
+            VALUES ('a', 'text', '{}', 'data:image/png;base64,iVBORw0KGgo='),
+                   ('b', 'text', '{}', 'data:image/png;base64,iVBORw0KGgo='),
+                   ('c', 'text', '{}', NULL);
+            INSERT INTO templates (name, style_json, preview)
+            VALUES ('t', '{}', 'data:image/png;base64,iVBORw0KGgo=');
+            "#,
+        )
+        .unwrap();
+
+        init_schema(&conn).unwrap();
+
+        let inline: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM history WHERE thumbnail IS NOT NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let refs: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM history WHERE thumbnail_hash IS NOT NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let preview_hash: String = conn
+            .query_row("SELECT preview_hash FROM templates", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(inline, 0);
+        assert_eq!(refs, 2);
+        assert_eq!(blob_count(&conn), 1);
+        assert_eq!(
+            get_blob(&conn, &preview_hash)
+                .unwrap()
+                .unwrap()
+                .to_data_url(),
+            PNG_URL
+        );
+
+        // Nothing left to do on the next startup
+        assert_eq!(migrate_inline_images(&conn).unwrap(), 0);
+    }
+}
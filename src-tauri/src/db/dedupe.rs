@@ -0,0 +1,415 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::encryption::{
+    is_sensitive_content, reveal_history_content, seal_content, EncryptionError, EncryptionKey,
+};
+use super::history::{
+    history_from_row, insert_history_row, HistoryItem, NewHistoryItem, HISTORY_COLUMNS,
+};
+
+/// Key shared by identical saves: the type, content as stored, and style
+///
+/// Encrypted rows store redacted content, so a matching key on them is only
+/// a candidate until the decrypted content is compared.
+pub(super) fn content_hash(qr_type: &str, content: &str, style_json: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [qr_type, content, style_json] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Recompute a row's `content_hash` after its content or style changed
+pub(super) fn refresh_content_hash(conn: &Connection, id: i64) -> Result<(), rusqlite::Error> {
+    let row: Option<(String, String, String)> = conn
+        .query_row(
+            "SELECT qr_type, content, style_json FROM history WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+
+    if let Some((qr_type, content, style_json)) = row {
+        conn.execute(
+            "UPDATE history SET content_hash = ?1 WHERE id = ?2",
+            params![content_hash(&qr_type, &content, &style_json), id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Hash rows saved before hashes were stored; returns how many were filled
+pub(super) fn fill_content_hashes(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    let rows: Vec<(i64, String, String, String)> = conn
+        .prepare("SELECT id, qr_type, content, style_json FROM history WHERE content_hash IS NULL")?
+        .query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let mut stmt = conn.prepare("UPDATE history SET content_hash = ?1 WHERE id = ?2")?;
+    for (id, qr_type, content, style_json) in &rows {
+        stmt.execute(params![content_hash(qr_type, content, style_json), id])?;
+    }
+    Ok(rows.len() as i64)
+}
+
+/// Live item with the same key and content, most recently used first
+fn find_live_match(
+    conn: &Connection,
+    hash: &str,
+    content: &str,
+    key: Option<&EncryptionKey>,
+) -> Result<Option<i64>, EncryptionError> {
+    let candidates: Vec<(i64, bool)> = conn
+        .prepare(
+            r#"
+            SELECT id, content_cipher IS NOT NULL FROM history
+            WHERE content_hash = ?1 AND deleted_at IS NULL
+            ORDER BY updated_at DESC, id DESC
+            "#,
+        )?
+        .query_map(params![hash], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+
+    for (id, encrypted) in candidates {
+        if !encrypted || reveal_history_content(conn, id, key)?.as_deref() == Some(content) {
+            return Ok(Some(id));
+        }
+    }
+    Ok(None)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpsertOutcome {
+    pub id: i64,
+    pub created: bool,
+    pub use_count: i64,
+}
+
+/// Save a history item, or bump the live item it duplicates
+///
+/// A duplicate has the same type, content and style. Saving it again moves
+/// its `updated_at` to now, counts the use, and takes the new label if one
+/// is given.
+pub fn upsert_history(
+    conn: &Connection,
+    item: &NewHistoryItem,
+    key: Option<&EncryptionKey>,
+) -> Result<UpsertOutcome, EncryptionError> {
+    let sensitive = is_sensitive_content(&item.content);
+    let sealed = seal_content(conn, &item.content, sensitive, key)?;
+    let stored = sealed.as_ref().map_or(&item.content, |s| &s.redacted);
+    let hash = content_hash(&item.qr_type, stored, &item.style_json);
+
+    let tx = conn.unchecked_transaction()?;
+    let outcome = match find_live_match(&tx, &hash, &item.content, key)? {
+        Some(id) => {
+            let use_count = tx.query_row(
+                r#"
+                UPDATE history
+                SET use_count = use_count + 1, updated_at = datetime('now'),
+                    label = COALESCE(?1, label)
+                WHERE id = ?2
+                RETURNING use_count
+                "#,
+                params![item.label, id],
+                |row| row.get(0),
+            )?;
+            UpsertOutcome {
+                id,
+                created: false,
+                use_count,
+            }
+        }
+        None => UpsertOutcome {
+            id: insert_history_row(&tx, item, sensitive, sealed.as_ref())?,
+            created: true,
+            use_count: 1,
+        },
+    };
+    tx.commit()?;
+
+    Ok(outcome)
+}
+
+/// Live history items sharing type, content and style
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub keep: HistoryItem, // the copy the others merge into
+    pub duplicate_ids: Vec<i64>,
+}
+
+/// Find groups of live duplicates
+///
+/// Each group keeps a pinned copy if there is one, then a labeled one, then
+/// the oldest. Encrypted items are only compared while `key` is available.
+pub fn find_duplicate_history(
+    conn: &Connection,
+    key: Option<&EncryptionKey>,
+) -> Result<Vec<DuplicateGroup>, EncryptionError> {
+    let rows: Vec<(i64, String, bool)> = conn
+        .prepare(
+            r#"
+            SELECT id, content_hash, content_cipher IS NOT NULL FROM history
+            WHERE deleted_at IS NULL AND content_hash IN (
+                SELECT content_hash FROM history
+                WHERE deleted_at IS NULL AND content_hash IS NOT NULL
+                GROUP BY content_hash HAVING COUNT(*) > 1
+            )
+            ORDER BY content_hash, pinned DESC, label IS NULL, created_at, id
+            "#,
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<_, _>>()?;
+
+    // Rows of one hash, split by decrypted content where encrypted
+    let mut buckets: Vec<(String, Option<String>, Vec<i64>)> = Vec::new();
+    for (id, hash, encrypted) in rows {
+        let content = match (encrypted, key) {
+            (false, _) => None,
+            (true, None) => continue,
+            (true, Some(key)) => reveal_history_content(conn, id, Some(key))?,
+        };
+        match buckets
+            .iter_mut()
+            .find(|(h, c, _)| *h == hash && *c == content)
+        {
+            Some((_, _, ids)) => ids.push(id),
+            None => buckets.push((hash, content, vec![id])),
+        }
+    }
+
+    let mut groups = Vec::new();
+    for (_, _, ids) in buckets.into_iter().filter(|(_, _, ids)| ids.len() > 1) {
+        let keep = conn.query_row(
+            &format!("SELECT {} FROM history h WHERE h.id = ?1", HISTORY_COLUMNS),
+            params![ids[0]],
+            history_from_row,
+        )?;
+        groups.push(DuplicateGroup {
+            keep,
+            duplicate_ids: ids[1..].to_vec(),
+        });
+    }
+    Ok(groups)
+}
+
+/// Fold each group's duplicates into the copy it keeps
+///
+/// The kept item adds up the use counts, takes the latest `updated_at` and
+/// earliest `created_at`, gains the duplicates' tags, and fills in a missing
+/// label, folder or thumbnail. Duplicates go to the trash. Groups should
+/// come from `find_duplicate_history`; items that were edited or trashed
+/// since are left alone. Returns how many items were merged away.
+pub fn merge_duplicate_history(
+    conn: &Connection,
+    groups: &[DuplicateGroup],
+) -> Result<i64, rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    let mut merged = 0;
+    for group in groups {
+        let keep_id = group.keep.id;
+        for duplicate_id in &group.duplicate_ids {
+            let affected = tx.execute(
+                r#"
+                UPDATE history
+                SET use_count = history.use_count + d.use_count,
+                    updated_at = max(history.updated_at, d.updated_at),
+                    created_at = min(history.created_at, d.created_at),
+                    pinned = max(history.pinned, d.pinned),
+                    label = COALESCE(history.label, d.label),
+                    folder_id = COALESCE(history.folder_id, d.folder_id),
+                    thumbnail_hash = COALESCE(history.thumbnail_hash, d.thumbnail_hash)
+                FROM (SELECT * FROM history WHERE id = ?2 AND deleted_at IS NULL) AS d
+                WHERE history.id = ?1 AND history.id != d.id
+                  AND history.deleted_at IS NULL AND history.content_hash = d.content_hash
+                "#,
+                params![keep_id, duplicate_id],
+            )?;
+            if affected == 0 {
+                continue;
+            }
+
+            tx.execute(
+                r#"
+                INSERT OR IGNORE INTO history_tags (history_id, tag_id)
+                SELECT ?1, tag_id FROM history_tags WHERE history_id = ?2
+                "#,
+                params![keep_id, duplicate_id],
+            )?;
+            tx.execute(
+                "UPDATE history SET deleted_at = datetime('now') WHERE id = ?1",
+                params![duplicate_id],
+            )?;
+            merged += 1;
+        }
+    }
+    tx.commit()?;
+
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        add_history_tags, delete_history, enable_encryption, init_schema, list_history_page,
+        save_history, EncryptionScope, HistoryFilter, HistorySort, KeyMaterial,
+    };
+
+    const WIFI: &str = "WIFI:T:WPA;S:Office;P:hunter2;;";
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn item(content: &str, style_json: &str, label: Option<&str>) -> NewHistoryItem {
+        NewHistoryItem {
+            content: content.to_string(),
+            qr_type: "url".to_string(),
+            label: label.map(str::to_string),
+            style_json: style_json.to_string(),
+            thumbnail: None,
+        }
+    }
+
+    fn live_items(conn: &Connection) -> Vec<HistoryItem> {
+        list_history_page(
+            conn,
+            &HistoryFilter::default(),
+            HistorySort::CreatedAsc,
+            None,
+            100,
+            0,
+        )
+        .unwrap()
+        .items
+    }
+
+    #[test]
+    fn test_upsert_bumps_existing_item() {
+        let conn = setup_test_db();
+        let first = upsert_history(&conn, &item("https://a.example", "{}", None), None).unwrap();
+        assert!(first.created);
+
+        conn.execute(
+            "UPDATE history SET updated_at = '2020-01-01 00:00:00' WHERE id = ?1",
+            params![first.id],
+        )
+        .unwrap();
+
+        let again =
+            upsert_history(&conn, &item("https://a.example", "{}", Some("Site")), None).unwrap();
+        assert_eq!(again.id, first.id);
+        assert!(!again.created);
+        assert_eq!(again.use_count, 2);
+
+        let items = live_items(&conn);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].use_count, 2);
+        assert_eq!(items[0].label.as_deref(), Some("Site"));
+        assert!(items[0].updated_at.as_str() > "2020-01-01 00:00:00");
+
+        // A different style is a different item
+        let styled = upsert_history(
+            &conn,
+            &item("https://a.example", r#"{"dotStyle":"dots"}"#, None),
+            None,
+        )
+        .unwrap();
+        assert!(styled.created);
+    }
+
+    #[test]
+    fn test_upsert_ignores_trashed_items() {
+        let conn = setup_test_db();
+        let first = upsert_history(&conn, &item("https://a.example", "{}", None), None).unwrap();
+        delete_history(&conn, first.id).unwrap();
+
+        let again = upsert_history(&conn, &item("https://a.example", "{}", None), None).unwrap();
+        assert_ne!(again.id, first.id);
+        assert!(again.created);
+    }
+
+    #[test]
+    fn test_upsert_compares_encrypted_content() {
+        let conn = setup_test_db();
+        let key = enable_encryption(
+            &conn,
+            EncryptionScope::Sensitive,
+            KeyMaterial::Passphrase("correct horse"),
+        )
+        .unwrap();
+
+        let first = upsert_history(&conn, &item(WIFI, "{}", None), Some(&key)).unwrap();
+        let again = upsert_history(&conn, &item(WIFI, "{}", None), Some(&key)).unwrap();
+        assert_eq!(again.id, first.id);
+
+        // Same redacted form, different password
+        let other = "WIFI:T:WPA;S:Office;P:swordfish;;";
+        let other = upsert_history(&conn, &item(other, "{}", None), Some(&key)).unwrap();
+        assert!(other.created);
+    }
+
+    #[test]
+    fn test_find_and_merge_duplicates() {
+        let conn = setup_test_db();
+        let a = save_history(&conn, &item("https://a.example", "{}", None)).unwrap();
+        let b = save_history(&conn, &item("https://a.example", "{}", Some("Site"))).unwrap();
+        let c = save_history(&conn, &item("https://a.example", "{}", None)).unwrap();
+        save_history(&conn, &item("https://b.example", "{}", None)).unwrap();
+        add_history_tags(&conn, &[c], &["print".to_string()]).unwrap();
+
+        let groups = find_duplicate_history(&conn, None).unwrap();
+        assert_eq!(groups.len(), 1);
+        // The labeled copy is kept
+        assert_eq!(groups[0].keep.id, b);
+        assert_eq!(groups[0].duplicate_ids, vec![a, c]);
+
+        assert_eq!(merge_duplicate_history(&conn, &groups).unwrap(), 2);
+        assert!(find_duplicate_history(&conn, None).unwrap().is_empty());
+
+        let items = live_items(&conn);
+        assert_eq!(items.len(), 2);
+        let kept = items.iter().find(|i| i.id == b).unwrap();
+        assert_eq!(kept.use_count, 3);
+        assert_eq!(kept.tags, vec!["print"]);
+
+        // Merging a stale group again does nothing
+        assert_eq!(merge_duplicate_history(&conn, &groups).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_hashes_follow_encryption() {
+        let conn = setup_test_db();
+        let a = save_history(&conn, &item(WIFI, "{}", None)).unwrap();
+        let b = save_history(&conn, &item(WIFI, "{}", None)).unwrap();
+
+        let key = enable_encryption(
+            &conn,
+            EncryptionScope::Sensitive,
+            KeyMaterial::Passphrase("correct horse"),
+        )
+        .unwrap();
+
+        // Encrypted rows are only compared with the key
+        assert!(find_duplicate_history(&conn, None).unwrap().is_empty());
+        let groups = find_duplicate_history(&conn, Some(&key)).unwrap();
+        assert_eq!(groups[0].keep.id, a);
+        assert_eq!(groups[0].duplicate_ids, vec![b]);
+
+        let again = upsert_history(&conn, &item(WIFI, "{}", None), Some(&key)).unwrap();
+        assert_eq!(again.id, b);
+    }
+}
@@ -0,0 +1,1034 @@
+use crate::qr_type::detect_qr_type;
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+use super::backup::{create_backup, purge_backups, BackupKind};
+use super::dedupe::refresh_content_hash;
+use super::history::{insert_history, save_history, NewHistoryItem};
+use super::migrations::remove_migration_backups;
+use super::scans::{open_scan, seal_scan};
+use super::storage::vacuum_database;
+
+/// QR types whose content holds a secret: WiFi passwords and OTP seeds
+pub const SENSITIVE_TYPES: &[&str] = &["wifi", "otp"];
+
+/// Shown in place of a secret in lists and exports
+pub const REDACTED: &str = "********";
+
+/// Shortest passphrase accepted when enabling encryption
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+
+/// Encrypted into `key_check` so a wrong key is caught before any row is read
+const KEY_CHECK: &[u8] = b"qr-foundry history key";
+
+/// Where the encryption key comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeySource {
+    /// Random key stored in a file next to the database; unlocks on open
+    Keyfile,
+    /// Key derived from a passphrase with Argon2id; unlocked per session
+    Passphrase,
+}
+
+/// Which history rows get encrypted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncryptionScope {
+    /// Only WiFi and OTP content
+    Sensitive,
+    /// The content of every history row
+    All,
+}
+
+impl KeySource {
+    fn as_str(self) -> &'static str {
+        match self {
+            KeySource::Keyfile => "keyfile",
+            KeySource::Passphrase => "passphrase",
+        }
+    }
+}
+
+impl EncryptionScope {
+    fn as_str(self) -> &'static str {
+        match self {
+            EncryptionScope::Sensitive => "sensitive",
+            EncryptionScope::All => "all",
+        }
+    }
+}
+
+/// What unlocks the key: the keyfile's location or the passphrase
+pub enum KeyMaterial<'a> {
+    Keyfile(&'a Path),
+    Passphrase(&'a str),
+}
+
+#[derive(Debug)]
+pub enum EncryptionError {
+    NotEnabled,
+    AlreadyEnabled,
+    /// Encrypted content was needed but the key isn't unlocked
+    Locked,
+    /// The passphrase or keyfile doesn't match the one used to encrypt
+    WrongKey,
+    /// Unlocking with a passphrase when a keyfile is in use, or vice versa
+    WrongKeySource(KeySource),
+    WeakPassphrase,
+    InvalidKeyfile(String),
+    Io(std::io::Error),
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncryptionError::NotEnabled => write!(f, "Encryption is not enabled"),
+            EncryptionError::AlreadyEnabled => write!(f, "Encryption is already enabled"),
+            EncryptionError::Locked => {
+                write!(f, "Encrypted history is locked; unlock it first")
+            }
+            EncryptionError::WrongKey => write!(f, "Wrong passphrase or keyfile"),
+            EncryptionError::WrongKeySource(source) => {
+                write!(f, "History is encrypted with a {}", source.as_str())
+            }
+            EncryptionError::WeakPassphrase => write!(
+                f,
+                "Passphrase must be at least {} characters",
+                MIN_PASSPHRASE_LEN
+            ),
+            EncryptionError::InvalidKeyfile(message) => write!(f, "Invalid keyfile: {}", message),
+            EncryptionError::Io(e) => write!(f, "{}", e),
+            EncryptionError::Sqlite(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for EncryptionError {}
+
+impl From<rusqlite::Error> for EncryptionError {
+    fn from(e: rusqlite::Error) -> Self {
+        EncryptionError::Sqlite(e)
+    }
+}
+
+impl From<std::io::Error> for EncryptionError {
+    fn from(e: std::io::Error) -> Self {
+        EncryptionError::Io(e)
+    }
+}
+
+/// A 256-bit XChaCha20-Poly1305 key; deliberately not `Debug`
+#[derive(Clone)]
+pub struct EncryptionKey(Key);
+
+impl EncryptionKey {
+    fn generate() -> Self {
+        Self(XChaCha20Poly1305::generate_key(&mut OsRng))
+    }
+
+    fn derive(passphrase: &str, salt: &[u8]) -> Result<Self, EncryptionError> {
+        let mut key = Key::default();
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|_| EncryptionError::WrongKey)?;
+        Ok(Self(key))
+    }
+
+    /// Encrypt under a fresh random nonce, returned as `nonce || ciphertext`
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = XChaCha20Poly1305::new(&self.0)
+            .encrypt(&nonce, plaintext)
+            .expect("plaintext within the AEAD size limit");
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        sealed
+    }
+
+    /// Decrypt the output of `encrypt`; fails if the key or data is wrong
+    pub fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        if sealed.len() < NONCE_LEN {
+            return Err(EncryptionError::WrongKey);
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        XChaCha20Poly1305::new(&self.0)
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| EncryptionError::WrongKey)
+    }
+
+    fn decrypt_string(&self, sealed: &[u8]) -> Result<String, EncryptionError> {
+        String::from_utf8(self.decrypt(sealed)?).map_err(|_| EncryptionError::WrongKey)
+    }
+}
+
+/// Read a keyfile written by `enable_encryption`
+pub fn read_keyfile(path: &Path) -> Result<EncryptionKey, EncryptionError> {
+    let encoded = std::fs::read_to_string(path)?;
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| EncryptionError::InvalidKeyfile(e.to_string()))?;
+    if bytes.len() != 32 {
+        return Err(EncryptionError::InvalidKeyfile(
+            "expected a 256-bit key".to_string(),
+        ));
+    }
+    Ok(EncryptionKey(*Key::from_slice(&bytes)))
+}
+
+/// Write a new keyfile, readable only by the current user where supported
+fn write_keyfile(path: &Path, key: &EncryptionKey) -> std::io::Result<()> {
+    use std::io::Write;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    file.write_all(STANDARD.encode(key.0).as_bytes())?;
+    file.sync_all()
+}
+
+/// Whether encryption has been turned on for this database
+pub(super) fn is_encryption_enabled(conn: &Connection) -> Result<bool, rusqlite::Error> {
+    Ok(load_config(conn)?.is_some())
+}
+
+/// Whether content holds a secret, going by its detected QR type
+pub fn is_sensitive_content(content: &str) -> bool {
+    SENSITIVE_TYPES.contains(&detect_qr_type(content).as_str())
+}
+
+/// Mask the secret in sensitive content, keeping enough to recognize it
+///
+/// WiFi keeps everything but the password; OTP URIs keep the issuer and
+/// account but lose the seed. Anything else is masked entirely.
+pub fn redact_content(content: &str) -> String {
+    match detect_qr_type(content).as_str() {
+        "wifi" => redact_wifi(content),
+        "otp" => redact_otp(content),
+        _ => REDACTED.to_string(),
+    }
+}
+
+/// Replace the `P:` field of a `WIFI:` payload, honoring `\;` escapes
+fn redact_wifi(content: &str) -> String {
+    let Some((prefix, fields)) = content.split_at_checked(5) else {
+        return REDACTED.to_string();
+    };
+
+    let mut redacted = prefix.to_string();
+    let mut chars = fields.chars().peekable();
+    let mut at_field_start = true;
+    while let Some(c) = chars.next() {
+        if at_field_start && (c == 'P' || c == 'p') && chars.peek() == Some(&':') {
+            chars.next();
+            redacted.push(c);
+            redacted.push(':');
+            redacted.push_str(REDACTED);
+            // Skip the password up to its unescaped terminator
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    ';' => {
+                        redacted.push(';');
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            at_field_start = true;
+            continue;
+        }
+
+        redacted.push(c);
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    redacted.push(escaped);
+                }
+                at_field_start = false;
+            }
+            ';' => at_field_start = true,
+            _ => at_field_start = false,
+        }
+    }
+    redacted
+}
+
+/// Replace the `secret` query parameter of an `otpauth://` URI
+fn redact_otp(content: &str) -> String {
+    let Some((base, query)) = content.split_once('?') else {
+        return content.to_string();
+    };
+
+    let query: Vec<String> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if name.eq_ignore_ascii_case("secret") => {
+                format!("{}={}", name, REDACTED)
+            }
+            _ => pair.to_string(),
+        })
+        .collect();
+    format!("{}?{}", base, query.join("&"))
+}
+
+/// Flag history rows holding secrets; returns how many were newly flagged
+pub fn mark_sensitive_history(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    let rows: Vec<(i64, String)> = conn
+        .prepare("SELECT id, content FROM history WHERE sensitive = 0 AND content_cipher IS NULL")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut stmt = conn.prepare("UPDATE history SET sensitive = 1 WHERE id = ?1")?;
+    let mut marked = 0;
+    for (id, content) in rows {
+        if is_sensitive_content(&content) {
+            marked += stmt.execute(params![id])? as i64;
+        }
+    }
+    Ok(marked)
+}
+
+struct EncryptionConfig {
+    key_source: KeySource,
+    scope: EncryptionScope,
+    salt: Option<Vec<u8>>,
+    key_check: Vec<u8>,
+}
+
+impl EncryptionConfig {
+    /// Whether a row with this sensitivity gets encrypted
+    fn covers(&self, sensitive: bool) -> bool {
+        sensitive || self.scope == EncryptionScope::All
+    }
+
+    fn verify(&self, key: &EncryptionKey) -> Result<(), EncryptionError> {
+        match key.decrypt(&self.key_check) {
+            Ok(check) if check == KEY_CHECK => Ok(()),
+            _ => Err(EncryptionError::WrongKey),
+        }
+    }
+}
+
+fn load_config(conn: &Connection) -> Result<Option<EncryptionConfig>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT key_source, scope, salt, key_check FROM encryption WHERE id = 1",
+        [],
+        |row| {
+            let key_source = match row.get::<_, String>(0)?.as_str() {
+                "passphrase" => KeySource::Passphrase,
+                _ => KeySource::Keyfile,
+            };
+            let scope = match row.get::<_, String>(1)?.as_str() {
+                "all" => EncryptionScope::All,
+                _ => EncryptionScope::Sensitive,
+            };
+            Ok(EncryptionConfig {
+                key_source,
+                scope,
+                salt: row.get(2)?,
+                key_check: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionStatus {
+    pub enabled: bool,
+    pub key_source: Option<KeySource>,
+    pub scope: Option<EncryptionScope>,
+    pub unlocked: bool, // whether encrypted content can be read this session
+    pub sensitive_items: i64,
+    pub encrypted_items: i64,
+    pub error: Option<String>, // why secrets may still be on disk in the clear
+}
+
+/// Report whether encryption is on and how many rows it covers
+pub fn encryption_status(
+    conn: &Connection,
+    unlocked: bool,
+) -> Result<EncryptionStatus, rusqlite::Error> {
+    let config = load_config(conn)?;
+    let (sensitive_items, encrypted_items) = conn.query_row(
+        r#"
+        SELECT COALESCE(SUM(sensitive), 0), COUNT(content_cipher)
+        FROM history
+        "#,
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    Ok(EncryptionStatus {
+        enabled: config.is_some(),
+        key_source: config.as_ref().map(|c| c.key_source),
+        scope: config.as_ref().map(|c| c.scope),
+        unlocked: config.is_none() || unlocked,
+        sensitive_items,
+        encrypted_items,
+        error: None,
+    })
+}
+
+/// Turn encryption on and encrypt the rows it covers
+///
+/// A keyfile is created at the given path unless one is already there, in
+/// which case its key is reused. Returns the key to keep for the session.
+pub fn enable_encryption(
+    conn: &Connection,
+    scope: EncryptionScope,
+    material: KeyMaterial,
+) -> Result<EncryptionKey, EncryptionError> {
+    if load_config(conn)?.is_some() {
+        return Err(EncryptionError::AlreadyEnabled);
+    }
+
+    let (key, key_source, salt) = match material {
+        KeyMaterial::Keyfile(path) if path.exists() => {
+            (read_keyfile(path)?, KeySource::Keyfile, None)
+        }
+        KeyMaterial::Keyfile(path) => {
+            let key = EncryptionKey::generate();
+            write_keyfile(path, &key)?;
+            (key, KeySource::Keyfile, None)
+        }
+        KeyMaterial::Passphrase(passphrase) => {
+            if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+                return Err(EncryptionError::WeakPassphrase);
+            }
+            let mut salt = vec![0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            let key = EncryptionKey::derive(passphrase, &salt)?;
+            (key, KeySource::Passphrase, Some(salt))
+        }
+    };
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        r#"
+        INSERT INTO encryption (id, key_source, scope, salt, key_check)
+        VALUES (1, ?1, ?2, ?3, ?4)
+        "#,
+        params![
+            key_source.as_str(),
+            scope.as_str(),
+            salt,
+            key.encrypt(KEY_CHECK)
+        ],
+    )?;
+
+    seal_rows(&tx, scope, &key)?;
+    tx.commit()?;
+
+    Ok(key)
+}
+
+/// Encrypt the history and scan rows `scope` covers that are still plaintext
+///
+/// Returns how many rows were encrypted.
+fn seal_rows(
+    conn: &Connection,
+    scope: EncryptionScope,
+    key: &EncryptionKey,
+) -> Result<i64, rusqlite::Error> {
+    let rows: Vec<(i64, String)> = conn
+        .prepare(
+            r#"
+            SELECT id, content FROM history
+            WHERE content_cipher IS NULL AND (sensitive = 1 OR ?1 = 'all')
+            "#,
+        )?
+        .query_map(params![scope.as_str()], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .collect::<Result<_, _>>()?;
+    let mut sealed = rows.len() as i64;
+    {
+        let mut stmt =
+            conn.prepare("UPDATE history SET content = ?1, content_cipher = ?2 WHERE id = ?3")?;
+        for (id, content) in rows {
+            stmt.execute(params![
+                redact_content(&content),
+                key.encrypt(content.as_bytes()),
+                id
+            ])?;
+            refresh_content_hash(conn, id)?;
+        }
+    }
+
+    let scans: Vec<(i64, String, Option<String>, Option<String>)> = conn
+        .prepare(
+            r#"
+            SELECT id, content, metadata_json, thumbnail FROM scans
+            WHERE content_cipher IS NULL AND (sensitive = 1 OR ?1 = 'all')
+            "#,
+        )?
+        .query_map(params![scope.as_str()], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<Result<_, _>>()?;
+    sealed += scans.len() as i64;
+    {
+        let mut stmt = conn.prepare(
+            r#"
+            UPDATE scans
+            SET content = ?1, metadata_json = NULL, thumbnail = NULL, content_cipher = ?2
+            WHERE id = ?3
+            "#,
+        )?;
+        for (id, content, metadata_json, thumbnail) in scans {
+            stmt.execute(params![
+                redact_content(&content),
+                seal_scan(
+                    key,
+                    &content,
+                    metadata_json.as_deref(),
+                    thumbnail.as_deref()
+                ),
+                id
+            ])?;
+        }
+    }
+
+    // Drop the plaintext from the search index's segments as well
+    if sealed > 0 {
+        conn.execute(
+            "INSERT INTO history_fts (history_fts) VALUES ('optimize')",
+            [],
+        )?;
+    }
+    Ok(sealed)
+}
+
+/// Encrypt rows that were saved or migrated while encryption was locked
+///
+/// Call once the key is known. Returns how many rows were encrypted.
+pub fn seal_unencrypted_rows(
+    conn: &Connection,
+    key: &EncryptionKey,
+) -> Result<i64, EncryptionError> {
+    let config = load_config(conn)?.ok_or(EncryptionError::NotEnabled)?;
+    config.verify(key)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let sealed = seal_rows(&tx, config.scope, key)?;
+    tx.commit()?;
+    Ok(sealed)
+}
+
+/// Get rid of plaintext copies of rows that have just been encrypted
+///
+/// Rebuilds the database and truncates its write-ahead log so freed pages
+/// are gone, deletes the copies taken before migrations, and replaces every
+/// backup in `backups_dir` with a single one of the encrypted database.
+pub fn scrub_plaintext_copies(
+    conn: &Connection,
+    backups_dir: &Path,
+) -> Result<(), rusqlite::Error> {
+    vacuum_database(conn)?;
+    remove_migration_backups(conn);
+    purge_backups(backups_dir);
+    create_backup(conn, backups_dir, BackupKind::Auto)?;
+    Ok(())
+}
+
+/// Unlock with the keyfile when a workspace opens, then encrypt rows saved
+/// while locked and remove their plaintext copies
+///
+/// Returns the key if the keyfile unlocked it, and the failure that may have
+/// left secrets unencrypted on disk. History encrypted with a passphrase
+/// stays locked without an error.
+pub fn unlock_on_open(
+    conn: &Connection,
+    keyfile: &Path,
+    backups_dir: &Path,
+) -> (Option<EncryptionKey>, Option<String>) {
+    let key = match unlock_encryption(conn, KeyMaterial::Keyfile(keyfile)) {
+        Ok(key) => key,
+        Err(EncryptionError::NotEnabled | EncryptionError::WrongKeySource(_)) => {
+            return (None, None)
+        }
+        Err(e) => {
+            return (
+                None,
+                Some(format!("Failed to unlock with the keyfile: {}", e)),
+            )
+        }
+    };
+
+    let error = match seal_unencrypted_rows(conn, &key) {
+        Ok(0) => None,
+        Ok(_) => scrub_plaintext_copies(conn, backups_dir)
+            .err()
+            .map(|e| format!("Failed to remove plaintext copies: {}", e)),
+        Err(e) => Some(format!("Failed to encrypt saved rows: {}", e)),
+    };
+    (Some(key), error)
+}
+
+/// Check the passphrase or keyfile and return the key for the session
+pub fn unlock_encryption(
+    conn: &Connection,
+    material: KeyMaterial,
+) -> Result<EncryptionKey, EncryptionError> {
+    let config = load_config(conn)?.ok_or(EncryptionError::NotEnabled)?;
+
+    let key = match (config.key_source, material) {
+        (KeySource::Keyfile, KeyMaterial::Keyfile(path)) => read_keyfile(path)?,
+        (KeySource::Passphrase, KeyMaterial::Passphrase(passphrase)) => {
+            EncryptionKey::derive(passphrase, config.salt.as_deref().unwrap_or_default())?
+        }
+        (source, _) => return Err(EncryptionError::WrongKeySource(source)),
+    };
+
+    config.verify(&key)?;
+    Ok(key)
+}
+
+/// Decrypt every row back to plaintext and turn encryption off
+///
+/// Covers history and scans; returns how many rows were decrypted. The keyfile, if any, is left for
+/// the caller to remove.
+pub fn disable_encryption(conn: &Connection, key: &EncryptionKey) -> Result<i64, EncryptionError> {
+    let config = load_config(conn)?.ok_or(EncryptionError::NotEnabled)?;
+    config.verify(key)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let rows: Vec<(i64, Vec<u8>)> = tx
+        .prepare("SELECT id, content_cipher FROM history WHERE content_cipher IS NOT NULL")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut decrypted = 0;
+    {
+        let mut stmt =
+            tx.prepare("UPDATE history SET content = ?1, content_cipher = NULL WHERE id = ?2")?;
+        for (id, sealed) in rows {
+            decrypted += stmt.execute(params![key.decrypt_string(&sealed)?, id])? as i64;
+            refresh_content_hash(&tx, id)?;
+        }
+    }
+
+    let scans: Vec<(i64, Vec<u8>)> = tx
+        .prepare("SELECT id, content_cipher FROM scans WHERE content_cipher IS NOT NULL")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    {
+        let mut stmt = tx.prepare(
+            r#"
+            UPDATE scans
+            SET content = ?1, metadata_json = ?2, thumbnail = ?3, content_cipher = NULL
+            WHERE id = ?4
+            "#,
+        )?;
+        for (id, sealed) in scans {
+            let (content, metadata_json, thumbnail) = open_scan(key, &sealed)?;
+            decrypted += stmt.execute(params![content, metadata_json, thumbnail, id])? as i64;
+        }
+    }
+    tx.execute("DELETE FROM encryption", [])?;
+    tx.commit()?;
+
+    Ok(decrypted)
+}
+
+/// Content as stored while encrypted: a redacted copy and the ciphertext
+pub struct SealedContent {
+    pub redacted: String,
+    pub cipher: Vec<u8>,
+}
+
+/// Encrypt `content` for storage if encryption is on and covers it
+pub(super) fn seal_content(
+    conn: &Connection,
+    content: &str,
+    sensitive: bool,
+    key: Option<&EncryptionKey>,
+) -> Result<Option<SealedContent>, EncryptionError> {
+    match load_config(conn)? {
+        Some(config) if config.covers(sensitive) => {
+            let key = key.ok_or(EncryptionError::Locked)?;
+            config.verify(key)?;
+            Ok(Some(SealedContent {
+                redacted: redact_content(content),
+                cipher: key.encrypt(content.as_bytes()),
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Save a history item, encrypting its content if encryption covers it
+pub fn save_history_encrypted(
+    conn: &Connection,
+    item: &NewHistoryItem,
+    key: Option<&EncryptionKey>,
+) -> Result<i64, EncryptionError> {
+    let sensitive = is_sensitive_content(&item.content);
+
+    match seal_content(conn, &item.content, sensitive, key)? {
+        Some(sealed) => Ok(insert_history(conn, item, sensitive, Some(&sealed))?),
+        None => Ok(save_history(conn, item)?),
+    }
+}
+
+/// Full content of a history item, decrypting it if needed
+///
+/// Returns `None` if the item doesn't exist.
+pub fn reveal_history_content(
+    conn: &Connection,
+    id: i64,
+    key: Option<&EncryptionKey>,
+) -> Result<Option<String>, EncryptionError> {
+    let row: Option<(String, Option<Vec<u8>>)> = conn
+        .query_row(
+            "SELECT content, content_cipher FROM history WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    match row {
+        None => Ok(None),
+        Some((content, None)) => Ok(Some(content)),
+        Some((_, Some(sealed))) => {
+            let key = key.ok_or(EncryptionError::Locked)?;
+            key.decrypt_string(&sealed).map(Some)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        init_schema, list_backups, list_history_page, save_scan, search_history, HistoryFilter,
+        HistorySort, NewScanRecord,
+    };
+
+    const WIFI: &str = "WIFI:T:WPA;S:Office;P:hunter2\\;x;H:false;;";
+    const OTP: &str = "otpauth://totp/Acme:alice?secret=JBSWY3DPEHPK3PXP&issuer=Acme";
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn item(content: &str) -> NewHistoryItem {
+        NewHistoryItem {
+            content: content.to_string(),
+            qr_type: detect_qr_type(content),
+            label: None,
+            style_json: "{}".to_string(),
+            thumbnail: None,
+        }
+    }
+
+    fn stored(conn: &Connection, id: i64) -> (String, Option<Vec<u8>>) {
+        conn.query_row(
+            "SELECT content, content_cipher FROM history WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_redacts_secrets() {
+        assert_eq!(
+            redact_content(WIFI),
+            "WIFI:T:WPA;S:Office;P:********;H:false;;"
+        );
+        assert_eq!(
+            redact_content(OTP),
+            "otpauth://totp/Acme:alice?secret=********&issuer=Acme"
+        );
+        assert_eq!(redact_content("https://example.com"), REDACTED);
+
+        assert!(is_sensitive_content(WIFI));
+        assert!(is_sensitive_content(OTP));
+        assert!(!is_sensitive_content("https://example.com"));
+    }
+
+    #[test]
+    fn test_round_trip_and_wrong_key() {
+        let key = EncryptionKey::generate();
+        let sealed = key.encrypt(b"secret");
+        assert_eq!(key.decrypt(&sealed).unwrap(), b"secret");
+        assert_ne!(key.encrypt(b"secret"), sealed);
+
+        let other = EncryptionKey::generate();
+        assert!(matches!(
+            other.decrypt(&sealed),
+            Err(EncryptionError::WrongKey)
+        ));
+    }
+
+    #[test]
+    fn test_lists_redact_sensitive_items() {
+        let conn = setup_test_db();
+        let id = save_history(&conn, &item(WIFI)).unwrap();
+        save_history(&conn, &item("https://example.com")).unwrap();
+
+        let page = list_history_page(
+            &conn,
+            &HistoryFilter::default(),
+            HistorySort::default(),
+            None,
+            50,
+            0,
+        )
+        .unwrap();
+        let wifi = page.items.iter().find(|i| i.id == id).unwrap();
+        assert!(wifi.sensitive);
+        assert!(!wifi.content.contains("hunter2"));
+
+        let hits = search_history(&conn, "Office", 10, 0).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(!hits[0].content_snippet.contains("hunter2"));
+
+        // The full content is still one call away while unencrypted
+        assert_eq!(
+            reveal_history_content(&conn, id, None).unwrap().unwrap(),
+            WIFI
+        );
+    }
+
+    #[test]
+    fn test_keyfile_encryption() {
+        let dir = tempfile::tempdir().unwrap();
+        let keyfile = dir.path().join("qr-foundry.key");
+        let conn = setup_test_db();
+        let wifi = save_history(&conn, &item(WIFI)).unwrap();
+        let url = save_history(&conn, &item("https://example.com")).unwrap();
+
+        let key = enable_encryption(
+            &conn,
+            EncryptionScope::Sensitive,
+            KeyMaterial::Keyfile(&keyfile),
+        )
+        .unwrap();
+        assert!(keyfile.is_file());
+
+        let (content, sealed) = stored(&conn, wifi);
+        assert!(!content.contains("hunter2"));
+        assert!(sealed.is_some());
+        assert!(stored(&conn, url).1.is_none());
+        assert!(search_history(&conn, "hunter2", 10, 0).unwrap().is_empty());
+
+        // New sensitive items need the key
+        assert!(matches!(
+            save_history_encrypted(&conn, &item(OTP), None),
+            Err(EncryptionError::Locked)
+        ));
+        let otp = save_history_encrypted(&conn, &item(OTP), Some(&key)).unwrap();
+        assert!(stored(&conn, otp).1.is_some());
+
+        let unlocked = unlock_encryption(&conn, KeyMaterial::Keyfile(&keyfile)).unwrap();
+        assert_eq!(
+            reveal_history_content(&conn, wifi, Some(&unlocked))
+                .unwrap()
+                .unwrap(),
+            WIFI
+        );
+        assert!(matches!(
+            reveal_history_content(&conn, wifi, None),
+            Err(EncryptionError::Locked)
+        ));
+
+        let status = encryption_status(&conn, true).unwrap();
+        assert!(status.enabled);
+        assert_eq!(status.key_source, Some(KeySource::Keyfile));
+        assert_eq!(status.sensitive_items, 2);
+        assert_eq!(status.encrypted_items, 2);
+
+        assert_eq!(disable_encryption(&conn, &unlocked).unwrap(), 2);
+        assert_eq!(stored(&conn, wifi), (WIFI.to_string(), None));
+        assert_eq!(stored(&conn, otp), (OTP.to_string(), None));
+        assert!(!encryption_status(&conn, false).unwrap().enabled);
+    }
+
+    #[test]
+    fn test_passphrase_encryption_of_everything() {
+        let conn = setup_test_db();
+        let url = save_history(&conn, &item("https://example.com")).unwrap();
+
+        assert!(matches!(
+            enable_encryption(
+                &conn,
+                EncryptionScope::All,
+                KeyMaterial::Passphrase("short")
+            ),
+            Err(EncryptionError::WeakPassphrase)
+        ));
+        enable_encryption(
+            &conn,
+            EncryptionScope::All,
+            KeyMaterial::Passphrase("correct horse"),
+        )
+        .unwrap();
+        assert_eq!(stored(&conn, url).0, REDACTED);
+
+        assert!(matches!(
+            unlock_encryption(&conn, KeyMaterial::Passphrase("wrong horse")),
+            Err(EncryptionError::WrongKey)
+        ));
+        assert!(matches!(
+            unlock_encryption(&conn, KeyMaterial::Keyfile(Path::new("/nonexistent"))),
+            Err(EncryptionError::WrongKeySource(KeySource::Passphrase))
+        ));
+
+        let key = unlock_encryption(&conn, KeyMaterial::Passphrase("correct horse")).unwrap();
+        assert_eq!(
+            reveal_history_content(&conn, url, Some(&key))
+                .unwrap()
+                .unwrap(),
+            "https://example.com"
+        );
+    }
+
+    #[test]
+    fn test_marks_existing_rows() {
+        let conn = setup_test_db();
+        let id = save_history(&conn, &item(WIFI)).unwrap();
+        conn.execute("UPDATE history SET sensitive = 0", [])
+            .unwrap();
+
+        assert_eq!(mark_sensitive_history(&conn).unwrap(), 1);
+        let sensitive: bool = conn
+            .query_row(
+                "SELECT sensitive FROM history WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(sensitive);
+    }
+
+    #[test]
+    fn test_unlock_on_open_reports_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let keyfile = dir.path().join("qr-foundry.key");
+        // A file where the backups folder should be makes every backup fail
+        let backups = dir.path().join("backups");
+        std::fs::write(&backups, "").unwrap();
+        let conn = setup_test_db();
+        let (key, error) = unlock_on_open(&conn, &keyfile, &backups);
+        assert!(key.is_none() && error.is_none());
+
+        enable_encryption(
+            &conn,
+            EncryptionScope::Sensitive,
+            KeyMaterial::Keyfile(&keyfile),
+        )
+        .unwrap();
+        let late = save_history(&conn, &item(OTP)).unwrap();
+
+        let (key, error) = unlock_on_open(&conn, &keyfile, &backups);
+        assert!(key.is_some());
+        assert!(stored(&conn, late).1.is_some());
+        assert!(error
+            .unwrap()
+            .starts_with("Failed to remove plaintext copies"));
+
+        std::fs::remove_file(&keyfile).unwrap();
+        let (key, error) = unlock_on_open(&conn, &keyfile, &backups);
+        assert!(key.is_none());
+        assert!(error
+            .unwrap()
+            .starts_with("Failed to unlock with the keyfile"));
+
+        // A passphrase is asked for later; nothing has gone wrong yet
+        let conn = setup_test_db();
+        enable_encryption(
+            &conn,
+            EncryptionScope::All,
+            KeyMaterial::Passphrase("correct horse"),
+        )
+        .unwrap();
+        let (key, error) = unlock_on_open(&conn, &keyfile, &backups);
+        assert!(key.is_none() && error.is_none());
+    }
+
+    #[test]
+    fn test_scrubs_plaintext_copies() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = dir.path().join("backups");
+        let conn = Connection::open(dir.path().join("qr-foundry.db")).unwrap();
+        conn.pragma_update(None, "journal_mode", "WAL").unwrap();
+        init_schema(&conn).unwrap();
+        save_history(&conn, &item(WIFI)).unwrap();
+        let scan = NewScanRecord {
+            source: "file".to_string(),
+            source_path: None,
+            content: OTP.to_string(),
+            qr_type: "otp".to_string(),
+            metadata_json: None,
+            thumbnail: None,
+        };
+        save_scan(&conn, &scan, None).unwrap();
+        create_backup(&conn, &backups, BackupKind::Manual).unwrap();
+        let migration_backup = dir.path().join("qr-foundry.db.v6.bak");
+        std::fs::write(&migration_backup, WIFI).unwrap();
+
+        let key = enable_encryption(
+            &conn,
+            EncryptionScope::Sensitive,
+            KeyMaterial::Keyfile(&dir.path().join("qr-foundry.key")),
+        )
+        .unwrap();
+
+        // Rows written without the key are caught up later
+        let late = save_history(&conn, &item(OTP)).unwrap();
+        assert_eq!(seal_unencrypted_rows(&conn, &key).unwrap(), 1);
+        assert!(stored(&conn, late).1.is_some());
+        assert_eq!(seal_unencrypted_rows(&conn, &key).unwrap(), 0);
+
+        scrub_plaintext_copies(&conn, &backups).unwrap();
+        assert!(!migration_backup.exists());
+        assert_eq!(list_backups(&backups).len(), 1);
+
+        // No file on disk holds either secret any more
+        let files = std::fs::read_dir(dir.path())
+            .unwrap()
+            .chain(std::fs::read_dir(&backups).unwrap())
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_file());
+        for path in files {
+            let bytes = std::fs::read(&path).unwrap();
+            for secret in [&b"hunter2"[..], &b"JBSWY3DP"[..]] {
+                assert!(
+                    !bytes.windows(secret.len()).any(|w| w == secret),
+                    "{} holds a secret",
+                    path.display()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+-- Schema written by unversioned releases (scans), with sample rows
+-- History of generated QR codes
+CREATE TABLE IF NOT EXISTS history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    content TEXT NOT NULL,
+    qr_type TEXT NOT NULL,
+    label TEXT,
+    style_json TEXT NOT NULL,
+    thumbnail TEXT,
+    created_at TEXT DEFAULT (datetime('now')),
+    updated_at TEXT DEFAULT (datetime('now'))
+);
+
+-- Create index on created_at for faster sorting
+CREATE INDEX IF NOT EXISTS idx_history_created_at ON history(created_at DESC);
+
+-- Saved style templates
+CREATE TABLE IF NOT EXISTS templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    style_json TEXT NOT NULL,
+    preview TEXT,
+    is_default INTEGER DEFAULT 0,
+    created_at TEXT DEFAULT (datetime('now'))
+);
+
+-- Decoded results from the scanner
+CREATE TABLE IF NOT EXISTS scans (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source TEXT NOT NULL,
+    source_path TEXT,
+    content TEXT NOT NULL,
+    qr_type TEXT NOT NULL,
+    metadata_json TEXT,
+    thumbnail TEXT,
+    created_at TEXT DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_scans_created_at ON scans(created_at DESC);
+
+-- App settings
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+-- Sample data
+INSERT INTO history (content, qr_type, label, style_json, thumbnail, created_at, updated_at)
+VALUES ('https://example.com/menu', 'url', 'Cafe menu', '{}', 'data:image/png;base64,iVBORw0KGgo=', '2025-03-01 09:00:00', '2025-03-01 09:00:00'),
+       ('WIFI:T:WPA;S:office;P:secret;;', 'wifi', NULL, '{}', NULL, '2025-03-02 09:00:00', '2025-03-02 09:00:00');
+INSERT INTO templates (name, style_json, preview, is_default)
+VALUES ('Classic', '{"dotStyle":"square"}', 'data:image/png;base64,iVBORw0KGgo=', 1);
+INSERT INTO settings (key, value) VALUES ('theme', 'dark');
+INSERT INTO scans (source, source_path, content, qr_type)
+VALUES ('file', '/tmp/poster.png', 'https://example.com/poster', 'url');
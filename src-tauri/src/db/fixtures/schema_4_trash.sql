@@ -0,0 +1,148 @@
+-- Schema written by unversioned releases (trash), with sample rows
+-- History of generated QR codes
+CREATE TABLE IF NOT EXISTS history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    content TEXT NOT NULL,
+    qr_type TEXT NOT NULL,
+    label TEXT,
+    style_json TEXT NOT NULL,
+    thumbnail TEXT,
+    created_at TEXT DEFAULT (datetime('now')),
+    updated_at TEXT DEFAULT (datetime('now')),
+    folder_id INTEGER,
+    pinned INTEGER NOT NULL DEFAULT 0,
+    deleted_at TEXT
+);
+
+-- Create index on created_at for faster sorting
+CREATE INDEX IF NOT EXISTS idx_history_created_at ON history(created_at DESC);
+
+-- Full-text index over history, kept in sync by triggers
+CREATE VIRTUAL TABLE IF NOT EXISTS history_fts USING fts5(
+    content,
+    label,
+    qr_type,
+    tags,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER IF NOT EXISTS history_fts_insert AFTER INSERT ON history BEGIN
+    INSERT INTO history_fts (rowid, content, label, qr_type, tags)
+    VALUES (new.id, new.content, coalesce(new.label, ''), new.qr_type, '');
+END;
+
+CREATE TRIGGER IF NOT EXISTS history_fts_update
+AFTER UPDATE OF content, label, qr_type ON history BEGIN
+    UPDATE history_fts
+    SET content = new.content, label = coalesce(new.label, ''), qr_type = new.qr_type
+    WHERE rowid = new.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS history_fts_delete AFTER DELETE ON history BEGIN
+    DELETE FROM history_fts WHERE rowid = old.id;
+END;
+
+-- Folder hierarchy for organizing history
+CREATE TABLE IF NOT EXISTS folders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    parent_id INTEGER REFERENCES folders(id),
+    created_at TEXT DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_folders_parent_id ON folders(parent_id);
+
+-- Tags, attached to history items many-to-many
+CREATE TABLE IF NOT EXISTS tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
+
+CREATE TABLE IF NOT EXISTS history_tags (
+    history_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    PRIMARY KEY (history_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_history_tags_tag_id ON history_tags(tag_id);
+
+CREATE TRIGGER IF NOT EXISTS history_tags_cleanup AFTER DELETE ON history BEGIN
+    DELETE FROM history_tags WHERE history_id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tags_cleanup AFTER DELETE ON tags BEGIN
+    DELETE FROM history_tags WHERE tag_id = old.id;
+END;
+
+-- Keep the tags column of the full-text index current
+CREATE TRIGGER IF NOT EXISTS history_tags_fts_insert AFTER INSERT ON history_tags BEGIN
+    UPDATE history_fts SET tags = (
+        SELECT coalesce(group_concat(t.name, ' '), '') FROM history_tags ht
+        JOIN tags t ON t.id = ht.tag_id WHERE ht.history_id = new.history_id
+    ) WHERE rowid = new.history_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS history_tags_fts_delete AFTER DELETE ON history_tags BEGIN
+    UPDATE history_fts SET tags = (
+        SELECT coalesce(group_concat(t.name, ' '), '') FROM history_tags ht
+        JOIN tags t ON t.id = ht.tag_id WHERE ht.history_id = old.history_id
+    ) WHERE rowid = old.history_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tags_fts_rename AFTER UPDATE OF name ON tags BEGIN
+    UPDATE history_fts SET tags = (
+        SELECT coalesce(group_concat(t.name, ' '), '') FROM history_tags ht
+        JOIN tags t ON t.id = ht.tag_id WHERE ht.history_id = history_fts.rowid
+    ) WHERE rowid IN (SELECT history_id FROM history_tags WHERE tag_id = new.id);
+END;
+
+-- Saved style templates
+CREATE TABLE IF NOT EXISTS templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    style_json TEXT NOT NULL,
+    preview TEXT,
+    is_default INTEGER DEFAULT 0,
+    created_at TEXT DEFAULT (datetime('now')),
+    deleted_at TEXT
+);
+
+-- Decoded results from the scanner
+CREATE TABLE IF NOT EXISTS scans (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source TEXT NOT NULL,
+    source_path TEXT,
+    content TEXT NOT NULL,
+    qr_type TEXT NOT NULL,
+    metadata_json TEXT,
+    thumbnail TEXT,
+    created_at TEXT DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_scans_created_at ON scans(created_at DESC);
+
+-- App settings
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_history_deleted_at ON history(deleted_at);
+CREATE INDEX IF NOT EXISTS idx_history_folder_id ON history(folder_id);
+CREATE INDEX IF NOT EXISTS idx_history_pinned ON history(pinned) WHERE pinned = 1;
+
+-- Sample data
+INSERT INTO history (content, qr_type, label, style_json, thumbnail, created_at, updated_at)
+VALUES ('https://example.com/menu', 'url', 'Cafe menu', '{}', 'data:image/png;base64,iVBORw0KGgo=', '2025-03-01 09:00:00', '2025-03-01 09:00:00'),
+       ('WIFI:T:WPA;S:office;P:secret;;', 'wifi', NULL, '{}', NULL, '2025-03-02 09:00:00', '2025-03-02 09:00:00');
+INSERT INTO templates (name, style_json, preview, is_default)
+VALUES ('Classic', '{"dotStyle":"square"}', 'data:image/png;base64,iVBORw0KGgo=', 1);
+INSERT INTO settings (key, value) VALUES ('theme', 'dark');
+INSERT INTO scans (source, source_path, content, qr_type)
+VALUES ('file', '/tmp/poster.png', 'https://example.com/poster', 'url');
+INSERT INTO folders (name) VALUES ('Clients');
+INSERT INTO tags (name) VALUES ('print');
+INSERT INTO history_tags (history_id, tag_id) VALUES (1, 1);
+UPDATE history SET folder_id = 1, pinned = 1 WHERE id = 1;
+INSERT INTO history (content, qr_type, label, style_json, deleted_at)
+VALUES ('https://example.com/old', 'url', NULL, '{}', '2025-03-03 09:00:00');
@@ -0,0 +1,282 @@
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Folder {
+    pub id: i64,
+    pub name: String,
+    pub parent_id: Option<i64>,
+    pub item_count: i64, // live items directly in this folder
+    pub created_at: String,
+}
+
+/// Subquery selecting folder `?param` and every folder nested under it
+pub fn folder_tree_query(param: usize) -> String {
+    format!(
+        "WITH RECURSIVE tree(id) AS (SELECT ?{} UNION \
+         SELECT f.id FROM folders f JOIN tree ON f.parent_id = tree.id) SELECT id FROM tree",
+        param
+    )
+}
+
+fn folder_from_row(row: &Row) -> Result<Folder, rusqlite::Error> {
+    Ok(Folder {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        parent_id: row.get(2)?,
+        item_count: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+/// List every folder, flat; clients build the tree from `parent_id`
+pub fn list_folders(conn: &Connection) -> Result<Vec<Folder>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT f.id, f.name, f.parent_id,
+               (SELECT COUNT(*) FROM history h
+                WHERE h.folder_id = f.id AND h.deleted_at IS NULL),
+               f.created_at
+        FROM folders f
+        ORDER BY f.name COLLATE NOCASE, f.id
+        "#,
+    )?;
+
+    let folders = stmt
+        .query_map([], folder_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(folders)
+}
+
+/// Get a folder by ID
+pub fn get_folder(conn: &Connection, id: i64) -> Result<Option<Folder>, rusqlite::Error> {
+    let result = conn.query_row(
+        r#"
+        SELECT f.id, f.name, f.parent_id,
+               (SELECT COUNT(*) FROM history h
+                WHERE h.folder_id = f.id AND h.deleted_at IS NULL),
+               f.created_at
+        FROM folders f
+        WHERE f.id = ?1
+        "#,
+        params![id],
+        folder_from_row,
+    );
+
+    match result {
+        Ok(folder) => Ok(Some(folder)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Create a folder, optionally inside another
+pub fn create_folder(
+    conn: &Connection,
+    name: &str,
+    parent_id: Option<i64>,
+) -> Result<i64, rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO folders (name, parent_id) VALUES (?1, ?2)",
+        params![name, parent_id],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Rename a folder
+pub fn rename_folder(conn: &Connection, id: i64, name: &str) -> Result<bool, rusqlite::Error> {
+    let affected = conn.execute(
+        "UPDATE folders SET name = ?1 WHERE id = ?2",
+        params![name, id],
+    )?;
+    Ok(affected > 0)
+}
+
+/// Whether `candidate` is `folder_id` itself or nested anywhere under it
+pub fn is_within_folder(
+    conn: &Connection,
+    folder_id: i64,
+    candidate: i64,
+) -> Result<bool, rusqlite::Error> {
+    let query = format!(
+        "SELECT EXISTS(SELECT 1 FROM ({}) WHERE id = ?2)",
+        folder_tree_query(1)
+    );
+    conn.query_row(&query, params![folder_id, candidate], |row| row.get(0))
+}
+
+/// Move a folder under a new parent, or to the top level
+///
+/// Callers must check `is_within_folder` first so the tree stays acyclic.
+pub fn move_folder(
+    conn: &Connection,
+    id: i64,
+    parent_id: Option<i64>,
+) -> Result<bool, rusqlite::Error> {
+    let affected = conn.execute(
+        "UPDATE folders SET parent_id = ?1 WHERE id = ?2",
+        params![parent_id, id],
+    )?;
+    Ok(affected > 0)
+}
+
+/// Delete a folder; its items and subfolders move up to its parent
+pub fn delete_folder(conn: &Connection, id: i64) -> Result<bool, rusqlite::Error> {
+    let Some(folder) = get_folder(conn, id)? else {
+        return Ok(false);
+    };
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE history SET folder_id = ?1 WHERE folder_id = ?2",
+        params![folder.parent_id, id],
+    )?;
+    tx.execute(
+        "UPDATE folders SET parent_id = ?1 WHERE parent_id = ?2",
+        params![folder.parent_id, id],
+    )?;
+    tx.execute("DELETE FROM folders WHERE id = ?1", params![id])?;
+    tx.commit()?;
+
+    Ok(true)
+}
+
+/// Move history items into a folder, or out of any folder with `None`
+pub fn move_history_to_folder(
+    conn: &Connection,
+    ids: &[i64],
+    folder_id: Option<i64>,
+) -> Result<i64, rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    let mut moved = 0;
+    {
+        let mut stmt = tx.prepare("UPDATE history SET folder_id = ?1 WHERE id = ?2")?;
+        for id in ids {
+            moved += stmt.execute(params![folder_id, id])? as i64;
+        }
+    }
+    tx.commit()?;
+
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        count_filtered_history, init_schema, save_history, HistoryFilter, NewHistoryItem,
+    };
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn save(conn: &Connection, content: &str) -> i64 {
+        save_history(
+            conn,
+            &NewHistoryItem {
+                content: content.to_string(),
+                qr_type: "text".to_string(),
+                label: None,
+                style_json: "{}".to_string(),
+                thumbnail: None,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_create_and_list_folders() {
+        let conn = setup_test_db();
+        let clients = create_folder(&conn, "Clients", None).unwrap();
+        let acme = create_folder(&conn, "acme", Some(clients)).unwrap();
+
+        let folders = list_folders(&conn).unwrap();
+        assert_eq!(folders.len(), 2);
+        assert_eq!(folders[0].name, "acme");
+        assert_eq!(folders[0].parent_id, Some(clients));
+
+        assert!(rename_folder(&conn, acme, "ACME Corp").unwrap());
+        assert_eq!(get_folder(&conn, acme).unwrap().unwrap().name, "ACME Corp");
+        assert!(!rename_folder(&conn, 999, "x").unwrap());
+    }
+
+    #[test]
+    fn test_move_items_and_filter_by_folder() {
+        let conn = setup_test_db();
+        let clients = create_folder(&conn, "Clients", None).unwrap();
+        let acme = create_folder(&conn, "Acme", Some(clients)).unwrap();
+        let a = save(&conn, "a");
+        let b = save(&conn, "b");
+        save(&conn, "c");
+
+        assert_eq!(
+            move_history_to_folder(&conn, &[a], Some(clients)).unwrap(),
+            1
+        );
+        assert_eq!(
+            move_history_to_folder(&conn, &[b, 999], Some(acme)).unwrap(),
+            1
+        );
+
+        let direct = HistoryFilter {
+            folder_id: Some(clients),
+            ..Default::default()
+        };
+        assert_eq!(count_filtered_history(&conn, &direct).unwrap(), 1);
+
+        let nested = HistoryFilter {
+            include_subfolders: true,
+            ..direct
+        };
+        assert_eq!(count_filtered_history(&conn, &nested).unwrap(), 2);
+
+        let unfiled = HistoryFilter {
+            unfiled: true,
+            ..Default::default()
+        };
+        assert_eq!(count_filtered_history(&conn, &unfiled).unwrap(), 1);
+        assert_eq!(get_folder(&conn, acme).unwrap().unwrap().item_count, 1);
+    }
+
+    #[test]
+    fn test_is_within_folder() {
+        let conn = setup_test_db();
+        let root = create_folder(&conn, "Root", None).unwrap();
+        let child = create_folder(&conn, "Child", Some(root)).unwrap();
+        let grandchild = create_folder(&conn, "Grandchild", Some(child)).unwrap();
+        let other = create_folder(&conn, "Other", None).unwrap();
+
+        assert!(is_within_folder(&conn, root, grandchild).unwrap());
+        assert!(is_within_folder(&conn, root, root).unwrap());
+        assert!(!is_within_folder(&conn, child, root).unwrap());
+        assert!(!is_within_folder(&conn, root, other).unwrap());
+
+        assert!(move_folder(&conn, grandchild, None).unwrap());
+        assert!(!is_within_folder(&conn, root, grandchild).unwrap());
+    }
+
+    #[test]
+    fn test_delete_folder_moves_contents_up() {
+        let conn = setup_test_db();
+        let root = create_folder(&conn, "Root", None).unwrap();
+        let child = create_folder(&conn, "Child", Some(root)).unwrap();
+        let grandchild = create_folder(&conn, "Grandchild", Some(child)).unwrap();
+        let item = save(&conn, "a");
+        move_history_to_folder(&conn, &[item], Some(child)).unwrap();
+
+        assert!(delete_folder(&conn, child).unwrap());
+        assert!(!delete_folder(&conn, child).unwrap());
+
+        assert_eq!(
+            get_folder(&conn, grandchild).unwrap().unwrap().parent_id,
+            Some(root)
+        );
+        assert_eq!(get_folder(&conn, root).unwrap().unwrap().item_count, 1);
+    }
+}
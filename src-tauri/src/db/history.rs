@@ -1,6 +1,13 @@
-use rusqlite::{params, Connection};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, Row};
 use serde::{Deserialize, Serialize};
 
+use super::blobs::put_optional_blob;
+use super::dedupe::content_hash;
+use super::encryption::{is_sensitive_content, redact_content, SealedContent};
+use super::folders::folder_tree_query;
+use super::search::{build_fts_query, fts_filter_clause};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoryItem {
@@ -9,9 +16,17 @@ pub struct HistoryItem {
     pub qr_type: String,
     pub label: Option<String>,
     pub style_json: String,
-    pub thumbnail: Option<String>,
+    pub thumbnail_ref: Option<String>, // blob hash; fetch the image with `get_blob`
     pub created_at: String,
     pub updated_at: String,
+    pub folder_id: Option<i64>,
+    pub pinned: bool,
+    pub tags: Vec<String>,
+    pub deleted_at: Option<String>, // set while the item is in the trash
+    pub sensitive: bool,            // WiFi or OTP; `content` is redacted
+    pub encrypted: bool,            // full content needs the encryption key
+    pub uuid: String,               // stable across devices, unlike `id`
+    pub use_count: i64,             // times saved; see `upsert_history`
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,128 +39,445 @@ pub struct NewHistoryItem {
     pub thumbnail: Option<String>,
 }
 
-/// List history items with pagination
-pub fn list_history(
+/// Filters for listing and counting history
+///
+/// Dates accept anything SQLite's `datetime()` understands (`2026-01-31`,
+/// `2026-01-31T09:00:00Z`, ...). `*After` bounds are inclusive, `*Before`
+/// bounds exclusive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryFilter {
+    pub search: Option<String>,
+    pub qr_types: Vec<String>,
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    pub updated_after: Option<String>,
+    pub updated_before: Option<String>,
+    pub has_label: Option<bool>,
+    /// Items carrying every one of these tags
+    pub tags: Vec<String>,
+    pub folder_id: Option<i64>,
+    /// Also match items in folders nested under `folder_id`
+    pub include_subfolders: bool,
+    /// Only items outside any folder
+    pub unfiled: bool,
+    pub pinned: Option<bool>,
+    /// List the trash instead of live items
+    pub trashed: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HistorySort {
+    #[default]
+    CreatedDesc,
+    CreatedAsc,
+    UpdatedDesc,
+    UpdatedAsc,
+    LabelAsc,
+    LabelDesc,
+    DeletedDesc,
+}
+
+impl HistorySort {
+    /// Expression rows are ordered by; ties are broken by `id`
+    fn key(self) -> &'static str {
+        match self {
+            HistorySort::CreatedDesc | HistorySort::CreatedAsc => "created_at",
+            HistorySort::UpdatedDesc | HistorySort::UpdatedAsc => "updated_at",
+            HistorySort::LabelAsc | HistorySort::LabelDesc => "lower(coalesce(label, ''))",
+            HistorySort::DeletedDesc => "coalesce(deleted_at, '')",
+        }
+    }
+
+    fn descending(self) -> bool {
+        matches!(
+            self,
+            HistorySort::CreatedDesc
+                | HistorySort::UpdatedDesc
+                | HistorySort::LabelDesc
+                | HistorySort::DeletedDesc
+        )
+    }
+}
+
+/// Position after the last row of a page, for the sort it was produced with
+///
+/// Unlike an offset, a cursor keeps pointing at the same place when new rows
+/// are inserted ahead of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryCursor {
+    pub key: String,
+    pub id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPage {
+    pub items: Vec<HistoryItem>,
+    pub next_cursor: Option<HistoryCursor>,
+}
+
+/// WHERE clause and its positional parameters for a filter
+fn filter_clause(filter: &HistoryFilter) -> (String, Vec<Value>) {
+    let mut conditions = vec![if filter.trashed {
+        "deleted_at IS NOT NULL".to_string()
+    } else {
+        "deleted_at IS NULL".to_string()
+    }];
+    let mut values = Vec::new();
+
+    if let Some(fts_query) = filter.search.as_deref().and_then(build_fts_query) {
+        values.push(Value::Text(fts_query));
+        conditions.push(fts_filter_clause(values.len()));
+    }
+
+    if !filter.qr_types.is_empty() {
+        let placeholders = filter
+            .qr_types
+            .iter()
+            .map(|qr_type| {
+                values.push(Value::Text(qr_type.clone()));
+                format!("?{}", values.len())
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        conditions.push(format!("qr_type IN ({})", placeholders));
+    }
+
+    let ranges = [
+        ("created_at >=", &filter.created_after),
+        ("created_at <", &filter.created_before),
+        ("updated_at >=", &filter.updated_after),
+        ("updated_at <", &filter.updated_before),
+    ];
+    for (comparison, bound) in ranges {
+        if let Some(bound) = bound {
+            values.push(Value::Text(bound.clone()));
+            conditions.push(format!("{} datetime(?{})", comparison, values.len()));
+        }
+    }
+
+    match filter.has_label {
+        Some(true) => conditions.push("coalesce(label, '') <> ''".to_string()),
+        Some(false) => conditions.push("coalesce(label, '') = ''".to_string()),
+        None => {}
+    }
+
+    for tag in &filter.tags {
+        values.push(Value::Text(tag.trim().to_string()));
+        conditions.push(format!(
+            "id IN (SELECT ht.history_id FROM history_tags ht JOIN tags t ON t.id = ht.tag_id \
+             WHERE t.name = ?{} COLLATE NOCASE)",
+            values.len()
+        ));
+    }
+
+    if let Some(folder_id) = filter.folder_id {
+        values.push(Value::Integer(folder_id));
+        if filter.include_subfolders {
+            conditions.push(format!(
+                "folder_id IN ({})",
+                folder_tree_query(values.len())
+            ));
+        } else {
+            conditions.push(format!("folder_id = ?{}", values.len()));
+        }
+    }
+    if filter.unfiled {
+        conditions.push("folder_id IS NULL".to_string());
+    }
+
+    if let Some(pinned) = filter.pinned {
+        conditions.push(format!("pinned = {}", pinned as i64));
+    }
+
+    (conditions.join(" AND "), values)
+}
+
+/// List a filtered, sorted page of history
+///
+/// Pass the previous page's `next_cursor` to continue after it; `offset` is
+/// applied on top of the cursor, so leave it at 0 when paging by cursor.
+pub fn list_history_page(
     conn: &Connection,
+    filter: &HistoryFilter,
+    sort: HistorySort,
+    cursor: Option<&HistoryCursor>,
     limit: i64,
     offset: i64,
-    search: Option<&str>,
-) -> Result<Vec<HistoryItem>, rusqlite::Error> {
-    let mut stmt = if let Some(search_term) = search {
-        let query = r#"
-            SELECT id, content, qr_type, label, style_json, thumbnail, created_at, updated_at
-            FROM history
-            WHERE content LIKE ?1 OR label LIKE ?1
-            ORDER BY created_at DESC
-            LIMIT ?2 OFFSET ?3
-        "#;
-        let mut stmt = conn.prepare(query)?;
-        let search_pattern = format!("%{}%", search_term);
-        let items = stmt
-            .query_map(params![search_pattern, limit, offset], |row| {
-                Ok(HistoryItem {
-                    id: row.get(0)?,
-                    content: row.get(1)?,
-                    qr_type: row.get(2)?,
-                    label: row.get(3)?,
-                    style_json: row.get(4)?,
-                    thumbnail: row.get(5)?,
-                    created_at: row.get(6)?,
-                    updated_at: row.get(7)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-        return Ok(items);
+) -> Result<HistoryPage, rusqlite::Error> {
+    let (mut clause, mut values) = filter_clause(filter);
+    let key = sort.key();
+    let (direction, comparison) = if sort.descending() {
+        ("DESC", "<")
     } else {
-        conn.prepare(
-            r#"
-            SELECT id, content, qr_type, label, style_json, thumbnail, created_at, updated_at
-            FROM history
-            ORDER BY created_at DESC
-            LIMIT ?1 OFFSET ?2
-        "#,
-        )?
+        ("ASC", ">")
     };
 
-    let items = stmt
-        .query_map(params![limit, offset], |row| {
-            Ok(HistoryItem {
-                id: row.get(0)?,
-                content: row.get(1)?,
-                qr_type: row.get(2)?,
-                label: row.get(3)?,
-                style_json: row.get(4)?,
-                thumbnail: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-            })
+    if let Some(cursor) = cursor {
+        values.push(Value::Text(cursor.key.clone()));
+        values.push(Value::Integer(cursor.id));
+        clause.push_str(&format!(
+            " AND ({}, id) {} (?{}, ?{})",
+            key,
+            comparison,
+            values.len() - 1,
+            values.len()
+        ));
+    }
+
+    // Fetch one extra row to know whether another page follows
+    values.push(Value::Integer(limit + 1));
+    values.push(Value::Integer(offset));
+
+    let query = format!(
+        r#"
+        SELECT {HISTORY_COLUMNS}, {key} AS sort_key
+        FROM history h
+        WHERE {clause}
+        ORDER BY sort_key {direction}, id {direction}
+        LIMIT ?{} OFFSET ?{}
+        "#,
+        values.len() - 1,
+        values.len()
+    );
+
+    let mut stmt = conn.prepare(&query)?;
+    let mut rows = stmt
+        .query_map(params_from_iter(values), |row| {
+            Ok((
+                history_from_row(row)?,
+                row.get::<_, String>(HISTORY_COLUMN_COUNT)?,
+            ))
         })?
         .collect::<Result<Vec<_>, _>>()?;
 
-    Ok(items)
+    let has_more = rows.len() as i64 > limit;
+    rows.truncate(limit.max(0) as usize);
+
+    let next_cursor = has_more
+        .then(|| rows.last())
+        .flatten()
+        .map(|(item, key)| HistoryCursor {
+            key: key.clone(),
+            id: item.id,
+        });
+
+    Ok(HistoryPage {
+        items: rows.into_iter().map(|(item, _)| item).collect(),
+        next_cursor,
+    })
+}
+
+/// Columns read by `history_from_row`, for a query over `history h`
+pub(crate) const HISTORY_COLUMNS: &str = "h.id, h.content, h.qr_type, h.label, h.style_json, \
+     h.thumbnail_hash, h.created_at, h.updated_at, h.folder_id, h.pinned, h.deleted_at, \
+     (SELECT group_concat(t.name, char(31)) FROM history_tags ht \
+      JOIN tags t ON t.id = ht.tag_id WHERE ht.history_id = h.id), \
+     h.sensitive, h.content_cipher IS NOT NULL, h.uuid, h.use_count";
+
+/// How many columns `HISTORY_COLUMNS` selects; extra columns follow
+pub(crate) const HISTORY_COLUMN_COUNT: usize = 16;
+
+pub(crate) fn history_from_row(row: &Row) -> Result<HistoryItem, rusqlite::Error> {
+    let tags: Option<String> = row.get(11)?;
+    let mut tags: Vec<String> = tags
+        .map(|tags| tags.split('\u{1f}').map(str::to_string).collect())
+        .unwrap_or_default();
+    tags.sort_by_key(|tag| tag.to_lowercase());
+
+    let sensitive: bool = row.get(12)?;
+    let encrypted: bool = row.get(13)?;
+    let content: String = row.get(1)?;
+    // Encrypted rows already store a redacted copy
+    let content = if sensitive && !encrypted {
+        redact_content(&content)
+    } else {
+        content
+    };
+
+    Ok(HistoryItem {
+        id: row.get(0)?,
+        content,
+        qr_type: row.get(2)?,
+        label: row.get(3)?,
+        style_json: row.get(4)?,
+        thumbnail_ref: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+        folder_id: row.get(8)?,
+        pinned: row.get(9)?,
+        deleted_at: row.get(10)?,
+        tags,
+        sensitive,
+        encrypted,
+        uuid: row.get(14)?,
+        use_count: row.get(15)?,
+    })
 }
 
 /// Save a new history item
+///
+/// Content is stored as given; use `save_history_encrypted` where
+/// encryption may be on.
 pub fn save_history(conn: &Connection, item: &NewHistoryItem) -> Result<i64, rusqlite::Error> {
+    insert_history(conn, item, is_sensitive_content(&item.content), None)
+}
+
+/// Insert a history row, storing `sealed` in place of the content if given
+pub(super) fn insert_history(
+    conn: &Connection,
+    item: &NewHistoryItem,
+    sensitive: bool,
+    sealed: Option<&SealedContent>,
+) -> Result<i64, rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    let id = insert_history_row(&tx, item, sensitive, sealed)?;
+    tx.commit()?;
+
+    Ok(id)
+}
+
+/// `insert_history` for callers already inside a transaction
+pub(super) fn insert_history_row(
+    conn: &Connection,
+    item: &NewHistoryItem,
+    sensitive: bool,
+    sealed: Option<&SealedContent>,
+) -> Result<i64, rusqlite::Error> {
+    let (content, content_cipher) = match sealed {
+        Some(sealed) => (sealed.redacted.as_str(), Some(&sealed.cipher)),
+        None => (item.content.as_str(), None),
+    };
+
+    let thumbnail_hash = put_optional_blob(conn, item.thumbnail.as_deref())?;
     conn.execute(
         r#"
-        INSERT INTO history (content, qr_type, label, style_json, thumbnail)
-        VALUES (?1, ?2, ?3, ?4, ?5)
+        INSERT INTO history
+            (content, qr_type, label, style_json, thumbnail_hash, sensitive, content_cipher,
+             content_hash)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
         "#,
         params![
-            item.content,
+            content,
             item.qr_type,
             item.label,
             item.style_json,
-            item.thumbnail
+            thumbnail_hash,
+            sensitive,
+            content_cipher,
+            content_hash(&item.qr_type, content, &item.style_json)
         ],
     )?;
 
     Ok(conn.last_insert_rowid())
 }
 
-/// Delete a history item
+/// Pin or unpin history items; returns how many were updated
+pub fn set_history_pinned(
+    conn: &Connection,
+    ids: &[i64],
+    pinned: bool,
+) -> Result<i64, rusqlite::Error> {
+    let mut stmt = conn.prepare("UPDATE history SET pinned = ?1 WHERE id = ?2")?;
+    let mut updated = 0;
+    for id in ids {
+        updated += stmt.execute(params![pinned, id])? as i64;
+    }
+    Ok(updated)
+}
+
+/// Move a history item to the trash
 pub fn delete_history(conn: &Connection, id: i64) -> Result<bool, rusqlite::Error> {
-    let affected = conn.execute("DELETE FROM history WHERE id = ?1", params![id])?;
+    let affected = conn.execute(
+        "UPDATE history SET deleted_at = datetime('now') WHERE id = ?1 AND deleted_at IS NULL",
+        params![id],
+    )?;
     Ok(affected > 0)
 }
 
-/// Clear all history
+/// Move all history to the trash
 pub fn clear_history(conn: &Connection) -> Result<i64, rusqlite::Error> {
-    let affected = conn.execute("DELETE FROM history", [])?;
+    let affected = conn.execute(
+        "UPDATE history SET deleted_at = datetime('now') WHERE deleted_at IS NULL",
+        [],
+    )?;
     Ok(affected as i64)
 }
 
-/// Get history count
-pub fn count_history(conn: &Connection) -> Result<i64, rusqlite::Error> {
-    let count: i64 = conn.query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0))?;
-    Ok(count)
+/// Bring history items back from the trash
+pub fn restore_history(conn: &Connection, ids: &[i64]) -> Result<i64, rusqlite::Error> {
+    let mut stmt = conn
+        .prepare("UPDATE history SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL")?;
+    let mut restored = 0;
+    for id in ids {
+        restored += stmt.execute(params![id])? as i64;
+    }
+    Ok(restored)
+}
+
+/// Permanently delete trashed history items
+pub fn purge_history(conn: &Connection, ids: &[i64]) -> Result<i64, rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    let mut purged = 0;
+    {
+        let mut stmt =
+            tx.prepare("DELETE FROM history WHERE id = ?1 AND deleted_at IS NOT NULL")?;
+        for id in ids {
+            purged += stmt.execute(params![id])? as i64;
+        }
+    }
+    tx.commit()?;
+    Ok(purged)
+}
+
+/// Permanently delete everything in the history trash
+pub fn empty_history_trash(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    let affected = conn.execute("DELETE FROM history WHERE deleted_at IS NOT NULL", [])?;
+    Ok(affected as i64)
+}
+
+/// Count history rows matching a filter
+pub fn count_filtered_history(
+    conn: &Connection,
+    filter: &HistoryFilter,
+) -> Result<i64, rusqlite::Error> {
+    let (clause, values) = filter_clause(filter);
+    let query = format!("SELECT COUNT(*) FROM history WHERE {}", clause);
+    conn.query_row(&query, params_from_iter(values), |row| row.get(0))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::get_blob;
 
     fn setup_test_db() -> Connection {
         let conn = Connection::open_in_memory().unwrap();
-        conn.execute_batch(
-            r#"
-            CREATE TABLE history (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                content TEXT NOT NULL,
-                qr_type TEXT NOT NULL,
-                label TEXT,
-                style_json TEXT NOT NULL,
-                thumbnail TEXT,
-                created_at TEXT DEFAULT (datetime('now')),
-                updated_at TEXT DEFAULT (datetime('now'))
-            );
-            "#,
-        )
-        .unwrap();
+        crate::db::init_schema(&conn).unwrap();
         conn
     }
 
+    fn list_history(
+        conn: &Connection,
+        limit: i64,
+        offset: i64,
+        search: Option<&str>,
+    ) -> Result<Vec<HistoryItem>, rusqlite::Error> {
+        let filter = HistoryFilter {
+            search: search.map(str::to_string),
+            ..Default::default()
+        };
+        list_history_page(conn, &filter, HistorySort::default(), None, limit, offset)
+            .map(|page| page.items)
+    }
+
+    fn count_history(conn: &Connection) -> Result<i64, rusqlite::Error> {
+        count_filtered_history(conn, &HistoryFilter::default())
+    }
+
     fn create_test_item(content: &str, qr_type: &str) -> NewHistoryItem {
         NewHistoryItem {
             content: content.to_string(),
@@ -330,8 +662,168 @@ mod tests {
         assert_eq!(retrieved.qr_type, "url");
         assert_eq!(retrieved.label, Some("My Link".to_string()));
         assert_eq!(retrieved.style_json, style);
-        assert_eq!(retrieved.thumbnail, Some("data:image/png;base64,thumb".to_string()));
+        let thumbnail = get_blob(&conn, retrieved.thumbnail_ref.as_ref().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(thumbnail.to_data_url(), "data:image/png;base64,thumb");
         assert!(!retrieved.created_at.is_empty());
         assert!(!retrieved.updated_at.is_empty());
     }
+
+    fn save_dated(conn: &Connection, content: &str, qr_type: &str, created_at: &str) -> i64 {
+        let id = save_history(conn, &create_test_item(content, qr_type)).unwrap();
+        conn.execute(
+            "UPDATE history SET created_at = ?1, updated_at = ?1 WHERE id = ?2",
+            params![created_at, id],
+        )
+        .unwrap();
+        id
+    }
+
+    #[test]
+    fn test_filter_by_type_date_and_label() {
+        let conn = setup_test_db();
+        save_dated(&conn, "https://a.com", "url", "2026-01-05 10:00:00");
+        save_dated(&conn, "WIFI:S:Office;;", "wifi", "2026-02-10 10:00:00");
+        let labelled = save_dated(&conn, "https://b.com", "url", "2026-03-01 09:00:00");
+        conn.execute(
+            "UPDATE history SET label = 'Promo' WHERE id = ?1",
+            params![labelled],
+        )
+        .unwrap();
+
+        let urls = HistoryFilter {
+            qr_types: vec!["url".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(count_filtered_history(&conn, &urls).unwrap(), 2);
+
+        let february = HistoryFilter {
+            created_after: Some("2026-02-01".to_string()),
+            created_before: Some("2026-03-01".to_string()),
+            ..Default::default()
+        };
+        let page =
+            list_history_page(&conn, &february, HistorySort::default(), None, 50, 0).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].qr_type, "wifi");
+
+        let labelled_urls = HistoryFilter {
+            has_label: Some(true),
+            ..urls.clone()
+        };
+        assert_eq!(count_filtered_history(&conn, &labelled_urls).unwrap(), 1);
+        let unlabelled = HistoryFilter {
+            has_label: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(count_filtered_history(&conn, &unlabelled).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_filtered_count_matches_search() {
+        let conn = setup_test_db();
+        save_history(&conn, &create_test_item("https://example.com", "url")).unwrap();
+        save_history(&conn, &create_test_item("https://other.org", "url")).unwrap();
+
+        let filter = HistoryFilter {
+            search: Some("example".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(count_filtered_history(&conn, &filter).unwrap(), 1);
+        assert_eq!(count_history(&conn).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_sort_orders() {
+        let conn = setup_test_db();
+        let old = save_dated(&conn, "old", "text", "2026-01-01 00:00:00");
+        let new = save_dated(&conn, "new", "text", "2026-06-01 00:00:00");
+        conn.execute(
+            "UPDATE history SET label = 'zebra', updated_at = '2026-09-01 00:00:00' WHERE id = ?1",
+            params![old],
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE history SET label = 'Apple' WHERE id = ?1",
+            params![new],
+        )
+        .unwrap();
+
+        let ids = |sort| {
+            list_history_page(&conn, &HistoryFilter::default(), sort, None, 50, 0)
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| item.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(HistorySort::CreatedDesc), vec![new, old]);
+        assert_eq!(ids(HistorySort::CreatedAsc), vec![old, new]);
+        assert_eq!(ids(HistorySort::UpdatedDesc), vec![old, new]);
+        assert_eq!(ids(HistorySort::LabelAsc), vec![new, old]);
+        assert_eq!(ids(HistorySort::LabelDesc), vec![old, new]);
+    }
+
+    #[test]
+    fn test_cursor_pagination_stable_under_inserts() {
+        let conn = setup_test_db();
+        // Same timestamp for every row so ordering falls back to the id
+        for i in 0..5 {
+            save_dated(&conn, &format!("item {}", i), "text", "2026-01-01 00:00:00");
+        }
+        let filter = HistoryFilter::default();
+        let sort = HistorySort::CreatedDesc;
+
+        let first = list_history_page(&conn, &filter, sort, None, 2, 0).unwrap();
+        assert_eq!(first.items[0].content, "item 4");
+        let cursor = first.next_cursor.unwrap();
+
+        // A new row lands at the top; the next page must not repeat "item 3"
+        save_dated(&conn, "late", "text", "2026-02-01 00:00:00");
+
+        let second = list_history_page(&conn, &filter, sort, Some(&cursor), 2, 0).unwrap();
+        let contents: Vec<_> = second.items.iter().map(|i| i.content.as_str()).collect();
+        assert_eq!(contents, vec!["item 2", "item 1"]);
+
+        let third =
+            list_history_page(&conn, &filter, sort, second.next_cursor.as_ref(), 2, 0).unwrap();
+        assert_eq!(third.items.len(), 1);
+        assert_eq!(third.items[0].content, "item 0");
+        assert!(third.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_trash_restore_and_purge() {
+        let conn = setup_test_db();
+        let a = save_history(&conn, &create_test_item("https://a.com", "url")).unwrap();
+        let b = save_history(&conn, &create_test_item("https://b.com", "url")).unwrap();
+
+        let trash = HistoryFilter {
+            trashed: true,
+            ..Default::default()
+        };
+
+        assert!(delete_history(&conn, a).unwrap());
+        assert!(!delete_history(&conn, a).unwrap());
+        assert_eq!(count_history(&conn).unwrap(), 1);
+        let trashed = list_history_page(&conn, &trash, HistorySort::DeletedDesc, None, 10, 0)
+            .unwrap()
+            .items;
+        assert_eq!(trashed.len(), 1);
+        assert!(trashed[0].deleted_at.is_some());
+        assert_eq!(list_history(&conn, 10, 0, Some("a.com")).unwrap().len(), 0);
+
+        // Purge only touches trashed rows
+        assert_eq!(purge_history(&conn, &[b]).unwrap(), 0);
+
+        assert_eq!(restore_history(&conn, &[a]).unwrap(), 1);
+        assert_eq!(count_history(&conn).unwrap(), 2);
+
+        assert_eq!(clear_history(&conn).unwrap(), 2);
+        assert_eq!(count_filtered_history(&conn, &trash).unwrap(), 2);
+        assert_eq!(purge_history(&conn, &[a]).unwrap(), 1);
+        assert_eq!(empty_history_trash(&conn).unwrap(), 1);
+        assert_eq!(count_filtered_history(&conn, &trash).unwrap(), 0);
+    }
 }
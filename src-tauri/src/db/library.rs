@@ -0,0 +1,767 @@
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::blobs::{get_blob, put_optional_blob};
+use super::dedupe::refresh_content_hash;
+use super::encryption::{
+    is_sensitive_content, redact_content, reveal_history_content, seal_content, EncryptionError,
+    EncryptionKey, REDACTED,
+};
+use super::history::{history_from_row, insert_history_row, NewHistoryItem, HISTORY_COLUMNS};
+use super::tags::ensure_tag;
+
+/// Identifies a library file, whatever container it came in
+pub const LIBRARY_FORMAT: &str = "qr-foundry-library";
+
+/// Bump when a change would stop older versions from reading the file
+pub const LIBRARY_VERSION: i64 = 1;
+
+/// History and templates in the portable format shared by desktop and web
+///
+/// Images are data URLs, except inside a `.qrfoundry` bundle where they
+/// point at files in the archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Library {
+    pub format: String,
+    pub version: i64,
+    pub exported_at: String,
+    #[serde(default)]
+    pub history: Vec<LibraryHistoryItem>,
+    #[serde(default)]
+    pub templates: Vec<LibraryTemplate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryHistoryItem {
+    pub content: String,
+    pub qr_type: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default = "empty_style")]
+    pub style_json: String,
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    /// The secret was masked on export, so `content` can't be used as is
+    #[serde(default)]
+    pub redacted: bool,
+}
+
+impl LibraryHistoryItem {
+    /// Whether the content is a masked copy, flagged or not
+    ///
+    /// Files written before the flag existed still carry the mask itself.
+    pub fn is_redacted(&self) -> bool {
+        self.redacted
+            || (self.content.contains(REDACTED)
+                && is_sensitive_content(&self.content)
+                && redact_content(&self.content) == self.content)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryTemplate {
+    pub name: String,
+    pub style_json: String,
+    #[serde(default)]
+    pub preview: Option<String>,
+    #[serde(default)]
+    pub is_default: bool,
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+fn empty_style() -> String {
+    "{}".to_string()
+}
+
+impl Library {
+    pub fn new(history: Vec<LibraryHistoryItem>, templates: Vec<LibraryTemplate>) -> Self {
+        Self {
+            format: LIBRARY_FORMAT.to_string(),
+            version: LIBRARY_VERSION,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            history,
+            templates,
+        }
+    }
+
+    /// Check a parsed file is a library this version can read
+    pub fn validate(&self) -> Result<(), String> {
+        if self.format != LIBRARY_FORMAT {
+            return Err("Not a QR Foundry library file".to_string());
+        }
+        if self.version > LIBRARY_VERSION {
+            return Err(format!(
+                "Library version {} is newer than this app supports ({}); please update QR Foundry",
+                self.version, LIBRARY_VERSION
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What to export; `None` means everything, an empty list means nothing
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibrarySelection {
+    pub history_ids: Option<Vec<i64>>,
+    pub template_ids: Option<Vec<i64>>,
+}
+
+/// What to do when an imported item clashes with an existing one
+///
+/// History clashes on content and type with a different label or style;
+/// templates clash on name with a different style.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictStrategy {
+    /// Keep the existing item and drop the imported one
+    #[default]
+    Skip,
+    /// Import alongside; templates get a numbered name
+    KeepBoth,
+    /// Overwrite the existing item's label, style and image
+    Replace,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCounts {
+    pub imported: i64,
+    pub duplicates: i64, // identical to an existing item, so skipped
+    pub replaced: i64,
+    pub skipped: i64,  // conflicts left as they were
+    pub redacted: i64, // exported without their secret, so not imported
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportConflict {
+    pub kind: String, // "history" | "template"
+    pub name: String, // label or (redacted) content; template name
+    pub existing_id: i64,
+    pub resolution: ConflictStrategy,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub history: ImportCounts,
+    pub templates: ImportCounts,
+    pub conflicts: Vec<ImportConflict>,
+}
+
+/// SQLite's UTC `YYYY-MM-DD HH:MM:SS` as RFC 3339, which browsers parse
+fn to_rfc3339(timestamp: &str) -> String {
+    chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+        .map(|t| {
+            t.and_utc()
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        })
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+/// `AND id IN (...)` for a selection, with the IDs appended to `values`
+fn selection_clause(ids: Option<&[i64]>, column: &str, values: &mut Vec<Value>) -> String {
+    let Some(ids) = ids else {
+        return String::new();
+    };
+    let start = values.len();
+    values.extend(ids.iter().map(|id| Value::Integer(*id)));
+    let placeholders: Vec<String> = (start..values.len())
+        .map(|i| format!("?{}", i + 1))
+        .collect();
+    format!("AND {} IN ({})", column, placeholders.join(", "))
+}
+
+/// Gather live history and templates in the portable format
+///
+/// Encrypted content is decrypted with `key`. Unless `include_secrets`,
+/// WiFi passwords and OTP secrets are redacted.
+pub fn export_library(
+    conn: &Connection,
+    selection: &LibrarySelection,
+    include_secrets: bool,
+    key: Option<&EncryptionKey>,
+) -> Result<Library, EncryptionError> {
+    let mut values = Vec::new();
+    let clause = selection_clause(selection.history_ids.as_deref(), "h.id", &mut values);
+    let query = format!(
+        r#"
+        SELECT {HISTORY_COLUMNS}
+        FROM history h
+        WHERE h.deleted_at IS NULL {clause}
+        ORDER BY h.created_at, h.id
+        "#
+    );
+    let items = conn
+        .prepare(&query)?
+        .query_map(params_from_iter(values), history_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut history = Vec::with_capacity(items.len());
+    for item in items {
+        let redacted = item.sensitive && !include_secrets;
+        let content = if redacted {
+            item.content
+        } else {
+            reveal_history_content(conn, item.id, key)?.unwrap_or(item.content)
+        };
+        let thumbnail = match &item.thumbnail_ref {
+            Some(hash) => get_blob(conn, hash)?.map(|blob| blob.to_data_url()),
+            None => None,
+        };
+
+        history.push(LibraryHistoryItem {
+            content,
+            qr_type: item.qr_type,
+            label: item.label,
+            style_json: item.style_json,
+            thumbnail,
+            pinned: item.pinned,
+            tags: item.tags,
+            created_at: Some(to_rfc3339(&item.created_at)),
+            updated_at: Some(to_rfc3339(&item.updated_at)),
+            redacted,
+        });
+    }
+
+    let mut values = Vec::new();
+    let clause = selection_clause(selection.template_ids.as_deref(), "id", &mut values);
+    let query = format!(
+        r#"
+        SELECT name, style_json, preview_hash, is_default, created_at
+        FROM templates
+        WHERE deleted_at IS NULL {clause}
+        ORDER BY created_at, id
+        "#
+    );
+    let rows = conn
+        .prepare(&query)?
+        .query_map(params_from_iter(values), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, bool>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut templates = Vec::with_capacity(rows.len());
+    for (name, style_json, preview_hash, is_default, created_at) in rows {
+        let preview = match preview_hash {
+            Some(hash) => get_blob(conn, &hash)?.map(|blob| blob.to_data_url()),
+            None => None,
+        };
+        templates.push(LibraryTemplate {
+            name,
+            style_json,
+            preview,
+            is_default,
+            created_at: Some(to_rfc3339(&created_at)),
+        });
+    }
+
+    Ok(Library::new(history, templates))
+}
+
+/// An existing history row with the same content and type
+struct HistoryMatch {
+    id: i64,
+    identical: bool, // same label and style too
+}
+
+fn find_history_match(
+    conn: &Connection,
+    item: &LibraryHistoryItem,
+    key: Option<&EncryptionKey>,
+) -> Result<Option<HistoryMatch>, EncryptionError> {
+    // Encrypted rows store a redacted copy, so match on that and decrypt
+    let candidates: Vec<(i64, Option<String>, String, bool)> = conn
+        .prepare(
+            r#"
+            SELECT id, label, style_json, content_cipher IS NOT NULL
+            FROM history
+            WHERE deleted_at IS NULL AND qr_type = ?1
+              AND (content = ?2 OR (content_cipher IS NOT NULL AND content = ?3))
+            ORDER BY id
+            "#,
+        )?
+        .query_map(
+            params![item.qr_type, item.content, redact_content(&item.content)],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )?
+        .collect::<Result<_, _>>()?;
+
+    let mut first = None;
+    for (id, label, style_json, encrypted) in candidates {
+        if encrypted
+            && reveal_history_content(conn, id, key)?.as_deref() != Some(item.content.as_str())
+        {
+            continue;
+        }
+        if label == item.label && style_json == item.style_json {
+            return Ok(Some(HistoryMatch {
+                id,
+                identical: true,
+            }));
+        }
+        first.get_or_insert(HistoryMatch {
+            id,
+            identical: false,
+        });
+    }
+    Ok(first)
+}
+
+fn add_tags(conn: &Connection, history_id: i64, tags: &[String]) -> Result<(), rusqlite::Error> {
+    for name in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        let tag_id = ensure_tag(conn, name)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO history_tags (history_id, tag_id) VALUES (?1, ?2)",
+            params![history_id, tag_id],
+        )?;
+    }
+    Ok(())
+}
+
+fn import_history_item(
+    conn: &Connection,
+    item: &LibraryHistoryItem,
+    key: Option<&EncryptionKey>,
+) -> Result<i64, EncryptionError> {
+    let sensitive = is_sensitive_content(&item.content);
+    let sealed = seal_content(conn, &item.content, sensitive, key)?;
+    let new_item = NewHistoryItem {
+        content: item.content.clone(),
+        qr_type: item.qr_type.clone(),
+        label: item.label.clone(),
+        style_json: item.style_json.clone(),
+        thumbnail: item.thumbnail.clone(),
+    };
+    let id = insert_history_row(conn, &new_item, sensitive, sealed.as_ref())?;
+
+    conn.execute(
+        r#"
+        UPDATE history
+        SET pinned = ?1,
+            created_at = COALESCE(datetime(?2), created_at),
+            updated_at = COALESCE(datetime(?3), datetime(?2), updated_at)
+        WHERE id = ?4
+        "#,
+        params![item.pinned, item.created_at, item.updated_at, id],
+    )?;
+    add_tags(conn, id, &item.tags)?;
+    Ok(id)
+}
+
+/// A name not yet used by a live template: "Name", "Name (2)", ...
+fn unused_template_name(conn: &Connection, name: &str) -> Result<String, rusqlite::Error> {
+    let mut candidate = name.to_string();
+    let mut n = 2;
+    while find_template_by_name(conn, &candidate)?.is_some() {
+        candidate = format!("{} ({})", name, n);
+        n += 1;
+    }
+    Ok(candidate)
+}
+
+fn find_template_by_name(
+    conn: &Connection,
+    name: &str,
+) -> Result<Option<(i64, String)>, rusqlite::Error> {
+    conn.query_row(
+        r#"
+        SELECT id, style_json FROM templates
+        WHERE deleted_at IS NULL AND name = ?1 COLLATE NOCASE
+        ORDER BY id LIMIT 1
+        "#,
+        params![name],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+}
+
+fn insert_template(
+    conn: &Connection,
+    template: &LibraryTemplate,
+    name: &str,
+) -> Result<i64, rusqlite::Error> {
+    // Keep the current default; only take over if there isn't one
+    let has_default: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM templates WHERE is_default = 1 AND deleted_at IS NULL)",
+        [],
+        |row| row.get(0),
+    )?;
+    let preview_hash = put_optional_blob(conn, template.preview.as_deref())?;
+    conn.execute(
+        r#"
+        INSERT INTO templates (name, style_json, preview_hash, is_default, created_at)
+        VALUES (?1, ?2, ?3, ?4, COALESCE(datetime(?5), datetime('now')))
+        "#,
+        params![
+            name,
+            template.style_json,
+            preview_hash,
+            template.is_default && !has_default,
+            template.created_at
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Add a library's items, skipping ones already present
+///
+/// Runs in one transaction, so a failure leaves the database untouched.
+/// Clashing items are handled per `strategy` and listed in the report.
+/// Items whose secret was redacted on export are never saved, since the
+/// masked copy would be a broken code.
+pub fn import_library(
+    conn: &Connection,
+    library: &Library,
+    strategy: ConflictStrategy,
+    key: Option<&EncryptionKey>,
+) -> Result<ImportReport, EncryptionError> {
+    let mut report = ImportReport::default();
+    let tx = conn.unchecked_transaction()?;
+
+    for item in &library.history {
+        if item.is_redacted() {
+            report.history.redacted += 1;
+            continue;
+        }
+
+        match find_history_match(&tx, item, key)? {
+            None => {
+                import_history_item(&tx, item, key)?;
+                report.history.imported += 1;
+            }
+            Some(existing) if existing.identical => {
+                add_tags(&tx, existing.id, &item.tags)?;
+                report.history.duplicates += 1;
+            }
+            Some(existing) => {
+                match strategy {
+                    ConflictStrategy::Skip => report.history.skipped += 1,
+                    ConflictStrategy::KeepBoth => {
+                        import_history_item(&tx, item, key)?;
+                        report.history.imported += 1;
+                    }
+                    ConflictStrategy::Replace => {
+                        let thumbnail_hash = put_optional_blob(&tx, item.thumbnail.as_deref())?;
+                        tx.execute(
+                            r#"
+                            UPDATE history
+                            SET label = ?1, style_json = ?2,
+                                thumbnail_hash = COALESCE(?3, thumbnail_hash),
+                                updated_at = datetime('now')
+                            WHERE id = ?4
+                            "#,
+                            params![item.label, item.style_json, thumbnail_hash, existing.id],
+                        )?;
+                        refresh_content_hash(&tx, existing.id)?;
+                        add_tags(&tx, existing.id, &item.tags)?;
+                        report.history.replaced += 1;
+                    }
+                }
+
+                let name = match &item.label {
+                    Some(label) => label.clone(),
+                    None if is_sensitive_content(&item.content) => redact_content(&item.content),
+                    None => item.content.clone(),
+                };
+                report.conflicts.push(ImportConflict {
+                    kind: "history".to_string(),
+                    name,
+                    existing_id: existing.id,
+                    resolution: strategy,
+                });
+            }
+        }
+    }
+
+    for template in &library.templates {
+        let name = template.name.trim();
+        if name.is_empty() {
+            report.templates.skipped += 1;
+            continue;
+        }
+
+        match find_template_by_name(&tx, name)? {
+            None => {
+                insert_template(&tx, template, name)?;
+                report.templates.imported += 1;
+            }
+            Some((_, style_json)) if style_json == template.style_json => {
+                report.templates.duplicates += 1;
+            }
+            Some((existing_id, _)) => {
+                match strategy {
+                    ConflictStrategy::Skip => report.templates.skipped += 1,
+                    ConflictStrategy::KeepBoth => {
+                        let name = unused_template_name(&tx, name)?;
+                        insert_template(&tx, template, &name)?;
+                        report.templates.imported += 1;
+                    }
+                    ConflictStrategy::Replace => {
+                        let preview_hash = put_optional_blob(&tx, template.preview.as_deref())?;
+                        tx.execute(
+                            r#"
+                            UPDATE templates
+                            SET style_json = ?1, preview_hash = COALESCE(?2, preview_hash)
+                            WHERE id = ?3
+                            "#,
+                            params![template.style_json, preview_hash, existing_id],
+                        )?;
+                        report.templates.replaced += 1;
+                    }
+                }
+                report.conflicts.push(ImportConflict {
+                    kind: "template".to_string(),
+                    name: name.to_string(),
+                    existing_id,
+                    resolution: strategy,
+                });
+            }
+        }
+    }
+
+    tx.commit()?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        enable_encryption, init_schema, save_history, save_template, set_history_tags,
+        EncryptionScope, KeyMaterial, NewTemplate,
+    };
+
+    const WIFI: &str = "WIFI:T:WPA;S:Office;P:hunter2;;";
+    const PNG: &str = "data:image/png;base64,iVBORw0KGgo=";
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn save(conn: &Connection, content: &str, label: Option<&str>) -> i64 {
+        save_history(
+            conn,
+            &NewHistoryItem {
+                content: content.to_string(),
+                qr_type: crate::qr_type::detect_qr_type(content),
+                label: label.map(str::to_string),
+                style_json: r#"{"dotStyle":"dots"}"#.to_string(),
+                thumbnail: Some(PNG.to_string()),
+            },
+        )
+        .unwrap()
+    }
+
+    fn save_named_template(conn: &Connection, name: &str, style_json: &str) -> i64 {
+        save_template(
+            conn,
+            &NewTemplate {
+                name: name.to_string(),
+                style_json: style_json.to_string(),
+                preview: Some(PNG.to_string()),
+                is_default: Some(true),
+            },
+        )
+        .unwrap()
+    }
+
+    fn export_all(conn: &Connection) -> Library {
+        export_library(conn, &LibrarySelection::default(), true, None).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let source = setup_test_db();
+        let id = save(&source, "https://example.com", Some("Site"));
+        set_history_tags(&source, id, &["promo".to_string()]).unwrap();
+        source
+            .execute(
+                "UPDATE history SET pinned = 1, created_at = '2026-01-02 03:04:05' WHERE id = ?1",
+                params![id],
+            )
+            .unwrap();
+        save_named_template(&source, "Brand", r##"{"foreground":"#123456"}"##);
+
+        let library = export_all(&source);
+        assert_eq!(
+            library.history[0].created_at.as_deref(),
+            Some("2026-01-02T03:04:05Z")
+        );
+        assert_eq!(library.history[0].thumbnail.as_deref(), Some(PNG));
+
+        let target = setup_test_db();
+        let report = import_library(&target, &library, ConflictStrategy::Skip, None).unwrap();
+        assert_eq!(report.history.imported, 1);
+        assert_eq!(report.templates.imported, 1);
+        assert!(report.conflicts.is_empty());
+
+        let imported = export_all(&target);
+        assert_eq!(imported.history, library.history);
+        assert_eq!(imported.templates, library.templates);
+    }
+
+    #[test]
+    fn test_selection() {
+        let conn = setup_test_db();
+        let first = save(&conn, "https://one.example", None);
+        save(&conn, "https://two.example", None);
+        save_named_template(&conn, "Brand", "{}");
+
+        let selection = LibrarySelection {
+            history_ids: Some(vec![first]),
+            template_ids: Some(vec![]),
+        };
+        let library = export_library(&conn, &selection, true, None).unwrap();
+        assert_eq!(library.history.len(), 1);
+        assert_eq!(library.history[0].content, "https://one.example");
+        assert!(library.templates.is_empty());
+    }
+
+    #[test]
+    fn test_dedupes_and_reports_conflicts() {
+        let conn = setup_test_db();
+        save(&conn, "https://example.com", Some("Site"));
+        save_named_template(&conn, "Brand", "{}");
+
+        let mut library = export_all(&conn);
+        let report = import_library(&conn, &library, ConflictStrategy::Skip, None).unwrap();
+        assert_eq!(report.history.duplicates, 1);
+        assert_eq!(report.templates.duplicates, 1);
+        assert!(report.conflicts.is_empty());
+
+        library.history[0].label = Some("Renamed".to_string());
+        library.templates[0].style_json = r#"{"dotStyle":"square"}"#.to_string();
+
+        let report = import_library(&conn, &library, ConflictStrategy::Skip, None).unwrap();
+        assert_eq!(report.history.skipped, 1);
+        assert_eq!(report.templates.skipped, 1);
+        assert_eq!(report.conflicts.len(), 2);
+        assert_eq!(report.conflicts[0].name, "Renamed");
+
+        let report = import_library(&conn, &library, ConflictStrategy::KeepBoth, None).unwrap();
+        assert_eq!(report.history.imported, 1);
+        assert_eq!(report.templates.imported, 1);
+        let names: Vec<_> = export_all(&conn)
+            .templates
+            .into_iter()
+            .map(|t| (t.name, t.is_default))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("Brand".to_string(), true),
+                ("Brand (2)".to_string(), false)
+            ]
+        );
+
+        // Both history rows now exist, so the renamed one is an exact match
+        let report = import_library(&conn, &library, ConflictStrategy::Replace, None).unwrap();
+        assert_eq!(report.history.duplicates, 1);
+        assert_eq!(report.templates.replaced, 1);
+        assert_eq!(
+            export_all(&conn).templates[0].style_json,
+            r#"{"dotStyle":"square"}"#
+        );
+    }
+
+    #[test]
+    fn test_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = setup_test_db();
+        save(&conn, WIFI, None);
+        let key = enable_encryption(
+            &conn,
+            EncryptionScope::Sensitive,
+            KeyMaterial::Keyfile(&dir.path().join("key")),
+        )
+        .unwrap();
+
+        let redacted = export_library(&conn, &LibrarySelection::default(), false, None).unwrap();
+        assert!(!redacted.history[0].content.contains("hunter2"));
+
+        assert!(matches!(
+            export_library(&conn, &LibrarySelection::default(), true, None),
+            Err(EncryptionError::Locked)
+        ));
+        let full = export_library(&conn, &LibrarySelection::default(), true, Some(&key)).unwrap();
+        assert_eq!(full.history[0].content, WIFI);
+
+        // The encrypted row is still found as a duplicate
+        let report = import_library(&conn, &full, ConflictStrategy::Skip, Some(&key)).unwrap();
+        assert_eq!(report.history.duplicates, 1);
+    }
+
+    #[test]
+    fn test_redacted_items_are_not_imported() {
+        let source = setup_test_db();
+        save(&source, WIFI, None);
+        save(
+            &source,
+            "otpauth://totp/Acme?secret=JBSWY3DP&issuer=Acme",
+            None,
+        );
+        save(&source, "https://example.com", None);
+
+        let library = export_library(&source, &LibrarySelection::default(), false, None).unwrap();
+        assert!(library.history.iter().filter(|h| h.redacted).count() == 2);
+
+        let target = setup_test_db();
+        let report = import_library(&target, &library, ConflictStrategy::Skip, None).unwrap();
+        assert_eq!(report.history.imported, 1);
+        assert_eq!(report.history.redacted, 2);
+
+        let masked: i64 = target
+            .query_row(
+                "SELECT COUNT(*) FROM history WHERE content LIKE '%' || ?1 || '%'",
+                params![REDACTED],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(masked, 0);
+
+        // Files from before the flag are caught by the mask itself
+        let mut unflagged = library.clone();
+        unflagged
+            .history
+            .iter_mut()
+            .for_each(|h| h.redacted = false);
+        let report = import_library(&target, &unflagged, ConflictStrategy::KeepBoth, None).unwrap();
+        assert_eq!(report.history.redacted, 2);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut library = Library::new(vec![], vec![]);
+        assert!(library.validate().is_ok());
+
+        library.version = LIBRARY_VERSION + 1;
+        assert!(library.validate().is_err());
+
+        library.version = LIBRARY_VERSION;
+        library.format = "something-else".to_string();
+        assert!(library.validate().is_err());
+    }
+}
@@ -0,0 +1,977 @@
+use rusqlite::{params, Connection};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::blobs::migrate_inline_images;
+use super::dedupe::fill_content_hashes;
+use super::encryption::{is_encryption_enabled, mark_sensitive_history};
+use super::scans::mark_sensitive_scans;
+
+/// One step of the schema history, applied in order
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub up: fn(&Connection) -> Result<(), rusqlite::Error>,
+}
+
+/// Every schema change, oldest first. Append new steps; never edit old ones.
+///
+/// Databases written before versioning (user_version 0) may already have any
+/// prefix of these changes, so the early steps are idempotent.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "history, templates and settings",
+        up: create_base_tables,
+    },
+    Migration {
+        version: 2,
+        description: "scan history",
+        up: create_scans,
+    },
+    Migration {
+        version: 3,
+        description: "history full-text search",
+        up: create_history_fts,
+    },
+    Migration {
+        version: 4,
+        description: "folders, tags and pinned items",
+        up: create_folders_and_tags,
+    },
+    Migration {
+        version: 5,
+        description: "trash for history and templates",
+        up: add_soft_delete,
+    },
+    Migration {
+        version: 6,
+        description: "content-addressed image blobs",
+        up: create_blobs,
+    },
+    Migration {
+        version: 7,
+        description: "sensitive and encrypted history",
+        up: add_history_encryption,
+    },
+    Migration {
+        version: 8,
+        description: "row UUIDs and sync change tracking",
+        up: add_sync_tracking,
+    },
+    Migration {
+        version: 9,
+        description: "history content hashes and use counts",
+        up: add_history_dedupe,
+    },
+    Migration {
+        version: 10,
+        description: "sensitive and encrypted scans",
+        up: add_scan_encryption,
+    },
+];
+
+/// Schema version this build writes
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The database was written by a newer app version
+    NewerVersion {
+        found: i64,
+        supported: i64,
+    },
+    /// Migration `version` failed and was rolled back
+    Failed {
+        version: i64,
+        description: &'static str,
+        source: rusqlite::Error,
+    },
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::NewerVersion { found, supported } => write!(
+                f,
+                "Database schema version {} is newer than this app supports ({}); please update QR Foundry",
+                found, supported
+            ),
+            MigrationError::Failed {
+                version,
+                description,
+                source,
+            } => write!(
+                f,
+                "Migration to schema version {} ({}) failed: {}",
+                version, description, source
+            ),
+            MigrationError::Sqlite(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(e: rusqlite::Error) -> Self {
+        MigrationError::Sqlite(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub from: i64,
+    pub to: i64,
+    pub backup: Option<PathBuf>, // copy of the database taken before migrating
+}
+
+pub fn schema_version(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Bring the schema up to date, backing up existing data first
+///
+/// With encryption on, the backup holds secrets in the clear, so it is
+/// deleted once every step has succeeded.
+pub fn migrate(conn: &Connection) -> Result<MigrationReport, MigrationError> {
+    let mut report = run_migrations(conn, MIGRATIONS)?;
+    if report.backup.is_some() && is_encryption_enabled(conn)? {
+        remove_migration_backups(conn);
+        report.backup = None;
+    }
+    Ok(report)
+}
+
+fn run_migrations(
+    conn: &Connection,
+    migrations: &[Migration],
+) -> Result<MigrationReport, MigrationError> {
+    let from = schema_version(conn)?;
+    let supported = migrations.last().map(|m| m.version).unwrap_or(0);
+    if from > supported {
+        return Err(MigrationError::NewerVersion {
+            found: from,
+            supported,
+        });
+    }
+
+    let pending: Vec<&Migration> = migrations.iter().filter(|m| m.version > from).collect();
+    if pending.is_empty() {
+        return Ok(MigrationReport {
+            from,
+            to: from,
+            backup: None,
+        });
+    }
+
+    let backup = backup_before_migrating(conn, from)?;
+
+    for migration in pending {
+        apply(conn, migration).map_err(|source| MigrationError::Failed {
+            version: migration.version,
+            description: migration.description,
+            source,
+        })?;
+    }
+
+    Ok(MigrationReport {
+        from,
+        to: supported,
+        backup,
+    })
+}
+
+/// Run one step and record its version, all or nothing
+fn apply(conn: &Connection, migration: &Migration) -> Result<(), rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    (migration.up)(&tx)?;
+    tx.pragma_update(None, "user_version", migration.version)?;
+    tx.commit()
+}
+
+/// Copy a file-backed database that already holds data to
+/// `<name>.v<version>.bak` next to it
+fn backup_before_migrating(
+    conn: &Connection,
+    version: i64,
+) -> Result<Option<PathBuf>, rusqlite::Error> {
+    let Some(path) = conn.path().filter(|path| !path.is_empty()) else {
+        return Ok(None);
+    };
+
+    let has_tables: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_tables {
+        return Ok(None);
+    }
+
+    let backup = PathBuf::from(format!("{}.v{}.bak", path, version));
+    // VACUUM INTO refuses to overwrite an existing file
+    std::fs::remove_file(&backup).ok();
+    conn.execute(
+        "VACUUM INTO ?1",
+        params![backup.to_string_lossy().to_string()],
+    )?;
+
+    Ok(Some(backup))
+}
+
+/// Delete every `<name>.v<version>.bak` left next to a file-backed database;
+/// returns how many went
+pub fn remove_migration_backups(conn: &Connection) -> usize {
+    let Some(path) = conn.path().filter(|path| !path.is_empty()) else {
+        return 0;
+    };
+    let path = Path::new(path);
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name().and_then(|n| n.to_str())) else {
+        return 0;
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+
+    let prefix = format!("{}.v", name);
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|file| file.strip_prefix(&prefix)?.strip_suffix(".bak"))
+                .is_some_and(|version| version.parse::<i64>().is_ok())
+        })
+        .filter(|entry| std::fs::remove_file(entry.path()).is_ok())
+        .count()
+}
+
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), rusqlite::Error> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+        params![table, column],
+        |row| row.get(0),
+    )?;
+
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn create_base_tables(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        -- History of generated QR codes
+        CREATE TABLE IF NOT EXISTS history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            content TEXT NOT NULL,
+            qr_type TEXT NOT NULL,
+            label TEXT,
+            style_json TEXT NOT NULL,
+            thumbnail TEXT,
+            created_at TEXT DEFAULT (datetime('now')),
+            updated_at TEXT DEFAULT (datetime('now'))
+        );
+
+        -- Create index on created_at for faster sorting
+        CREATE INDEX IF NOT EXISTS idx_history_created_at ON history(created_at DESC);
+
+        -- Saved style templates
+        CREATE TABLE IF NOT EXISTS templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            style_json TEXT NOT NULL,
+            preview TEXT,
+            is_default INTEGER DEFAULT 0,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+
+        -- App settings
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        "#,
+    )
+}
+
+fn create_scans(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        -- Decoded results from the scanner
+        CREATE TABLE IF NOT EXISTS scans (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source TEXT NOT NULL,
+            source_path TEXT,
+            content TEXT NOT NULL,
+            qr_type TEXT NOT NULL,
+            metadata_json TEXT,
+            thumbnail TEXT,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_scans_created_at ON scans(created_at DESC);
+        "#,
+    )
+}
+
+fn create_history_fts(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let fts_exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'history_fts')",
+        [],
+        |row| row.get(0),
+    )?;
+
+    conn.execute_batch(
+        r#"
+        -- Full-text index over history, kept in sync by triggers
+        CREATE VIRTUAL TABLE IF NOT EXISTS history_fts USING fts5(
+            content,
+            label,
+            qr_type,
+            tags,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS history_fts_insert AFTER INSERT ON history BEGIN
+            INSERT INTO history_fts (rowid, content, label, qr_type, tags)
+            VALUES (new.id, new.content, coalesce(new.label, ''), new.qr_type, '');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS history_fts_update
+        AFTER UPDATE OF content, label, qr_type ON history BEGIN
+            UPDATE history_fts
+            SET content = new.content, label = coalesce(new.label, ''), qr_type = new.qr_type
+            WHERE rowid = new.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS history_fts_delete AFTER DELETE ON history BEGIN
+            DELETE FROM history_fts WHERE rowid = old.id;
+        END;
+        "#,
+    )?;
+
+    // Index rows saved before full-text search existed
+    if !fts_exists {
+        conn.execute(
+            r#"
+            INSERT INTO history_fts (rowid, content, label, qr_type, tags)
+            SELECT id, content, coalesce(label, ''), qr_type, '' FROM history
+            "#,
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn create_folders_and_tags(conn: &Connection) -> Result<(), rusqlite::Error> {
+    add_column_if_missing(conn, "history", "folder_id", "INTEGER")?;
+    add_column_if_missing(conn, "history", "pinned", "INTEGER NOT NULL DEFAULT 0")?;
+
+    conn.execute_batch(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_history_folder_id ON history(folder_id);
+        CREATE INDEX IF NOT EXISTS idx_history_pinned ON history(pinned) WHERE pinned = 1;
+
+        -- Folder hierarchy for organizing history
+        CREATE TABLE IF NOT EXISTS folders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            parent_id INTEGER REFERENCES folders(id),
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_folders_parent_id ON folders(parent_id);
+
+        -- Tags, attached to history items many-to-many
+        CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE
+        );
+
+        CREATE TABLE IF NOT EXISTS history_tags (
+            history_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (history_id, tag_id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_history_tags_tag_id ON history_tags(tag_id);
+
+        CREATE TRIGGER IF NOT EXISTS history_tags_cleanup AFTER DELETE ON history BEGIN
+            DELETE FROM history_tags WHERE history_id = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS tags_cleanup AFTER DELETE ON tags BEGIN
+            DELETE FROM history_tags WHERE tag_id = old.id;
+        END;
+
+        -- Keep the tags column of the full-text index current
+        CREATE TRIGGER IF NOT EXISTS history_tags_fts_insert AFTER INSERT ON history_tags BEGIN
+            UPDATE history_fts SET tags = (
+                SELECT coalesce(group_concat(t.name, ' '), '') FROM history_tags ht
+                JOIN tags t ON t.id = ht.tag_id WHERE ht.history_id = new.history_id
+            ) WHERE rowid = new.history_id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS history_tags_fts_delete AFTER DELETE ON history_tags BEGIN
+            UPDATE history_fts SET tags = (
+                SELECT coalesce(group_concat(t.name, ' '), '') FROM history_tags ht
+                JOIN tags t ON t.id = ht.tag_id WHERE ht.history_id = old.history_id
+            ) WHERE rowid = old.history_id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS tags_fts_rename AFTER UPDATE OF name ON tags BEGIN
+            UPDATE history_fts SET tags = (
+                SELECT coalesce(group_concat(t.name, ' '), '') FROM history_tags ht
+                JOIN tags t ON t.id = ht.tag_id WHERE ht.history_id = history_fts.rowid
+            ) WHERE rowid IN (SELECT history_id FROM history_tags WHERE tag_id = new.id);
+        END;
+        "#,
+    )
+}
+
+fn add_soft_delete(conn: &Connection) -> Result<(), rusqlite::Error> {
+    add_column_if_missing(conn, "history", "deleted_at", "TEXT")?;
+    add_column_if_missing(conn, "templates", "deleted_at", "TEXT")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_history_deleted_at ON history(deleted_at)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn create_blobs(conn: &Connection) -> Result<(), rusqlite::Error> {
+    add_column_if_missing(conn, "history", "thumbnail_hash", "TEXT")?;
+    add_column_if_missing(conn, "templates", "preview_hash", "TEXT")?;
+
+    conn.execute_batch(
+        r#"
+        -- Thumbnails and previews, stored once per distinct image
+        CREATE TABLE IF NOT EXISTS blobs (
+            hash TEXT PRIMARY KEY,
+            mime TEXT NOT NULL,
+            data BLOB NOT NULL,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_history_thumbnail_hash ON history(thumbnail_hash);
+        CREATE INDEX IF NOT EXISTS idx_templates_preview_hash ON templates(preview_hash);
+
+        -- Drop a blob once nothing references it
+        CREATE TRIGGER IF NOT EXISTS history_blob_delete AFTER DELETE ON history
+        WHEN old.thumbnail_hash IS NOT NULL BEGIN
+            DELETE FROM blobs WHERE hash = old.thumbnail_hash
+                AND NOT EXISTS (SELECT 1 FROM history WHERE thumbnail_hash = old.thumbnail_hash)
+                AND NOT EXISTS (SELECT 1 FROM templates WHERE preview_hash = old.thumbnail_hash);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS history_blob_update AFTER UPDATE OF thumbnail_hash ON history
+        WHEN old.thumbnail_hash IS NOT NULL BEGIN
+            DELETE FROM blobs WHERE hash = old.thumbnail_hash
+                AND NOT EXISTS (SELECT 1 FROM history WHERE thumbnail_hash = old.thumbnail_hash)
+                AND NOT EXISTS (SELECT 1 FROM templates WHERE preview_hash = old.thumbnail_hash);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS templates_blob_delete AFTER DELETE ON templates
+        WHEN old.preview_hash IS NOT NULL BEGIN
+            DELETE FROM blobs WHERE hash = old.preview_hash
+                AND NOT EXISTS (SELECT 1 FROM history WHERE thumbnail_hash = old.preview_hash)
+                AND NOT EXISTS (SELECT 1 FROM templates WHERE preview_hash = old.preview_hash);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS templates_blob_update AFTER UPDATE OF preview_hash ON templates
+        WHEN old.preview_hash IS NOT NULL BEGIN
+            DELETE FROM blobs WHERE hash = old.preview_hash
+                AND NOT EXISTS (SELECT 1 FROM history WHERE thumbnail_hash = old.preview_hash)
+                AND NOT EXISTS (SELECT 1 FROM templates WHERE preview_hash = old.preview_hash);
+        END;
+        "#,
+    )?;
+
+    // Move images saved inline in older versions into the blob store
+    migrate_inline_images(conn)?;
+
+    Ok(())
+}
+
+fn add_history_encryption(conn: &Connection) -> Result<(), rusqlite::Error> {
+    add_column_if_missing(conn, "history", "sensitive", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "history", "content_cipher", "BLOB")?;
+
+    conn.execute_batch(
+        r#"
+        -- Present only while encryption is on; `content` then holds a
+        -- redacted copy of each encrypted row's content
+        CREATE TABLE IF NOT EXISTS encryption (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            key_source TEXT NOT NULL,
+            scope TEXT NOT NULL,
+            salt BLOB,
+            key_check BLOB NOT NULL,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+        "#,
+    )?;
+
+    // Flag WiFi and OTP items saved before sensitivity was tracked
+    mark_sensitive_history(conn)?;
+
+    Ok(())
+}
+
+/// SQL expression for a random version 4 UUID
+const NEW_UUID: &str = "lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || \
+     substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) || \
+     substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))";
+
+/// Trigger statement marking a synced row as changed on this device
+fn sync_touch(entity: &str, uuid: &str) -> String {
+    format!(
+        r#"
+            INSERT INTO sync_rows (entity, uuid, modified_at, device_id, pending)
+            VALUES ('{entity}', {uuid}, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), NULL, 1)
+            ON CONFLICT (entity, uuid) DO UPDATE SET
+                -- Stay ahead of the version being edited, even if this clock is behind
+                modified_at = max(
+                    excluded.modified_at,
+                    strftime('%Y-%m-%dT%H:%M:%fZ', sync_rows.modified_at, '+0.001 seconds')
+                ),
+                device_id = NULL,
+                pending = 1;
+        "#
+    )
+}
+
+fn add_sync_tracking(conn: &Connection) -> Result<(), rusqlite::Error> {
+    for table in ["history", "templates", "tags"] {
+        add_column_if_missing(conn, table, "uuid", "TEXT")?;
+        conn.execute(
+            &format!("UPDATE {table} SET uuid = {NEW_UUID} WHERE uuid IS NULL"),
+            [],
+        )?;
+    }
+
+    let history = sync_touch("history", "new.uuid");
+    let history_deleted = sync_touch("history", "old.uuid");
+    let history_tagged = sync_touch(
+        "history",
+        "(SELECT uuid FROM history WHERE id = new.history_id)",
+    );
+    let history_untagged = sync_touch(
+        "history",
+        "(SELECT uuid FROM history WHERE id = old.history_id)",
+    );
+    let template = sync_touch("template", "new.uuid");
+    let template_deleted = sync_touch("template", "old.uuid");
+    let tag = sync_touch("tag", "new.uuid");
+    let tag_deleted = sync_touch("tag", "old.uuid");
+
+    conn.execute_batch(&format!(
+        r#"
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_history_uuid ON history(uuid);
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_templates_uuid ON templates(uuid);
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_uuid ON tags(uuid);
+
+        -- This install's identity and sync folder, once sync is set up
+        CREATE TABLE IF NOT EXISTS sync_state (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            device_id TEXT NOT NULL,
+            folder TEXT,
+            include_secrets INTEGER NOT NULL DEFAULT 0,
+            last_synced_at TEXT
+        );
+
+        -- Latest known version of every synced row, deleted ones included
+        CREATE TABLE IF NOT EXISTS sync_rows (
+            entity TEXT NOT NULL,
+            uuid TEXT NOT NULL,
+            modified_at TEXT NOT NULL,
+            device_id TEXT,                      -- NULL: changed on this device
+            pending INTEGER NOT NULL DEFAULT 1,  -- not yet written to this device's log
+            PRIMARY KEY (entity, uuid)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_sync_rows_pending ON sync_rows(pending) WHERE pending = 1;
+
+        -- How far each other device's log has been merged
+        CREATE TABLE IF NOT EXISTS sync_peers (
+            device_id TEXT PRIMARY KEY,
+            log_offset INTEGER NOT NULL DEFAULT 0,
+            synced_at TEXT
+        );
+
+        -- Rows inserted without a UUID get one
+        CREATE TRIGGER IF NOT EXISTS history_uuid AFTER INSERT ON history
+        WHEN new.uuid IS NULL BEGIN
+            UPDATE history SET uuid = {NEW_UUID} WHERE id = new.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS templates_uuid AFTER INSERT ON templates
+        WHEN new.uuid IS NULL BEGIN
+            UPDATE templates SET uuid = {NEW_UUID} WHERE id = new.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS tags_uuid AFTER INSERT ON tags
+        WHEN new.uuid IS NULL BEGIN
+            UPDATE tags SET uuid = {NEW_UUID} WHERE id = new.id;
+        END;
+
+        -- Track changes to synced columns
+        CREATE TRIGGER IF NOT EXISTS history_sync_insert AFTER INSERT ON history
+        WHEN new.uuid IS NOT NULL BEGIN {history} END;
+
+        CREATE TRIGGER IF NOT EXISTS history_sync_update AFTER UPDATE ON history
+        WHEN new.uuid IS NOT NULL AND (
+            old.uuid IS NOT new.uuid OR old.qr_type IS NOT new.qr_type
+            OR old.label IS NOT new.label OR old.style_json IS NOT new.style_json
+            OR old.thumbnail_hash IS NOT new.thumbnail_hash OR old.pinned IS NOT new.pinned
+            OR old.deleted_at IS NOT new.deleted_at
+            -- Encrypting or decrypting swaps both; an edit changes content alone
+            OR (old.content IS NOT new.content AND old.content_cipher IS new.content_cipher)
+        ) BEGIN {history} END;
+
+        CREATE TRIGGER IF NOT EXISTS history_sync_delete AFTER DELETE ON history
+        WHEN old.uuid IS NOT NULL BEGIN {history_deleted} END;
+
+        CREATE TRIGGER IF NOT EXISTS history_tags_sync_insert AFTER INSERT ON history_tags
+        WHEN EXISTS (SELECT 1 FROM history WHERE id = new.history_id AND uuid IS NOT NULL)
+        BEGIN {history_tagged} END;
+
+        CREATE TRIGGER IF NOT EXISTS history_tags_sync_delete AFTER DELETE ON history_tags
+        WHEN EXISTS (SELECT 1 FROM history WHERE id = old.history_id AND uuid IS NOT NULL)
+        BEGIN {history_untagged} END;
+
+        CREATE TRIGGER IF NOT EXISTS templates_sync_insert AFTER INSERT ON templates
+        WHEN new.uuid IS NOT NULL BEGIN {template} END;
+
+        CREATE TRIGGER IF NOT EXISTS templates_sync_update AFTER UPDATE ON templates
+        WHEN new.uuid IS NOT NULL AND (
+            old.uuid IS NOT new.uuid OR old.name IS NOT new.name
+            OR old.style_json IS NOT new.style_json OR old.preview_hash IS NOT new.preview_hash
+            OR old.is_default IS NOT new.is_default OR old.deleted_at IS NOT new.deleted_at
+        ) BEGIN {template} END;
+
+        CREATE TRIGGER IF NOT EXISTS templates_sync_delete AFTER DELETE ON templates
+        WHEN old.uuid IS NOT NULL BEGIN {template_deleted} END;
+
+        CREATE TRIGGER IF NOT EXISTS tags_sync_insert AFTER INSERT ON tags
+        WHEN new.uuid IS NOT NULL BEGIN {tag} END;
+
+        CREATE TRIGGER IF NOT EXISTS tags_sync_update AFTER UPDATE ON tags
+        WHEN new.uuid IS NOT NULL AND (old.uuid IS NOT new.uuid OR old.name IS NOT new.name)
+        BEGIN {tag} END;
+
+        CREATE TRIGGER IF NOT EXISTS tags_sync_delete AFTER DELETE ON tags
+        WHEN old.uuid IS NOT NULL BEGIN {tag_deleted} END;
+        "#
+    ))?;
+
+    // Everything saved so far goes out with the first sync
+    conn.execute_batch(
+        r#"
+        INSERT OR IGNORE INTO sync_rows (entity, uuid, modified_at)
+        SELECT 'history', uuid,
+               strftime('%Y-%m-%dT%H:%M:%fZ', coalesce(updated_at, created_at, 'now'))
+        FROM history;
+
+        INSERT OR IGNORE INTO sync_rows (entity, uuid, modified_at)
+        SELECT 'template', uuid, strftime('%Y-%m-%dT%H:%M:%fZ', coalesce(created_at, 'now'))
+        FROM templates;
+
+        INSERT OR IGNORE INTO sync_rows (entity, uuid, modified_at)
+        SELECT 'tag', uuid, strftime('%Y-%m-%dT%H:%M:%fZ', 'now') FROM tags;
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn add_history_dedupe(conn: &Connection) -> Result<(), rusqlite::Error> {
+    add_column_if_missing(conn, "history", "content_hash", "TEXT")?;
+    add_column_if_missing(conn, "history", "use_count", "INTEGER NOT NULL DEFAULT 1")?;
+
+    conn.execute_batch(
+        r#"
+        -- Saves with the same type, content and style share a hash
+        CREATE INDEX IF NOT EXISTS idx_history_content_hash ON history(content_hash);
+        "#,
+    )?;
+
+    fill_content_hashes(conn)?;
+
+    Ok(())
+}
+
+fn add_scan_encryption(conn: &Connection) -> Result<(), rusqlite::Error> {
+    add_column_if_missing(conn, "scans", "sensitive", "INTEGER NOT NULL DEFAULT 0")?;
+    // Encrypted content, metadata and thumbnail; the row keeps only a
+    // redacted copy of the content
+    add_column_if_missing(conn, "scans", "content_cipher", "BLOB")?;
+
+    // Flag WiFi and OTP scans so they are redacted and can be encrypted
+    mark_sensitive_scans(conn)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        get_blob, list_history_page, list_templates, search_history, HistoryFilter, HistorySort,
+    };
+
+    /// Schemas written by releases before `user_version` was tracked, oldest first
+    const FIXTURES: &[(&str, &str)] = &[
+        ("baseline", include_str!("fixtures/schema_0_baseline.sql")),
+        ("scans", include_str!("fixtures/schema_1_scans.sql")),
+        ("fts", include_str!("fixtures/schema_2_fts.sql")),
+        ("organize", include_str!("fixtures/schema_3_organize.sql")),
+        ("trash", include_str!("fixtures/schema_4_trash.sql")),
+        ("blobs", include_str!("fixtures/schema_5_blobs.sql")),
+    ];
+
+    const PNG_URL: &str = "data:image/png;base64,iVBORw0KGgo=";
+
+    fn fresh_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+        conn
+    }
+
+    fn fixture_db(sql: &str) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(sql).unwrap();
+        conn
+    }
+
+    /// Every column, index and trigger, independent of column order
+    fn schema_signature(conn: &Connection) -> Vec<String> {
+        let mut signature: Vec<String> = conn
+            .prepare(
+                r#"
+                SELECT m.name || '.' || c.name || ' ' || c.type || ' ' || c."notnull"
+                       || ' ' || coalesce(c.dflt_value, '') || ' ' || c.pk
+                FROM sqlite_master m, pragma_table_info(m.name) c
+                WHERE m.type = 'table'
+                UNION ALL
+                SELECT type || ' ' || name FROM sqlite_master
+                WHERE type IN ('index', 'trigger') AND name NOT LIKE 'sqlite_autoindex%'
+                "#,
+            )
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        signature.sort();
+        signature
+    }
+
+    #[test]
+    fn test_fresh_database_is_latest() {
+        let conn = fresh_db();
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+
+        let report = migrate(&conn).unwrap();
+        assert_eq!(report.from, report.to);
+    }
+
+    #[test]
+    fn test_versions_are_ordered() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as i64 + 1, "{}", migration.description);
+        }
+    }
+
+    #[test]
+    fn test_upgrades_every_past_schema() {
+        let expected = schema_signature(&fresh_db());
+
+        for (name, sql) in FIXTURES {
+            let conn = fixture_db(sql);
+            let report = migrate(&conn).unwrap();
+            assert_eq!(report.from, 0, "{}", name);
+            assert_eq!(report.to, latest_version(), "{}", name);
+            assert_eq!(schema_signature(&conn), expected, "{}", name);
+
+            // Data survives, stays searchable and loses its inline images
+            let page = list_history_page(
+                &conn,
+                &HistoryFilter::default(),
+                HistorySort::CreatedAsc,
+                None,
+                10,
+                0,
+            )
+            .unwrap();
+            assert_eq!(page.items.len(), 2, "{}", name);
+            assert_eq!(
+                page.items[0].label.as_deref(),
+                Some("Cafe menu"),
+                "{}",
+                name
+            );
+            assert_eq!(
+                search_history(&conn, "menu", 10, 0).unwrap().len(),
+                1,
+                "{}",
+                name
+            );
+
+            let thumbnail = page.items[0].thumbnail_ref.as_deref().unwrap();
+            let template = &list_templates(&conn).unwrap()[0];
+            assert_eq!(template.preview_ref.as_deref(), Some(thumbnail), "{}", name);
+            assert_eq!(
+                get_blob(&conn, thumbnail).unwrap().unwrap().to_data_url(),
+                PNG_URL,
+                "{}",
+                name
+            );
+
+            let settings: i64 = conn
+                .query_row("SELECT COUNT(*) FROM settings", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(settings, 1, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_upgrades_from_every_version() {
+        let expected = schema_signature(&fresh_db());
+
+        for version in 1..latest_version() {
+            let conn = Connection::open_in_memory().unwrap();
+            let report = run_migrations(&conn, &MIGRATIONS[..version as usize]).unwrap();
+            assert_eq!(report.to, version);
+
+            let report = migrate(&conn).unwrap();
+            assert_eq!(report.from, version);
+            assert_eq!(schema_signature(&conn), expected, "from v{}", version);
+        }
+    }
+
+    #[test]
+    fn test_rejects_newer_database() {
+        let conn = fresh_db();
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .unwrap();
+
+        match migrate(&conn) {
+            Err(MigrationError::NewerVersion { found, supported }) => {
+                assert_eq!(found, latest_version() + 1);
+                assert_eq!(supported, latest_version());
+            }
+            other => panic!("expected NewerVersion, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        fn broken(conn: &Connection) -> Result<(), rusqlite::Error> {
+            conn.execute("CREATE TABLE half_done (id INTEGER)", [])?;
+            conn.execute("INSERT INTO missing_table VALUES (1)", [])?;
+            Ok(())
+        }
+
+        let conn = Connection::open_in_memory().unwrap();
+        let migrations = [
+            Migration {
+                version: 1,
+                description: "base",
+                up: create_base_tables,
+            },
+            Migration {
+                version: 2,
+                description: "broken",
+                up: broken,
+            },
+        ];
+
+        match run_migrations(&conn, &migrations) {
+            Err(MigrationError::Failed { version, .. }) => assert_eq!(version, 2),
+            other => panic!("expected Failed, got {:?}", other),
+        }
+        assert_eq!(schema_version(&conn).unwrap(), 1);
+
+        let half_done: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'half_done')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!half_done);
+    }
+
+    #[test]
+    fn test_backs_up_before_migrating() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("qr-foundry.db");
+
+        // A brand new file has nothing worth backing up
+        let conn = Connection::open(&path).unwrap();
+        assert_eq!(migrate(&conn).unwrap().backup, None);
+        drop(conn);
+        std::fs::remove_file(&path).unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(FIXTURES[0].1).unwrap();
+        let report = migrate(&conn).unwrap();
+
+        let backup = report.backup.unwrap();
+        assert_eq!(backup, dir.path().join("qr-foundry.db.v0.bak"));
+
+        let copy = Connection::open(&backup).unwrap();
+        assert_eq!(schema_version(&copy).unwrap(), 0);
+        let thumbnail: Option<String> = copy
+            .query_row("SELECT thumbnail FROM history WHERE id = 1", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(thumbnail.as_deref(), Some(PNG_URL));
+    }
+
+    #[test]
+    fn test_drops_backup_once_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("qr-foundry.db");
+        let conn = Connection::open(&path).unwrap();
+        run_migrations(&conn, &MIGRATIONS[..9]).unwrap();
+        conn.execute(
+            "INSERT INTO encryption (id, key_source, scope, key_check) VALUES (1, 'keyfile', 'sensitive', x'00')",
+            [],
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("qr-foundry.db.v3.bak"), "").unwrap();
+
+        // The copy would hold secrets in the clear, so none is kept
+        let report = migrate(&conn).unwrap();
+        assert_eq!(report.from, 9);
+        assert_eq!(report.backup, None);
+        let left: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".bak"))
+            .collect();
+        assert!(left.is_empty());
+    }
+}
@@ -1,80 +1,311 @@
-use rusqlite::{Connection, Result};
-use std::path::PathBuf;
-use std::sync::Mutex;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
 
+pub mod backup;
+pub mod blobs;
+pub mod dedupe;
+pub mod encryption;
+pub mod folders;
 pub mod history;
+pub mod library;
+pub mod migrations;
+pub mod pool;
+pub mod retention;
+pub mod scans;
+pub mod search;
+pub mod settings;
+pub mod storage;
+pub mod sync;
+pub mod tags;
 pub mod templates;
+pub mod trash;
+pub mod workspaces;
 
+pub use backup::*;
+pub use blobs::*;
+pub use dedupe::*;
+pub use encryption::*;
+pub use folders::*;
 pub use history::*;
+pub use library::*;
+pub use migrations::*;
+pub use pool::*;
+pub use retention::*;
+pub use scans::*;
+pub use search::*;
+pub use settings::*;
+pub use storage::*;
+pub use sync::*;
+pub use tags::*;
 pub use templates::*;
+pub use trash::*;
+pub use workspaces::*;
 
-/// Get the database path in the app data directory
-pub fn get_db_path() -> PathBuf {
-    let data_dir = dirs::data_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("com.jonathanlam.qr-foundry");
+/// An open workspace: its connection pool and where its files live
+pub struct Workspace {
+    pub name: String,
+    pub data_dir: PathBuf,
+    pub paths: WorkspacePaths,
+    pool: ConnectionPool,
+    key: Mutex<Option<EncryptionKey>>, // history encryption key, once unlocked
+    encryption_error: Mutex<Option<String>>, // set when opening left secrets unencrypted
+    auto_backup_error: Mutex<Option<String>>, // set when the backup due on opening failed
+}
+
+impl Workspace {
+    /// Open a workspace, creating and upgrading its database as needed
+    pub fn open(data_dir: &Path, name: &str) -> Result<Self, MigrationError> {
+        let paths = workspace_paths(data_dir, name);
+        if let Some(dir) = paths.db.parent() {
+            // Create directory if it doesn't exist
+            std::fs::create_dir_all(dir).ok();
+        }
+        let pool = ConnectionPool::open(&paths.db, DEFAULT_READERS)?;
+
+        let (key, encryption_error, auto_backup_error) = {
+            let conn = pool.writer();
+
+            // Initialize schema, upgrading databases from older versions
+            init_schema(&conn)?;
+            let settings = get_settings(&conn)?;
+
+            // History encrypted with a keyfile unlocks without asking. Rows
+            // saved or migrated while locked get encrypted before any backup
+            // is taken.
+            let (key, encryption_error) = unlock_on_open(&conn, &paths.keyfile, &paths.backups);
+
+            // Keep a rolling set of daily backups; a failed backup shouldn't
+            // stop the app from starting, so it's reported with the backups
+            let auto_backup_error = create_automatic_backup_if_due(
+                &conn,
+                &paths.backups,
+                settings.auto_backup_keep as usize,
+            )
+            .err()
+            .map(|e| format!("Automatic backup failed: {}", e));
+
+            // Drop items that have sat in the trash past the retention period,
+            // then anything outside the history retention policy
+            purge_expired_trash(&conn, settings.trash_retention_days)?;
+            enforce_retention(&conn, &settings.retention_policy)?;
+
+            (key, encryption_error, auto_backup_error)
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            data_dir: data_dir.to_path_buf(),
+            paths,
+            pool,
+            key: Mutex::new(key),
+            encryption_error: Mutex::new(encryption_error),
+            auto_backup_error: Mutex::new(auto_backup_error),
+        })
+    }
+
+    /// The history encryption key, if unlocked this session
+    pub fn key(&self) -> Option<EncryptionKey> {
+        self.key.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Remember an unlocked key, or forget it with `None`
+    pub fn set_key(&self, key: Option<EncryptionKey>) {
+        *self.key.lock().unwrap_or_else(|e| e.into_inner()) = key;
+    }
+
+    /// Why secrets may still be unencrypted on disk, until a later unlock,
+    /// enable or disable deals with them
+    pub fn encryption_error(&self) -> Option<String> {
+        self.encryption_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn set_encryption_error(&self, error: Option<String>) {
+        *self
+            .encryption_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = error;
+    }
+
+    /// Why the automatic backup due on opening wasn't made, until a later
+    /// backup succeeds
+    pub fn auto_backup_error(&self) -> Option<String> {
+        self.auto_backup_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn set_auto_backup_error(&self, error: Option<String>) {
+        *self
+            .auto_backup_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = error;
+    }
 
-    // Create directory if it doesn't exist
-    std::fs::create_dir_all(&data_dir).ok();
+    /// Run `f` on a pooled read-only connection
+    ///
+    /// Reads see the last committed state and don't wait on each other or on
+    /// a write in progress.
+    pub async fn read<T, F>(self: Arc<Self>, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&Connection) -> Result<T, String> + Send + 'static,
+        T: Send + 'static,
+    {
+        tauri::async_runtime::spawn_blocking(move || {
+            let conn = self
+                .pool
+                .reader()
+                .map_err(|e| format!("Failed to open database: {}", e))?;
+            f(&conn)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
 
-    data_dir.join("qr-foundry.db")
+    /// Run `f` on the writer, after any other write has finished
+    pub async fn write<T, F>(self: Arc<Self>, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&mut Connection) -> Result<T, String> + Send + 'static,
+        T: Send + 'static,
+    {
+        tauri::async_runtime::spawn_blocking(move || f(&mut self.pool.writer()))
+            .await
+            .map_err(|e| e.to_string())?
+    }
 }
 
 /// Database state managed by Tauri
+///
+/// Holds the current workspace, which can be swapped while the app runs.
+/// Commands run their queries through `read` and `write`, which hop onto a
+/// blocking thread so SQLite never stalls the async runtime.
 pub struct DbState {
-    pub conn: Mutex<Connection>,
+    home: PathBuf,
+    current: RwLock<Arc<Workspace>>,
+    data_dir_error: Mutex<Option<String>>, // why the configured data dir isn't in use
+}
+
+/// Open the workspace named in the config, or the default one if it's gone
+fn open_last_used(data_dir: &Path, config: &AppConfig) -> Result<Workspace, MigrationError> {
+    let name = config
+        .workspace
+        .clone()
+        .filter(|name| workspace_paths(data_dir, name).db.is_file())
+        .unwrap_or_else(|| DEFAULT_WORKSPACE.to_string());
+
+    Workspace::open(data_dir, &name)
 }
 
 impl DbState {
-    pub fn new() -> Result<Self> {
-        let db_path = get_db_path();
-        let conn = Connection::open(&db_path)?;
+    pub fn new() -> Result<Self, MigrationError> {
+        Self::open(home_dir())
+    }
+
+    /// Open the workspace last used, per the config in `home`
+    ///
+    /// A moved data dir may sit on a drive that isn't mounted. Then the home
+    /// dir is used for this run and the config is left alone, so the next
+    /// launch tries the data dir again.
+    pub fn open(home: PathBuf) -> Result<Self, MigrationError> {
+        let config = load_config(&home);
+        let data_dir = data_dir(&home, &config);
 
-        // Initialize schema
-        init_schema(&conn)?;
+        let mut data_dir_error = None;
+        let workspace = if data_dir == home {
+            open_last_used(&home, &config)?
+        } else {
+            let opened = if data_dir.is_dir() {
+                open_last_used(&data_dir, &config).map_err(|e| e.to_string())
+            } else {
+                Err("directory not found".to_string())
+            };
+            match opened {
+                Ok(workspace) => workspace,
+                Err(e) => {
+                    // Reported to the UI through the workspace list
+                    data_dir_error = Some(format!(
+                        "Data directory {} is unavailable ({}); using {} instead",
+                        data_dir.display(),
+                        e,
+                        home.display()
+                    ));
+                    open_last_used(&home, &config)?
+                }
+            }
+        };
 
         Ok(Self {
-            conn: Mutex::new(conn),
+            home,
+            current: RwLock::new(Arc::new(workspace)),
+            data_dir_error: Mutex::new(data_dir_error),
         })
     }
-}
 
-/// Initialize the database schema
-fn init_schema(conn: &Connection) -> Result<()> {
-    conn.execute_batch(
-        r#"
-        -- History of generated QR codes
-        CREATE TABLE IF NOT EXISTS history (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            content TEXT NOT NULL,
-            qr_type TEXT NOT NULL,
-            label TEXT,
-            style_json TEXT NOT NULL,
-            thumbnail TEXT,
-            created_at TEXT DEFAULT (datetime('now')),
-            updated_at TEXT DEFAULT (datetime('now'))
-        );
-
-        -- Create index on created_at for faster sorting
-        CREATE INDEX IF NOT EXISTS idx_history_created_at ON history(created_at DESC);
-
-        -- Saved style templates
-        CREATE TABLE IF NOT EXISTS templates (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            style_json TEXT NOT NULL,
-            preview TEXT,
-            is_default INTEGER DEFAULT 0,
-            created_at TEXT DEFAULT (datetime('now'))
-        );
-
-        -- App settings
-        CREATE TABLE IF NOT EXISTS settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL
-        );
-        "#,
-    )?;
+    /// Directory holding the config
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    /// Set when startup fell back to the home dir, until another workspace
+    /// is activated
+    pub fn data_dir_error(&self) -> Option<String> {
+        self.data_dir_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn workspace(&self) -> Arc<Workspace> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Make an opened workspace current and remember it for next launch
+    ///
+    /// Queries already running finish against the previous workspace.
+    pub fn activate(&self, workspace: Workspace) -> std::io::Result<Arc<Workspace>> {
+        let config = AppConfig {
+            data_dir: (workspace.data_dir != self.home).then(|| workspace.data_dir.clone()),
+            workspace: (workspace.name != DEFAULT_WORKSPACE).then(|| workspace.name.clone()),
+        };
+        save_config(&self.home, &config)?;
+        self.data_dir_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+
+        let workspace = Arc::new(workspace);
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = workspace.clone();
+        Ok(workspace)
+    }
+
+    /// Run `f` on a read-only connection to the current workspace
+    pub async fn read<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&Connection) -> Result<T, String> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.workspace().read(f).await
+    }
+
+    /// Run `f` on the current workspace's writer
+    pub async fn write<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&mut Connection) -> Result<T, String> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.workspace().write(f).await
+    }
+}
 
+/// Initialize the database schema, applying any pending migrations
+pub fn init_schema(conn: &Connection) -> Result<(), MigrationError> {
+    migrate(conn)?;
     Ok(())
 }
@@ -0,0 +1,233 @@
+use rusqlite::{Connection, OpenFlags};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Read-only connections opened alongside the writer
+pub const DEFAULT_READERS: usize = 4;
+
+/// How long a connection waits on a locked database before giving up
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Readers {
+    idle: Vec<Connection>,
+    open: usize,
+}
+
+/// One writer and a bounded set of readers over a WAL-mode database file
+///
+/// WAL lets readers run alongside each other and alongside the writer, each
+/// seeing the last committed state. Writes go through the single writer so
+/// transactions never race to upgrade their locks. Both kinds of connection
+/// block the calling thread, so async code should take them from a blocking
+/// task.
+pub struct ConnectionPool {
+    path: PathBuf,
+    writer: Mutex<Connection>,
+    readers: Mutex<Readers>,
+    reader_returned: Condvar,
+    max_readers: usize,
+}
+
+impl ConnectionPool {
+    /// Open the writer, switching the file to WAL mode; readers open lazily
+    pub fn open(path: &Path, max_readers: usize) -> Result<Self, rusqlite::Error> {
+        let writer = Connection::open(path)?;
+        writer.busy_timeout(BUSY_TIMEOUT)?;
+        writer
+            .pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+        // Safe with WAL: a power loss can drop the last commits, not corrupt
+        writer.pragma_update(None, "synchronous", "NORMAL")?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: Mutex::new(writer),
+            readers: Mutex::new(Readers {
+                idle: Vec::new(),
+                open: 0,
+            }),
+            reader_returned: Condvar::new(),
+            max_readers: max_readers.max(1),
+        })
+    }
+
+    /// Take the writer, waiting for any write in progress to finish
+    pub fn writer(&self) -> MutexGuard<'_, Connection> {
+        // A panic mid-write rolls its transaction back, so the connection
+        // is still usable
+        self.writer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Take an idle reader, opening one if under the limit or else waiting
+    pub fn reader(&self) -> Result<PooledReader<'_>, rusqlite::Error> {
+        let mut readers = self.readers.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if let Some(conn) = readers.idle.pop() {
+                return Ok(PooledReader {
+                    pool: self,
+                    conn: Some(conn),
+                });
+            }
+            if readers.open < self.max_readers {
+                readers.open += 1;
+                drop(readers);
+                return match self.open_reader() {
+                    Ok(conn) => Ok(PooledReader {
+                        pool: self,
+                        conn: Some(conn),
+                    }),
+                    Err(e) => {
+                        self.release_slot();
+                        Err(e)
+                    }
+                };
+            }
+            readers = self
+                .reader_returned
+                .wait(readers)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    fn open_reader(&self) -> Result<Connection, rusqlite::Error> {
+        let conn = Connection::open_with_flags(
+            &self.path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+                | OpenFlags::SQLITE_OPEN_URI
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        Ok(conn)
+    }
+
+    fn release_slot(&self) {
+        let mut readers = self.readers.lock().unwrap_or_else(|e| e.into_inner());
+        readers.open -= 1;
+        self.reader_returned.notify_one();
+    }
+
+    fn give_back(&self, conn: Connection) {
+        // Don't pool a connection left inside a transaction
+        if !conn.is_autocommit() {
+            drop(conn);
+            self.release_slot();
+            return;
+        }
+        let mut readers = self.readers.lock().unwrap_or_else(|e| e.into_inner());
+        readers.idle.push(conn);
+        self.reader_returned.notify_one();
+    }
+}
+
+/// A reader on loan from the pool, returned when dropped
+pub struct PooledReader<'a> {
+    pool: &'a ConnectionPool,
+    conn: Option<Connection>,
+}
+
+impl std::ops::Deref for PooledReader<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("reader already returned")
+    }
+}
+
+impl Drop for PooledReader<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.give_back(conn);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn open_pool(dir: &tempfile::TempDir, max_readers: usize) -> ConnectionPool {
+        let pool = ConnectionPool::open(&dir.path().join("test.db"), max_readers).unwrap();
+        pool.writer()
+            .execute_batch("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)")
+            .unwrap();
+        pool
+    }
+
+    fn count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_uses_wal() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = open_pool(&dir, 2);
+
+        let mode: String = pool
+            .reader()
+            .unwrap()
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+    }
+
+    #[test]
+    fn test_readers_run_alongside_a_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = open_pool(&dir, 2);
+        pool.writer()
+            .execute("INSERT INTO items (name) VALUES ('a')", [])
+            .unwrap();
+
+        let writer = pool.writer();
+        let tx = writer.unchecked_transaction().unwrap();
+        tx.execute("INSERT INTO items (name) VALUES ('b')", [])
+            .unwrap();
+
+        // Both readers see the last commit while the write is open
+        let first = pool.reader().unwrap();
+        let second = pool.reader().unwrap();
+        assert_eq!(count(&first), 1);
+        assert_eq!(count(&second), 1);
+
+        tx.commit().unwrap();
+        assert_eq!(count(&first), 2);
+    }
+
+    #[test]
+    fn test_readers_are_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = open_pool(&dir, 1);
+
+        let reader = pool.reader().unwrap();
+        assert!(reader
+            .execute("INSERT INTO items (name) VALUES ('x')", [])
+            .is_err());
+    }
+
+    #[test]
+    fn test_reader_limit_waits_for_a_return() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = Arc::new(open_pool(&dir, 1));
+
+        let held = pool.reader().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let waiting = {
+            let pool = pool.clone();
+            thread::spawn(move || {
+                let reader = pool.reader().unwrap();
+                sender.send(count(&reader)).unwrap();
+            })
+        };
+
+        assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+        drop(held);
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)).unwrap(), 0);
+        waiting.join().unwrap();
+
+        assert_eq!(pool.readers.lock().unwrap().open, 1);
+    }
+}
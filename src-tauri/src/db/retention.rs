@@ -0,0 +1,351 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::storage::used_bytes;
+
+/// Rows that automatic pruning may delete: not pinned and not tagged
+const PRUNABLE: &str = "pinned = 0 AND NOT EXISTS \
+     (SELECT 1 FROM history_tags ht WHERE ht.history_id = history.id)";
+
+/// Limits applied to history; `None` means unlimited
+///
+/// Pinned and tagged items are never pruned, and don't count towards
+/// `max_items`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionPolicy {
+    pub max_items: Option<i64>,
+    pub max_age_days: Option<i64>,
+    pub max_db_bytes: Option<i64>,
+}
+
+impl RetentionPolicy {
+    /// Check that every set limit is positive
+    pub fn validate(&self) -> Result<(), String> {
+        let limits = [
+            ("Maximum items", self.max_items),
+            ("Maximum age", self.max_age_days),
+            ("Maximum database size", self.max_db_bytes),
+        ];
+        for (name, limit) in limits {
+            if limit.is_some_and(|limit| limit < 1) {
+                return Err(format!("{} must be at least 1", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionReport {
+    pub by_age: i64,
+    pub by_count: i64,
+    pub by_size: i64,
+}
+
+/// Delete unprotected history that falls outside the policy, oldest first
+///
+/// Age and count limits look at live items by last update. The size limit
+/// removes trashed items before live ones until the estimated payload freed
+/// covers the overage; run a VACUUM afterwards to shrink the file itself.
+pub fn enforce_retention(
+    conn: &Connection,
+    policy: &RetentionPolicy,
+) -> Result<RetentionReport, rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    let mut report = RetentionReport::default();
+
+    if let Some(days) = policy.max_age_days.filter(|days| *days > 0) {
+        let ids = select_ids(
+            &tx,
+            &format!(
+                "SELECT id FROM history WHERE {} AND updated_at < datetime('now', ?1)",
+                PRUNABLE
+            ),
+            params![format!("-{} days", days)],
+        )?;
+        report.by_age = prune(&tx, &ids)?;
+    }
+
+    if let Some(max_items) = policy.max_items.filter(|max| *max > 0) {
+        let live: i64 = tx.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM history WHERE deleted_at IS NULL AND {}",
+                PRUNABLE
+            ),
+            [],
+            |row| row.get(0),
+        )?;
+        if live > max_items {
+            let ids = select_ids(
+                &tx,
+                &format!(
+                    r#"
+                    SELECT id FROM history WHERE deleted_at IS NULL AND {}
+                    ORDER BY updated_at, id LIMIT ?1
+                    "#,
+                    PRUNABLE
+                ),
+                params![live - max_items],
+            )?;
+            report.by_count = prune(&tx, &ids)?;
+        }
+    }
+
+    if let Some(max_bytes) = policy.max_db_bytes.filter(|max| *max > 0) {
+        let excess = used_bytes(&tx)? - max_bytes;
+        if excess > 0 {
+            report.by_size = prune_bytes(&tx, excess)?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(report)
+}
+
+/// Delete prunable rows until their estimated size covers `bytes`
+fn prune_bytes(conn: &Connection, bytes: i64) -> Result<i64, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT id, 64 + length(content) + length(style_json)
+                  + coalesce(length(label), 0)
+                  + coalesce((SELECT length(data) FROM blobs WHERE hash = thumbnail_hash), 0)
+        FROM history
+        WHERE {}
+        ORDER BY deleted_at IS NULL, updated_at, id
+        "#,
+        PRUNABLE
+    ))?;
+    let mut rows = stmt.query([])?;
+
+    let mut ids = Vec::new();
+    let mut freed = 0;
+    while freed < bytes {
+        let Some(row) = rows.next()? else {
+            break;
+        };
+        ids.push(row.get::<_, i64>(0)?);
+        freed += row.get::<_, i64>(1)?;
+    }
+
+    prune(conn, &ids)
+}
+
+fn select_ids(
+    conn: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> Result<Vec<i64>, rusqlite::Error> {
+    conn.prepare(sql)?
+        .query_map(params, |row| row.get(0))?
+        .collect()
+}
+
+/// Delete history rows on this device only
+///
+/// Retention is a per-device policy, so a pruned row must not reach other
+/// devices as a deletion. The delete trigger marks it as changed; its sync
+/// entry is put back as it was, or dropped if the row was never sent.
+fn prune(conn: &Connection, ids: &[i64]) -> Result<i64, rusqlite::Error> {
+    let mut pruned = 0;
+    for id in ids {
+        let uuid: Option<String> = conn
+            .query_row(
+                "SELECT uuid FROM history WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        let sent: Option<(String, Option<String>)> = match &uuid {
+            Some(uuid) => conn
+                .query_row(
+                    r#"
+                    SELECT modified_at, device_id FROM sync_rows
+                    WHERE entity = 'history' AND uuid = ?1 AND pending = 0
+                    "#,
+                    params![uuid],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?,
+            None => None,
+        };
+
+        pruned += conn.execute("DELETE FROM history WHERE id = ?1", params![id])? as i64;
+
+        let Some(uuid) = uuid else {
+            continue;
+        };
+        match sent {
+            Some((modified_at, device_id)) => conn.execute(
+                r#"
+                UPDATE sync_rows SET modified_at = ?1, device_id = ?2, pending = 0
+                WHERE entity = 'history' AND uuid = ?3
+                "#,
+                params![modified_at, device_id, uuid],
+            )?,
+            None => conn.execute(
+                "DELETE FROM sync_rows WHERE entity = 'history' AND uuid = ?1",
+                params![uuid],
+            )?,
+        };
+    }
+    Ok(pruned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        add_history_tags, count_filtered_history, delete_history, get_settings, init_schema,
+        save_history, set_history_pinned, update_settings, HistoryFilter, NewHistoryItem,
+    };
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn save(conn: &Connection, content: &str, updated_at: &str) -> i64 {
+        let id = save_history(
+            conn,
+            &NewHistoryItem {
+                content: content.to_string(),
+                qr_type: "text".to_string(),
+                label: None,
+                style_json: "{}".to_string(),
+                thumbnail: Some(format!("{}{}", content, "x".repeat(20_000))),
+            },
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE history SET updated_at = ?1 WHERE id = ?2",
+            params![updated_at, id],
+        )
+        .unwrap();
+        id
+    }
+
+    fn exists(conn: &Connection, id: i64) -> bool {
+        conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM history WHERE id = ?1)",
+            params![id],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_policy_round_trip() {
+        let conn = setup_test_db();
+        assert_eq!(
+            get_settings(&conn).unwrap().retention_policy,
+            RetentionPolicy::default()
+        );
+
+        let policy = RetentionPolicy {
+            max_items: Some(500),
+            max_age_days: None,
+            max_db_bytes: Some(50 * 1024 * 1024),
+        };
+        let mut patch = serde_json::Map::new();
+        patch.insert(
+            "retentionPolicy".to_string(),
+            serde_json::to_value(&policy).unwrap(),
+        );
+        update_settings(&conn, &patch).unwrap();
+        assert_eq!(get_settings(&conn).unwrap().retention_policy, policy);
+    }
+
+    #[test]
+    fn test_validate_policy() {
+        assert!(RetentionPolicy::default().validate().is_ok());
+        assert!(RetentionPolicy {
+            max_items: Some(100),
+            max_age_days: Some(90),
+            max_db_bytes: Some(1 << 30),
+        }
+        .validate()
+        .is_ok());
+
+        let err = RetentionPolicy {
+            max_items: Some(0),
+            ..Default::default()
+        }
+        .validate()
+        .unwrap_err();
+        assert!(err.contains("Maximum items"));
+    }
+
+    #[test]
+    fn test_max_age_spares_pinned_and_tagged() {
+        let conn = setup_test_db();
+        let old = save(&conn, "old", "2000-01-01 00:00:00");
+        let pinned = save(&conn, "pinned", "2000-01-01 00:00:00");
+        let tagged = save(&conn, "tagged", "2000-01-01 00:00:00");
+        let fresh = save(&conn, "fresh", "2999-01-01 00:00:00");
+        set_history_pinned(&conn, &[pinned], true).unwrap();
+        add_history_tags(&conn, &[tagged], &["keep".to_string()]).unwrap();
+
+        let policy = RetentionPolicy {
+            max_age_days: Some(30),
+            ..Default::default()
+        };
+        let report = enforce_retention(&conn, &policy).unwrap();
+
+        assert_eq!(report.by_age, 1);
+        assert!(!exists(&conn, old));
+        assert!(exists(&conn, pinned) && exists(&conn, tagged) && exists(&conn, fresh));
+    }
+
+    #[test]
+    fn test_max_items_removes_oldest() {
+        let conn = setup_test_db();
+        let oldest = save(&conn, "a", "2026-01-01 00:00:00");
+        let older = save(&conn, "b", "2026-01-02 00:00:00");
+        save(&conn, "c", "2026-01-03 00:00:00");
+        save(&conn, "d", "2026-01-04 00:00:00");
+        let pinned = save(&conn, "e", "2025-01-01 00:00:00");
+        set_history_pinned(&conn, &[pinned], true).unwrap();
+
+        let policy = RetentionPolicy {
+            max_items: Some(2),
+            ..Default::default()
+        };
+        let report = enforce_retention(&conn, &policy).unwrap();
+
+        assert_eq!(report.by_count, 2);
+        assert!(!exists(&conn, oldest) && !exists(&conn, older));
+        assert!(exists(&conn, pinned));
+        assert_eq!(
+            count_filtered_history(&conn, &HistoryFilter::default()).unwrap(),
+            3
+        );
+
+        // Already within limits: nothing more to do
+        assert_eq!(enforce_retention(&conn, &policy).unwrap().by_count, 0);
+    }
+
+    #[test]
+    fn test_max_size_prunes_trash_first() {
+        let conn = setup_test_db();
+        let trashed = save(&conn, "trashed", "2026-06-01 00:00:00");
+        delete_history(&conn, trashed).unwrap();
+        let oldest = save(&conn, "oldest", "2026-01-01 00:00:00");
+        let newest = save(&conn, "newest", "2026-02-01 00:00:00");
+
+        // Just over the limit by less than one thumbnail
+        let limit = used_bytes(&conn).unwrap() - 10_000;
+        let policy = RetentionPolicy {
+            max_db_bytes: Some(limit),
+            ..Default::default()
+        };
+        let report = enforce_retention(&conn, &policy).unwrap();
+
+        assert_eq!(report.by_size, 1);
+        assert!(!exists(&conn, trashed));
+        assert!(exists(&conn, oldest) && exists(&conn, newest));
+    }
+}
@@ -0,0 +1,527 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::encryption::{
+    is_sensitive_content, redact_content, save_history_encrypted, seal_content, EncryptionError,
+    EncryptionKey,
+};
+use super::history::NewHistoryItem;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanRecord {
+    pub id: i64,
+    pub source: String, // "file" | "clipboard" | "data" | "folder" | "pdf"
+    pub source_path: Option<String>,
+    pub content: String,
+    pub qr_type: String,
+    pub metadata_json: Option<String>,
+    pub thumbnail: Option<String>,
+    pub created_at: String,
+    pub sensitive: bool, // WiFi or OTP; `content` is redacted, metadata left out
+    pub encrypted: bool, // full scan needs the encryption key
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewScanRecord {
+    pub source: String,
+    pub source_path: Option<String>,
+    pub content: String,
+    pub qr_type: String,
+    pub metadata_json: Option<String>,
+    pub thumbnail: Option<String>,
+}
+
+/// What an encrypted scan keeps in `content_cipher`
+///
+/// Metadata repeats the decoded payload and the thumbnail shows the code
+/// itself, so both are sealed along with the content.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanSecret {
+    content: String,
+    metadata_json: Option<String>,
+    thumbnail: Option<String>,
+}
+
+/// Encrypt a scan's content, metadata and thumbnail together
+pub(super) fn seal_scan(
+    key: &EncryptionKey,
+    content: &str,
+    metadata_json: Option<&str>,
+    thumbnail: Option<&str>,
+) -> Vec<u8> {
+    let secret = ScanSecret {
+        content: content.to_string(),
+        metadata_json: metadata_json.map(str::to_string),
+        thumbnail: thumbnail.map(str::to_string),
+    };
+    key.encrypt(&serde_json::to_vec(&secret).expect("strings serialize"))
+}
+
+/// Decrypt what `seal_scan` stored: content, metadata and thumbnail
+pub(super) fn open_scan(
+    key: &EncryptionKey,
+    sealed: &[u8],
+) -> Result<(String, Option<String>, Option<String>), EncryptionError> {
+    let secret: ScanSecret =
+        serde_json::from_slice(&key.decrypt(sealed)?).map_err(|_| EncryptionError::WrongKey)?;
+    Ok((secret.content, secret.metadata_json, secret.thumbnail))
+}
+
+const SCAN_COLUMNS: &str = "id, source, source_path, content, qr_type, metadata_json, thumbnail, \
+     created_at, sensitive, content_cipher";
+
+/// A scan as stored, and its ciphertext if encrypted
+fn stored_scan_from_row(row: &Row) -> Result<(ScanRecord, Option<Vec<u8>>), rusqlite::Error> {
+    let content_cipher: Option<Vec<u8>> = row.get(9)?;
+    let scan = ScanRecord {
+        id: row.get(0)?,
+        source: row.get(1)?,
+        source_path: row.get(2)?,
+        content: row.get(3)?,
+        qr_type: row.get(4)?,
+        metadata_json: row.get(5)?,
+        thumbnail: row.get(6)?,
+        created_at: row.get(7)?,
+        sensitive: row.get(8)?,
+        encrypted: content_cipher.is_some(),
+    };
+    Ok((scan, content_cipher))
+}
+
+fn scan_from_row(row: &Row) -> Result<ScanRecord, rusqlite::Error> {
+    let (mut scan, _) = stored_scan_from_row(row)?;
+    // Encrypted rows already store a redacted copy
+    if scan.sensitive && !scan.encrypted {
+        scan.content = redact_content(&scan.content);
+        scan.metadata_json = None;
+    }
+    Ok(scan)
+}
+
+/// List scans newest first, optionally matching content, type or source path
+pub fn list_scans(
+    conn: &Connection,
+    limit: i64,
+    offset: i64,
+    search: Option<&str>,
+) -> Result<Vec<ScanRecord>, rusqlite::Error> {
+    let query = format!(
+        r#"
+        SELECT {}
+        FROM scans
+        WHERE ?1 IS NULL OR content LIKE ?1 OR qr_type LIKE ?1 OR source_path LIKE ?1
+        ORDER BY created_at DESC, id DESC
+        LIMIT ?2 OFFSET ?3
+        "#,
+        SCAN_COLUMNS
+    );
+    let pattern = search.map(|term| format!("%{}%", term));
+
+    let mut stmt = conn.prepare(&query)?;
+    let items = stmt
+        .query_map(params![pattern, limit, offset], scan_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(items)
+}
+
+/// Count scans matching the same search as `list_scans`
+pub fn count_scans(conn: &Connection, search: Option<&str>) -> Result<i64, rusqlite::Error> {
+    let pattern = search.map(|term| format!("%{}%", term));
+    conn.query_row(
+        r#"
+        SELECT COUNT(*) FROM scans
+        WHERE ?1 IS NULL OR content LIKE ?1 OR qr_type LIKE ?1 OR source_path LIKE ?1
+        "#,
+        params![pattern],
+        |row| row.get(0),
+    )
+}
+
+/// Get a scan by ID
+pub fn get_scan(conn: &Connection, id: i64) -> Result<Option<ScanRecord>, rusqlite::Error> {
+    let query = format!("SELECT {} FROM scans WHERE id = ?1", SCAN_COLUMNS);
+    match conn.query_row(&query, params![id], scan_from_row) {
+        Ok(scan) => Ok(Some(scan)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Get scans by ID, or every scan when `ids` is empty
+pub fn get_scans(conn: &Connection, ids: &[i64]) -> Result<Vec<ScanRecord>, rusqlite::Error> {
+    if ids.is_empty() {
+        let query = format!(
+            "SELECT {} FROM scans ORDER BY created_at DESC, id DESC",
+            SCAN_COLUMNS
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let items = stmt
+            .query_map([], scan_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(items);
+    }
+
+    let mut items = Vec::new();
+    for id in ids {
+        if let Some(scan) = get_scan(conn, *id)? {
+            items.push(scan);
+        }
+    }
+    Ok(items)
+}
+
+/// Save a new scan, encrypting it if encryption covers its content
+pub fn save_scan(
+    conn: &Connection,
+    scan: &NewScanRecord,
+    key: Option<&EncryptionKey>,
+) -> Result<i64, EncryptionError> {
+    let sensitive = is_sensitive_content(&scan.content);
+    let sealed = match (seal_content(conn, &scan.content, sensitive, key)?, key) {
+        (Some(sealed), Some(key)) => Some((
+            sealed.redacted,
+            seal_scan(
+                key,
+                &scan.content,
+                scan.metadata_json.as_deref(),
+                scan.thumbnail.as_deref(),
+            ),
+        )),
+        _ => None,
+    };
+    Ok(insert_scan(conn, scan, sensitive, sealed)?)
+}
+
+/// Insert a scan, storing `sealed` (redacted content, ciphertext) if given
+fn insert_scan(
+    conn: &Connection,
+    scan: &NewScanRecord,
+    sensitive: bool,
+    sealed: Option<(String, Vec<u8>)>,
+) -> Result<i64, rusqlite::Error> {
+    let (content, metadata_json, thumbnail, content_cipher) = match sealed {
+        Some((redacted, cipher)) => (redacted, None, None, Some(cipher)),
+        None => (
+            scan.content.clone(),
+            scan.metadata_json.clone(),
+            scan.thumbnail.clone(),
+            None,
+        ),
+    };
+
+    conn.execute(
+        r#"
+        INSERT INTO scans
+            (source, source_path, content, qr_type, metadata_json, thumbnail, sensitive,
+             content_cipher)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        "#,
+        params![
+            scan.source,
+            scan.source_path,
+            content,
+            scan.qr_type,
+            metadata_json,
+            thumbnail,
+            sensitive,
+            content_cipher
+        ],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// The full scan, unredacted and decrypted
+///
+/// Returns `None` if the scan doesn't exist.
+pub fn reveal_scan(
+    conn: &Connection,
+    id: i64,
+    key: Option<&EncryptionKey>,
+) -> Result<Option<ScanRecord>, EncryptionError> {
+    let query = format!("SELECT {} FROM scans WHERE id = ?1", SCAN_COLUMNS);
+    let Some((mut scan, content_cipher)) = conn
+        .query_row(&query, params![id], stored_scan_from_row)
+        .optional()?
+    else {
+        return Ok(None);
+    };
+
+    if let Some(sealed) = content_cipher {
+        let key = key.ok_or(EncryptionError::Locked)?;
+        (scan.content, scan.metadata_json, scan.thumbnail) = open_scan(key, &sealed)?;
+    }
+    Ok(Some(scan))
+}
+
+/// Flag scans holding secrets; returns how many were newly flagged
+pub fn mark_sensitive_scans(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    let rows: Vec<(i64, String)> = conn
+        .prepare("SELECT id, content FROM scans WHERE sensitive = 0 AND content_cipher IS NULL")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut stmt = conn.prepare("UPDATE scans SET sensitive = 1 WHERE id = ?1")?;
+    let mut marked = 0;
+    for (id, content) in rows {
+        if is_sensitive_content(&content) {
+            marked += stmt.execute(params![id])? as i64;
+        }
+    }
+    Ok(marked)
+}
+
+/// Delete a scan
+pub fn delete_scan(conn: &Connection, id: i64) -> Result<bool, rusqlite::Error> {
+    let affected = conn.execute("DELETE FROM scans WHERE id = ?1", params![id])?;
+    Ok(affected > 0)
+}
+
+/// Clear all scans
+pub fn clear_scans(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    let affected = conn.execute("DELETE FROM scans", [])?;
+    Ok(affected as i64)
+}
+
+/// Copy a scan into generator history so it can be re-styled
+///
+/// Returns the new history ID, or `None` if the scan doesn't exist.
+pub fn promote_scan(
+    conn: &Connection,
+    id: i64,
+    style_json: &str,
+    label: Option<String>,
+    key: Option<&EncryptionKey>,
+) -> Result<Option<i64>, EncryptionError> {
+    let Some(scan) = reveal_scan(conn, id, key)? else {
+        return Ok(None);
+    };
+
+    let item = NewHistoryItem {
+        content: scan.content,
+        qr_type: scan.qr_type,
+        label,
+        style_json: style_json.to_string(),
+        thumbnail: None,
+    };
+
+    save_history_encrypted(conn, &item, key).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        disable_encryption, enable_encryption, list_history_page, EncryptionScope, HistoryFilter,
+        HistorySort, KeyMaterial,
+    };
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn create_test_scan(content: &str, qr_type: &str) -> NewScanRecord {
+        NewScanRecord {
+            source: "file".to_string(),
+            source_path: Some("/tmp/poster.png".to_string()),
+            content: content.to_string(),
+            qr_type: qr_type.to_string(),
+            metadata_json: None,
+            thumbnail: None,
+        }
+    }
+
+    #[test]
+    fn test_save_and_get_scan() {
+        let conn = setup_test_db();
+        let mut scan = create_test_scan("WIFI:T:WPA;S:Office;P:secret;;", "wifi");
+        scan.metadata_json = Some(r#"{"pass":"inverted"}"#.to_string());
+        scan.thumbnail = Some("data:image/png;base64,abc".to_string());
+
+        let id = save_scan(&conn, &scan, None).unwrap();
+        let saved = reveal_scan(&conn, id, None).unwrap().unwrap();
+
+        assert_eq!(saved.source, "file");
+        assert_eq!(saved.source_path, Some("/tmp/poster.png".to_string()));
+        assert_eq!(saved.qr_type, "wifi");
+        assert_eq!(
+            saved.metadata_json,
+            Some(r#"{"pass":"inverted"}"#.to_string())
+        );
+        assert_eq!(
+            saved.thumbnail,
+            Some("data:image/png;base64,abc".to_string())
+        );
+        assert!(!saved.created_at.is_empty());
+    }
+
+    #[test]
+    fn test_get_scan_nonexistent() {
+        let conn = setup_test_db();
+        assert!(get_scan(&conn, 42).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_scans_newest_first() {
+        let conn = setup_test_db();
+        save_scan(&conn, &create_test_scan("first", "text"), None).unwrap();
+        save_scan(&conn, &create_test_scan("second", "text"), None).unwrap();
+
+        let items = list_scans(&conn, 50, 0, None).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].content, "second");
+    }
+
+    #[test]
+    fn test_list_scans_search_and_count() {
+        let conn = setup_test_db();
+        save_scan(&conn, &create_test_scan("https://example.com", "url"), None).unwrap();
+        save_scan(&conn, &create_test_scan("tel:+15551234567", "phone"), None).unwrap();
+        let mut clip = create_test_scan("hello", "text");
+        clip.source = "clipboard".to_string();
+        clip.source_path = None;
+        save_scan(&conn, &clip, None).unwrap();
+
+        assert_eq!(list_scans(&conn, 50, 0, Some("example")).unwrap().len(), 1);
+        assert_eq!(list_scans(&conn, 50, 0, Some("phone")).unwrap().len(), 1);
+        assert_eq!(list_scans(&conn, 50, 0, Some("poster")).unwrap().len(), 2);
+        assert_eq!(count_scans(&conn, Some("poster")).unwrap(), 2);
+        assert_eq!(count_scans(&conn, None).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_list_scans_pagination() {
+        let conn = setup_test_db();
+        for i in 0..5 {
+            save_scan(
+                &conn,
+                &create_test_scan(&format!("scan {}", i), "text"),
+                None,
+            )
+            .unwrap();
+        }
+
+        assert_eq!(list_scans(&conn, 2, 0, None).unwrap().len(), 2);
+        assert_eq!(list_scans(&conn, 2, 4, None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_get_scans_by_ids() {
+        let conn = setup_test_db();
+        let a = save_scan(&conn, &create_test_scan("a", "text"), None).unwrap();
+        save_scan(&conn, &create_test_scan("b", "text"), None).unwrap();
+
+        assert_eq!(get_scans(&conn, &[a, 999]).unwrap().len(), 1);
+        assert_eq!(get_scans(&conn, &[]).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete_and_clear_scans() {
+        let conn = setup_test_db();
+        let id = save_scan(&conn, &create_test_scan("a", "text"), None).unwrap();
+        save_scan(&conn, &create_test_scan("b", "text"), None).unwrap();
+
+        assert!(delete_scan(&conn, id).unwrap());
+        assert!(!delete_scan(&conn, id).unwrap());
+        assert_eq!(clear_scans(&conn).unwrap(), 1);
+        assert_eq!(count_scans(&conn, None).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_promote_scan_to_history() {
+        let conn = setup_test_db();
+        let id = save_scan(&conn, &create_test_scan("https://example.com", "url"), None).unwrap();
+
+        let history_id = promote_scan(
+            &conn,
+            id,
+            r#"{"dotStyle":"rounded"}"#,
+            Some("Poster".into()),
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        let items = list_history_page(
+            &conn,
+            &HistoryFilter::default(),
+            HistorySort::default(),
+            None,
+            10,
+            0,
+        )
+        .unwrap()
+        .items;
+
+        assert_eq!(items[0].id, history_id);
+        assert_eq!(items[0].content, "https://example.com");
+        assert_eq!(items[0].qr_type, "url");
+        assert_eq!(items[0].label, Some("Poster".to_string()));
+        assert_eq!(items[0].style_json, r#"{"dotStyle":"rounded"}"#);
+
+        // The scan itself is kept
+        assert!(get_scan(&conn, id).unwrap().is_some());
+        assert!(promote_scan(&conn, 999, "{}", None, None)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_sensitive_scans_are_redacted_and_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = setup_test_db();
+        let wifi = "WIFI:T:WPA;S:Office;P:secret;;";
+        let mut scan = create_test_scan(wifi, "wifi");
+        scan.metadata_json = Some(format!(r#"{{"parsed":"{}"}}"#, wifi));
+        scan.thumbnail = Some("data:image/png;base64,abc".to_string());
+
+        let plain = save_scan(&conn, &scan, None).unwrap();
+        let listed = get_scan(&conn, plain).unwrap().unwrap();
+        assert!(listed.sensitive && !listed.encrypted);
+        assert!(!listed.content.contains("secret"));
+        assert!(listed.metadata_json.is_none());
+
+        let key = enable_encryption(
+            &conn,
+            EncryptionScope::Sensitive,
+            KeyMaterial::Keyfile(&dir.path().join("key")),
+        )
+        .unwrap();
+        let sealed = save_scan(&conn, &scan, Some(&key)).unwrap();
+
+        // Nothing readable is left in either row
+        let leaks: i64 = conn
+            .query_row(
+                r#"
+                SELECT COUNT(*) FROM scans
+                WHERE content LIKE '%secret%' OR metadata_json IS NOT NULL
+                   OR thumbnail IS NOT NULL OR content_cipher IS NULL
+                "#,
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(leaks, 0);
+
+        assert!(matches!(
+            reveal_scan(&conn, sealed, None),
+            Err(EncryptionError::Locked)
+        ));
+        for id in [plain, sealed] {
+            let full = reveal_scan(&conn, id, Some(&key)).unwrap().unwrap();
+            assert_eq!(full.content, wifi);
+            assert_eq!(full.metadata_json, scan.metadata_json);
+            assert_eq!(full.thumbnail, scan.thumbnail);
+        }
+
+        disable_encryption(&conn, &key).unwrap();
+        let full = reveal_scan(&conn, sealed, None).unwrap().unwrap();
+        assert_eq!(full.content, wifi);
+        assert_eq!(full.thumbnail, scan.thumbnail);
+    }
+}
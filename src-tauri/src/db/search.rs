@@ -0,0 +1,353 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::history::{history_from_row, HistoryItem, HISTORY_COLUMNS, HISTORY_COLUMN_COUNT};
+
+/// Markers passed to FTS5 `snippet()`; swapped for `<mark>` after escaping
+const MATCH_START: &str = "\u{1}";
+const MATCH_END: &str = "\u{2}";
+
+/// Tokens of context on each side of a content match
+const SNIPPET_TOKENS: i64 = 12;
+
+/// Query prefixes users can type and the FTS column they filter
+const SEARCH_FIELDS: &[(&str, &str)] = &[
+    ("content", "content"),
+    ("label", "label"),
+    ("type", "qr_type"),
+    ("tag", "tags"),
+    ("tags", "tags"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySearchHit {
+    #[serde(flatten)]
+    pub item: HistoryItem,
+    pub rank: f64,
+    /// HTML-escaped content excerpt with matches wrapped in `<mark>`
+    pub content_snippet: String,
+    /// HTML-escaped label with matches wrapped in `<mark>`
+    pub label_snippet: Option<String>,
+}
+
+/// Translate a user query into FTS5 syntax
+///
+/// Bare words match as prefixes, `"quoted text"` matches a phrase and
+/// `type:wifi` / `label:"front desk"` restrict a term to one field.
+/// Returns `None` when the query has no searchable terms.
+pub fn build_fts_query(input: &str) -> Option<String> {
+    let mut terms = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        // Read up to the next unquoted whitespace
+        let mut raw = String::new();
+        let mut in_quotes = false;
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() && !in_quotes {
+                break;
+            }
+            if c == '"' {
+                in_quotes = !in_quotes;
+            }
+            raw.push(c);
+            chars.next();
+        }
+
+        if let Some(term) = fts_term(&raw) {
+            terms.push(term);
+        }
+    }
+
+    (!terms.is_empty()).then(|| terms.join(" AND "))
+}
+
+fn fts_term(raw: &str) -> Option<String> {
+    let (column, value) = match raw.split_once(':') {
+        Some((field, value)) => match SEARCH_FIELDS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(field))
+        {
+            Some((_, column)) => (Some(*column), value),
+            None => (None, raw),
+        },
+        None => (None, raw),
+    };
+
+    let phrase = value.starts_with('"');
+    let text = value.trim_matches('"').trim_end_matches('*');
+    if !text.chars().any(char::is_alphanumeric) {
+        return None;
+    }
+
+    // Quote everything so punctuation can't be read as FTS operators
+    let mut term = format!("\"{}\"", text.replace('"', ""));
+    if !phrase {
+        term.push('*');
+    }
+
+    Some(match column {
+        Some(column) => format!("{} : {}", column, term),
+        None => term,
+    })
+}
+
+/// IDs matching a query, for use as a filter in other history queries
+pub fn fts_filter_clause(param: usize) -> String {
+    format!(
+        "id IN (SELECT rowid FROM history_fts WHERE history_fts MATCH ?{})",
+        param
+    )
+}
+
+/// Ranked full-text search over history
+pub fn search_history(
+    conn: &Connection,
+    query: &str,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<HistorySearchHit>, rusqlite::Error> {
+    let Some(fts_query) = build_fts_query(query) else {
+        return Ok(vec![]);
+    };
+
+    let query = format!(
+        r#"
+        SELECT {HISTORY_COLUMNS},
+               bm25(history_fts, 1.0, 5.0, 2.0, 3.0) AS rank,
+               snippet(history_fts, 0, ?2, ?3, '…', ?4),
+               highlight(history_fts, 1, ?2, ?3)
+        FROM history_fts
+        JOIN history h ON h.id = history_fts.rowid
+        WHERE history_fts MATCH ?1 AND h.deleted_at IS NULL
+        ORDER BY rank, h.created_at DESC
+        LIMIT ?5 OFFSET ?6
+        "#
+    );
+
+    let mut stmt = conn.prepare(&query)?;
+
+    let hits = stmt
+        .query_map(
+            params![
+                fts_query,
+                MATCH_START,
+                MATCH_END,
+                SNIPPET_TOKENS,
+                limit,
+                offset
+            ],
+            |row| {
+                let item = history_from_row(row)?;
+                let label_snippet: String = row.get(HISTORY_COLUMN_COUNT + 2)?;
+                // The index holds a sensitive row's content unredacted until
+                // it's encrypted, so don't excerpt it
+                let content_snippet = if item.sensitive {
+                    mark_matches(&item.content)
+                } else {
+                    mark_matches(&row.get::<_, String>(HISTORY_COLUMN_COUNT + 1)?)
+                };
+                Ok(HistorySearchHit {
+                    rank: row.get(HISTORY_COLUMN_COUNT)?,
+                    content_snippet,
+                    label_snippet: item.label.as_ref().map(|_| mark_matches(&label_snippet)),
+                    item,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(hits)
+}
+
+/// Count live (not trashed) history rows matching a search query
+pub fn count_history_search(conn: &Connection, query: &str) -> Result<i64, rusqlite::Error> {
+    let Some(fts_query) = build_fts_query(query) else {
+        return Ok(0);
+    };
+
+    conn.query_row(
+        r#"
+        SELECT COUNT(*) FROM history_fts
+        JOIN history h ON h.id = history_fts.rowid
+        WHERE history_fts MATCH ?1 AND h.deleted_at IS NULL
+        "#,
+        params![fts_query],
+        |row| row.get(0),
+    )
+}
+
+/// Escape HTML, then turn snippet markers into `<mark>` tags
+fn mark_matches(snippet: &str) -> String {
+    snippet
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace(MATCH_START, "<mark>")
+        .replace(MATCH_END, "</mark>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{init_schema, save_history, NewHistoryItem};
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn save(conn: &Connection, content: &str, qr_type: &str, label: Option<&str>) -> i64 {
+        save_history(
+            conn,
+            &NewHistoryItem {
+                content: content.to_string(),
+                qr_type: qr_type.to_string(),
+                label: label.map(str::to_string),
+                style_json: "{}".to_string(),
+                thumbnail: None,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_build_fts_query() {
+        assert_eq!(build_fts_query("office"), Some("\"office\"*".to_string()));
+        assert_eq!(
+            build_fts_query("type:wifi label:office"),
+            Some("qr_type : \"wifi\"* AND label : \"office\"*".to_string())
+        );
+        assert_eq!(
+            build_fts_query("\"front desk\" guest"),
+            Some("\"front desk\" AND \"guest\"*".to_string())
+        );
+        assert_eq!(
+            build_fts_query("label:\"front desk\""),
+            Some("label : \"front desk\"".to_string())
+        );
+        // Unknown prefixes and punctuation are searched literally
+        assert_eq!(
+            build_fts_query("https://example.com"),
+            Some("\"https://example.com\"*".to_string())
+        );
+        assert_eq!(build_fts_query("say\"hi"), Some("\"sayhi\"*".to_string()));
+        assert_eq!(build_fts_query("   "), None);
+        assert_eq!(build_fts_query("* : \"\""), None);
+    }
+
+    #[test]
+    fn test_search_prefix_and_field() {
+        let conn = setup_test_db();
+        save(
+            &conn,
+            "WIFI:T:WPA;S:OfficeNet;P:secret;;",
+            "wifi",
+            Some("Office"),
+        );
+        save(&conn, "https://office.example.com", "url", Some("Intranet"));
+        save(&conn, "WIFI:T:WPA;S:Home;P:pw;;", "wifi", Some("Home"));
+
+        assert_eq!(search_history(&conn, "offi", 50, 0).unwrap().len(), 2);
+        assert_eq!(count_history_search(&conn, "offi").unwrap(), 2);
+
+        let hits = search_history(&conn, "type:wifi label:office", 50, 0).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].item.label, Some("Office".to_string()));
+        assert_eq!(
+            hits[0].label_snippet,
+            Some("<mark>Office</mark>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_search_phrase() {
+        let conn = setup_test_db();
+        save(&conn, "meet at the front desk", "text", None);
+        save(&conn, "desk in front of the window", "text", None);
+
+        let hits = search_history(&conn, "\"front desk\"", 50, 0).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].content_snippet,
+            "meet at the <mark>front desk</mark>"
+        );
+        assert_eq!(hits[0].label_snippet, None);
+    }
+
+    #[test]
+    fn test_search_ranks_label_matches_first() {
+        let conn = setup_test_db();
+        save(&conn, "https://example.com/menu", "url", Some("Lunch"));
+        save(&conn, "https://example.com/", "url", Some("Menu"));
+
+        let hits = search_history(&conn, "menu", 50, 0).unwrap();
+        assert_eq!(hits[0].item.label, Some("Menu".to_string()));
+        assert!(hits[0].rank <= hits[1].rank);
+    }
+
+    #[test]
+    fn test_search_escapes_html() {
+        let conn = setup_test_db();
+        save(&conn, "<script>alert('x')</script> promo", "text", None);
+
+        let hits = search_history(&conn, "promo", 50, 0).unwrap();
+        assert!(hits[0].content_snippet.contains("&lt;script&gt;"));
+        assert!(hits[0].content_snippet.contains("<mark>promo</mark>"));
+    }
+
+    #[test]
+    fn test_fts_stays_in_sync() {
+        let conn = setup_test_db();
+        let id = save(&conn, "original words", "text", None);
+
+        conn.execute(
+            "UPDATE history SET content = 'replacement', label = 'renamed' WHERE id = ?1",
+            params![id],
+        )
+        .unwrap();
+        assert_eq!(count_history_search(&conn, "original").unwrap(), 0);
+        assert_eq!(count_history_search(&conn, "replacement").unwrap(), 1);
+        assert_eq!(count_history_search(&conn, "label:renamed").unwrap(), 1);
+
+        conn.execute("DELETE FROM history WHERE id = ?1", params![id])
+            .unwrap();
+        assert_eq!(count_history_search(&conn, "replacement").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_existing_rows_are_indexed() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                content TEXT NOT NULL,
+                qr_type TEXT NOT NULL,
+                label TEXT,
+                style_json TEXT NOT NULL,
+                thumbnail TEXT,
+                created_at TEXT DEFAULT (datetime('now')),
+                updated_at TEXT DEFAULT (datetime('now'))
+            );
+            INSERT INTO history (content, qr_type, style_json) VALUES ('legacy entry', 'text', '{}');
+            "#,
+        )
+        .unwrap();
+
+        init_schema(&conn).unwrap();
+        assert_eq!(count_history_search(&conn, "legacy").unwrap(), 1);
+
+        // Re-running the schema setup doesn't duplicate the index
+        init_schema(&conn).unwrap();
+        assert_eq!(count_history_search(&conn, "legacy").unwrap(), 1);
+    }
+}
@@ -0,0 +1,472 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::path::Path;
+
+use super::backup::DEFAULT_BACKUP_KEEP;
+use super::retention::RetentionPolicy;
+use super::trash::DEFAULT_TRASH_RETENTION_DAYS;
+
+/// Placeholders allowed in `filename_pattern`
+pub const FILENAME_TOKENS: &[&str] = &["{label}", "{type}", "{date}", "{time}", "{index}"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Png,
+    Svg,
+    Pdf,
+    Eps,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorCorrection {
+    L,
+    M,
+    Q,
+    H,
+}
+
+/// App preferences, one row per field in the `settings` table
+///
+/// Rows are keyed by the snake_case field name and hold JSON values. Missing
+/// or unreadable rows fall back to the defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub export_format: ExportFormat,
+    pub export_size: i64, // pixels
+    pub error_correction: ErrorCorrection,
+    pub export_directory: Option<String>,
+    pub filename_pattern: String,
+    pub trash_retention_days: i64, // 0 keeps trash until emptied by hand
+    pub retention_policy: RetentionPolicy,
+    pub auto_backup_keep: i64,
+    pub last_template_id: Option<i64>,
+    pub upsert_history: bool, // saving an existing item again bumps it instead
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            export_format: ExportFormat::Png,
+            export_size: 1024,
+            error_correction: ErrorCorrection::M,
+            export_directory: None,
+            filename_pattern: "qr-{type}-{date}".to_string(),
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            retention_policy: RetentionPolicy::default(),
+            auto_backup_keep: DEFAULT_BACKUP_KEEP as i64,
+            last_template_id: None,
+            upsert_history: true,
+        }
+    }
+}
+
+impl Settings {
+    /// Check every value, naming the first invalid setting
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let invalid = |key: &str, message: String| {
+            Err(SettingsError::Invalid {
+                key: key.to_string(),
+                message,
+            })
+        };
+
+        if !(64..=8192).contains(&self.export_size) {
+            return invalid(
+                "exportSize",
+                "Export size must be between 64 and 8192 pixels".to_string(),
+            );
+        }
+        if let Some(dir) = &self.export_directory {
+            if !Path::new(dir).is_absolute() {
+                return invalid(
+                    "exportDirectory",
+                    "Export directory must be an absolute path".to_string(),
+                );
+            }
+        }
+        if let Err(message) = validate_filename_pattern(&self.filename_pattern) {
+            return invalid("filenamePattern", message);
+        }
+        if self.trash_retention_days < 0 {
+            return invalid(
+                "trashRetentionDays",
+                "Retention days cannot be negative".to_string(),
+            );
+        }
+        if let Err(message) = self.retention_policy.validate() {
+            return invalid("retentionPolicy", message);
+        }
+        if self.auto_backup_keep < 1 {
+            return invalid(
+                "autoBackupKeep",
+                "Keep at least one automatic backup".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+fn validate_filename_pattern(pattern: &str) -> Result<(), String> {
+    if pattern.trim().is_empty() {
+        return Err("Filename pattern cannot be empty".to_string());
+    }
+    if pattern.contains(['/', '\\']) {
+        return Err("Filename pattern cannot contain path separators".to_string());
+    }
+
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| "Unclosed '{' in filename pattern".to_string())?;
+        let token = &rest[start..start + end + 1];
+        if !FILENAME_TOKENS.contains(&token) {
+            return Err(format!(
+                "Unknown placeholder {}; use one of {}",
+                token,
+                FILENAME_TOKENS.join(", ")
+            ));
+        }
+        rest = &rest[start + end + 1..];
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Invalid { key: String, message: String },
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::Invalid { key, message } => write!(f, "{}: {}", key, message),
+            SettingsError::Sqlite(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+impl From<rusqlite::Error> for SettingsError {
+    fn from(e: rusqlite::Error) -> Self {
+        SettingsError::Sqlite(e)
+    }
+}
+
+/// Settings after an update, with the camelCase keys whose values changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsChange {
+    pub settings: Settings,
+    pub changed: Vec<String>,
+}
+
+impl SettingsChange {
+    pub fn has(&self, key: &str) -> bool {
+        self.changed.iter().any(|changed| changed == key)
+    }
+}
+
+fn to_map(settings: &Settings) -> Map<String, Value> {
+    match serde_json::to_value(settings) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+fn from_map(map: &Map<String, Value>) -> Result<Settings, serde_json::Error> {
+    serde_json::from_value(Value::Object(map.clone()))
+}
+
+/// `exportSize` -> `export_size`
+fn column_key(key: &str) -> String {
+    let mut out = String::with_capacity(key.len() + 4);
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Load all settings, using the default for anything missing or invalid
+pub fn get_settings(conn: &Connection) -> Result<Settings, rusqlite::Error> {
+    let mut map = to_map(&Settings::default());
+    let fields: Vec<String> = map.keys().cloned().collect();
+
+    let mut stmt = conn.prepare("SELECT key, value FROM settings")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for field in fields {
+        let column = column_key(&field);
+        let Some((_, raw)) = rows.iter().find(|(key, _)| *key == column) else {
+            continue;
+        };
+        let Ok(value) = serde_json::from_str::<Value>(raw) else {
+            continue;
+        };
+
+        // Keep the stored value only if it still parses and validates
+        let previous = map.insert(field.clone(), value);
+        let valid = from_map(&map).is_ok_and(|settings| settings.validate().is_ok());
+        if !valid {
+            if let Some(previous) = previous {
+                map.insert(field, previous);
+            }
+        }
+    }
+
+    Ok(from_map(&map).unwrap_or_default())
+}
+
+/// Apply a partial update of camelCase keys and store what changed
+pub fn update_settings(
+    conn: &Connection,
+    patch: &Map<String, Value>,
+) -> Result<SettingsChange, SettingsError> {
+    let current = get_settings(conn)?;
+    let before = to_map(&current);
+    let mut map = before.clone();
+
+    for (key, value) in patch {
+        if !map.contains_key(key) {
+            return Err(SettingsError::Invalid {
+                key: key.clone(),
+                message: "Unknown setting".to_string(),
+            });
+        }
+        map.insert(key.clone(), value.clone());
+        from_map(&map).map_err(|e| SettingsError::Invalid {
+            key: key.clone(),
+            message: format!("Invalid value: {}", e),
+        })?;
+    }
+
+    let settings = from_map(&map).map_err(|e| SettingsError::Invalid {
+        key: "settings".to_string(),
+        message: e.to_string(),
+    })?;
+    settings.validate()?;
+
+    if patch.contains_key("exportDirectory") {
+        if let Some(dir) = &settings.export_directory {
+            if !Path::new(dir).is_dir() {
+                return Err(SettingsError::Invalid {
+                    key: "exportDirectory".to_string(),
+                    message: format!("Export directory does not exist: {}", dir),
+                });
+            }
+        }
+    }
+
+    let after = to_map(&settings);
+    let changed: Vec<String> = after
+        .iter()
+        .filter(|(key, value)| before.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect();
+
+    let tx = conn.unchecked_transaction()?;
+    for key in &changed {
+        tx.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            params![column_key(key), after[key].to_string()],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(SettingsChange { settings, changed })
+}
+
+/// Restore settings to their defaults; every setting when `keys` is empty
+pub fn reset_settings(conn: &Connection, keys: &[String]) -> Result<SettingsChange, SettingsError> {
+    let defaults = to_map(&Settings::default());
+    let keys: Vec<String> = if keys.is_empty() {
+        defaults.keys().cloned().collect()
+    } else {
+        keys.to_vec()
+    };
+
+    let mut patch = Map::new();
+    for key in keys {
+        let value = defaults
+            .get(&key)
+            .cloned()
+            .ok_or_else(|| SettingsError::Invalid {
+                key: key.clone(),
+                message: "Unknown setting".to_string(),
+            })?;
+        patch.insert(key, value);
+    }
+
+    let change = update_settings(conn, &patch)?;
+
+    // Defaults aren't stored, so later default changes apply
+    let tx = conn.unchecked_transaction()?;
+    for key in patch.keys() {
+        tx.execute(
+            "DELETE FROM settings WHERE key = ?1",
+            params![column_key(key)],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::init_schema;
+    use serde_json::json;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn patch(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("patch must be an object"),
+        }
+    }
+
+    fn stored(conn: &Connection, key: &str) -> Option<String> {
+        conn.query_row(
+            "SELECT value FROM settings WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        )
+        .ok()
+    }
+
+    #[test]
+    fn test_defaults_when_empty() {
+        let conn = setup_test_db();
+        assert_eq!(get_settings(&conn).unwrap(), Settings::default());
+        assert!(Settings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_update_reports_changes() {
+        let conn = setup_test_db();
+        let change = update_settings(
+            &conn,
+            &patch(json!({
+                "exportFormat": "svg",
+                "exportSize": 1024,
+                "errorCorrection": "H",
+                "retentionPolicy": { "maxItems": 500 }
+            })),
+        )
+        .unwrap();
+
+        assert_eq!(
+            change.changed,
+            vec!["errorCorrection", "exportFormat", "retentionPolicy"]
+        );
+        assert!(change.has("exportFormat"));
+        assert_eq!(stored(&conn, "export_format").as_deref(), Some("\"svg\""));
+        assert_eq!(stored(&conn, "export_size"), None);
+
+        let settings = get_settings(&conn).unwrap();
+        assert_eq!(settings.export_format, ExportFormat::Svg);
+        assert_eq!(settings.error_correction, ErrorCorrection::H);
+        assert_eq!(settings.retention_policy.max_items, Some(500));
+    }
+
+    #[test]
+    fn test_rejects_invalid_values() {
+        let conn = setup_test_db();
+        let cases = [
+            (json!({ "exportSize": 10 }), "exportSize"),
+            (json!({ "exportFormat": "gif" }), "exportFormat"),
+            (json!({ "errorCorrection": "X" }), "errorCorrection"),
+            (
+                json!({ "filenamePattern": "{label}/{date}" }),
+                "filenamePattern",
+            ),
+            (
+                json!({ "filenamePattern": "qr-{colour}" }),
+                "filenamePattern",
+            ),
+            (json!({ "trashRetentionDays": -1 }), "trashRetentionDays"),
+            (
+                json!({ "retentionPolicy": { "maxItems": 0 } }),
+                "retentionPolicy",
+            ),
+            (
+                json!({ "exportDirectory": "relative/dir" }),
+                "exportDirectory",
+            ),
+            (
+                json!({ "exportDirectory": "/no/such/dir/here" }),
+                "exportDirectory",
+            ),
+            (json!({ "theme": "dark" }), "theme"),
+        ];
+
+        for (value, expected) in cases {
+            match update_settings(&conn, &patch(value.clone())) {
+                Err(SettingsError::Invalid { key, .. }) => assert_eq!(key, expected, "{}", value),
+                other => panic!("expected {} to be rejected, got {:?}", value, other),
+            }
+        }
+        assert_eq!(get_settings(&conn).unwrap(), Settings::default());
+    }
+
+    #[test]
+    fn test_reads_existing_rows_and_skips_bad_ones() {
+        let conn = setup_test_db();
+        conn.execute_batch(
+            r#"
+            INSERT INTO settings (key, value) VALUES ('trash_retention_days', '7');
+            INSERT INTO settings (key, value) VALUES ('retention_policy', '{"maxAgeDays":90}');
+            INSERT INTO settings (key, value) VALUES ('export_size', '"huge"');
+            INSERT INTO settings (key, value) VALUES ('filename_pattern', '"a/b"');
+            INSERT INTO settings (key, value) VALUES ('unrelated', 'not json');
+            "#,
+        )
+        .unwrap();
+
+        let settings = get_settings(&conn).unwrap();
+        assert_eq!(settings.trash_retention_days, 7);
+        assert_eq!(settings.retention_policy.max_age_days, Some(90));
+        assert_eq!(settings.export_size, 1024);
+        assert_eq!(settings.filename_pattern, "qr-{type}-{date}");
+    }
+
+    #[test]
+    fn test_reset_settings() {
+        let conn = setup_test_db();
+        update_settings(
+            &conn,
+            &patch(json!({ "exportSize": 2048, "lastTemplateId": 4 })),
+        )
+        .unwrap();
+
+        let change = reset_settings(&conn, &["exportSize".to_string()]).unwrap();
+        assert_eq!(change.changed, vec!["exportSize"]);
+        assert_eq!(stored(&conn, "export_size"), None);
+        assert_eq!(get_settings(&conn).unwrap().last_template_id, Some(4));
+
+        reset_settings(&conn, &[]).unwrap();
+        assert_eq!(get_settings(&conn).unwrap(), Settings::default());
+        assert!(reset_settings(&conn, &["nope".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableUsage {
+    pub name: String,
+    pub rows: i64,
+    pub bytes: i64, // table, index and FTS shadow pages
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageReport {
+    pub total_bytes: i64,
+    pub free_bytes: i64, // reclaimable with a VACUUM
+    pub thumbnail_bytes: i64,
+    pub tables: Vec<TableUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VacuumResult {
+    pub bytes_before: i64,
+    pub bytes_after: i64,
+}
+
+fn page_size(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    conn.query_row("PRAGMA page_size", [], |row| row.get(0))
+}
+
+/// Size of the database file
+pub fn total_bytes(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    let pages: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+    Ok(pages * page_size(conn)?)
+}
+
+/// Bytes in pages holding data, excluding the free list
+pub fn used_bytes(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    let free: i64 = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+    Ok(total_bytes(conn)? - free * page_size(conn)?)
+}
+
+/// Storage used by each table, largest first
+///
+/// Index pages count towards their table, and FTS5 shadow tables towards
+/// the virtual table they back.
+pub fn storage_report(conn: &Connection) -> Result<StorageReport, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT coalesce(m.tbl_name, s.name), SUM(s.pgsize)
+        FROM dbstat s
+        LEFT JOIN sqlite_master m ON m.name = s.name
+        GROUP BY 1
+        "#,
+    )?;
+    let usage = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut tables: Vec<TableUsage> = Vec::new();
+    for (name, bytes) in usage {
+        let name = fts_owner(conn, &name)?.unwrap_or(name);
+        match tables.iter_mut().find(|table| table.name == name) {
+            Some(table) => table.bytes += bytes,
+            None => tables.push(TableUsage {
+                rows: conn.query_row(&format!("SELECT COUNT(*) FROM \"{}\"", name), [], |row| {
+                    row.get(0)
+                })?,
+                name,
+                bytes,
+            }),
+        }
+    }
+    tables.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+
+    let free: i64 = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+    let thumbnail_bytes = conn.query_row(
+        "SELECT coalesce(SUM(length(data)), 0) FROM blobs",
+        [],
+        |row| row.get(0),
+    )?;
+
+    Ok(StorageReport {
+        total_bytes: total_bytes(conn)?,
+        free_bytes: free * page_size(conn)?,
+        thumbnail_bytes,
+        tables,
+    })
+}
+
+/// The FTS5 table a shadow table (`history_fts_data`, ...) belongs to
+fn fts_owner(conn: &Connection, name: &str) -> Result<Option<String>, rusqlite::Error> {
+    let Some((owner, _)) = name.rsplit_once('_') else {
+        return Ok(None);
+    };
+    let is_fts: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = ?1 AND sql LIKE '%USING fts5%')",
+        [owner],
+        |row| row.get(0),
+    )?;
+    Ok(is_fts.then(|| owner.to_string()))
+}
+
+/// Merge FTS segments and rebuild the file to release free pages
+///
+/// The write-ahead log is checkpointed and truncated afterwards, so pages
+/// the vacuum dropped don't linger there either.
+pub fn vacuum_database(conn: &Connection) -> Result<VacuumResult, rusqlite::Error> {
+    let bytes_before = total_bytes(conn)?;
+
+    conn.execute(
+        "INSERT INTO history_fts (history_fts) VALUES ('optimize')",
+        [],
+    )?;
+    conn.execute_batch("VACUUM")?;
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+
+    Ok(VacuumResult {
+        bytes_before,
+        bytes_after: total_bytes(conn)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{clear_history, init_schema, purge_history, save_history, NewHistoryItem};
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn save_large(conn: &Connection, count: usize) -> Vec<i64> {
+        (0..count)
+            .map(|i| {
+                save_history(
+                    conn,
+                    &NewHistoryItem {
+                        content: format!("https://example.com/{}", i),
+                        qr_type: "url".to_string(),
+                        label: None,
+                        style_json: "{}".to_string(),
+                        thumbnail: Some(format!("{}{}", i, "x".repeat(49_999))),
+                    },
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_storage_report_groups_tables() {
+        let conn = setup_test_db();
+        save_large(&conn, 3);
+
+        let report = storage_report(&conn).unwrap();
+        assert_eq!(report.thumbnail_bytes, 150_000);
+        assert_eq!(report.tables[0].name, "blobs");
+        assert_eq!(report.tables[0].rows, 3);
+        assert!(report.tables[0].bytes >= 150_000);
+
+        let history = report.tables.iter().find(|t| t.name == "history").unwrap();
+        assert_eq!(history.rows, 3);
+
+        let fts = report
+            .tables
+            .iter()
+            .find(|t| t.name == "history_fts")
+            .unwrap();
+        assert_eq!(fts.rows, 3);
+        assert!(!report
+            .tables
+            .iter()
+            .any(|t| t.name.starts_with("history_fts_")));
+        assert!(!report.tables.iter().any(|t| t.name.starts_with("idx_")));
+    }
+
+    #[test]
+    fn test_vacuum_releases_free_pages() {
+        let conn = setup_test_db();
+        let ids = save_large(&conn, 10);
+        clear_history(&conn).unwrap();
+        purge_history(&conn, &ids).unwrap();
+
+        assert!(storage_report(&conn).unwrap().free_bytes > 0);
+
+        let result = vacuum_database(&conn).unwrap();
+        assert!(result.bytes_after < result.bytes_before);
+        assert_eq!(storage_report(&conn).unwrap().free_bytes, 0);
+    }
+}
@@ -0,0 +1,1063 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::blobs::{get_blob, put_optional_blob};
+use super::dedupe::refresh_content_hash;
+use super::encryption::{
+    is_sensitive_content, reveal_history_content, seal_content, EncryptionError, EncryptionKey,
+};
+use super::history::{history_from_row, insert_history_row, NewHistoryItem, HISTORY_COLUMNS};
+use super::tags::ensure_tag;
+
+/// Extension of the per-device change logs in the sync folder
+pub const SYNC_LOG_EXTENSION: &str = "qrsync";
+
+/// A history row as written to a change log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncHistory {
+    pub content: String,
+    pub qr_type: String,
+    pub label: Option<String>,
+    pub style_json: String,
+    pub thumbnail: Option<String>, // data URL
+    pub pinned: bool,
+    pub tags: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+    /// `content` has its secret masked; receivers keep the content they have
+    #[serde(default)]
+    pub redacted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncTemplate {
+    pub name: String,
+    pub style_json: String,
+    pub preview: Option<String>, // data URL
+    pub is_default: bool,
+    pub created_at: String,
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncTag {
+    pub name: String,
+}
+
+/// A row's state after a change; `None` once it has been deleted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "entity", content = "row", rename_all = "lowercase")]
+pub enum SyncRecord {
+    History(Option<SyncHistory>),
+    Template(Option<SyncTemplate>),
+    Tag(Option<SyncTag>),
+}
+
+impl SyncRecord {
+    fn entity(&self) -> &'static str {
+        match self {
+            SyncRecord::History(_) => "history",
+            SyncRecord::Template(_) => "template",
+            SyncRecord::Tag(_) => "tag",
+        }
+    }
+
+    /// Order to apply a batch in, so rows exist before others refer to them
+    fn rank(&self) -> u8 {
+        match self {
+            SyncRecord::Tag(_) => 0,
+            SyncRecord::Template(_) => 1,
+            SyncRecord::History(_) => 2,
+        }
+    }
+}
+
+/// One line of a change log
+///
+/// The newest `(modified_at, device_id)` wins, so every device settles on
+/// the same version whatever order the logs are read in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncChange {
+    pub uuid: String,
+    pub modified_at: String,
+    pub device_id: String,
+    #[serde(flatten)]
+    pub record: SyncRecord,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPeer {
+    pub device_id: String,
+    pub synced_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    pub device_id: Option<String>, // None until sync is first set up
+    pub folder: Option<String>,
+    pub include_secrets: bool,
+    pub last_synced_at: Option<String>,
+    pub pending_changes: i64,
+    pub peers: Vec<SyncPeer>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    pub sent: i64,     // changes written to this device's log
+    pub received: i64, // changes read from other devices' logs
+    pub applied: i64,
+    pub stale: i64,   // older than the version already here
+    pub invalid: i64, // log lines that couldn't be read
+    pub peers: i64,
+}
+
+#[derive(Debug)]
+pub enum SyncError {
+    NotConfigured,
+    FolderMissing(PathBuf),
+    Encryption(EncryptionError),
+    Io(std::io::Error),
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::NotConfigured => write!(f, "Choose a sync folder first"),
+            SyncError::FolderMissing(path) => {
+                write!(f, "Sync folder {} is not available", path.display())
+            }
+            SyncError::Encryption(e) => write!(f, "{}", e),
+            SyncError::Io(e) => write!(f, "{}", e),
+            SyncError::Sqlite(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SyncError {}
+
+impl From<rusqlite::Error> for SyncError {
+    fn from(e: rusqlite::Error) -> Self {
+        SyncError::Sqlite(e)
+    }
+}
+
+impl From<std::io::Error> for SyncError {
+    fn from(e: std::io::Error) -> Self {
+        SyncError::Io(e)
+    }
+}
+
+impl From<EncryptionError> for SyncError {
+    fn from(e: EncryptionError) -> Self {
+        match e {
+            EncryptionError::Sqlite(e) => SyncError::Sqlite(e),
+            e => SyncError::Encryption(e),
+        }
+    }
+}
+
+struct SyncState {
+    device_id: String,
+    folder: Option<String>,
+    include_secrets: bool,
+    last_synced_at: Option<String>,
+}
+
+fn load_state(conn: &Connection) -> Result<Option<SyncState>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT device_id, folder, include_secrets, last_synced_at FROM sync_state WHERE id = 1",
+        [],
+        |row| {
+            Ok(SyncState {
+                device_id: row.get(0)?,
+                folder: row.get(1)?,
+                include_secrets: row.get(2)?,
+                last_synced_at: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Where sync stands: this device, its folder and the devices seen there
+pub fn sync_status(conn: &Connection) -> Result<SyncStatus, rusqlite::Error> {
+    let state = load_state(conn)?;
+    let pending_changes: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sync_rows WHERE pending = 1",
+        [],
+        |row| row.get(0),
+    )?;
+    let peers = conn
+        .prepare("SELECT device_id, synced_at FROM sync_peers ORDER BY synced_at DESC")?
+        .query_map([], |row| {
+            Ok(SyncPeer {
+                device_id: row.get(0)?,
+                synced_at: row.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(SyncStatus {
+        device_id: state.as_ref().map(|s| s.device_id.clone()),
+        folder: state.as_ref().and_then(|s| s.folder.clone()),
+        include_secrets: state.as_ref().is_some_and(|s| s.include_secrets),
+        last_synced_at: state.and_then(|s| s.last_synced_at),
+        pending_changes,
+        peers,
+    })
+}
+
+/// Set the sync folder, or turn sync off with `None`
+///
+/// Gives this install its device ID the first time. WiFi passwords and OTP
+/// secrets are only written to the folder with `include_secrets`.
+pub fn configure_sync(
+    conn: &Connection,
+    folder: Option<&Path>,
+    include_secrets: bool,
+) -> Result<SyncStatus, SyncError> {
+    if let Some(folder) = folder.filter(|folder| !folder.is_dir()) {
+        return Err(SyncError::FolderMissing(folder.to_path_buf()));
+    }
+
+    conn.execute(
+        r#"
+        INSERT INTO sync_state (id, device_id, folder, include_secrets)
+        VALUES (1, ?1, ?2, ?3)
+        ON CONFLICT (id) DO UPDATE SET
+            folder = excluded.folder,
+            include_secrets = excluded.include_secrets
+        "#,
+        params![
+            uuid::Uuid::new_v4().to_string(),
+            folder.map(|f| f.to_string_lossy().to_string()),
+            include_secrets
+        ],
+    )?;
+
+    Ok(sync_status(conn)?)
+}
+
+fn log_path(folder: &Path, device_id: &str) -> PathBuf {
+    folder.join(format!("{}.{}", device_id, SYNC_LOG_EXTENSION))
+}
+
+/// Complete lines of a log from `offset`, and the offset just past them
+///
+/// A line still being written is left for next time. A log that has
+/// shrunk was replaced, so it is read again from the start.
+fn read_log(path: &Path, offset: u64) -> Result<(Vec<u8>, u64), std::io::Error> {
+    let mut file = File::open(path)?;
+    let offset = if file.metadata()?.len() < offset {
+        0
+    } else {
+        offset
+    };
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    let complete = data.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    data.truncate(complete);
+    Ok((data, offset + complete as u64))
+}
+
+/// Append lines to this device's log, after any torn final line
+fn append_log(path: &Path, lines: &[String]) -> Result<(), std::io::Error> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)?;
+
+    let mut data = String::new();
+    if file.metadata()?.len() > 0 {
+        let mut last = [0u8];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            data.push('\n');
+        }
+    }
+    for line in lines {
+        data.push_str(line);
+        data.push('\n');
+    }
+    file.write_all(data.as_bytes())?;
+    file.sync_all()
+}
+
+fn find_id(conn: &Connection, table: &str, uuid: &str) -> Result<Option<i64>, rusqlite::Error> {
+    conn.query_row(
+        &format!("SELECT id FROM {} WHERE uuid = ?1", table),
+        params![uuid],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+fn read_history(
+    conn: &Connection,
+    uuid: &str,
+    include_secrets: bool,
+    key: Option<&EncryptionKey>,
+) -> Result<Option<SyncHistory>, SyncError> {
+    let query = format!("SELECT {HISTORY_COLUMNS} FROM history h WHERE h.uuid = ?1");
+    let Some(item) = conn
+        .query_row(&query, params![uuid], history_from_row)
+        .optional()?
+    else {
+        return Ok(None);
+    };
+
+    let redacted = item.sensitive && !include_secrets;
+    let content = if redacted {
+        item.content
+    } else {
+        reveal_history_content(conn, item.id, key)?.unwrap_or(item.content)
+    };
+    let thumbnail = match &item.thumbnail_ref {
+        Some(hash) => get_blob(conn, hash)?.map(|blob| blob.to_data_url()),
+        None => None,
+    };
+
+    Ok(Some(SyncHistory {
+        content,
+        qr_type: item.qr_type,
+        label: item.label,
+        style_json: item.style_json,
+        thumbnail,
+        pinned: item.pinned,
+        tags: item.tags,
+        created_at: item.created_at,
+        updated_at: item.updated_at,
+        deleted_at: item.deleted_at,
+        redacted,
+    }))
+}
+
+fn read_template(conn: &Connection, uuid: &str) -> Result<Option<SyncTemplate>, rusqlite::Error> {
+    let template = conn
+        .query_row(
+            r#"
+            SELECT name, style_json, preview_hash, is_default, created_at, deleted_at
+            FROM templates WHERE uuid = ?1
+            "#,
+            params![uuid],
+            |row| {
+                Ok(SyncTemplate {
+                    name: row.get(0)?,
+                    style_json: row.get(1)?,
+                    preview: row.get(2)?, // blob hash until swapped below
+                    is_default: row.get(3)?,
+                    created_at: row.get(4)?,
+                    deleted_at: row.get(5)?,
+                })
+            },
+        )
+        .optional()?;
+
+    let Some(mut template) = template else {
+        return Ok(None);
+    };
+    template.preview = match template.preview.take() {
+        Some(hash) => get_blob(conn, &hash)?.map(|blob| blob.to_data_url()),
+        None => None,
+    };
+    Ok(Some(template))
+}
+
+fn read_tag(conn: &Connection, uuid: &str) -> Result<Option<SyncTag>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT name FROM tags WHERE uuid = ?1",
+        params![uuid],
+        |row| Ok(SyncTag { name: row.get(0)? }),
+    )
+    .optional()
+}
+
+fn set_tags(conn: &Connection, history_id: i64, names: &[String]) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM history_tags WHERE history_id = ?1",
+        params![history_id],
+    )?;
+    for name in names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+        let tag_id = ensure_tag(conn, name)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO history_tags (history_id, tag_id) VALUES (?1, ?2)",
+            params![history_id, tag_id],
+        )?;
+    }
+    Ok(())
+}
+
+fn apply_history(
+    conn: &Connection,
+    uuid: &str,
+    row: Option<&SyncHistory>,
+    key: Option<&EncryptionKey>,
+) -> Result<(), SyncError> {
+    let existing = find_id(conn, "history", uuid)?;
+    let Some(row) = row else {
+        conn.execute("DELETE FROM history WHERE uuid = ?1", params![uuid])?;
+        return Ok(());
+    };
+
+    let sensitive = row.redacted || is_sensitive_content(&row.content);
+    let sealed = if row.redacted {
+        None
+    } else {
+        seal_content(conn, &row.content, sensitive, key)?
+    };
+
+    let id = match existing {
+        Some(id) => {
+            // A redacted copy can't replace the real secret
+            if !row.redacted {
+                conn.execute(
+                    "UPDATE history SET content = ?1, content_cipher = ?2, sensitive = ?3 WHERE id = ?4",
+                    params![
+                        sealed.as_ref().map_or(&row.content, |s| &s.redacted),
+                        sealed.as_ref().map(|s| &s.cipher),
+                        sensitive,
+                        id
+                    ],
+                )?;
+            }
+            let thumbnail_hash = put_optional_blob(conn, row.thumbnail.as_deref())?;
+            conn.execute(
+                r#"
+                UPDATE history
+                SET qr_type = ?1, label = ?2, style_json = ?3, thumbnail_hash = ?4
+                WHERE id = ?5
+                "#,
+                params![row.qr_type, row.label, row.style_json, thumbnail_hash, id],
+            )?;
+            refresh_content_hash(conn, id)?;
+            id
+        }
+        None => {
+            let item = NewHistoryItem {
+                content: row.content.clone(),
+                qr_type: row.qr_type.clone(),
+                label: row.label.clone(),
+                style_json: row.style_json.clone(),
+                thumbnail: row.thumbnail.clone(),
+            };
+            let id = insert_history_row(conn, &item, sensitive, sealed.as_ref())?;
+
+            // Take the sender's UUID in place of the one generated on insert
+            conn.execute(
+                r#"
+                DELETE FROM sync_rows
+                WHERE entity = 'history' AND uuid = (SELECT uuid FROM history WHERE id = ?1)
+                "#,
+                params![id],
+            )?;
+            conn.execute(
+                "UPDATE history SET uuid = ?1, created_at = ?2 WHERE id = ?3",
+                params![uuid, row.created_at, id],
+            )?;
+            id
+        }
+    };
+
+    conn.execute(
+        "UPDATE history SET pinned = ?1, deleted_at = ?2, updated_at = ?3 WHERE id = ?4",
+        params![row.pinned, row.deleted_at, row.updated_at, id],
+    )?;
+    set_tags(conn, id, &row.tags)?;
+    Ok(())
+}
+
+fn apply_template(
+    conn: &Connection,
+    uuid: &str,
+    row: Option<&SyncTemplate>,
+) -> Result<(), rusqlite::Error> {
+    let Some(row) = row else {
+        conn.execute("DELETE FROM templates WHERE uuid = ?1", params![uuid])?;
+        return Ok(());
+    };
+
+    let preview_hash = put_optional_blob(conn, row.preview.as_deref())?;
+    match find_id(conn, "templates", uuid)? {
+        Some(id) => conn.execute(
+            r#"
+            UPDATE templates
+            SET name = ?1, style_json = ?2, preview_hash = ?3, is_default = ?4, deleted_at = ?5
+            WHERE id = ?6
+            "#,
+            params![
+                row.name,
+                row.style_json,
+                preview_hash,
+                row.is_default,
+                row.deleted_at,
+                id
+            ],
+        )?,
+        None => conn.execute(
+            r#"
+            INSERT INTO templates
+                (uuid, name, style_json, preview_hash, is_default, created_at, deleted_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+            params![
+                uuid,
+                row.name,
+                row.style_json,
+                preview_hash,
+                row.is_default,
+                row.created_at,
+                row.deleted_at
+            ],
+        )?,
+    };
+
+    if row.is_default {
+        conn.execute(
+            "UPDATE templates SET is_default = 0 WHERE is_default = 1 AND uuid != ?1",
+            params![uuid],
+        )?;
+    }
+    Ok(())
+}
+
+/// Tag names are unique, so two devices that each created a tag of the same
+/// name end up with one: the tag with the smaller UUID absorbs the other.
+fn apply_tag(conn: &Connection, uuid: &str, row: Option<&SyncTag>) -> Result<(), rusqlite::Error> {
+    let Some(row) = row else {
+        conn.execute("DELETE FROM tags WHERE uuid = ?1", params![uuid])?;
+        return Ok(());
+    };
+
+    let existing = find_id(conn, "tags", uuid)?;
+    let named: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT id, uuid FROM tags WHERE name = ?1 COLLATE NOCASE",
+            params![row.name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    match (existing, named) {
+        (None, None) => {
+            conn.execute(
+                "INSERT INTO tags (uuid, name) VALUES (?1, ?2)",
+                params![uuid, row.name],
+            )?;
+        }
+        (None, Some((named_id, named_uuid))) => {
+            if uuid < named_uuid.as_str() {
+                conn.execute(
+                    "UPDATE tags SET uuid = ?1, name = ?2 WHERE id = ?3",
+                    params![uuid, row.name, named_id],
+                )?;
+                // No other device has seen the old UUID if it was never sent
+                conn.execute(
+                    "DELETE FROM sync_rows WHERE entity = 'tag' AND uuid = ?1 AND pending = 1",
+                    params![named_uuid],
+                )?;
+            }
+        }
+        (Some(id), Some((named_id, named_uuid))) if named_id != id => {
+            let (keep, fold) = if uuid < named_uuid.as_str() {
+                (id, named_id)
+            } else {
+                (named_id, id)
+            };
+            conn.execute(
+                r#"
+                INSERT OR IGNORE INTO history_tags (history_id, tag_id)
+                SELECT history_id, ?2 FROM history_tags WHERE tag_id = ?1
+                "#,
+                params![fold, keep],
+            )?;
+            conn.execute("DELETE FROM tags WHERE id = ?1", params![fold])?;
+            conn.execute(
+                "UPDATE tags SET name = ?1 WHERE id = ?2",
+                params![row.name, keep],
+            )?;
+        }
+        (Some(id), _) => {
+            conn.execute(
+                "UPDATE tags SET name = ?1 WHERE id = ?2",
+                params![row.name, id],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Apply a change unless this device already has a newer version
+///
+/// Returns whether it was applied.
+fn apply_change(
+    conn: &Connection,
+    change: &SyncChange,
+    device_id: &str,
+    key: Option<&EncryptionKey>,
+) -> Result<bool, SyncError> {
+    let entity = change.record.entity();
+    let local: Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT modified_at, device_id FROM sync_rows WHERE entity = ?1 AND uuid = ?2",
+            params![entity, change.uuid],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    if let Some((modified_at, local_device)) = local {
+        let local_device = local_device.as_deref().unwrap_or(device_id);
+        if (modified_at.as_str(), local_device)
+            >= (change.modified_at.as_str(), change.device_id.as_str())
+        {
+            return Ok(false);
+        }
+    }
+
+    match &change.record {
+        SyncRecord::History(row) => apply_history(conn, &change.uuid, row.as_ref(), key)?,
+        SyncRecord::Template(row) => apply_template(conn, &change.uuid, row.as_ref())?,
+        SyncRecord::Tag(row) => apply_tag(conn, &change.uuid, row.as_ref())?,
+    }
+
+    // The triggers marked the row as changed here; record the sender's version
+    conn.execute(
+        r#"
+        INSERT INTO sync_rows (entity, uuid, modified_at, device_id, pending)
+        VALUES (?1, ?2, ?3, ?4, 0)
+        ON CONFLICT (entity, uuid) DO UPDATE SET
+            modified_at = excluded.modified_at,
+            device_id = excluded.device_id,
+            pending = 0
+        "#,
+        params![entity, change.uuid, change.modified_at, change.device_id],
+    )?;
+    Ok(true)
+}
+
+/// Merge other devices' logs from the sync folder, then append this
+/// device's unsent changes to its own log
+///
+/// Runs in one transaction. Encrypted history must be unlocked to send or
+/// receive changes it covers.
+pub fn sync_now(conn: &Connection, key: Option<&EncryptionKey>) -> Result<SyncReport, SyncError> {
+    let state = load_state(conn)?.ok_or(SyncError::NotConfigured)?;
+    let folder = PathBuf::from(state.folder.ok_or(SyncError::NotConfigured)?);
+    if !folder.is_dir() {
+        return Err(SyncError::FolderMissing(folder));
+    }
+
+    let mut report = SyncReport::default();
+    let mut changes = Vec::new();
+    let tx = conn.unchecked_transaction()?;
+
+    for entry in std::fs::read_dir(&folder)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(SYNC_LOG_EXTENSION) {
+            continue;
+        }
+        let Some(peer) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if peer == state.device_id {
+            continue;
+        }
+
+        let offset: i64 = tx
+            .query_row(
+                "SELECT log_offset FROM sync_peers WHERE device_id = ?1",
+                params![peer],
+                |row| row.get(0),
+            )
+            .optional()?
+            .unwrap_or(0);
+        let (data, offset) = read_log(&path, offset as u64)?;
+
+        for line in data.split(|&b| b == b'\n').filter(|l| !l.is_empty()) {
+            match serde_json::from_slice::<SyncChange>(line) {
+                Ok(change) => changes.push(change),
+                Err(_) => report.invalid += 1,
+            }
+        }
+
+        tx.execute(
+            r#"
+            INSERT INTO sync_peers (device_id, log_offset, synced_at)
+            VALUES (?1, ?2, datetime('now'))
+            ON CONFLICT (device_id) DO UPDATE SET
+                log_offset = excluded.log_offset,
+                synced_at = excluded.synced_at
+            "#,
+            params![peer, offset as i64],
+        )?;
+        report.peers += 1;
+    }
+
+    // Tags first, so history finds the tags it names instead of making its own
+    changes.sort_by_key(|change| change.record.rank());
+    for change in &changes {
+        report.received += 1;
+        if apply_change(&tx, change, &state.device_id, key)? {
+            report.applied += 1;
+        } else {
+            report.stale += 1;
+        }
+    }
+
+    let pending: Vec<(String, String, String)> = tx
+        .prepare(
+            "SELECT entity, uuid, modified_at FROM sync_rows WHERE pending = 1 ORDER BY modified_at",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut lines = Vec::with_capacity(pending.len());
+    for (entity, uuid, modified_at) in pending {
+        let record = match entity.as_str() {
+            "history" => SyncRecord::History(read_history(&tx, &uuid, state.include_secrets, key)?),
+            "template" => SyncRecord::Template(read_template(&tx, &uuid)?),
+            "tag" => SyncRecord::Tag(read_tag(&tx, &uuid)?),
+            _ => continue,
+        };
+        let change = SyncChange {
+            uuid,
+            modified_at,
+            device_id: state.device_id.clone(),
+            record,
+        };
+        lines.push(serde_json::to_string(&change).map_err(std::io::Error::other)?);
+    }
+
+    if !lines.is_empty() {
+        append_log(&log_path(&folder, &state.device_id), &lines)?;
+        report.sent = lines.len() as i64;
+    }
+    tx.execute("UPDATE sync_rows SET pending = 0 WHERE pending = 1", [])?;
+    tx.execute(
+        "UPDATE sync_state SET last_synced_at = datetime('now') WHERE id = 1",
+        [],
+    )?;
+
+    tx.commit()?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        delete_history, enforce_retention, init_schema, list_tags, list_templates, save_history,
+        save_template, set_history_tags, HistoryItem, NewTemplate, RetentionPolicy,
+    };
+
+    const WIFI: &str = "WIFI:T:WPA;S:Office;P:hunter2;;";
+
+    fn device(folder: &Path) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        configure_sync(&conn, Some(folder), false).unwrap();
+        conn
+    }
+
+    fn save(conn: &Connection, content: &str, label: &str) -> i64 {
+        save_history(
+            conn,
+            &NewHistoryItem {
+                content: content.to_string(),
+                qr_type: crate::qr_type::detect_qr_type(content),
+                label: Some(label.to_string()),
+                style_json: "{}".to_string(),
+                thumbnail: Some("data:image/png;base64,iVBORw0KGgo=".to_string()),
+            },
+        )
+        .unwrap()
+    }
+
+    /// Every history item, trashed ones included
+    fn history(conn: &Connection) -> Vec<HistoryItem> {
+        conn.prepare(&format!(
+            "SELECT {HISTORY_COLUMNS} FROM history h ORDER BY h.id"
+        ))
+        .unwrap()
+        .query_map([], history_from_row)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+    }
+
+    fn raw_content(conn: &Connection, uuid: &str) -> String {
+        conn.query_row(
+            "SELECT content FROM history WHERE uuid = ?1",
+            params![uuid],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    /// Make every pending change on `conn` look older than anything else
+    fn backdate(conn: &Connection) {
+        conn.execute(
+            "UPDATE sync_rows SET modified_at = '2000-01-01T00:00:00.000Z' WHERE pending = 1",
+            [],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_rows_get_uuids() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = device(dir.path());
+        save(&conn, "https://a.example", "A");
+        save(&conn, "https://b.example", "B");
+
+        let items = history(&conn);
+        assert_eq!(items[0].uuid.len(), 36);
+        assert_eq!(&items[0].uuid[14..15], "4");
+        assert_ne!(items[0].uuid, items[1].uuid);
+        assert_eq!(sync_status(&conn).unwrap().pending_changes, 2);
+    }
+
+    #[test]
+    fn test_devices_converge() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = device(dir.path());
+        let b = device(dir.path());
+
+        let id = save(&a, "https://example.com", "Site");
+        set_history_tags(&a, id, &["work".to_string()]).unwrap();
+        save_template(
+            &a,
+            &NewTemplate {
+                name: "Brand".to_string(),
+                style_json: r#"{"dotStyle":"dots"}"#.to_string(),
+                preview: None,
+                is_default: Some(true),
+            },
+        )
+        .unwrap();
+
+        let report = sync_now(&a, None).unwrap();
+        assert_eq!(report.sent, 3); // history, tag and template
+        let report = sync_now(&b, None).unwrap();
+        assert_eq!(report.applied, 3);
+        assert_eq!(report.peers, 1);
+
+        let (item_a, item_b) = (&history(&a)[0], &history(&b)[0]);
+        assert_eq!(item_a.uuid, item_b.uuid);
+        assert_eq!(item_b.label.as_deref(), Some("Site"));
+        assert_eq!(item_b.tags, vec!["work"]);
+        assert!(item_b.thumbnail_ref.is_some());
+        let templates = list_templates(&b).unwrap();
+        assert_eq!(templates[0].name, "Brand");
+        assert!(templates[0].is_default);
+        assert_eq!(list_tags(&b).unwrap().len(), 1);
+
+        // Received changes aren't echoed back
+        assert_eq!(sync_status(&b).unwrap().pending_changes, 0);
+
+        // An edit on B comes back to A
+        b.execute(
+            "UPDATE history SET label = 'Renamed' WHERE uuid = ?1",
+            params![item_b.uuid],
+        )
+        .unwrap();
+        assert_eq!(sync_now(&b, None).unwrap().sent, 1);
+        assert_eq!(sync_now(&a, None).unwrap().applied, 1);
+        assert_eq!(history(&a)[0].label.as_deref(), Some("Renamed"));
+
+        // Nothing new the second time round
+        let report = sync_now(&a, None).unwrap();
+        assert_eq!((report.received, report.sent), (0, 0));
+    }
+
+    #[test]
+    fn test_newest_change_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = device(dir.path());
+        let b = device(dir.path());
+        save(&a, "https://example.com", "Original");
+        sync_now(&a, None).unwrap();
+        sync_now(&b, None).unwrap();
+        let uuid = history(&a)[0].uuid.clone();
+
+        // Both edit the same item; B's edit is older
+        a.execute(
+            "UPDATE history SET label = 'From A' WHERE uuid = ?1",
+            params![uuid],
+        )
+        .unwrap();
+        b.execute(
+            "UPDATE history SET label = 'From B' WHERE uuid = ?1",
+            params![uuid],
+        )
+        .unwrap();
+        backdate(&b);
+
+        sync_now(&a, None).unwrap();
+        let report = sync_now(&b, None).unwrap();
+        assert_eq!(report.applied, 1);
+        sync_now(&a, None).unwrap();
+
+        assert_eq!(history(&a)[0].label.as_deref(), Some("From A"));
+        assert_eq!(history(&b)[0].label.as_deref(), Some("From A"));
+    }
+
+    #[test]
+    fn test_deletes_propagate() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = device(dir.path());
+        let b = device(dir.path());
+        let id = save(&a, "https://example.com", "Site");
+        sync_now(&a, None).unwrap();
+        sync_now(&b, None).unwrap();
+
+        // Trashing syncs as a change, purging as a deletion
+        delete_history(&a, id).unwrap();
+        sync_now(&a, None).unwrap();
+        sync_now(&b, None).unwrap();
+        assert!(history(&b)[0].deleted_at.is_some());
+
+        a.execute("DELETE FROM history WHERE id = ?1", params![id])
+            .unwrap();
+        sync_now(&a, None).unwrap();
+        sync_now(&b, None).unwrap();
+        assert!(history(&b).is_empty());
+    }
+
+    #[test]
+    fn test_retention_pruning_stays_local() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = device(dir.path());
+        let b = device(dir.path());
+        for i in 0..3 {
+            save(&a, &format!("https://{}.example", i), "Site");
+        }
+        sync_now(&a, None).unwrap();
+        sync_now(&b, None).unwrap();
+
+        // A keeps only its newest item; B has no limit
+        let policy = RetentionPolicy {
+            max_items: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(enforce_retention(&a, &policy).unwrap().by_count, 2);
+        assert_eq!(sync_status(&a).unwrap().pending_changes, 0);
+
+        let report = sync_now(&a, None).unwrap();
+        assert_eq!(report.sent, 0);
+        sync_now(&b, None).unwrap();
+
+        assert_eq!(history(&a).len(), 1);
+        assert_eq!(history(&b).len(), 3);
+    }
+
+    #[test]
+    fn test_secrets_stay_local_unless_included() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = device(dir.path());
+        let b = device(dir.path());
+        save(&a, WIFI, "Office");
+        sync_now(&a, None).unwrap();
+        sync_now(&b, None).unwrap();
+
+        let uuid = history(&a)[0].uuid.clone();
+        assert!(!raw_content(&b, &uuid).contains("hunter2"));
+        assert!(history(&b)[0].sensitive);
+
+        // A redacted label change leaves B's copy alone, and the real
+        // content reaches B once A shares secrets
+        configure_sync(&a, Some(dir.path()), true).unwrap();
+        a.execute(
+            "UPDATE history SET label = 'HQ' WHERE uuid = ?1",
+            params![uuid],
+        )
+        .unwrap();
+        sync_now(&a, None).unwrap();
+        sync_now(&b, None).unwrap();
+        assert_eq!(raw_content(&b, &uuid), WIFI);
+        assert_eq!(history(&b)[0].label.as_deref(), Some("HQ"));
+    }
+
+    #[test]
+    fn test_tags_with_the_same_name_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = device(dir.path());
+        let b = device(dir.path());
+        let id_a = save(&a, "https://a.example", "A");
+        let id_b = save(&b, "https://b.example", "B");
+        set_history_tags(&a, id_a, &["Work".to_string()]).unwrap();
+        set_history_tags(&b, id_b, &["work".to_string()]).unwrap();
+
+        for _ in 0..2 {
+            sync_now(&a, None).unwrap();
+            sync_now(&b, None).unwrap();
+        }
+
+        let tag_uuid = |conn: &Connection| -> Vec<String> {
+            conn.prepare("SELECT uuid FROM tags")
+                .unwrap()
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+        assert_eq!(tag_uuid(&a).len(), 1);
+        assert_eq!(tag_uuid(&a), tag_uuid(&b));
+        assert_eq!(list_tags(&a).unwrap()[0].item_count, 2);
+        assert_eq!(list_tags(&b).unwrap()[0].item_count, 2);
+    }
+
+    #[test]
+    fn test_skips_torn_and_invalid_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = device(dir.path());
+        let b = device(dir.path());
+        save(&a, "https://example.com", "Site");
+        sync_now(&a, None).unwrap();
+
+        // A line still being written, then garbage once it completes
+        let device_a = sync_status(&a).unwrap().device_id.unwrap();
+        let path = log_path(dir.path(), &device_a);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"uuid\":").unwrap();
+
+        assert_eq!(sync_now(&b, None).unwrap().applied, 1);
+        file.write_all(b" nonsense\n").unwrap();
+        let report = sync_now(&b, None).unwrap();
+        assert_eq!((report.received, report.invalid), (0, 1));
+
+        // The next append starts on a fresh line
+        save(&a, "https://other.example", "Other");
+        sync_now(&a, None).unwrap();
+        assert_eq!(sync_now(&b, None).unwrap().applied, 1);
+    }
+
+    #[test]
+    fn test_requires_a_folder() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        assert!(matches!(
+            sync_now(&conn, None),
+            Err(SyncError::NotConfigured)
+        ));
+        assert!(matches!(
+            configure_sync(&conn, Some(Path::new("/nonexistent/sync")), false),
+            Err(SyncError::FolderMissing(_))
+        ));
+    }
+}
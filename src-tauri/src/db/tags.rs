@@ -0,0 +1,355 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub item_count: i64,
+}
+
+/// List all tags with how many live history items carry each
+pub fn list_tags(conn: &Connection) -> Result<Vec<Tag>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT t.id, t.name,
+               (SELECT COUNT(*) FROM history_tags ht JOIN history h ON h.id = ht.history_id
+                WHERE ht.tag_id = t.id AND h.deleted_at IS NULL)
+        FROM tags t
+        ORDER BY t.name COLLATE NOCASE
+        "#,
+    )?;
+
+    let tags = stmt
+        .query_map([], |row| {
+            Ok(Tag {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                item_count: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(tags)
+}
+
+/// Find a tag by name, ignoring case
+pub fn find_tag(conn: &Connection, name: &str) -> Result<Option<i64>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT id FROM tags WHERE name = ?1 COLLATE NOCASE",
+        params![name.trim()],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Whether a tag with this ID exists
+pub fn tag_exists(conn: &Connection, id: i64) -> Result<bool, rusqlite::Error> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM tags WHERE id = ?1)",
+        params![id],
+        |row| row.get(0),
+    )
+}
+
+/// Get the ID of a tag, creating it if needed
+pub(super) fn ensure_tag(conn: &Connection, name: &str) -> Result<i64, rusqlite::Error> {
+    if let Some(id) = find_tag(conn, name)? {
+        return Ok(id);
+    }
+    conn.execute("INSERT INTO tags (name) VALUES (?1)", params![name.trim()])?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn clean_names(names: &[String]) -> impl Iterator<Item = &str> {
+    names
+        .iter()
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+}
+
+/// Replace the tags on a history item
+pub fn set_history_tags(
+    conn: &Connection,
+    history_id: i64,
+    names: &[String],
+) -> Result<(), rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM history_tags WHERE history_id = ?1",
+        params![history_id],
+    )?;
+    for name in clean_names(names) {
+        let tag_id = ensure_tag(&tx, name)?;
+        tx.execute(
+            "INSERT OR IGNORE INTO history_tags (history_id, tag_id) VALUES (?1, ?2)",
+            params![history_id, tag_id],
+        )?;
+    }
+    tx.commit()
+}
+
+/// Add tags to several history items; returns how many links were created
+pub fn add_history_tags(
+    conn: &Connection,
+    history_ids: &[i64],
+    names: &[String],
+) -> Result<i64, rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    let mut added = 0;
+    for name in clean_names(names) {
+        let tag_id = ensure_tag(&tx, name)?;
+        for history_id in history_ids {
+            added += tx.execute(
+                r#"
+                INSERT OR IGNORE INTO history_tags (history_id, tag_id)
+                SELECT id, ?2 FROM history WHERE id = ?1
+                "#,
+                params![history_id, tag_id],
+            )? as i64;
+        }
+    }
+    tx.commit()?;
+    Ok(added)
+}
+
+/// Remove tags from several history items; returns how many links were removed
+pub fn remove_history_tags(
+    conn: &Connection,
+    history_ids: &[i64],
+    names: &[String],
+) -> Result<i64, rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    let mut removed = 0;
+    for name in clean_names(names) {
+        let Some(tag_id) = find_tag(&tx, name)? else {
+            continue;
+        };
+        for history_id in history_ids {
+            removed += tx.execute(
+                "DELETE FROM history_tags WHERE history_id = ?1 AND tag_id = ?2",
+                params![history_id, tag_id],
+            )? as i64;
+        }
+    }
+    tx.commit()?;
+    Ok(removed)
+}
+
+/// Rename a tag
+///
+/// Callers should check `find_tag` first; renaming onto an existing name
+/// fails the unique constraint, and `merge_tags` is the way to combine them.
+pub fn rename_tag(conn: &Connection, id: i64, name: &str) -> Result<bool, rusqlite::Error> {
+    let affected = conn.execute(
+        "UPDATE tags SET name = ?1 WHERE id = ?2",
+        params![name.trim(), id],
+    )?;
+    Ok(affected > 0)
+}
+
+/// Fold `source_ids` into `target_id`, deleting the source tags
+///
+/// Returns how many items gained the target tag. Fails with
+/// `QueryReturnedNoRows`, changing nothing, if the target doesn't exist.
+pub fn merge_tags(
+    conn: &Connection,
+    source_ids: &[i64],
+    target_id: i64,
+) -> Result<i64, rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    if !tag_exists(&tx, target_id)? {
+        return Err(rusqlite::Error::QueryReturnedNoRows);
+    }
+    let mut moved = 0;
+    for source_id in source_ids.iter().filter(|id| **id != target_id) {
+        moved += tx.execute(
+            r#"
+            INSERT OR IGNORE INTO history_tags (history_id, tag_id)
+            SELECT history_id, ?2 FROM history_tags WHERE tag_id = ?1
+            "#,
+            params![source_id, target_id],
+        )? as i64;
+        tx.execute("DELETE FROM tags WHERE id = ?1", params![source_id])?;
+    }
+    tx.commit()?;
+    Ok(moved)
+}
+
+/// Delete a tag, removing it from every item
+pub fn delete_tag(conn: &Connection, id: i64) -> Result<bool, rusqlite::Error> {
+    let affected = conn.execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+    Ok(affected > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        count_filtered_history, count_history_search, delete_history, init_schema,
+        list_history_page, purge_history, restore_history, save_history, HistoryFilter,
+        HistorySort, NewHistoryItem,
+    };
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn save(conn: &Connection, content: &str) -> i64 {
+        save_history(
+            conn,
+            &NewHistoryItem {
+                content: content.to_string(),
+                qr_type: "url".to_string(),
+                label: None,
+                style_json: "{}".to_string(),
+                thumbnail: None,
+            },
+        )
+        .unwrap()
+    }
+
+    fn names(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn tag_count(conn: &Connection, name: &str) -> i64 {
+        let filter = HistoryFilter {
+            tags: names(&[name]),
+            ..Default::default()
+        };
+        count_filtered_history(conn, &filter).unwrap()
+    }
+
+    #[test]
+    fn test_set_and_list_tags() {
+        let conn = setup_test_db();
+        let id = save(&conn, "https://example.com/spring");
+
+        set_history_tags(&conn, id, &names(&["Spring", " promo ", "", "PROMO"])).unwrap();
+
+        let tags = list_tags(&conn).unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].name, "promo");
+        assert_eq!(tags[0].item_count, 1);
+
+        let page = list_history_page(
+            &conn,
+            &HistoryFilter::default(),
+            HistorySort::default(),
+            None,
+            10,
+            0,
+        )
+        .unwrap();
+        assert_eq!(page.items[0].tags, names(&["promo", "Spring"]));
+
+        set_history_tags(&conn, id, &names(&["autumn"])).unwrap();
+        assert_eq!(tag_count(&conn, "spring"), 0);
+        assert_eq!(tag_count(&conn, "Autumn"), 1);
+    }
+
+    #[test]
+    fn test_filter_requires_every_tag() {
+        let conn = setup_test_db();
+        let a = save(&conn, "a");
+        let b = save(&conn, "b");
+        add_history_tags(&conn, &[a, b], &names(&["acme"])).unwrap();
+        add_history_tags(&conn, &[a], &names(&["print"])).unwrap();
+
+        let filter = HistoryFilter {
+            tags: names(&["acme", "print"]),
+            ..Default::default()
+        };
+        assert_eq!(count_filtered_history(&conn, &filter).unwrap(), 1);
+
+        assert_eq!(
+            remove_history_tags(&conn, &[a, b], &names(&["acme"])).unwrap(),
+            2
+        );
+        assert_eq!(tag_count(&conn, "acme"), 0);
+    }
+
+    #[test]
+    fn test_rename_and_merge_tags() {
+        let conn = setup_test_db();
+        let a = save(&conn, "a");
+        let b = save(&conn, "b");
+        add_history_tags(&conn, &[a], &names(&["clinet"])).unwrap();
+        add_history_tags(&conn, &[a, b], &names(&["client"])).unwrap();
+        add_history_tags(&conn, &[b], &names(&["customer"])).unwrap();
+
+        let typo = find_tag(&conn, "clinet").unwrap().unwrap();
+        let client = find_tag(&conn, "CLIENT").unwrap().unwrap();
+        let customer = find_tag(&conn, "customer").unwrap().unwrap();
+
+        // Renaming onto an existing name is rejected
+        assert!(rename_tag(&conn, typo, "client").is_err());
+        assert!(rename_tag(&conn, customer, "Clients").unwrap());
+
+        assert_eq!(merge_tags(&conn, &[typo, client], customer).unwrap(), 1);
+        assert_eq!(list_tags(&conn).unwrap().len(), 1);
+        assert_eq!(tag_count(&conn, "clients"), 2);
+    }
+
+    #[test]
+    fn test_merge_into_missing_tag_changes_nothing() {
+        let conn = setup_test_db();
+        let a = save(&conn, "a");
+        add_history_tags(&conn, &[a], &names(&["client"])).unwrap();
+        let client = find_tag(&conn, "client").unwrap().unwrap();
+
+        let missing = client + 100;
+        assert!(!tag_exists(&conn, missing).unwrap());
+        assert!(matches!(
+            merge_tags(&conn, &[client], missing),
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        ));
+
+        assert_eq!(tag_count(&conn, "client"), 1);
+        let links: i64 = conn
+            .query_row("SELECT COUNT(*) FROM history_tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(links, 1);
+    }
+
+    #[test]
+    fn test_tags_are_searchable() {
+        let conn = setup_test_db();
+        let id = save(&conn, "https://example.com");
+        add_history_tags(&conn, &[id], &names(&["campaign"])).unwrap();
+
+        assert_eq!(count_history_search(&conn, "tag:campaign").unwrap(), 1);
+
+        let tag = find_tag(&conn, "campaign").unwrap().unwrap();
+        rename_tag(&conn, tag, "launch").unwrap();
+        assert_eq!(count_history_search(&conn, "tag:campaign").unwrap(), 0);
+        assert_eq!(count_history_search(&conn, "tag:launch").unwrap(), 1);
+
+        delete_tag(&conn, tag).unwrap();
+        assert_eq!(count_history_search(&conn, "tag:launch").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_trashed_items_keep_tags_until_purged() {
+        let conn = setup_test_db();
+        let id = save(&conn, "a");
+        add_history_tags(&conn, &[id], &names(&["x"])).unwrap();
+
+        delete_history(&conn, id).unwrap();
+        assert_eq!(list_tags(&conn).unwrap()[0].item_count, 0);
+
+        restore_history(&conn, &[id]).unwrap();
+        assert_eq!(list_tags(&conn).unwrap()[0].item_count, 1);
+
+        delete_history(&conn, id).unwrap();
+        purge_history(&conn, &[id]).unwrap();
+        let links: i64 = conn
+            .query_row("SELECT COUNT(*) FROM history_tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(links, 0);
+    }
+}
@@ -1,15 +1,19 @@
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, Row};
 use serde::{Deserialize, Serialize};
 
+use super::blobs::put_optional_blob;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Template {
     pub id: i64,
     pub name: String,
     pub style_json: String,
-    pub preview: Option<String>,
+    pub preview_ref: Option<String>, // blob hash; fetch the image with `get_blob`
     pub is_default: bool,
     pub created_at: String,
+    pub deleted_at: Option<String>, // set while the template is in the trash
+    pub uuid: String,               // stable across devices, unlike `id`
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,52 +25,69 @@ pub struct NewTemplate {
     pub is_default: Option<bool>,
 }
 
+const TEMPLATE_COLUMNS: &str =
+    "id, name, style_json, preview_hash, is_default, created_at, deleted_at, uuid";
+
+fn template_from_row(row: &Row) -> Result<Template, rusqlite::Error> {
+    Ok(Template {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        style_json: row.get(2)?,
+        preview_ref: row.get(3)?,
+        is_default: row.get::<_, i64>(4)? != 0,
+        created_at: row.get(5)?,
+        deleted_at: row.get(6)?,
+        uuid: row.get(7)?,
+    })
+}
+
 /// List all templates
 pub fn list_templates(conn: &Connection) -> Result<Vec<Template>, rusqlite::Error> {
-    let mut stmt = conn.prepare(
+    let query = format!(
         r#"
-        SELECT id, name, style_json, preview, is_default, created_at
+        SELECT {}
         FROM templates
+        WHERE deleted_at IS NULL
         ORDER BY is_default DESC, created_at DESC
         "#,
-    )?;
+        TEMPLATE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&query)?;
 
     let items = stmt
-        .query_map([], |row| {
-            Ok(Template {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                style_json: row.get(2)?,
-                preview: row.get(3)?,
-                is_default: row.get::<_, i64>(4)? != 0,
-                created_at: row.get(5)?,
-            })
-        })?
+        .query_map([], template_from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(items)
 }
 
-/// Get a template by ID
-pub fn get_template(conn: &Connection, id: i64) -> Result<Option<Template>, rusqlite::Error> {
-    let mut stmt = conn.prepare(
+/// List templates in the trash, most recently deleted first
+pub fn list_template_trash(conn: &Connection) -> Result<Vec<Template>, rusqlite::Error> {
+    let query = format!(
         r#"
-        SELECT id, name, style_json, preview, is_default, created_at
+        SELECT {}
         FROM templates
-        WHERE id = ?1
+        WHERE deleted_at IS NOT NULL
+        ORDER BY deleted_at DESC, id DESC
         "#,
-    )?;
+        TEMPLATE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&query)?;
+
+    let items = stmt
+        .query_map([], template_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(items)
+}
 
-    let result = stmt.query_row(params![id], |row| {
-        Ok(Template {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            style_json: row.get(2)?,
-            preview: row.get(3)?,
-            is_default: row.get::<_, i64>(4)? != 0,
-            created_at: row.get(5)?,
-        })
-    });
+/// Get a template by ID
+pub fn get_template(conn: &Connection, id: i64) -> Result<Option<Template>, rusqlite::Error> {
+    let query = format!(
+        "SELECT {} FROM templates WHERE id = ?1 AND deleted_at IS NULL",
+        TEMPLATE_COLUMNS
+    );
+    let result = conn.query_row(&query, params![id], template_from_row);
 
     match result {
         Ok(template) => Ok(Some(template)),
@@ -77,20 +98,24 @@ pub fn get_template(conn: &Connection, id: i64) -> Result<Option<Template>, rusq
 
 /// Save a new template
 pub fn save_template(conn: &Connection, template: &NewTemplate) -> Result<i64, rusqlite::Error> {
-    conn.execute(
+    let tx = conn.unchecked_transaction()?;
+    let preview_hash = put_optional_blob(&tx, template.preview.as_deref())?;
+    tx.execute(
         r#"
-        INSERT INTO templates (name, style_json, preview, is_default)
+        INSERT INTO templates (name, style_json, preview_hash, is_default)
         VALUES (?1, ?2, ?3, ?4)
         "#,
         params![
             template.name,
             template.style_json,
-            template.preview,
+            preview_hash,
             template.is_default.unwrap_or(false) as i64
         ],
     )?;
+    let id = tx.last_insert_rowid();
+    tx.commit()?;
 
-    Ok(conn.last_insert_rowid())
+    Ok(id)
 }
 
 /// Update a template
@@ -99,32 +124,66 @@ pub fn update_template(
     id: i64,
     template: &NewTemplate,
 ) -> Result<bool, rusqlite::Error> {
-    let affected = conn.execute(
+    let tx = conn.unchecked_transaction()?;
+    let preview_hash = put_optional_blob(&tx, template.preview.as_deref())?;
+    let affected = tx.execute(
         r#"
         UPDATE templates
-        SET name = ?1, style_json = ?2, preview = ?3, is_default = ?4
-        WHERE id = ?5
+        SET name = ?1, style_json = ?2, preview_hash = ?3, is_default = ?4
+        WHERE id = ?5 AND deleted_at IS NULL
         "#,
         params![
             template.name,
             template.style_json,
-            template.preview,
+            preview_hash,
             template.is_default.unwrap_or(false) as i64,
             id
         ],
     )?;
+    tx.commit()?;
 
     Ok(affected > 0)
 }
 
-/// Delete a template
+/// Move a template to the trash
+///
+/// A trashed template stops being the default.
 pub fn delete_template(conn: &Connection, id: i64) -> Result<bool, rusqlite::Error> {
-    let affected = conn.execute("DELETE FROM templates WHERE id = ?1", params![id])?;
+    let affected = conn.execute(
+        r#"
+        UPDATE templates SET deleted_at = datetime('now'), is_default = 0
+        WHERE id = ?1 AND deleted_at IS NULL
+        "#,
+        params![id],
+    )?;
+    Ok(affected > 0)
+}
+
+/// Bring a template back from the trash
+pub fn restore_template(conn: &Connection, id: i64) -> Result<bool, rusqlite::Error> {
+    let affected = conn.execute(
+        "UPDATE templates SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+        params![id],
+    )?;
+    Ok(affected > 0)
+}
+
+/// Permanently delete a trashed template
+pub fn purge_template(conn: &Connection, id: i64) -> Result<bool, rusqlite::Error> {
+    let affected = conn.execute(
+        "DELETE FROM templates WHERE id = ?1 AND deleted_at IS NOT NULL",
+        params![id],
+    )?;
     Ok(affected > 0)
 }
 
 /// Set a template as default (unsets others)
 pub fn set_default_template(conn: &Connection, id: i64) -> Result<bool, rusqlite::Error> {
+    // Trashed templates can't become the default
+    if get_template(conn, id)?.is_none() {
+        return Ok(false);
+    }
+
     // Unset all defaults
     conn.execute("UPDATE templates SET is_default = 0", [])?;
 
@@ -140,25 +199,19 @@ pub fn set_default_template(conn: &Connection, id: i64) -> Result<bool, rusqlite
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::get_blob;
 
     fn setup_test_db() -> Connection {
         let conn = Connection::open_in_memory().unwrap();
-        conn.execute_batch(
-            r#"
-            CREATE TABLE templates (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL,
-                style_json TEXT NOT NULL,
-                preview TEXT,
-                is_default INTEGER DEFAULT 0,
-                created_at TEXT DEFAULT (datetime('now'))
-            );
-            "#,
-        )
-        .unwrap();
+        crate::db::init_schema(&conn).unwrap();
         conn
     }
 
+    fn preview_of(conn: &Connection, template: &Template) -> Option<String> {
+        let hash = template.preview_ref.as_ref()?;
+        get_blob(conn, hash).unwrap().map(|blob| blob.to_data_url())
+    }
+
     fn create_test_template(name: &str) -> NewTemplate {
         NewTemplate {
             name: name.to_string(),
@@ -196,7 +249,10 @@ mod tests {
 
         assert_eq!(retrieved.name, "Modern");
         assert!(retrieved.is_default);
-        assert_eq!(retrieved.preview, Some("data:image/png;base64,preview".to_string()));
+        assert_eq!(
+            preview_of(&conn, &retrieved),
+            Some("data:image/png;base64,preview".to_string())
+        );
     }
 
     #[test]
@@ -272,7 +328,10 @@ mod tests {
         let retrieved = get_template(&conn, id).unwrap().unwrap();
         assert_eq!(retrieved.name, "Updated");
         assert_eq!(retrieved.style_json, r#"{"dotStyle":"square"}"#);
-        assert_eq!(retrieved.preview, Some("new-preview".to_string()));
+        assert_eq!(
+            preview_of(&conn, &retrieved),
+            Some("new-preview".to_string())
+        );
         assert!(retrieved.is_default);
     }
 
@@ -377,4 +436,30 @@ mod tests {
 
         assert!(!template.created_at.is_empty());
     }
+
+    #[test]
+    fn test_trash_restore_and_purge_template() {
+        let conn = setup_test_db();
+        let mut template = create_test_template("Brand");
+        template.is_default = Some(true);
+        let id = save_template(&conn, &template).unwrap();
+
+        assert!(delete_template(&conn, id).unwrap());
+        assert!(!delete_template(&conn, id).unwrap());
+        assert!(get_template(&conn, id).unwrap().is_none());
+        assert!(!set_default_template(&conn, id).unwrap());
+
+        let trash = list_template_trash(&conn).unwrap();
+        assert_eq!(trash.len(), 1);
+        assert!(trash[0].deleted_at.is_some());
+        assert!(!trash[0].is_default);
+
+        assert!(restore_template(&conn, id).unwrap());
+        assert_eq!(list_templates(&conn).unwrap().len(), 1);
+        assert!(!purge_template(&conn, id).unwrap());
+
+        delete_template(&conn, id).unwrap();
+        assert!(purge_template(&conn, id).unwrap());
+        assert!(list_template_trash(&conn).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,136 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// Days trashed items are kept before being purged automatically
+pub const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashPurgeReport {
+    pub history: i64,
+    pub templates: i64,
+}
+
+/// Permanently delete history and templates trashed more than `days` ago
+///
+/// `days <= 0` disables automatic purging.
+pub fn purge_expired_trash(
+    conn: &Connection,
+    days: i64,
+) -> Result<TrashPurgeReport, rusqlite::Error> {
+    if days <= 0 {
+        return Ok(TrashPurgeReport::default());
+    }
+
+    let cutoff = format!("-{} days", days);
+    let tx = conn.unchecked_transaction()?;
+    let history = tx.execute(
+        "DELETE FROM history WHERE deleted_at < datetime('now', ?1)",
+        params![cutoff],
+    )?;
+    let templates = tx.execute(
+        "DELETE FROM templates WHERE deleted_at < datetime('now', ?1)",
+        params![cutoff],
+    )?;
+    tx.commit()?;
+
+    Ok(TrashPurgeReport {
+        history: history as i64,
+        templates: templates as i64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        delete_history, delete_template, get_settings, init_schema, list_template_trash,
+        save_history, save_template, NewHistoryItem, NewTemplate,
+    };
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn trash_history(conn: &Connection, deleted_at: &str) -> i64 {
+        let id = save_history(
+            conn,
+            &NewHistoryItem {
+                content: "https://example.com".to_string(),
+                qr_type: "url".to_string(),
+                label: None,
+                style_json: "{}".to_string(),
+                thumbnail: None,
+            },
+        )
+        .unwrap();
+        delete_history(conn, id).unwrap();
+        conn.execute(
+            "UPDATE history SET deleted_at = ?1 WHERE id = ?2",
+            params![deleted_at, id],
+        )
+        .unwrap();
+        id
+    }
+
+    fn history_rows(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_retention_setting() {
+        let conn = setup_test_db();
+        assert_eq!(
+            get_settings(&conn).unwrap().trash_retention_days,
+            DEFAULT_TRASH_RETENTION_DAYS
+        );
+
+        // Rows written before the typed settings stored the bare number
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES ('trash_retention_days', '7')",
+            [],
+        )
+        .unwrap();
+        assert_eq!(get_settings(&conn).unwrap().trash_retention_days, 7);
+    }
+
+    #[test]
+    fn test_purge_expired_trash() {
+        let conn = setup_test_db();
+        trash_history(&conn, "2000-01-01 00:00:00");
+        let recent = trash_history(&conn, "2999-01-01 00:00:00");
+
+        let template = save_template(
+            &conn,
+            &NewTemplate {
+                name: "Old".to_string(),
+                style_json: "{}".to_string(),
+                preview: None,
+                is_default: None,
+            },
+        )
+        .unwrap();
+        delete_template(&conn, template).unwrap();
+        conn.execute(
+            "UPDATE templates SET deleted_at = '2000-01-01 00:00:00'",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(purge_expired_trash(&conn, 0).unwrap().history, 0);
+
+        let report = purge_expired_trash(&conn, 30).unwrap();
+        assert_eq!(report.history, 1);
+        assert_eq!(report.templates, 1);
+        assert_eq!(history_rows(&conn), 1);
+        assert!(list_template_trash(&conn).unwrap().is_empty());
+
+        let remaining: i64 = conn
+            .query_row("SELECT id FROM history", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, recent);
+    }
+}
@@ -0,0 +1,422 @@
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::backup::{backup_to, io_error};
+
+/// Workspace that lives directly in the data directory
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// A file with this name next to the executable turns on portable mode
+pub const PORTABLE_MARKER: &str = "portable";
+
+const APP_DIR_NAME: &str = "com.jonathanlam.qr-foundry";
+const CONFIG_FILE: &str = "config.json";
+const DB_FILE: &str = "qr-foundry.db";
+const KEYFILE: &str = "qr-foundry.key";
+const WORKSPACES_DIR: &str = "workspaces";
+
+/// Where the app keeps its data, stored as `config.json` in the home dir
+///
+/// The home dir is fixed (the app data dir, or `data` next to the executable
+/// in portable mode) so the config can always be found; the databases
+/// themselves may live elsewhere.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppConfig {
+    pub data_dir: Option<PathBuf>, // None = the home dir
+    pub workspace: Option<String>, // None = the default workspace
+}
+
+/// Files belonging to one workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspacePaths {
+    pub db: PathBuf,
+    pub backups: PathBuf,
+    pub keyfile: PathBuf, // history encryption key, when using a keyfile
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceInfo {
+    pub name: String,
+    pub path: String, // database file
+    pub bytes: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceCopyReport {
+    pub copied: Vec<String>,
+    pub skipped: Vec<String>, // already present in the target
+}
+
+/// Directory holding the config, and the data unless moved elsewhere
+pub fn home_dir() -> PathBuf {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+
+    exe_dir
+        .and_then(|dir| portable_home(&dir))
+        .unwrap_or_else(|| {
+            dirs::data_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(APP_DIR_NAME)
+        })
+}
+
+/// Home dir in portable mode, if the marker file sits in `exe_dir`
+pub fn portable_home(exe_dir: &Path) -> Option<PathBuf> {
+    exe_dir
+        .join(PORTABLE_MARKER)
+        .is_file()
+        .then(|| exe_dir.join("data"))
+}
+
+pub fn is_portable() -> bool {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().and_then(portable_home))
+        .is_some()
+}
+
+/// Read the config, falling back to defaults if it's missing or unreadable
+pub fn load_config(home: &Path) -> AppConfig {
+    std::fs::read_to_string(home.join(CONFIG_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_config(home: &Path, config: &AppConfig) -> std::io::Result<()> {
+    std::fs::create_dir_all(home)?;
+    let json = serde_json::to_string_pretty(config)?;
+    std::fs::write(home.join(CONFIG_FILE), json)
+}
+
+/// The data directory the config points at
+pub fn data_dir(home: &Path, config: &AppConfig) -> PathBuf {
+    config
+        .data_dir
+        .clone()
+        .unwrap_or_else(|| home.to_path_buf())
+}
+
+/// Paths for a workspace; the default one keeps the original layout
+pub fn workspace_paths(data_dir: &Path, name: &str) -> WorkspacePaths {
+    let dir = if name == DEFAULT_WORKSPACE {
+        data_dir.to_path_buf()
+    } else {
+        data_dir.join(WORKSPACES_DIR).join(name)
+    };
+
+    WorkspacePaths {
+        db: dir.join(DB_FILE),
+        backups: dir.join("backups"),
+        keyfile: dir.join(KEYFILE),
+    }
+}
+
+/// Trim a workspace name and check it's usable as a directory name
+pub fn validate_workspace_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Workspace name cannot be empty".to_string());
+    }
+    if name.chars().count() > 64 {
+        return Err("Workspace name must be 64 characters or fewer".to_string());
+    }
+    if name.starts_with('.') {
+        return Err("Workspace name cannot start with a dot".to_string());
+    }
+    let allowed = |c: char| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_');
+    if !name.chars().all(allowed) {
+        return Err(
+            "Workspace names may only use letters, numbers, spaces, '-' and '_'".to_string(),
+        );
+    }
+    Ok(name.to_string())
+}
+
+/// Workspaces in `data_dir` with a database, default first then by name
+pub fn list_workspaces(data_dir: &Path) -> Vec<WorkspaceInfo> {
+    let mut names: Vec<String> = std::fs::read_dir(data_dir.join(WORKSPACES_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name != DEFAULT_WORKSPACE && validate_workspace_name(name).is_ok())
+        .filter(|name| workspace_paths(data_dir, name).db.is_file())
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names.insert(0, DEFAULT_WORKSPACE.to_string());
+
+    names
+        .into_iter()
+        .map(|name| {
+            let db = workspace_paths(data_dir, &name).db;
+            WorkspaceInfo {
+                bytes: db_bytes(&db),
+                path: db.to_string_lossy().to_string(),
+                name,
+            }
+        })
+        .collect()
+}
+
+/// Size of a database including its write-ahead log
+fn db_bytes(db: &Path) -> i64 {
+    let wal = PathBuf::from(format!("{}-wal", db.display()));
+    [db, wal.as_path()]
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|meta| meta.len() as i64)
+        .sum()
+}
+
+/// Delete a workspace's database and backups
+pub fn delete_workspace(data_dir: &Path, name: &str) -> std::io::Result<()> {
+    if let Err(message) = validate_workspace_name(name) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            message,
+        ));
+    }
+    if name == DEFAULT_WORKSPACE {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "The default workspace can't be deleted",
+        ));
+    }
+    let dir = data_dir.join(WORKSPACES_DIR).join(name);
+    std::fs::remove_dir_all(dir)
+}
+
+/// Copy every workspace database from one data directory to another
+///
+/// Workspaces that already have a database in `to` are left alone. Backups
+/// stay where they are.
+pub fn copy_workspaces(from: &Path, to: &Path) -> Result<WorkspaceCopyReport, rusqlite::Error> {
+    let mut report = WorkspaceCopyReport::default();
+
+    for workspace in list_workspaces(from) {
+        let source_paths = workspace_paths(from, &workspace.name);
+        let target_paths = workspace_paths(to, &workspace.name);
+        let (source, target) = (&source_paths.db, &target_paths.db);
+        if !source.is_file() {
+            continue;
+        }
+        if target.exists() {
+            report.skipped.push(workspace.name);
+            continue;
+        }
+
+        if let Some(dir) = target.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| io_error(dir, e))?;
+        }
+        let conn = Connection::open_with_flags(source, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        backup_to(&conn, target)?;
+        // Encrypted history is unreadable without its keyfile
+        if source_paths.keyfile.is_file() {
+            std::fs::copy(&source_paths.keyfile, &target_paths.keyfile)
+                .map_err(|e| io_error(&target_paths.keyfile, e))?;
+        }
+        report.copied.push(workspace.name);
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{DbState, Workspace};
+
+    fn history_count(state: &DbState) -> i64 {
+        state
+            .workspace()
+            .pool
+            .reader()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_workspace_paths() {
+        let data = Path::new("/data");
+        assert_eq!(
+            workspace_paths(data, DEFAULT_WORKSPACE),
+            WorkspacePaths {
+                db: PathBuf::from("/data/qr-foundry.db"),
+                backups: PathBuf::from("/data/backups"),
+                keyfile: PathBuf::from("/data/qr-foundry.key"),
+            }
+        );
+        assert_eq!(
+            workspace_paths(data, "Client A").db,
+            PathBuf::from("/data/workspaces/Client A/qr-foundry.db")
+        );
+    }
+
+    #[test]
+    fn test_validate_workspace_name() {
+        assert_eq!(validate_workspace_name("  Client A ").unwrap(), "Client A");
+        assert!(validate_workspace_name("café_2-b").is_ok());
+
+        for bad in ["", "   ", "../escape", "a/b", "a\\b", ".hidden", "x:y"] {
+            assert!(validate_workspace_name(bad).is_err(), "{:?}", bad);
+        }
+        assert!(validate_workspace_name(&"w".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load_config(dir.path()), AppConfig::default());
+        assert_eq!(data_dir(dir.path(), &AppConfig::default()), dir.path());
+
+        let config = AppConfig {
+            data_dir: Some(PathBuf::from("/elsewhere")),
+            workspace: Some("Client A".to_string()),
+        };
+        save_config(dir.path(), &config).unwrap();
+        assert_eq!(load_config(dir.path()), config);
+        assert_eq!(data_dir(dir.path(), &config), PathBuf::from("/elsewhere"));
+
+        std::fs::write(dir.path().join(CONFIG_FILE), "{ not json").unwrap();
+        assert_eq!(load_config(dir.path()), AppConfig::default());
+    }
+
+    #[test]
+    fn test_portable_home() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(portable_home(dir.path()), None);
+
+        std::fs::write(dir.path().join(PORTABLE_MARKER), "").unwrap();
+        assert_eq!(portable_home(dir.path()), Some(dir.path().join("data")));
+    }
+
+    #[test]
+    fn test_list_and_delete_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["beta", "Alpha"] {
+            let paths = workspace_paths(dir.path(), name);
+            std::fs::create_dir_all(paths.db.parent().unwrap()).unwrap();
+            std::fs::write(&paths.db, "db").unwrap();
+        }
+        // A folder without a database isn't a workspace
+        std::fs::create_dir_all(dir.path().join(WORKSPACES_DIR).join("empty")).unwrap();
+
+        let names: Vec<String> = list_workspaces(dir.path())
+            .into_iter()
+            .map(|workspace| workspace.name)
+            .collect();
+        assert_eq!(names, vec!["default", "Alpha", "beta"]);
+
+        delete_workspace(dir.path(), "beta").unwrap();
+        assert_eq!(list_workspaces(dir.path()).len(), 2);
+        assert!(delete_workspace(dir.path(), DEFAULT_WORKSPACE).is_err());
+        assert!(delete_workspace(&dir.path().join(WORKSPACES_DIR), "..").is_err());
+        assert!(dir.path().join(WORKSPACES_DIR).join("Alpha").is_dir());
+    }
+
+    #[test]
+    fn test_switch_workspaces() {
+        let home = tempfile::tempdir().unwrap();
+        let state = DbState::open(home.path().to_path_buf()).unwrap();
+        assert_eq!(state.workspace().name, DEFAULT_WORKSPACE);
+        state
+            .workspace()
+            .pool
+            .writer()
+            .execute(
+                "INSERT INTO history (content, qr_type, style_json) VALUES ('a', 'text', '{}')",
+                [],
+            )
+            .unwrap();
+
+        let client = Workspace::open(home.path(), "Client A").unwrap();
+        state.activate(client).unwrap();
+        assert_eq!(state.workspace().name, "Client A");
+        assert_eq!(history_count(&state), 0);
+        assert!(state.workspace().paths.backups.is_dir());
+
+        // The choice survives a restart
+        let reopened = DbState::open(home.path().to_path_buf()).unwrap();
+        assert_eq!(reopened.workspace().name, "Client A");
+        drop(reopened);
+
+        let default = Workspace::open(home.path(), DEFAULT_WORKSPACE).unwrap();
+        state.activate(default).unwrap();
+        assert_eq!(history_count(&state), 1);
+        assert_eq!(load_config(home.path()), AppConfig::default());
+
+        // A missing workspace falls back to the default
+        save_config(
+            home.path(),
+            &AppConfig {
+                data_dir: None,
+                workspace: Some("gone".to_string()),
+            },
+        )
+        .unwrap();
+        let reopened = DbState::open(home.path().to_path_buf()).unwrap();
+        assert_eq!(reopened.workspace().name, DEFAULT_WORKSPACE);
+    }
+
+    #[test]
+    fn test_unavailable_data_dir_falls_back_to_home() {
+        let home = tempfile::tempdir().unwrap();
+        let config = AppConfig {
+            data_dir: Some(home.path().join("unmounted")),
+            workspace: Some("Client A".to_string()),
+        };
+        save_config(home.path(), &config).unwrap();
+
+        let state = DbState::open(home.path().to_path_buf()).unwrap();
+        assert_eq!(state.workspace().data_dir, home.path());
+        assert_eq!(state.workspace().name, DEFAULT_WORKSPACE);
+        assert!(state.data_dir_error().unwrap().contains("unmounted"));
+
+        // The missing dir isn't created, and is tried again next launch
+        assert!(!home.path().join("unmounted").exists());
+        assert_eq!(load_config(home.path()), config);
+
+        let default = Workspace::open(home.path(), DEFAULT_WORKSPACE).unwrap();
+        state.activate(default).unwrap();
+        assert!(state.data_dir_error().is_none());
+    }
+
+    #[test]
+    fn test_failed_automatic_backup_is_reported() {
+        let home = tempfile::tempdir().unwrap();
+        // A file where the backups folder should be makes the backup fail
+        std::fs::write(workspace_paths(home.path(), DEFAULT_WORKSPACE).backups, "").unwrap();
+
+        let workspace = Workspace::open(home.path(), DEFAULT_WORKSPACE).unwrap();
+        assert!(workspace
+            .auto_backup_error()
+            .unwrap()
+            .starts_with("Automatic backup failed"));
+
+        let ok = tempfile::tempdir().unwrap();
+        let workspace = Workspace::open(ok.path(), DEFAULT_WORKSPACE).unwrap();
+        assert!(workspace.auto_backup_error().is_none());
+    }
+
+    #[test]
+    fn test_copy_workspaces() {
+        let from = tempfile::tempdir().unwrap();
+        let to = tempfile::tempdir().unwrap();
+        Workspace::open(from.path(), DEFAULT_WORKSPACE).unwrap();
+        Workspace::open(from.path(), "Client A").unwrap();
+        Workspace::open(to.path(), "Client A").unwrap();
+
+        let report = copy_workspaces(from.path(), to.path()).unwrap();
+        assert_eq!(report.copied, vec!["default"]);
+        assert_eq!(report.skipped, vec!["Client A"]);
+        assert!(workspace_paths(to.path(), DEFAULT_WORKSPACE).db.is_file());
+    }
+}
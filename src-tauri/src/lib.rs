@@ -1,16 +1,55 @@
 mod commands;
 mod db;
+mod qr_type;
 
 use commands::{
     // Export commands
     copy_image_to_clipboard, export_png, export_svg, pick_image_file,
     // Validation commands
-    scan_qr_from_data, scan_qr_from_file, validate_qr,
+    scan_qr_from_clipboard, scan_qr_from_data, scan_qr_from_file, validate_qr,
+    // Bulk scan commands
+    bulk_scan_folder, pick_scan_folder,
+    // Frame scan commands
+    scan_qr_frames, scan_qr_sequence,
+    // PDF scan commands
+    pick_pdf_file, scan_qr_from_pdf,
+    // Print size commands
+    estimate_print_size,
+    // URL risk commands
+    analyze_url_risk,
     // History commands
-    history_clear, history_delete, history_list, history_save,
+    history_clear, history_delete, history_duplicates, history_empty_trash, history_list,
+    history_merge_duplicates, history_purge, history_restore, history_reveal, history_save,
+    history_search, history_set_pinned,
+    // Image commands
+    blob_get,
+    // Tag commands
+    history_set_tags, tag_assign, tag_delete, tag_list, tag_merge, tag_rename, tag_unassign,
+    // Folder commands
+    folder_create, folder_delete, folder_list, folder_move, folder_rename, history_move_to_folder,
+    // Scan history commands
+    scan_clear, scan_delete, scan_export, scan_list, scan_promote, scan_reveal, scan_save,
     // Template commands
-    template_delete, template_get, template_list, template_save, template_set_default,
-    template_update,
+    template_delete, template_get, template_list, template_purge, template_restore, template_save,
+    template_set_default, template_trash_list, template_update,
+    // Trash commands
+    trash_get_retention, trash_purge_expired, trash_set_retention,
+    // Storage commands
+    retention_enforce, retention_get_policy, retention_set_policy, storage_report, storage_vacuum,
+    // Settings commands
+    settings_get, settings_reset, settings_update,
+    // Library commands
+    library_export, library_import, pick_library_file,
+    // Sync commands
+    pick_sync_folder, sync_configure, sync_now, sync_status,
+    // Encryption commands
+    encryption_disable, encryption_enable, encryption_lock, encryption_status, encryption_unlock,
+    // Workspace commands
+    data_dir_set, pick_data_dir, workspace_create, workspace_delete, workspace_list,
+    workspace_switch,
+    // Backup commands
+    backup_create, backup_list, backup_restore, backup_validate, db_integrity_check, db_recover,
+    pick_backup_file,
     // Batch commands
     batch_generate_zip, batch_parse_csv, batch_parse_csv_content, batch_save_files, batch_validate,
     pick_csv_file,
@@ -36,6 +75,20 @@ pub fn run() {
             validate_qr,
             scan_qr_from_file,
             scan_qr_from_data,
+            scan_qr_from_clipboard,
+            // Bulk scan
+            bulk_scan_folder,
+            pick_scan_folder,
+            // Frame scan
+            scan_qr_frames,
+            scan_qr_sequence,
+            // PDF scan
+            scan_qr_from_pdf,
+            pick_pdf_file,
+            // Print size
+            estimate_print_size,
+            // URL risk
+            analyze_url_risk,
             // Export
             export_png,
             export_svg,
@@ -46,6 +99,39 @@ pub fn run() {
             history_save,
             history_delete,
             history_clear,
+            history_search,
+            history_set_pinned,
+            history_restore,
+            history_purge,
+            history_empty_trash,
+            history_reveal,
+            history_duplicates,
+            history_merge_duplicates,
+            // Images
+            blob_get,
+            // Tags
+            tag_list,
+            history_set_tags,
+            tag_assign,
+            tag_unassign,
+            tag_rename,
+            tag_merge,
+            tag_delete,
+            // Folders
+            folder_list,
+            folder_create,
+            folder_rename,
+            folder_move,
+            folder_delete,
+            history_move_to_folder,
+            // Scan history
+            scan_list,
+            scan_save,
+            scan_delete,
+            scan_clear,
+            scan_export,
+            scan_promote,
+            scan_reveal,
             // Templates
             template_list,
             template_get,
@@ -53,6 +139,53 @@ pub fn run() {
             template_update,
             template_delete,
             template_set_default,
+            template_trash_list,
+            template_restore,
+            template_purge,
+            // Trash
+            trash_get_retention,
+            trash_set_retention,
+            trash_purge_expired,
+            // Storage
+            retention_get_policy,
+            retention_set_policy,
+            retention_enforce,
+            storage_report,
+            storage_vacuum,
+            // Settings
+            settings_get,
+            settings_update,
+            settings_reset,
+            // Library
+            library_export,
+            library_import,
+            pick_library_file,
+            // Sync
+            sync_status,
+            sync_configure,
+            sync_now,
+            pick_sync_folder,
+            // Encryption
+            encryption_status,
+            encryption_enable,
+            encryption_unlock,
+            encryption_lock,
+            encryption_disable,
+            // Workspaces
+            workspace_list,
+            workspace_create,
+            workspace_switch,
+            workspace_delete,
+            data_dir_set,
+            pick_data_dir,
+            // Backup
+            backup_create,
+            backup_list,
+            backup_validate,
+            backup_restore,
+            db_integrity_check,
+            db_recover,
+            pick_backup_file,
             // Batch
             batch_parse_csv,
             batch_parse_csv_content,
@@ -0,0 +1,488 @@
+use serde::{Deserialize, Serialize};
+
+/// Every QR type the app understands, shared by the scanner and CSV import
+///
+/// Mirrored with `TYPE_ALIASES` in src/lib/qrTypes.ts for the web build.
+pub const QR_TYPES: &[&str] = &[
+    "url", "text", "wifi", "vcard", "email", "sms", "phone", "geo", "calendar", "otp", "crypto",
+    "payment", "app", "media",
+];
+
+/// Alternative spellings accepted in CSV `type` columns
+const TYPE_ALIASES: &[(&str, &str)] = &[
+    ("link", "url"),
+    ("contact", "vcard"),
+    ("mecard", "vcard"),
+    ("mail", "email"),
+    ("mailto", "email"),
+    ("tel", "phone"),
+    ("location", "geo"),
+    ("event", "calendar"),
+    ("vevent", "calendar"),
+    ("totp", "otp"),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeDetection {
+    pub qr_type: String,
+    pub subtype: Option<String>,
+    pub confidence: f32,
+}
+
+/// One recognizable payload format
+///
+/// `detect` receives the trimmed content and its lowercase form, and returns a
+/// confidence between 0 and 1 when the content matches. Add a rule to `RULES`
+/// to teach the detector a new scheme.
+struct TypeRule {
+    qr_type: &'static str,
+    subtype: Option<&'static str>,
+    detect: fn(&str, &str) -> Option<f32>,
+}
+
+/// Confidence reported when nothing more specific matched
+const TEXT_CONFIDENCE: f32 = 0.5;
+
+// More specific rules come first: on equal confidence the earlier rule wins.
+const RULES: &[TypeRule] = &[
+    TypeRule {
+        qr_type: "wifi",
+        subtype: None,
+        detect: |_, lower| {
+            lower
+                .starts_with("wifi:")
+                .then(|| if lower.contains("s:") { 1.0 } else { 0.8 })
+        },
+    },
+    TypeRule {
+        qr_type: "vcard",
+        subtype: None,
+        detect: |_, lower| prefix(lower, "begin:vcard"),
+    },
+    TypeRule {
+        qr_type: "vcard",
+        subtype: Some("mecard"),
+        detect: |_, lower| prefix(lower, "mecard:"),
+    },
+    TypeRule {
+        qr_type: "calendar",
+        subtype: None,
+        detect: |_, lower| prefix(lower, "begin:vevent"),
+    },
+    TypeRule {
+        qr_type: "calendar",
+        subtype: Some("vcalendar"),
+        detect: |_, lower| {
+            (lower.starts_with("begin:vcalendar") && lower.contains("begin:vevent")).then_some(0.95)
+        },
+    },
+    TypeRule {
+        qr_type: "email",
+        subtype: None,
+        detect: |_, lower| prefix(lower, "mailto:"),
+    },
+    TypeRule {
+        qr_type: "email",
+        subtype: Some("matmsg"),
+        detect: |_, lower| prefix(lower, "matmsg:"),
+    },
+    TypeRule {
+        qr_type: "sms",
+        subtype: Some("smsto"),
+        detect: |_, lower| prefix(lower, "smsto:"),
+    },
+    TypeRule {
+        qr_type: "sms",
+        subtype: None,
+        detect: |_, lower| prefix(lower, "sms:"),
+    },
+    TypeRule {
+        qr_type: "phone",
+        subtype: None,
+        detect: |_, lower| prefix(lower, "tel:"),
+    },
+    TypeRule {
+        qr_type: "phone",
+        subtype: Some("facetime"),
+        detect: |_, lower| {
+            (lower.starts_with("facetime:") || lower.starts_with("facetime-audio:")).then_some(1.0)
+        },
+    },
+    TypeRule {
+        qr_type: "geo",
+        subtype: None,
+        detect: |_, lower| {
+            let coords = lower.strip_prefix("geo:")?;
+            let mut parts = coords.split([',', ';', '?']);
+            let valid = parts
+                .next()
+                .is_some_and(|p| p.trim().parse::<f64>().is_ok())
+                && parts
+                    .next()
+                    .is_some_and(|p| p.trim().parse::<f64>().is_ok());
+            Some(if valid { 1.0 } else { 0.7 })
+        },
+    },
+    TypeRule {
+        qr_type: "otp",
+        subtype: Some("totp"),
+        detect: |_, lower| prefix(lower, "otpauth://totp/"),
+    },
+    TypeRule {
+        qr_type: "otp",
+        subtype: Some("hotp"),
+        detect: |_, lower| prefix(lower, "otpauth://hotp/"),
+    },
+    TypeRule {
+        qr_type: "otp",
+        subtype: None,
+        detect: |_, lower| lower.starts_with("otpauth://").then_some(0.8),
+    },
+    TypeRule {
+        qr_type: "crypto",
+        subtype: Some("bitcoin"),
+        detect: |_, lower| prefix(lower, "bitcoin:"),
+    },
+    TypeRule {
+        qr_type: "crypto",
+        subtype: Some("ethereum"),
+        detect: |_, lower| prefix(lower, "ethereum:"),
+    },
+    TypeRule {
+        qr_type: "payment",
+        subtype: Some("upi"),
+        detect: |_, lower| prefix(lower, "upi://pay"),
+    },
+    TypeRule {
+        qr_type: "payment",
+        subtype: Some("epc"),
+        detect: |content, _| detect_epc(content),
+    },
+    TypeRule {
+        qr_type: "payment",
+        subtype: Some("swissQrBill"),
+        detect: |content, _| detect_swiss_qr_bill(content),
+    },
+    TypeRule {
+        qr_type: "app",
+        subtype: Some("playStore"),
+        detect: |_, lower| prefix(lower, "market://"),
+    },
+    TypeRule {
+        qr_type: "media",
+        subtype: Some("spotify"),
+        detect: |_, lower| prefix(lower, "spotify:"),
+    },
+    TypeRule {
+        qr_type: "url",
+        subtype: Some("gs1DigitalLink"),
+        detect: |_, lower| detect_gs1_digital_link(lower),
+    },
+    TypeRule {
+        qr_type: "url",
+        subtype: None,
+        detect: |_, lower| is_http_url(lower).then_some(1.0),
+    },
+];
+
+/// Detect the QR type of decoded or imported content
+pub fn detect_qr_type(content: &str) -> String {
+    classify_qr_type(content).qr_type
+}
+
+/// Detect the QR type along with a more specific subtype and a confidence
+pub fn classify_qr_type(content: &str) -> TypeDetection {
+    let trimmed = content.trim();
+    let lower = trimmed.to_lowercase();
+
+    let mut best: Option<(&TypeRule, f32)> = None;
+    for rule in RULES {
+        if let Some(confidence) = (rule.detect)(trimmed, &lower) {
+            if best.is_none_or(|(_, c)| confidence > c) {
+                best = Some((rule, confidence));
+            }
+        }
+    }
+
+    match best {
+        Some((rule, confidence)) => TypeDetection {
+            qr_type: rule.qr_type.to_string(),
+            subtype: rule.subtype.map(str::to_string),
+            confidence,
+        },
+        None => TypeDetection {
+            qr_type: "text".to_string(),
+            subtype: None,
+            confidence: TEXT_CONFIDENCE,
+        },
+    }
+}
+
+/// Map a user-supplied type name to a known QR type, accepting aliases
+pub fn normalize_qr_type(value: &str) -> Option<&'static str> {
+    let lower = value.trim().to_lowercase();
+
+    QR_TYPES.iter().find(|t| **t == lower).copied().or_else(|| {
+        TYPE_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == lower)
+            .map(|(_, qr_type)| *qr_type)
+    })
+}
+
+fn prefix(lower: &str, scheme: &str) -> Option<f32> {
+    lower.starts_with(scheme).then_some(1.0)
+}
+
+fn is_http_url(lower: &str) -> bool {
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+fn lines(content: &str) -> Vec<&str> {
+    content.lines().map(str::trim_end).collect()
+}
+
+/// EPC QR ("BCD" SEPA credit transfer): BCD / version / charset / SCT
+fn detect_epc(content: &str) -> Option<f32> {
+    let lines = lines(content);
+    if lines.first() != Some(&"BCD") {
+        return None;
+    }
+
+    let version_ok = matches!(lines.get(1), Some(&"001") | Some(&"002"));
+    let sct = lines.get(3) == Some(&"SCT");
+    Some(if version_ok && sct { 1.0 } else { 0.7 })
+}
+
+/// Swiss QR-bill: SPC / version 0200 / coding type 1
+fn detect_swiss_qr_bill(content: &str) -> Option<f32> {
+    let lines = lines(content);
+    if lines.first() != Some(&"SPC") {
+        return None;
+    }
+
+    let version_ok = lines.get(1).is_some_and(|v| v.starts_with("02"));
+    Some(if version_ok && lines.get(2) == Some(&"1") {
+        1.0
+    } else {
+        0.7
+    })
+}
+
+/// GS1 Digital Link: an HTTP URL whose path carries a GTIN as `/01/<digits>`
+fn detect_gs1_digital_link(lower: &str) -> Option<f32> {
+    if !is_http_url(lower) {
+        return None;
+    }
+
+    let path = lower.split_once("://")?.1.split(['?', '#']).next()?;
+    let segments: Vec<&str> = path.split('/').skip(1).collect();
+
+    let has_gtin = segments.windows(2).any(|pair| {
+        let gtin = pair[1];
+        pair[0] == "01"
+            && matches!(gtin.len(), 8 | 12 | 13 | 14)
+            && gtin.bytes().all(|b| b.is_ascii_digit())
+    });
+
+    has_gtin.then_some(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(content: &str) -> (String, Option<String>) {
+        let detection = classify_qr_type(content);
+        (detection.qr_type, detection.subtype)
+    }
+
+    fn typed(qr_type: &str, subtype: Option<&str>) -> (String, Option<String>) {
+        (qr_type.to_string(), subtype.map(str::to_string))
+    }
+
+    #[test]
+    fn test_classify_existing_types() {
+        assert_eq!(classify("WIFI:T:WPA;S:Net;;"), typed("wifi", None));
+        assert_eq!(classify("BEGIN:VCARD\nVERSION:3.0"), typed("vcard", None));
+        assert_eq!(classify("mailto:a@example.com"), typed("email", None));
+        assert_eq!(classify("sms:+1555"), typed("sms", None));
+        assert_eq!(classify("tel:+1555"), typed("phone", None));
+        assert_eq!(classify("geo:1,2"), typed("geo", None));
+        assert_eq!(classify("BEGIN:VEVENT"), typed("calendar", None));
+        assert_eq!(classify("https://example.com"), typed("url", None));
+        assert_eq!(classify("Hello"), typed("text", None));
+    }
+
+    #[test]
+    fn test_classify_contact_and_message_variants() {
+        assert_eq!(
+            classify("MECARD:N:Doe,John;;"),
+            typed("vcard", Some("mecard"))
+        );
+        assert_eq!(
+            classify("MATMSG:TO:a@b.c;;"),
+            typed("email", Some("matmsg"))
+        );
+        assert_eq!(classify("SMSTO:+1555:Hi"), typed("sms", Some("smsto")));
+        assert_eq!(
+            classify("facetime:user@example.com"),
+            typed("phone", Some("facetime"))
+        );
+        assert_eq!(
+            classify("BEGIN:VCALENDAR\nBEGIN:VEVENT\nEND:VEVENT"),
+            typed("calendar", Some("vcalendar"))
+        );
+    }
+
+    #[test]
+    fn test_classify_otp() {
+        assert_eq!(
+            classify("otpauth://totp/Acme:jo?secret=JBSWY3DPEHPK3PXP"),
+            typed("otp", Some("totp"))
+        );
+        assert_eq!(
+            classify("otpauth://hotp/Acme?counter=1"),
+            typed("otp", Some("hotp"))
+        );
+
+        let other = classify_qr_type("otpauth://other/x");
+        assert_eq!(other.qr_type, "otp");
+        assert!(other.subtype.is_none());
+        assert!(other.confidence < 1.0);
+    }
+
+    #[test]
+    fn test_classify_crypto_and_payments() {
+        assert_eq!(
+            classify("bitcoin:bc1qxyz?amount=0.1"),
+            typed("crypto", Some("bitcoin"))
+        );
+        assert_eq!(
+            classify("ethereum:0xabc@1"),
+            typed("crypto", Some("ethereum"))
+        );
+        assert_eq!(
+            classify("upi://pay?pa=shop@upi&pn=Shop"),
+            typed("payment", Some("upi"))
+        );
+    }
+
+    #[test]
+    fn test_classify_epc() {
+        let epc = "BCD\n002\n1\nSCT\nBHBLDEHHXXX\nRed Cross\nDE33100205000001194700\nEUR10";
+        let detection = classify_qr_type(epc);
+        assert_eq!(detection.qr_type, "payment");
+        assert_eq!(detection.subtype, Some("epc".to_string()));
+        assert_eq!(detection.confidence, 1.0);
+
+        let malformed = classify_qr_type("BCD\n999\n1\nXYZ");
+        assert_eq!(malformed.subtype, Some("epc".to_string()));
+        assert!(malformed.confidence < 1.0);
+    }
+
+    #[test]
+    fn test_classify_swiss_qr_bill() {
+        let bill = "SPC\r\n0200\r\n1\r\nCH4431999123000889012\r\nS";
+        assert_eq!(classify(bill), typed("payment", Some("swissQrBill")));
+    }
+
+    #[test]
+    fn test_classify_app_and_media() {
+        assert_eq!(
+            classify("market://details?id=com.example"),
+            typed("app", Some("playStore"))
+        );
+        assert_eq!(
+            classify("spotify:track:4uLU6hMCjMI75M1A2tKUQC"),
+            typed("media", Some("spotify"))
+        );
+    }
+
+    #[test]
+    fn test_classify_gs1_digital_link() {
+        assert_eq!(
+            classify("https://id.gs1.org/01/09506000134352/10/ABC123"),
+            typed("url", Some("gs1DigitalLink"))
+        );
+        assert_eq!(
+            classify("https://example.com/01/not-a-gtin"),
+            typed("url", None)
+        );
+    }
+
+    #[test]
+    fn test_classify_confidence() {
+        assert_eq!(classify_qr_type("https://example.com").confidence, 1.0);
+        assert_eq!(classify_qr_type("plain").confidence, TEXT_CONFIDENCE);
+        assert!(classify_qr_type("WIFI:T:WPA;;").confidence < 1.0);
+        assert!(classify_qr_type("geo:abc").confidence < 1.0);
+    }
+
+    #[test]
+    fn test_classify_trims_whitespace() {
+        assert_eq!(classify("  https://example.com\n"), typed("url", None));
+    }
+
+    #[test]
+    fn test_rules_use_known_types() {
+        for rule in RULES {
+            assert!(QR_TYPES.contains(&rule.qr_type), "{}", rule.qr_type);
+        }
+    }
+
+    #[test]
+    fn test_normalize_qr_type() {
+        assert_eq!(normalize_qr_type("URL"), Some("url"));
+        assert_eq!(normalize_qr_type(" wifi "), Some("wifi"));
+        assert_eq!(normalize_qr_type("tel"), Some("phone"));
+        assert_eq!(normalize_qr_type("contact"), Some("vcard"));
+        assert_eq!(normalize_qr_type("payment"), Some("payment"));
+        assert_eq!(normalize_qr_type("barcode"), None);
+        assert_eq!(normalize_qr_type(""), None);
+    }
+
+    /// The TS declaration that starts with `start`, up to `end`
+    fn ts_block<'a>(source: &'a str, start: &str, end: &str) -> &'a str {
+        let from = source
+            .find(start)
+            .unwrap_or_else(|| panic!("{} not found", start));
+        let block = &source[from..];
+        &block[..block.find(end).unwrap()]
+    }
+
+    fn quoted(block: &str) -> Vec<&str> {
+        block.split('\'').skip(1).step_by(2).collect()
+    }
+
+    #[test]
+    fn test_frontend_types_match() {
+        let source = include_str!("../../src/lib/qrTypes.ts");
+
+        let types = quoted(ts_block(source, "export const QR_TYPES", "];"));
+        assert_eq!(types, QR_TYPES);
+
+        let aliases: Vec<(&str, &str)> = ts_block(source, "export const QR_TYPE_ALIASES", "};")
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let (alias, qr_type) = line.split_once(':')?;
+                Some((
+                    alias.trim(),
+                    qr_type.trim().trim_end_matches(',').trim_matches('\''),
+                ))
+            })
+            .collect();
+        assert_eq!(aliases, TYPE_ALIASES);
+
+        let union = quoted(ts_block(
+            include_str!("../../src/types/qr.ts"),
+            "export type QrType",
+            ";",
+        ));
+        for qr_type in QR_TYPES {
+            assert!(union.contains(qr_type), "QrType is missing '{}'", qr_type);
+        }
+    }
+}
@@ -15,6 +15,7 @@ import { DynamicCodesView } from './components/dynamic/DynamicCodesView';
 import './stores/themeStore';
 import { useAuthStore } from './stores/authStore';
 import { useAuthModalStore } from './stores/authModalStore';
+import { useSettingsStore } from './stores/settingsStore';
 import { useUpdateCheck } from './hooks/useUpdateCheck';
 
 // Dev-only: expose auth helpers in browser console (calls real impersonation API)
@@ -35,6 +36,7 @@ function App() {
 
   useEffect(() => {
     useAuthStore.getState().initialize();
+    useSettingsStore.getState().initialize();
   }, []);
 
   useEffect(() => {
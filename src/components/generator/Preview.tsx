@@ -7,6 +7,7 @@ import { useExport } from '../../hooks/useExport';
 import { useQrStore } from '../../stores/qrStore';
 import { useAuthStore } from '../../stores/authStore';
 import { useAuthModalStore } from '../../stores/authModalStore';
+import { useSettingsStore, exportFilename } from '../../stores/settingsStore';
 import { workerApi, WorkerApiError } from '../../api/worker';
 import { isSessionExpired } from '../../api/session';
 import { ValidationBadge } from './ValidationBadge';
@@ -31,6 +32,7 @@ export function Preview() {
   const { exportPng, exportSvg, copyToClipboard, isExporting } = useExport();
   const store = useQrStore();
   const { exportSize, inputType, errorCorrection, content, validationState, transparentBg, isDynamic, dynamicShortCode } = store;
+  const settings = useSettingsStore((s) => s.settings);
 
   const [copySuccess, setCopySuccess] = useState(false);
   const [exportSuccess, setExportSuccess] = useState<string | null>(null);
@@ -167,12 +169,19 @@ export function Preview() {
     return result;
   }, [withDynamicContent, getDataUrl, getBlob, copyToClipboard, saveToHistory]);
 
+  const suggestedName = useCallback(
+    (extension: string) =>
+      exportFilename(settings?.filenamePattern, { label: store.dynamicLabel, type: inputType }, extension),
+    [settings, store.dynamicLabel, inputType]
+  );
+
   const handleExportPng = useCallback(async () => {
     await withDynamicContent(async () => {
       const dataUrl = await getDataUrl();
       if (dataUrl) {
+        const fileName = suggestedName('png');
         try {
-          const result = await exportPng(dataUrl, 'qr-code.png');
+          const result = await exportPng(dataUrl, fileName, settings?.exportDirectory);
           if (result.success) {
             setExportSuccess('PNG saved!');
             setTimeout(() => setExportSuccess(null), 2000);
@@ -186,7 +195,7 @@ export function Preview() {
             const url = URL.createObjectURL(blob);
             const a = document.createElement('a');
             a.href = url;
-            a.download = 'qr-code.png';
+            a.download = fileName;
             a.click();
             URL.revokeObjectURL(url);
             setExportSuccess('PNG downloaded!');
@@ -196,7 +205,7 @@ export function Preview() {
         }
       }
     });
-  }, [withDynamicContent, getDataUrl, getBlob, exportPng, saveToHistory]);
+  }, [withDynamicContent, getDataUrl, getBlob, exportPng, saveToHistory, suggestedName, settings]);
 
   const handleExportSvg = useCallback(async () => {
     await withDynamicContent(async () => {
@@ -204,8 +213,9 @@ export function Preview() {
       const blob = await getBlob('svg');
       if (blob) {
         const svgText = await blob.text();
+        const fileName = suggestedName('svg');
         try {
-          const result = await exportSvg(svgText, 'qr-code.svg');
+          const result = await exportSvg(svgText, fileName, settings?.exportDirectory);
           if (result.success) {
             setExportSuccess('SVG saved!');
             setTimeout(() => setExportSuccess(null), 2000);
@@ -217,7 +227,7 @@ export function Preview() {
           const url = URL.createObjectURL(blob);
           const a = document.createElement('a');
           a.href = url;
-          a.download = 'qr-code.svg';
+          a.download = fileName;
           a.click();
           URL.revokeObjectURL(url);
           setExportSuccess('SVG downloaded!');
@@ -226,7 +236,7 @@ export function Preview() {
         }
       }
     });
-  }, [withDynamicContent, getBlob, getDataUrl, exportSvg, saveToHistory]);
+  }, [withDynamicContent, getBlob, getDataUrl, exportSvg, saveToHistory, suggestedName, settings]);
 
   const ecPercent = {
     L: '7%',
@@ -18,6 +18,10 @@ vi.mock('../../hooks/useHistory', () => ({
   })),
 }));
 
+vi.mock('../../hooks/useTrash', () => ({
+  useTrash: vi.fn(),
+}));
+
 vi.mock('sonner', () => ({
   toast: {
     success: vi.fn(),
@@ -26,8 +30,10 @@ vi.mock('sonner', () => ({
 }));
 
 import { useHistory } from '../../hooks/useHistory';
+import { useTrash } from '../../hooks/useTrash';
 
 const mockUseHistory = vi.mocked(useHistory);
+const mockUseTrash = vi.mocked(useTrash);
 
 const mockHistoryItems = [
   {
@@ -52,6 +58,19 @@ const mockHistoryItems = [
   },
 ];
 
+const createMockUseTrash = (overrides = {}) => ({
+  historyItems: [],
+  templates: [],
+  isLoading: false,
+  fetchTrash: vi.fn(),
+  restoreHistory: vi.fn().mockResolvedValue(true),
+  purgeHistory: vi.fn().mockResolvedValue(true),
+  restoreTemplate: vi.fn().mockResolvedValue(true),
+  purgeTemplate: vi.fn().mockResolvedValue(true),
+  emptyTrash: vi.fn().mockResolvedValue(true),
+  ...overrides,
+});
+
 const createMockUseHistory = (overrides = {}) => ({
   items: [],
   isLoading: false,
@@ -69,6 +88,7 @@ describe('HistoryView', () => {
     vi.clearAllMocks();
     useQrStore.getState().reset();
     mockUseHistory.mockReturnValue(createMockUseHistory());
+    mockUseTrash.mockReturnValue(createMockUseTrash());
   });
 
   describe('empty state', () => {
@@ -222,7 +242,7 @@ describe('HistoryView', () => {
 
       await waitFor(() => {
         expect(deleteFromHistory).toHaveBeenCalledWith(1);
-        expect(toast.success).toHaveBeenCalledWith('Deleted from history');
+        expect(toast.success).toHaveBeenCalledWith('Moved to trash');
       });
     });
   });
@@ -243,7 +263,7 @@ describe('HistoryView', () => {
 
       await waitFor(() => {
         expect(clearHistory).toHaveBeenCalled();
-        expect(toast.success).toHaveBeenCalledWith('History cleared');
+        expect(toast.success).toHaveBeenCalledWith('History moved to trash');
       });
     });
 
@@ -264,6 +284,74 @@ describe('HistoryView', () => {
     });
   });
 
+  describe('trash', () => {
+    it('asks before moving everything to the trash', () => {
+      const confirm = vi.spyOn(window, 'confirm').mockReturnValue(false);
+
+      mockUseHistory.mockReturnValue(createMockUseHistory({
+        items: mockHistoryItems,
+        total: 2,
+      }));
+
+      render(<HistoryView />);
+      fireEvent.click(screen.getByText('Clear All'));
+
+      expect(confirm).toHaveBeenCalledWith(expect.stringContaining('trash'));
+    });
+
+    it('shows trashed items and restores them', async () => {
+      const restoreHistory = vi.fn().mockResolvedValue(true);
+      const fetchHistory = vi.fn();
+      mockUseHistory.mockReturnValue(createMockUseHistory({ fetchHistory }));
+      mockUseTrash.mockReturnValue(createMockUseTrash({
+        historyItems: [{ ...mockHistoryItems[0], deletedAt: new Date().toISOString() }],
+        templates: [{ id: 3, name: 'Brand', styleJson: '{}', isDefault: false, createdAt: '', deletedAt: '' }],
+        restoreHistory,
+      }));
+
+      render(<HistoryView />);
+      fireEvent.click(screen.getByText('Trash'));
+
+      expect(screen.getByText('https://example.com')).toBeInTheDocument();
+      expect(screen.getByText('Brand')).toBeInTheDocument();
+
+      fireEvent.click(screen.getAllByText('Restore')[0]);
+      await waitFor(() => {
+        expect(restoreHistory).toHaveBeenCalledWith(1);
+        expect(toast.success).toHaveBeenCalledWith('Restored from trash');
+      });
+
+      // Leaving the trash reloads history to pick up restored items
+      fireEvent.click(screen.getByText('Back'));
+      expect(fetchHistory).toHaveBeenCalledWith(50, 0, undefined);
+    });
+
+    it('deletes trashed items forever after confirmation', async () => {
+      const purgeTemplate = vi.fn().mockResolvedValue(true);
+      vi.spyOn(window, 'confirm').mockReturnValue(true);
+      mockUseTrash.mockReturnValue(createMockUseTrash({
+        templates: [{ id: 3, name: 'Brand', styleJson: '{}', isDefault: false, createdAt: '', deletedAt: '' }],
+        purgeTemplate,
+      }));
+
+      render(<HistoryView />);
+      fireEvent.click(screen.getByText('Trash'));
+      fireEvent.click(screen.getByText('Delete Forever'));
+
+      await waitFor(() => {
+        expect(purgeTemplate).toHaveBeenCalledWith(3);
+        expect(toast.success).toHaveBeenCalledWith('Deleted forever');
+      });
+    });
+
+    it('shows an empty trash', () => {
+      render(<HistoryView />);
+      fireEvent.click(screen.getByText('Trash'));
+
+      expect(screen.getByText('Trash is empty')).toBeInTheDocument();
+    });
+  });
+
   describe('search', () => {
     it('renders search input', () => {
       mockUseHistory.mockReturnValue(createMockUseHistory({
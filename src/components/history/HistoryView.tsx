@@ -1,18 +1,65 @@
 import { useEffect, useState, useCallback } from 'react';
 import { toast } from 'sonner';
 import { useHistory, type HistoryItem } from '../../hooks/useHistory';
+import { useWorkspaceChanged } from '../../hooks/useWorkspaces';
 import { useQrStore } from '../../stores/qrStore';
+import { historyAdapter } from '@platform';
+import { TrashPanel } from './TrashPanel';
+
+function loadInGenerator(item: HistoryItem) {
+  const store = useQrStore.getState();
+  store.setContent(item.content);
+
+  try {
+    const style = JSON.parse(item.styleJson);
+    if (style.dotStyle) store.setDotStyle(style.dotStyle);
+    if (style.cornerSquareStyle) store.setCornerSquareStyle(style.cornerSquareStyle);
+    if (style.cornerDotStyle) store.setCornerDotStyle(style.cornerDotStyle);
+    if (style.foreground) store.setForeground(style.foreground);
+    if (style.background) store.setBackground(style.background);
+    if (style.transparentBg !== undefined) store.setTransparentBg(style.transparentBg);
+    if (style.logo) store.setLogo(style.logo);
+    if (style.errorCorrection) store.setErrorCorrection(style.errorCorrection);
+    toast.success('Loaded in Generator');
+  } catch {
+    toast.error('Failed to load style');
+  }
+}
 
 export function HistoryView() {
   const { items, isLoading, total, hasMore, fetchHistory, deleteFromHistory, clearHistory } =
     useHistory();
   const [searchTerm, setSearchTerm] = useState('');
   const [selectedItem, setSelectedItem] = useState<HistoryItem | null>(null);
+  const [thumbnail, setThumbnail] = useState<string | null>(null);
+  const [showTrash, setShowTrash] = useState(false);
 
   useEffect(() => {
     fetchHistory();
   }, [fetchHistory]);
 
+  useWorkspaceChanged(() => {
+    setSelectedItem(null);
+    fetchHistory();
+  });
+
+  // Thumbnails aren't part of list results; load the selected one on demand
+  useEffect(() => {
+    setThumbnail(null);
+    if (!selectedItem) return;
+
+    let cancelled = false;
+    historyAdapter
+      .getThumbnail(selectedItem)
+      .then((image) => {
+        if (!cancelled) setThumbnail(image);
+      })
+      .catch((error) => console.error('Failed to load thumbnail:', error));
+    return () => {
+      cancelled = true;
+    };
+  }, [selectedItem]);
+
   const handleSearch = useCallback(
     (term: string) => {
       setSearchTerm(term);
@@ -29,34 +76,36 @@ export function HistoryView() {
     setSelectedItem(item);
   }, []);
 
-  const handleLoadItem = useCallback(
-    (item: HistoryItem) => {
-      const store = useQrStore.getState();
-      store.setContent(item.content);
+  const handleLoadItem = useCallback((item: HistoryItem) => {
+    // Lists carry sensitive content redacted; fetch the real thing first
+    if (item.sensitive || item.encrypted) {
+      historyAdapter
+        .getContent(item)
+        .then((content) => loadInGenerator({ ...item, content }))
+        .catch((error) => toast.error(`Failed to load: ${error}`));
+      return;
+    }
+    loadInGenerator(item);
+  }, []);
 
-      try {
-        const style = JSON.parse(item.styleJson);
-        if (style.dotStyle) store.setDotStyle(style.dotStyle);
-        if (style.cornerSquareStyle) store.setCornerSquareStyle(style.cornerSquareStyle);
-        if (style.cornerDotStyle) store.setCornerDotStyle(style.cornerDotStyle);
-        if (style.foreground) store.setForeground(style.foreground);
-        if (style.background) store.setBackground(style.background);
-        if (style.transparentBg !== undefined) store.setTransparentBg(style.transparentBg);
-        if (style.logo) store.setLogo(style.logo);
-        if (style.errorCorrection) store.setErrorCorrection(style.errorCorrection);
-        toast.success('Loaded in Generator');
-      } catch {
-        toast.error('Failed to load style');
-      }
-    },
-    []
-  );
+  const handleCopy = useCallback((item: HistoryItem) => {
+    if (item.sensitive || item.encrypted) {
+      historyAdapter
+        .getContent(item)
+        .then((content) => navigator.clipboard.writeText(content))
+        .then(() => toast.success('Copied to clipboard'))
+        .catch((error) => toast.error(`Failed to copy: ${error}`));
+      return;
+    }
+    navigator.clipboard.writeText(item.content);
+    toast.success('Copied to clipboard');
+  }, []);
 
   const handleDelete = useCallback(
     async (id: number) => {
       const success = await deleteFromHistory(id);
       if (success) {
-        toast.success('Deleted from history');
+        toast.success('Moved to trash');
         if (selectedItem?.id === id) {
           setSelectedItem(null);
         }
@@ -66,15 +115,22 @@ export function HistoryView() {
   );
 
   const handleClearAll = useCallback(async () => {
-    if (window.confirm('Are you sure you want to clear all history?')) {
+    if (window.confirm('Move all history to the trash? You can restore items from the trash until it is emptied.')) {
       const success = await clearHistory();
       if (success) {
-        toast.success('History cleared');
+        toast.success('History moved to trash');
         setSelectedItem(null);
       }
     }
   }, [clearHistory]);
 
+  const handleToggleTrash = useCallback(() => {
+    setSelectedItem(null);
+    // Coming back from the trash: pick up anything restored
+    if (showTrash) fetchHistory(50, 0, searchTerm || undefined);
+    setShowTrash(!showTrash);
+  }, [showTrash, fetchHistory, searchTerm]);
+
   const formatTime = (dateStr: string) => {
     const date = new Date(dateStr);
     const now = new Date();
@@ -103,36 +159,49 @@ export function HistoryView() {
               className="font-mono text-[11px] font-semibold uppercase tracking-[0.06em]"
               style={{ color: 'var(--text-muted)' }}
             >
-              History ({total})
+              {showTrash ? 'Trash' : `History (${total})`}
             </div>
-            {items.length > 0 && (
+            <div className="flex gap-3">
+              {!showTrash && items.length > 0 && (
+                <button
+                  onClick={handleClearAll}
+                  className="text-[10px] hover:underline"
+                  style={{ color: 'var(--danger)' }}
+                >
+                  Clear All
+                </button>
+              )}
               <button
-                onClick={handleClearAll}
+                onClick={handleToggleTrash}
                 className="text-[10px] hover:underline"
-                style={{ color: 'var(--danger)' }}
+                style={{ color: 'var(--text-muted)' }}
               >
-                Clear All
+                {showTrash ? 'Back' : 'Trash'}
               </button>
-            )}
+            </div>
           </div>
-          <input
-            type="text"
-            value={searchTerm}
-            onChange={(e) => handleSearch(e.target.value)}
-            placeholder="Search history..."
-            className="w-full rounded-sm px-3 py-2 text-xs outline-none border-2 transition-colors"
-            style={{
-              background: 'var(--input-bg)',
-              borderColor: 'var(--input-border)',
-              color: 'var(--text-primary)',
-            }}
-            onFocus={(e) => { e.currentTarget.style.borderColor = 'var(--accent)'; }}
-            onBlur={(e) => { e.currentTarget.style.borderColor = 'var(--input-border)'; }}
-          />
+          {!showTrash && (
+            <input
+              type="text"
+              value={searchTerm}
+              onChange={(e) => handleSearch(e.target.value)}
+              placeholder="Search history..."
+              className="w-full rounded-sm px-3 py-2 text-xs outline-none border-2 transition-colors"
+              style={{
+                background: 'var(--input-bg)',
+                borderColor: 'var(--input-border)',
+                color: 'var(--text-primary)',
+              }}
+              onFocus={(e) => { e.currentTarget.style.borderColor = 'var(--accent)'; }}
+              onBlur={(e) => { e.currentTarget.style.borderColor = 'var(--input-border)'; }}
+            />
+          )}
         </div>
 
         <div className="flex-1 overflow-y-auto p-4 space-y-2">
-          {isLoading && items.length === 0 ? (
+          {showTrash ? (
+            <TrashPanel />
+          ) : isLoading && items.length === 0 ? (
             <div className="text-center text-xs py-8" style={{ color: 'var(--text-faint)' }}>Loading...</div>
           ) : items.length === 0 ? (
             <div className="text-center text-xs py-8" style={{ color: 'var(--text-faint)' }}>
@@ -194,9 +263,9 @@ export function HistoryView() {
       <div className="flex-1 flex flex-col items-center justify-center p-6">
         {selectedItem ? (
           <div className="text-center max-w-md">
-            {selectedItem.thumbnail && (
+            {thumbnail && (
               <img
-                src={selectedItem.thumbnail}
+                src={thumbnail}
                 alt="QR Preview"
                 className="w-48 h-48 mx-auto mb-4 rounded-sm border"
                 style={{ borderColor: 'var(--border)' }}
@@ -228,10 +297,7 @@ export function HistoryView() {
                 Load in Generator
               </button>
               <button
-                onClick={() => {
-                  navigator.clipboard.writeText(selectedItem.content);
-                  toast.success('Copied to clipboard');
-                }}
+                onClick={() => handleCopy(selectedItem)}
                 className="px-4 py-2 rounded-sm text-sm font-semibold border transition-all"
                 style={{
                   background: 'var(--btn-secondary-bg)',
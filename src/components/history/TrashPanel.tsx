@@ -0,0 +1,129 @@
+import { useEffect, useCallback } from 'react';
+import { toast } from 'sonner';
+import { useTrash } from '../../hooks/useTrash';
+import { useWorkspaceChanged } from '../../hooks/useWorkspaces';
+
+const actionStyle = { color: 'var(--text-muted)' };
+const dangerStyle = { color: 'var(--danger)' };
+
+export function TrashPanel() {
+  const {
+    historyItems,
+    templates,
+    isLoading,
+    fetchTrash,
+    restoreHistory,
+    purgeHistory,
+    restoreTemplate,
+    purgeTemplate,
+    emptyTrash,
+  } = useTrash();
+
+  useEffect(() => {
+    fetchTrash();
+  }, [fetchTrash]);
+
+  useWorkspaceChanged(() => {
+    fetchTrash();
+  });
+
+  const handleRestore = useCallback(async (restore: (id: number) => Promise<boolean>, id: number) => {
+    if (await restore(id)) {
+      toast.success('Restored from trash');
+    } else {
+      toast.error('Failed to restore');
+    }
+  }, []);
+
+  const handlePurge = useCallback(async (purge: (id: number) => Promise<boolean>, id: number) => {
+    if (!window.confirm('Delete this item forever? This cannot be undone.')) return;
+    if (await purge(id)) {
+      toast.success('Deleted forever');
+    } else {
+      toast.error('Failed to delete');
+    }
+  }, []);
+
+  const handleEmptyTrash = useCallback(async () => {
+    if (!window.confirm('Delete everything in the trash forever? This cannot be undone.')) return;
+    if (await emptyTrash()) {
+      toast.success('Trash emptied');
+    } else {
+      toast.error('Failed to empty trash');
+    }
+  }, [emptyTrash]);
+
+  const isEmpty = historyItems.length === 0 && templates.length === 0;
+
+  const row = (key: string, title: string, subtitle: string | null, restore: () => void, purge: () => void) => (
+    <div
+      key={key}
+      className="p-3 rounded-sm border"
+      style={{ background: 'var(--input-bg)', borderColor: 'var(--border)' }}
+    >
+      <div className="font-mono text-xs truncate" style={{ color: 'var(--text-primary)' }}>{title}</div>
+      {subtitle && (
+        <div className="text-[10px] mt-1 truncate" style={{ color: 'var(--text-muted)' }}>{subtitle}</div>
+      )}
+      <div className="flex gap-3 mt-2">
+        <button onClick={restore} className="text-[10px] hover:underline" style={actionStyle}>
+          Restore
+        </button>
+        <button onClick={purge} className="text-[10px] hover:underline" style={dangerStyle}>
+          Delete Forever
+        </button>
+      </div>
+    </div>
+  );
+
+  const heading = (text: string) => (
+    <div
+      className="font-mono text-[10px] font-semibold uppercase tracking-[0.06em] pt-1"
+      style={{ color: 'var(--text-faint)' }}
+    >
+      {text}
+    </div>
+  );
+
+  if (isLoading && isEmpty) {
+    return <div className="text-center text-xs py-8" style={{ color: 'var(--text-faint)' }}>Loading...</div>;
+  }
+
+  if (isEmpty) {
+    return <div className="text-center text-xs py-8" style={{ color: 'var(--text-faint)' }}>Trash is empty</div>;
+  }
+
+  return (
+    <>
+      <button
+        onClick={handleEmptyTrash}
+        className="w-full py-2 text-xs rounded-sm border transition-colors"
+        style={{ color: 'var(--danger)', borderColor: 'var(--danger)' }}
+      >
+        Empty Trash
+      </button>
+
+      {historyItems.length > 0 && heading(`History (${historyItems.length})`)}
+      {historyItems.map((item) =>
+        row(
+          `history-${item.id}`,
+          item.content,
+          item.label,
+          () => handleRestore(restoreHistory, item.id),
+          () => handlePurge(purgeHistory, item.id)
+        )
+      )}
+
+      {templates.length > 0 && heading(`Templates (${templates.length})`)}
+      {templates.map((template) =>
+        row(
+          `template-${template.id}`,
+          template.name,
+          null,
+          () => handleRestore(restoreTemplate, template.id),
+          () => handlePurge(purgeTemplate, template.id)
+        )
+      )}
+    </>
+  );
+}
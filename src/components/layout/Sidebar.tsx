@@ -4,6 +4,8 @@ import { useAuth } from '../../hooks/useAuth';
 import { useAuthModalStore } from '../../stores/authModalStore';
 import { useAuthStore } from '../../stores/authStore';
 import type { PlanTier } from '../../api/types';
+import { isTauri } from '../../lib/platform';
+import { WorkspacePicker } from './WorkspacePicker';
 
 type TabId = 'generator' | 'batch' | 'scanner' | 'history' | 'templates' | 'dynamic';
 
@@ -259,6 +261,7 @@ export function Sidebar({ activeTab, onTabChange }: SidebarProps) {
         className="p-3 transition-colors"
         style={{ borderTop: '1px solid var(--border)' }}
       >
+        {!collapsed && isTauri() && <WorkspacePicker />}
         {collapsed ? (
           <div className="flex flex-col items-center gap-2">
             {isLoggedIn ? (
@@ -0,0 +1,143 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { render, screen, fireEvent, waitFor } from '@testing-library/react';
+import { WorkspacePicker } from './WorkspacePicker';
+import { toast } from 'sonner';
+
+vi.mock('sonner', () => ({
+  toast: {
+    success: vi.fn(),
+    error: vi.fn(),
+  },
+}));
+
+vi.mock('../../hooks/useWorkspaces', () => ({
+  useWorkspaces: vi.fn(),
+}));
+
+import { useWorkspaces } from '../../hooks/useWorkspaces';
+
+const mockUseWorkspaces = vi.mocked(useWorkspaces);
+
+const workspaceList = {
+  active: 'default',
+  dataDir: '/Users/test/qr-foundry',
+  portable: false,
+  workspaces: [
+    { name: 'default', path: '/Users/test/qr-foundry/qr-foundry.db', bytes: 8192 },
+    { name: 'Client A', path: '/Users/test/qr-foundry/workspaces/Client A/qr-foundry.db', bytes: 4096 },
+  ],
+  dataDirError: null as string | null,
+};
+
+function createMockUseWorkspaces(overrides = {}) {
+  return {
+    workspaces: workspaceList,
+    isLoading: false,
+    fetchWorkspaces: vi.fn(),
+    createWorkspace: vi.fn().mockResolvedValue(true),
+    switchWorkspace: vi.fn().mockResolvedValue(true),
+    deleteWorkspace: vi.fn().mockResolvedValue(true),
+    moveDataDir: vi.fn().mockResolvedValue({ workspaces: workspaceList, copy: null }),
+    pickDataDir: vi.fn().mockResolvedValue(null),
+    ...overrides,
+  };
+}
+
+describe('WorkspacePicker', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('renders nothing until workspaces load', () => {
+    mockUseWorkspaces.mockReturnValue(createMockUseWorkspaces({ workspaces: null }));
+    const { container } = render(<WorkspacePicker />);
+
+    expect(container).toBeEmptyDOMElement();
+  });
+
+  it('switches workspace from the picker', async () => {
+    const hook = createMockUseWorkspaces();
+    mockUseWorkspaces.mockReturnValue(hook);
+    render(<WorkspacePicker />);
+
+    expect(hook.fetchWorkspaces).toHaveBeenCalled();
+    fireEvent.change(screen.getByLabelText('Workspace'), { target: { value: 'Client A' } });
+
+    await waitFor(() => {
+      expect(hook.switchWorkspace).toHaveBeenCalledWith('Client A');
+      expect(toast.success).toHaveBeenCalledWith('Switched to Client A');
+    });
+  });
+
+  it('creates a workspace with the given name', async () => {
+    const hook = createMockUseWorkspaces();
+    mockUseWorkspaces.mockReturnValue(hook);
+    vi.spyOn(window, 'prompt').mockReturnValueOnce(' Client B ');
+    render(<WorkspacePicker />);
+
+    fireEvent.click(screen.getByText('Manage'));
+    fireEvent.click(screen.getByText('New Workspace'));
+
+    await waitFor(() => {
+      expect(hook.createWorkspace).toHaveBeenCalledWith('Client B');
+    });
+  });
+
+  it('refuses a name that is already taken', async () => {
+    const hook = createMockUseWorkspaces();
+    mockUseWorkspaces.mockReturnValue(hook);
+    vi.spyOn(window, 'prompt').mockReturnValueOnce('client a');
+    render(<WorkspacePicker />);
+
+    fireEvent.click(screen.getByText('Manage'));
+    fireEvent.click(screen.getByText('New Workspace'));
+
+    await waitFor(() => {
+      expect(toast.error).toHaveBeenCalledWith('A workspace named "client a" already exists');
+    });
+    expect(hook.createWorkspace).not.toHaveBeenCalled();
+  });
+
+  it('only offers to delete inactive workspaces', async () => {
+    const hook = createMockUseWorkspaces();
+    mockUseWorkspaces.mockReturnValue(hook);
+    vi.spyOn(window, 'confirm').mockReturnValueOnce(true);
+    render(<WorkspacePicker />);
+
+    fireEvent.click(screen.getByText('Manage'));
+    const deleteButtons = screen.getAllByText('Delete');
+    expect(deleteButtons).toHaveLength(1);
+
+    fireEvent.click(deleteButtons[0]);
+    await waitFor(() => {
+      expect(hook.deleteWorkspace).toHaveBeenCalledWith('Client A');
+    });
+  });
+
+  it('moves data to a picked folder', async () => {
+    const hook = createMockUseWorkspaces({
+      pickDataDir: vi.fn().mockResolvedValue('/Volumes/Shared/qr'),
+    });
+    mockUseWorkspaces.mockReturnValue(hook);
+    vi.spyOn(window, 'confirm').mockReturnValueOnce(true);
+    render(<WorkspacePicker />);
+
+    fireEvent.click(screen.getByText('Manage'));
+    fireEvent.click(screen.getByText('Move Data...'));
+
+    await waitFor(() => {
+      expect(hook.moveDataDir).toHaveBeenCalledWith('/Volumes/Shared/qr', true);
+    });
+  });
+
+  it('reports a data folder that could not be opened', () => {
+    const message = 'Data directory /Volumes/Shared/qr is unavailable (directory not found); using /Users/test/qr-foundry instead';
+    mockUseWorkspaces.mockReturnValue(
+      createMockUseWorkspaces({ workspaces: { ...workspaceList, dataDirError: message } })
+    );
+    render(<WorkspacePicker />);
+
+    expect(toast.error).toHaveBeenCalledWith(message, { id: 'data-dir-error' });
+    expect(screen.getByText('Data folder unavailable; using the default folder')).toBeInTheDocument();
+  });
+});
@@ -0,0 +1,210 @@
+import { useEffect, useState, useCallback } from 'react';
+import { toast } from 'sonner';
+import { useWorkspaces } from '../../hooks/useWorkspaces';
+
+const DEFAULT_WORKSPACE = 'default';
+
+const linkStyle = { color: 'var(--text-faint)' };
+
+function displayName(name: string): string {
+  return name === DEFAULT_WORKSPACE ? 'Default' : name;
+}
+
+function formatBytes(bytes: number): string {
+  if (bytes < 1024 * 1024) return `${Math.max(1, Math.round(bytes / 1024))} KB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+}
+
+export function WorkspacePicker() {
+  const {
+    workspaces,
+    fetchWorkspaces,
+    createWorkspace,
+    switchWorkspace,
+    deleteWorkspace,
+    moveDataDir,
+    pickDataDir,
+  } = useWorkspaces();
+  const [isManaging, setIsManaging] = useState(false);
+  const [isBusy, setIsBusy] = useState(false);
+
+  useEffect(() => {
+    fetchWorkspaces();
+  }, [fetchWorkspaces]);
+
+  // Startup fell back to the home directory; say why
+  const dataDirError = workspaces?.dataDirError;
+  useEffect(() => {
+    if (dataDirError) toast.error(dataDirError, { id: 'data-dir-error' });
+  }, [dataDirError]);
+
+  const busy = useCallback(async (fn: () => Promise<void>) => {
+    setIsBusy(true);
+    try {
+      await fn();
+    } finally {
+      setIsBusy(false);
+    }
+  }, []);
+
+  const handleSwitch = useCallback(
+    (name: string) =>
+      busy(async () => {
+        if (await switchWorkspace(name)) {
+          toast.success(`Switched to ${displayName(name)}`);
+        } else {
+          toast.error('Failed to switch workspace');
+        }
+      }),
+    [busy, switchWorkspace]
+  );
+
+  const handleCreate = useCallback(
+    () =>
+      busy(async () => {
+        const name = window.prompt('Name for the new workspace')?.trim();
+        if (!name) return;
+        if (workspaces?.workspaces.some((w) => w.name.toLowerCase() === name.toLowerCase())) {
+          toast.error(`A workspace named "${name}" already exists`);
+          return;
+        }
+        if (await createWorkspace(name)) {
+          toast.success(`Created ${name}`);
+        } else {
+          toast.error('Failed to create workspace');
+        }
+      }),
+    [busy, createWorkspace, workspaces]
+  );
+
+  const handleDelete = useCallback(
+    (name: string) =>
+      busy(async () => {
+        if (!window.confirm(`Delete the ${displayName(name)} workspace and its backups? This cannot be undone.`)) {
+          return;
+        }
+        if (await deleteWorkspace(name)) {
+          toast.success(`Deleted ${displayName(name)}`);
+        } else {
+          toast.error('Failed to delete workspace');
+        }
+      }),
+    [busy, deleteWorkspace]
+  );
+
+  const handleMove = useCallback(
+    (useDefault: boolean) =>
+      busy(async () => {
+        const path = useDefault ? null : await pickDataDir();
+        if (!useDefault && !path) return;
+        const copyExisting = window.confirm('Copy your existing workspaces to the new data folder?');
+        const result = await moveDataDir(path, copyExisting);
+        if (result) {
+          toast.success(`Data folder: ${result.workspaces.dataDir}`);
+        } else {
+          toast.error('Failed to change data folder');
+        }
+      }),
+    [busy, moveDataDir, pickDataDir]
+  );
+
+  if (!workspaces) return null;
+
+  return (
+    <div className="flex flex-col gap-1.5 px-1 pb-2">
+      <div className="flex items-center justify-between">
+        <span
+          className="font-mono text-[10px] font-semibold uppercase tracking-[0.06em]"
+          style={{ color: 'var(--text-faint)' }}
+        >
+          Workspace
+        </span>
+        <button
+          onClick={() => setIsManaging((open) => !open)}
+          className="text-[10px] hover:underline"
+          style={linkStyle}
+        >
+          {isManaging ? 'Done' : 'Manage'}
+        </button>
+      </div>
+
+      <select
+        aria-label="Workspace"
+        value={workspaces.active}
+        disabled={isBusy}
+        onChange={(e) => handleSwitch(e.target.value)}
+        className="w-full font-mono text-xs rounded-sm border outline-none cursor-pointer px-2 py-1"
+        style={{
+          background: 'var(--input-bg)',
+          borderColor: 'var(--border)',
+          color: 'var(--text-secondary)',
+        }}
+      >
+        {workspaces.workspaces.map((workspace) => (
+          <option key={workspace.name} value={workspace.name}>
+            {displayName(workspace.name)}
+          </option>
+        ))}
+      </select>
+
+      {workspaces.dataDirError && (
+        <div className="text-[10px]" style={{ color: 'var(--danger)' }}>
+          Data folder unavailable; using the default folder
+        </div>
+      )}
+
+      {isManaging && (
+        <div className="flex flex-col gap-1.5">
+          {workspaces.workspaces.map((workspace) => (
+            <div key={workspace.name} className="flex items-center gap-2 text-[10px]">
+              <span className="flex-1 truncate" style={{ color: 'var(--text-muted)' }}>
+                {displayName(workspace.name)}
+              </span>
+              <span className="font-mono" style={{ color: 'var(--text-faint)' }}>
+                {formatBytes(workspace.bytes)}
+              </span>
+              {workspace.name !== workspaces.active && (
+                <button
+                  onClick={() => handleDelete(workspace.name)}
+                  disabled={isBusy}
+                  className="hover:underline"
+                  style={{ color: 'var(--danger)' }}
+                >
+                  Delete
+                </button>
+              )}
+            </div>
+          ))}
+          <div className="text-[10px] font-mono truncate" style={linkStyle} title={workspaces.dataDir}>
+            {workspaces.dataDir}
+          </div>
+          <div className="flex gap-3">
+            <button onClick={handleCreate} disabled={isBusy} className="text-[10px] hover:underline" style={linkStyle}>
+              New Workspace
+            </button>
+            {!workspaces.portable && (
+              <>
+                <button
+                  onClick={() => handleMove(false)}
+                  disabled={isBusy}
+                  className="text-[10px] hover:underline"
+                  style={linkStyle}
+                >
+                  Move Data...
+                </button>
+                <button
+                  onClick={() => handleMove(true)}
+                  disabled={isBusy}
+                  className="text-[10px] hover:underline"
+                  style={linkStyle}
+                >
+                  Use Default
+                </button>
+              </>
+            )}
+          </div>
+        </div>
+      )}
+    </div>
+  );
+}
@@ -4,6 +4,7 @@ import { useScanQr } from '../../hooks/useValidation';
 import { useExport } from '../../hooks/useExport';
 import { useQrStore } from '../../stores/qrStore';
 import { useTauriDragDrop } from '../../hooks/useTauriDragDrop';
+import type { ScanSource } from '../../platform/types';
 
 export function ScannerView() {
   const { scanFromFile, scanFromData, isScanning, scanResult, clearScan } = useScanQr();
@@ -38,12 +39,12 @@ export function ScannerView() {
   }, [scanResult]);
 
   const handleFileDrop = useCallback(
-    async (file: File) => {
+    async (file: File, source: ScanSource = 'data') => {
       setError(null);
       const reader = new FileReader();
       reader.onloadend = async () => {
         const dataUrl = reader.result as string;
-        await scanFromData(dataUrl);
+        await scanFromData(dataUrl, source);
       };
       reader.onerror = () => {
         setError('Failed to read file');
@@ -75,7 +76,7 @@ export function ScannerView() {
         if (item.type.startsWith('image/')) {
           const file = item.getAsFile();
           if (file) {
-            handleFileDrop(file);
+            handleFileDrop(file, 'clipboard');
           }
           break;
         }
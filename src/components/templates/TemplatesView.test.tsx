@@ -3,6 +3,7 @@ import { render, screen, fireEvent, waitFor } from '@testing-library/react';
 import { TemplatesView } from './TemplatesView';
 import { useQrStore } from '../../stores/qrStore';
 import { toast } from 'sonner';
+import { settingsAdapter } from '@platform';
 
 // Mock the hooks
 vi.mock('../../hooks/useTemplates', () => ({
@@ -177,6 +178,22 @@ describe('TemplatesView', () => {
       expect(toast.success).toHaveBeenCalledWith('Applied "Corporate Blue" template');
     });
 
+    it('remembers the applied template in settings', () => {
+      vi.mocked(settingsAdapter.update).mockResolvedValueOnce({
+        settings: {} as never,
+        changed: ['lastTemplateId'],
+      });
+      mockUseTemplates.mockReturnValue(createMockUseTemplates({
+        templates: mockTemplates,
+      }));
+
+      render(<TemplatesView />);
+      fireEvent.click(screen.getByText('Corporate Blue'));
+      fireEvent.click(screen.getByText('Apply Style'));
+
+      expect(settingsAdapter.update).toHaveBeenCalledWith({ lastTemplateId: 1 });
+    });
+
     it('shows error toast on invalid style JSON', () => {
       const badTemplate = {
         ...mockTemplates[0],
@@ -1,7 +1,9 @@
 import { useEffect, useState, useCallback } from 'react';
 import { toast } from 'sonner';
 import { useTemplates, type Template } from '../../hooks/useTemplates';
+import { useWorkspaceChanged } from '../../hooks/useWorkspaces';
 import { useQrStore } from '../../stores/qrStore';
+import { useSettingsStore } from '../../stores/settingsStore';
 
 export function TemplatesView() {
   const { templates, isLoading, fetchTemplates, saveTemplate, deleteTemplate, setDefaultTemplate } =
@@ -16,6 +18,11 @@ export function TemplatesView() {
     fetchTemplates();
   }, [fetchTemplates]);
 
+  useWorkspaceChanged(() => {
+    setSelectedTemplate(null);
+    fetchTemplates();
+  });
+
   const handleSelectTemplate = useCallback((template: Template) => {
     setSelectedTemplate(template);
     setIsCreating(false);
@@ -35,6 +42,7 @@ export function TemplatesView() {
         if (style.gradient) store.setGradient(style.gradient);
         if (style.logo) store.setLogo(style.logo);
         if (style.errorCorrection) store.setErrorCorrection(style.errorCorrection);
+        useSettingsStore.getState().update({ lastTemplateId: template.id });
         toast.success(`Applied "${template.name}" template`);
       } catch {
         toast.error('Failed to apply template');
@@ -35,7 +35,7 @@ describe('useExport', () => {
         exportResult = await result.current.exportPng('data:image/png;base64,abc', 'my-qr.png');
       });
 
-      expect(mockExportPng).toHaveBeenCalledWith('data:image/png;base64,abc', 'my-qr.png');
+      expect(mockExportPng).toHaveBeenCalledWith('data:image/png;base64,abc', 'my-qr.png', undefined);
       expect(exportResult).toEqual({
         success: true,
         path: '/Users/test/qr-code.png',
@@ -57,7 +57,23 @@ describe('useExport', () => {
         await result.current.exportPng('data:image/png;base64,abc');
       });
 
-      expect(mockExportPng).toHaveBeenCalledWith('data:image/png;base64,abc', undefined);
+      expect(mockExportPng).toHaveBeenCalledWith('data:image/png;base64,abc', undefined, undefined);
+    });
+
+    it('opens the save dialog in the given directory', async () => {
+      mockExportPng.mockResolvedValueOnce({
+        success: true,
+        path: '/exports/qr.png',
+        error: null,
+      });
+
+      const { result } = renderHook(() => useExport());
+
+      await act(async () => {
+        await result.current.exportPng('data:image/png;base64,abc', 'qr.png', '/exports');
+      });
+
+      expect(mockExportPng).toHaveBeenCalledWith('data:image/png;base64,abc', 'qr.png', '/exports');
     });
 
     it('handles cancelled save', async () => {
@@ -107,7 +123,7 @@ describe('useExport', () => {
         await result.current.exportSvg('<svg>...</svg>', 'my-qr.svg');
       });
 
-      expect(mockExportSvg).toHaveBeenCalledWith('<svg>...</svg>', 'my-qr.svg');
+      expect(mockExportSvg).toHaveBeenCalledWith('<svg>...</svg>', 'my-qr.svg', undefined);
       expect(result.current.lastExportPath).toBe('/Users/test/qr-code.svg');
     });
 
@@ -124,7 +140,7 @@ describe('useExport', () => {
         await result.current.exportSvg('<svg></svg>');
       });
 
-      expect(mockExportSvg).toHaveBeenCalledWith('<svg></svg>', undefined);
+      expect(mockExportSvg).toHaveBeenCalledWith('<svg></svg>', undefined, undefined);
     });
 
     it('handles error', async () => {
@@ -9,10 +9,10 @@ export function useExport() {
   const [lastExportPath, setLastExportPath] = useState<string | null>(null);
 
   const exportPng = useCallback(
-    async (imageDataUrl: string, suggestedName?: string): Promise<ExportResult> => {
+    async (imageDataUrl: string, suggestedName?: string, directory?: string | null): Promise<ExportResult> => {
       setIsExporting(true);
       try {
-        const result = await exportAdapter.exportPng(imageDataUrl, suggestedName);
+        const result = await exportAdapter.exportPng(imageDataUrl, suggestedName, directory);
 
         if (result.success && result.path) {
           setLastExportPath(result.path);
@@ -34,10 +34,10 @@ export function useExport() {
   );
 
   const exportSvg = useCallback(
-    async (svgData: string, suggestedName?: string): Promise<ExportResult> => {
+    async (svgData: string, suggestedName?: string, directory?: string | null): Promise<ExportResult> => {
       setIsExporting(true);
       try {
-        const result = await exportAdapter.exportSvg(svgData, suggestedName);
+        const result = await exportAdapter.exportSvg(svgData, suggestedName, directory);
 
         if (result.success && result.path) {
           setLastExportPath(result.path);
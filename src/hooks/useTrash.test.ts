@@ -0,0 +1,144 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { renderHook, act } from '@testing-library/react';
+import { useTrash } from './useTrash';
+import { historyAdapter, templateAdapter } from '@platform';
+
+const mockListTrash = vi.mocked(historyAdapter.listTrash);
+const mockRestore = vi.mocked(historyAdapter.restore);
+const mockPurge = vi.mocked(historyAdapter.purge);
+const mockEmptyTrash = vi.mocked(historyAdapter.emptyTrash);
+const mockListTemplateTrash = vi.mocked(templateAdapter.listTrash);
+const mockRestoreTemplate = vi.mocked(templateAdapter.restore);
+const mockPurgeTemplate = vi.mocked(templateAdapter.purge);
+
+const trashedItems = [
+  {
+    id: 1,
+    content: 'https://example.com',
+    qrType: 'url',
+    label: 'Example',
+    styleJson: '{}',
+    createdAt: '2024-01-01T00:00:00Z',
+    updatedAt: '2024-01-01T00:00:00Z',
+    deletedAt: '2024-02-01T00:00:00Z',
+  },
+  {
+    id: 2,
+    content: 'tel:+15551234567',
+    qrType: 'phone',
+    label: null,
+    styleJson: '{}',
+    createdAt: '2024-01-02T00:00:00Z',
+    updatedAt: '2024-01-02T00:00:00Z',
+    deletedAt: '2024-02-02T00:00:00Z',
+  },
+];
+
+const trashedTemplates = [
+  {
+    id: 5,
+    name: 'Brand',
+    styleJson: '{}',
+    isDefault: false,
+    createdAt: '2024-01-01T00:00:00Z',
+    deletedAt: '2024-02-01T00:00:00Z',
+  },
+];
+
+async function loadedTrash() {
+  mockListTrash.mockResolvedValueOnce({ items: trashedItems, total: 2, hasMore: false });
+  mockListTemplateTrash.mockResolvedValueOnce(trashedTemplates);
+
+  const hook = renderHook(() => useTrash());
+  await act(async () => {
+    await hook.result.current.fetchTrash();
+  });
+  return hook;
+}
+
+describe('useTrash', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('fetches trashed history and templates', async () => {
+    const { result } = await loadedTrash();
+
+    expect(mockListTrash).toHaveBeenCalledWith(200, 0);
+    expect(result.current.historyItems).toEqual(trashedItems);
+    expect(result.current.templates).toEqual(trashedTemplates);
+    expect(result.current.isLoading).toBe(false);
+  });
+
+  it('restores a history item and drops it from the list', async () => {
+    mockRestore.mockResolvedValueOnce(1);
+    const { result } = await loadedTrash();
+
+    let success: boolean | undefined;
+    await act(async () => {
+      success = await result.current.restoreHistory(1);
+    });
+
+    expect(success).toBe(true);
+    expect(mockRestore).toHaveBeenCalledWith([1]);
+    expect(result.current.historyItems.map((i) => i.id)).toEqual([2]);
+  });
+
+  it('purges a history item', async () => {
+    mockPurge.mockResolvedValueOnce(1);
+    const { result } = await loadedTrash();
+
+    await act(async () => {
+      await result.current.purgeHistory(2);
+    });
+
+    expect(mockPurge).toHaveBeenCalledWith([2]);
+    expect(result.current.historyItems.map((i) => i.id)).toEqual([1]);
+  });
+
+  it('restores and purges templates', async () => {
+    mockRestoreTemplate.mockResolvedValueOnce(true);
+    mockPurgeTemplate.mockResolvedValueOnce(false);
+    const { result } = await loadedTrash();
+
+    let purged: boolean | undefined;
+    await act(async () => {
+      purged = await result.current.purgeTemplate(5);
+    });
+    expect(purged).toBe(false);
+
+    await act(async () => {
+      await result.current.restoreTemplate(5);
+    });
+    expect(mockRestoreTemplate).toHaveBeenCalledWith(5);
+    expect(result.current.templates).toEqual([]);
+  });
+
+  it('empties history and template trash', async () => {
+    mockEmptyTrash.mockResolvedValueOnce(2);
+    mockPurgeTemplate.mockResolvedValue(true);
+    const { result } = await loadedTrash();
+
+    await act(async () => {
+      await result.current.emptyTrash();
+    });
+
+    expect(mockEmptyTrash).toHaveBeenCalled();
+    expect(mockPurgeTemplate).toHaveBeenCalledWith(5);
+    expect(result.current.historyItems).toEqual([]);
+    expect(result.current.templates).toEqual([]);
+  });
+
+  it('returns false when restoring fails', async () => {
+    mockRestore.mockRejectedValueOnce(new Error('Database error'));
+    const { result } = await loadedTrash();
+
+    let success: boolean | undefined;
+    await act(async () => {
+      success = await result.current.restoreHistory(1);
+    });
+
+    expect(success).toBe(false);
+    expect(result.current.historyItems).toHaveLength(2);
+  });
+});
@@ -0,0 +1,97 @@
+import { useCallback, useState } from 'react';
+import { historyAdapter, templateAdapter } from '@platform';
+import type { HistoryItem, Template } from '../platform/types';
+
+export function useTrash() {
+  const [historyItems, setHistoryItems] = useState<HistoryItem[]>([]);
+  const [templates, setTemplates] = useState<Template[]>([]);
+  const [isLoading, setIsLoading] = useState(false);
+
+  const fetchTrash = useCallback(async (): Promise<void> => {
+    setIsLoading(true);
+    try {
+      const [history, trashedTemplates] = await Promise.all([
+        historyAdapter.listTrash(200, 0),
+        templateAdapter.listTrash(),
+      ]);
+      setHistoryItems(history.items);
+      setTemplates(trashedTemplates);
+    } catch (error) {
+      console.error('Failed to fetch trash:', error);
+    } finally {
+      setIsLoading(false);
+    }
+  }, []);
+
+  const restoreHistory = useCallback(async (id: number): Promise<boolean> => {
+    try {
+      const restored = await historyAdapter.restore([id]);
+      if (restored > 0) setHistoryItems((prev) => prev.filter((item) => item.id !== id));
+      return restored > 0;
+    } catch (error) {
+      console.error('Failed to restore history item:', error);
+      return false;
+    }
+  }, []);
+
+  const purgeHistory = useCallback(async (id: number): Promise<boolean> => {
+    try {
+      const purged = await historyAdapter.purge([id]);
+      if (purged > 0) setHistoryItems((prev) => prev.filter((item) => item.id !== id));
+      return purged > 0;
+    } catch (error) {
+      console.error('Failed to delete history item:', error);
+      return false;
+    }
+  }, []);
+
+  const restoreTemplate = useCallback(async (id: number): Promise<boolean> => {
+    try {
+      const success = await templateAdapter.restore(id);
+      if (success) setTemplates((prev) => prev.filter((template) => template.id !== id));
+      return success;
+    } catch (error) {
+      console.error('Failed to restore template:', error);
+      return false;
+    }
+  }, []);
+
+  const purgeTemplate = useCallback(async (id: number): Promise<boolean> => {
+    try {
+      const success = await templateAdapter.purge(id);
+      if (success) setTemplates((prev) => prev.filter((template) => template.id !== id));
+      return success;
+    } catch (error) {
+      console.error('Failed to delete template:', error);
+      return false;
+    }
+  }, []);
+
+  const emptyTrash = useCallback(async (): Promise<boolean> => {
+    try {
+      await historyAdapter.emptyTrash();
+      for (const template of templates) {
+        await templateAdapter.purge(template.id);
+      }
+      setHistoryItems([]);
+      setTemplates([]);
+      return true;
+    } catch (error) {
+      console.error('Failed to empty trash:', error);
+      await fetchTrash();
+      return false;
+    }
+  }, [templates, fetchTrash]);
+
+  return {
+    historyItems,
+    templates,
+    isLoading,
+    fetchTrash,
+    restoreHistory,
+    purgeHistory,
+    restoreTemplate,
+    purgeTemplate,
+    emptyTrash,
+  };
+}
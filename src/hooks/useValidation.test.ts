@@ -7,6 +7,7 @@ import { useQrStore } from '../stores/qrStore';
 const mockValidateQr = vi.mocked(scannerAdapter.validateQr);
 const mockScanFromFile = vi.mocked(scannerAdapter.scanFromFile);
 const mockScanFromData = vi.mocked(scannerAdapter.scanFromData);
+const mockSaveScan = vi.mocked(scannerAdapter.saveScan);
 
 describe('useValidation', () => {
   beforeEach(() => {
@@ -221,6 +222,53 @@ describe('useScanQr', () => {
     expect(result.current.scanResult?.error).toBe('No QR code found');
   });
 
+  it('saves successful scans to scan history', async () => {
+    const scan = { success: true, content: 'https://example.com', qrType: 'url', error: null };
+    mockScanFromFile.mockResolvedValueOnce(scan);
+    mockScanFromData.mockResolvedValueOnce(scan);
+
+    const { result } = renderHook(() => useScanQr());
+
+    await act(async () => {
+      await result.current.scanFromFile('/path/to/qr.png');
+      await result.current.scanFromData('data:image/png;base64,abc', 'clipboard');
+    });
+
+    expect(mockSaveScan).toHaveBeenCalledWith('file', '/path/to/qr.png', scan);
+    expect(mockSaveScan).toHaveBeenCalledWith('clipboard', null, scan);
+  });
+
+  it('does not save failed scans', async () => {
+    mockScanFromFile.mockResolvedValueOnce({
+      success: false,
+      content: null,
+      qrType: null,
+      error: 'No QR code found',
+    });
+
+    const { result } = renderHook(() => useScanQr());
+
+    await act(async () => {
+      await result.current.scanFromFile('/path/to/image.png');
+    });
+
+    expect(mockSaveScan).not.toHaveBeenCalled();
+  });
+
+  it('keeps the result when saving the scan fails', async () => {
+    mockScanFromData.mockResolvedValueOnce({ success: true, content: 'test', qrType: 'text', error: null });
+    mockSaveScan.mockRejectedValueOnce(new Error('Database is locked'));
+
+    const { result } = renderHook(() => useScanQr());
+
+    await act(async () => {
+      await result.current.scanFromData('data:image/png;base64,abc');
+    });
+
+    expect(mockSaveScan).toHaveBeenCalledWith('data', null, expect.objectContaining({ content: 'test' }));
+    expect(result.current.scanResult?.content).toBe('test');
+  });
+
   it('handles API error', async () => {
     mockScanFromFile.mockRejectedValueOnce(new Error('File not found'));
 
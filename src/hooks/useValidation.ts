@@ -2,7 +2,7 @@ import { useCallback, useState } from 'react';
 import { scannerAdapter } from '@platform';
 import { useQrStore } from '../stores/qrStore';
 import type { ValidationState } from '../types/qr';
-import type { ValidationResult, ScanResult } from '../platform/types';
+import type { ValidationResult, ScanResult, ScanSource } from '../platform/types';
 
 export type { ValidationResult, ScanResult };
 
@@ -58,6 +58,16 @@ export function useValidation() {
   };
 }
 
+/** Keep a successful scan in scan history; a failed save doesn't affect the result shown */
+async function recordScan(source: ScanSource, sourcePath: string | null, result: ScanResult) {
+  if (!result.success) return;
+  try {
+    await scannerAdapter.saveScan(source, sourcePath, result);
+  } catch (error) {
+    console.error('Failed to save scan:', error);
+  }
+}
+
 export function useScanQr() {
   const [isScanning, setIsScanning] = useState(false);
   const [scanResult, setScanResult] = useState<ScanResult | null>(null);
@@ -67,6 +77,7 @@ export function useScanQr() {
     try {
       const result = await scannerAdapter.scanFromFile(filePath);
       setScanResult(result);
+      await recordScan('file', filePath, result);
       return result;
     } catch (error) {
       console.error('Scan error:', error);
@@ -83,26 +94,30 @@ export function useScanQr() {
     }
   }, []);
 
-  const scanFromData = useCallback(async (imageData: string): Promise<ScanResult | null> => {
-    setIsScanning(true);
-    try {
-      const result = await scannerAdapter.scanFromData(imageData);
-      setScanResult(result);
-      return result;
-    } catch (error) {
-      console.error('Scan error:', error);
-      const errorResult: ScanResult = {
-        success: false,
-        content: null,
-        qrType: null,
-        error: `Scan error: ${error}`,
-      };
-      setScanResult(errorResult);
-      return null;
-    } finally {
-      setIsScanning(false);
-    }
-  }, []);
+  const scanFromData = useCallback(
+    async (imageData: string, source: ScanSource = 'data'): Promise<ScanResult | null> => {
+      setIsScanning(true);
+      try {
+        const result = await scannerAdapter.scanFromData(imageData);
+        setScanResult(result);
+        await recordScan(source, null, result);
+        return result;
+      } catch (error) {
+        console.error('Scan error:', error);
+        const errorResult: ScanResult = {
+          success: false,
+          content: null,
+          qrType: null,
+          error: `Scan error: ${error}`,
+        };
+        setScanResult(errorResult);
+        return null;
+      } finally {
+        setIsScanning(false);
+      }
+    },
+    []
+  );
 
   const clearScan = useCallback(() => {
     setScanResult(null);
@@ -0,0 +1,126 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { renderHook, act, waitFor } from '@testing-library/react';
+import { useWorkspaces, useWorkspaceChanged } from './useWorkspaces';
+import { workspaceAdapter } from '@platform';
+import type { WorkspaceList } from '../platform/types';
+
+const mockList = vi.mocked(workspaceAdapter.list);
+const mockCreate = vi.mocked(workspaceAdapter.create);
+const mockSwitch = vi.mocked(workspaceAdapter.switch);
+const mockDelete = vi.mocked(workspaceAdapter.delete);
+const mockSetDataDir = vi.mocked(workspaceAdapter.setDataDir);
+const mockListen = vi.mocked(workspaceAdapter.listen);
+
+function workspaceList(active: string, names: string[]): WorkspaceList {
+  return {
+    active,
+    dataDir: '/data',
+    portable: false,
+    workspaces: names.map((name) => ({ name, path: `/data/${name}.db`, bytes: 4096 })),
+    dataDirError: null,
+  };
+}
+
+describe('useWorkspaces', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('fetches workspaces', async () => {
+    mockList.mockResolvedValueOnce(workspaceList('default', ['default', 'Client A']));
+    const { result } = renderHook(() => useWorkspaces());
+
+    await act(async () => {
+      await result.current.fetchWorkspaces();
+    });
+
+    expect(result.current.workspaces?.active).toBe('default');
+    expect(result.current.workspaces?.workspaces).toHaveLength(2);
+    expect(result.current.isLoading).toBe(false);
+  });
+
+  it('creates a workspace and switches to it', async () => {
+    mockCreate.mockResolvedValueOnce({ name: 'Client B', path: '/data/Client B.db', bytes: 0 });
+    mockSwitch.mockResolvedValueOnce(workspaceList('Client B', ['default', 'Client B']));
+    const { result } = renderHook(() => useWorkspaces());
+
+    let success: boolean | undefined;
+    await act(async () => {
+      success = await result.current.createWorkspace('Client B');
+    });
+
+    expect(success).toBe(true);
+    expect(mockCreate).toHaveBeenCalledWith('Client B');
+    expect(mockSwitch).toHaveBeenCalledWith('Client B');
+    expect(result.current.workspaces?.active).toBe('Client B');
+  });
+
+  it('returns false when switching fails', async () => {
+    mockSwitch.mockRejectedValueOnce('Workspace "gone" not found');
+    const { result } = renderHook(() => useWorkspaces());
+
+    let success: boolean | undefined;
+    await act(async () => {
+      success = await result.current.switchWorkspace('gone');
+    });
+
+    expect(success).toBe(false);
+    expect(result.current.workspaces).toBeNull();
+  });
+
+  it('refreshes the list after deleting a workspace', async () => {
+    mockDelete.mockResolvedValueOnce(true);
+    mockList.mockResolvedValueOnce(workspaceList('default', ['default']));
+    const { result } = renderHook(() => useWorkspaces());
+
+    await act(async () => {
+      await result.current.deleteWorkspace('Client A');
+    });
+
+    expect(mockDelete).toHaveBeenCalledWith('Client A');
+    expect(result.current.workspaces?.workspaces.map((w) => w.name)).toEqual(['default']);
+  });
+
+  it('moves the data directory', async () => {
+    mockSetDataDir.mockResolvedValueOnce({
+      workspaces: { ...workspaceList('default', ['default']), dataDir: '/mnt/qr' },
+      copy: { copied: ['default'], skipped: [] },
+    });
+    const { result } = renderHook(() => useWorkspaces());
+
+    let moved: Awaited<ReturnType<typeof result.current.moveDataDir>> | undefined;
+    await act(async () => {
+      moved = await result.current.moveDataDir('/mnt/qr', true);
+    });
+
+    expect(mockSetDataDir).toHaveBeenCalledWith('/mnt/qr', true);
+    expect(moved?.copy?.copied).toEqual(['default']);
+    expect(result.current.workspaces?.dataDir).toBe('/mnt/qr');
+  });
+});
+
+describe('useWorkspaceChanged', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('calls back when the workspace changes and unlistens on unmount', async () => {
+    const unlisten = vi.fn();
+    let emit: ((list: WorkspaceList) => void) | undefined;
+    mockListen.mockImplementationOnce(async (callback) => {
+      emit = callback;
+      return unlisten;
+    });
+
+    const onChange = vi.fn();
+    const { unmount } = renderHook(() => useWorkspaceChanged(onChange));
+    await waitFor(() => expect(emit).toBeDefined());
+
+    const list = workspaceList('Client A', ['default', 'Client A']);
+    act(() => emit!(list));
+    expect(onChange).toHaveBeenCalledWith(list);
+
+    unmount();
+    expect(unlisten).toHaveBeenCalled();
+  });
+});
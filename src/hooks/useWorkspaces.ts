@@ -0,0 +1,121 @@
+import { useCallback, useEffect, useRef, useState } from 'react';
+import { workspaceAdapter } from '@platform';
+import type { DataDirResult, WorkspaceList } from '../platform/types';
+
+export type { WorkspaceList };
+
+/**
+ * Run `onChange` after switching workspace or data directory, so views can
+ * reload data that belongs to the previous workspace.
+ */
+export function useWorkspaceChanged(onChange: (list: WorkspaceList) => void) {
+  const callbackRef = useRef(onChange);
+  callbackRef.current = onChange;
+
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+
+    (async () => {
+      try {
+        const fn = await workspaceAdapter.listen((list) => callbackRef.current(list));
+        if (cancelled) fn?.();
+        else unlisten = fn;
+      } catch (error) {
+        console.warn('Workspace events not available:', error);
+      }
+    })();
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
+}
+
+export function useWorkspaces() {
+  const [workspaces, setWorkspaces] = useState<WorkspaceList | null>(null);
+  const [isLoading, setIsLoading] = useState(false);
+
+  useWorkspaceChanged(setWorkspaces);
+
+  const fetchWorkspaces = useCallback(async (): Promise<void> => {
+    setIsLoading(true);
+    try {
+      setWorkspaces(await workspaceAdapter.list());
+    } catch (error) {
+      console.error('Failed to fetch workspaces:', error);
+    } finally {
+      setIsLoading(false);
+    }
+  }, []);
+
+  const createWorkspace = useCallback(async (name: string): Promise<boolean> => {
+    try {
+      await workspaceAdapter.create(name);
+      setWorkspaces(await workspaceAdapter.switch(name));
+      return true;
+    } catch (error) {
+      console.error('Failed to create workspace:', error);
+      return false;
+    }
+  }, []);
+
+  const switchWorkspace = useCallback(async (name: string): Promise<boolean> => {
+    try {
+      setWorkspaces(await workspaceAdapter.switch(name));
+      return true;
+    } catch (error) {
+      console.error('Failed to switch workspace:', error);
+      return false;
+    }
+  }, []);
+
+  const deleteWorkspace = useCallback(
+    async (name: string): Promise<boolean> => {
+      try {
+        const deleted = await workspaceAdapter.delete(name);
+        if (deleted) await fetchWorkspaces();
+        return deleted;
+      } catch (error) {
+        console.error('Failed to delete workspace:', error);
+        return false;
+      }
+    },
+    [fetchWorkspaces]
+  );
+
+  const moveDataDir = useCallback(
+    async (path: string | null, copyExisting: boolean): Promise<DataDirResult | null> => {
+      try {
+        const result = await workspaceAdapter.setDataDir(path, copyExisting);
+        setWorkspaces(result.workspaces);
+        return result;
+      } catch (error) {
+        console.error('Failed to change data directory:', error);
+        return null;
+      }
+    },
+    []
+  );
+
+  const pickDataDir = useCallback(async (): Promise<string | null> => {
+    try {
+      return await workspaceAdapter.pickDataDir();
+    } catch (error) {
+      console.error('Failed to pick data directory:', error);
+      return null;
+    }
+  }, []);
+
+  return {
+    workspaces,
+    isLoading,
+    fetchWorkspaces,
+    createWorkspace,
+    switchWorkspace,
+    deleteWorkspace,
+    moveDataDir,
+    pickDataDir,
+  };
+}
@@ -0,0 +1,162 @@
+import { describe, it, expect } from 'vitest';
+import {
+  LIBRARY_FORMAT,
+  createLibrary,
+  crc32,
+  detectLibraryFormat,
+  isRedacted,
+  libraryFromBundle,
+  libraryFromCsv,
+  libraryFromJson,
+  libraryToBundle,
+  libraryToCsv,
+  libraryToJson,
+  readZip,
+  redactSecrets,
+  writeZip,
+  type Library,
+} from './library';
+
+const PNG = 'data:image/png;base64,iVBORw0KGgo=';
+const LOGO = 'data:image/jpeg;base64,/9j/4AAQ';
+
+function sampleLibrary(): Library {
+  return createLibrary(
+    [
+      {
+        content: 'https://example.com',
+        qrType: 'url',
+        label: 'Site, "quoted"',
+        styleJson: JSON.stringify({ dotStyle: 'dots', logo: LOGO }),
+        thumbnail: PNG,
+        pinned: true,
+        tags: ['work', 'links'],
+        createdAt: '2026-01-02T03:04:05Z',
+        updatedAt: '2026-01-03T03:04:05Z',
+        redacted: false,
+      },
+    ],
+    [
+      {
+        name: 'Brand',
+        styleJson: JSON.stringify({ logo: LOGO }),
+        preview: PNG,
+        isDefault: true,
+        createdAt: '2026-01-02T03:04:05Z',
+      },
+    ],
+  );
+}
+
+describe('redactSecrets', () => {
+  it('masks WiFi passwords, honoring escapes', () => {
+    expect(redactSecrets('WIFI:T:WPA;S:Office;P:hun\\;ter2;;')).toBe(
+      'WIFI:T:WPA;S:Office;P:********;;',
+    );
+  });
+
+  it('masks OTP secrets', () => {
+    expect(redactSecrets('otpauth://totp/Acme?secret=JBSWY3DP&issuer=Acme')).toBe(
+      'otpauth://totp/Acme?secret=********&issuer=Acme',
+    );
+  });
+
+  it('leaves other content alone', () => {
+    expect(redactSecrets('https://example.com')).toBe('https://example.com');
+  });
+});
+
+describe('isRedacted', () => {
+  const item = (content: string, redacted = false) => ({
+    ...sampleLibrary().history[0],
+    content,
+    redacted,
+  });
+
+  it('trusts the flag', () => {
+    expect(isRedacted(item('WIFI:S:Office;P:hunter2;;', true))).toBe(true);
+  });
+
+  it('recognizes masked content in files without the flag', () => {
+    expect(isRedacted(item('WIFI:T:WPA;S:Office;P:********;;'))).toBe(true);
+    expect(isRedacted(item('otpauth://totp/Acme?secret=********'))).toBe(true);
+    expect(isRedacted(item('WIFI:T:WPA;S:Office;P:hunter2;;'))).toBe(false);
+    expect(isRedacted(item('https://example.com/********'))).toBe(false);
+  });
+
+  it('survives a CSV round trip', () => {
+    const library = sampleLibrary();
+    library.history[0].redacted = true;
+    expect(libraryFromCsv(libraryToCsv(library)).history[0].redacted).toBe(true);
+  });
+});
+
+describe('JSON', () => {
+  it('round-trips a library', () => {
+    const library = sampleLibrary();
+    expect(libraryFromJson(libraryToJson(library))).toEqual(library);
+  });
+
+  it('fills in optional fields from a desktop export', () => {
+    const library = libraryFromJson(
+      JSON.stringify({
+        format: LIBRARY_FORMAT,
+        version: 1,
+        exportedAt: '2026-01-01T00:00:00Z',
+        history: [{ content: 'hello', qrType: 'text' }],
+      }),
+    );
+    expect(library.history[0]).toMatchObject({ styleJson: '{}', tags: [], pinned: false });
+    expect(library.templates).toEqual([]);
+  });
+
+  it('rejects other files and newer versions', () => {
+    expect(() => libraryFromJson('{"hello": 1}')).toThrow('Not a QR Foundry library');
+    expect(() => libraryFromJson(JSON.stringify({ format: LIBRARY_FORMAT, version: 99 }))).toThrow(
+      'newer',
+    );
+  });
+});
+
+describe('CSV', () => {
+  it('round-trips text fields', () => {
+    const library = sampleLibrary();
+    const parsed = libraryFromCsv(libraryToCsv(library));
+    expect(parsed.history[0]).toEqual({ ...library.history[0], thumbnail: null });
+    expect(parsed.templates[0]).toEqual({ ...library.templates[0], preview: null });
+  });
+
+  it('accepts a content-only sheet and detects types', () => {
+    const library = libraryFromCsv('Content\nhttps://example.com\n\nhello\n');
+    expect(library.history.map((h) => h.qrType)).toEqual(['url', 'text']);
+  });
+
+  it('requires a content column', () => {
+    expect(() => libraryFromCsv('name,value\na,b\n')).toThrow('content');
+  });
+});
+
+describe('ZIP', () => {
+  it('computes the standard CRC-32', () => {
+    expect(crc32(new TextEncoder().encode('123456789'))).toBe(0xcbf43926);
+  });
+
+  it('reads back what it writes', async () => {
+    const data = new TextEncoder().encode('hello');
+    const entries = await readZip(writeZip([{ name: 'a/b.txt', data }]));
+    expect(new TextDecoder().decode(entries.get('a/b.txt'))).toBe('hello');
+  });
+});
+
+describe('bundle', () => {
+  it('moves images into files and back', async () => {
+    const library = sampleLibrary();
+    const bundle = await libraryToBundle(library);
+    expect(detectLibraryFormat('export', bundle)).toBe('qrfoundry');
+
+    const manifest = new TextDecoder().decode((await readZip(bundle)).get('library.json'));
+    expect(manifest).not.toContain('base64');
+
+    expect(await libraryFromBundle(bundle)).toEqual(library);
+  });
+});
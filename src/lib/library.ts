@@ -0,0 +1,567 @@
+import type { LibraryFormat } from '../platform/types';
+import { detectQrType } from './formatters';
+
+/**
+ * The portable library format shared with the desktop app.
+ *
+ * Mirrors `src-tauri/src/commands/library.rs`: a JSON document, a flat CSV,
+ * or a `.qrfoundry` ZIP holding `library.json` plus an `images/` folder.
+ */
+
+export const LIBRARY_FORMAT = 'qr-foundry-library';
+export const LIBRARY_VERSION = 1;
+
+export interface LibraryHistoryItem {
+  content: string;
+  qrType: string;
+  label: string | null;
+  styleJson: string;
+  /** Data URL, or an `images/` path inside a bundle */
+  thumbnail: string | null;
+  pinned: boolean;
+  tags: string[];
+  createdAt: string | null;
+  updatedAt: string | null;
+  /** The secret was masked on export, so `content` can't be used as is */
+  redacted: boolean;
+}
+
+export interface LibraryTemplate {
+  name: string;
+  styleJson: string;
+  preview: string | null;
+  isDefault: boolean;
+  createdAt: string | null;
+}
+
+export interface Library {
+  format: string;
+  version: number;
+  exportedAt: string;
+  history: LibraryHistoryItem[];
+  templates: LibraryTemplate[];
+}
+
+const BUNDLE_MANIFEST = 'library.json';
+const BUNDLE_IMAGES = 'images/';
+
+const CSV_HEADERS = [
+  'kind',
+  'label',
+  'content',
+  'qrType',
+  'styleJson',
+  'tags',
+  'pinned',
+  'isDefault',
+  'createdAt',
+  'updatedAt',
+  'redacted',
+] as const;
+
+const CSV_TAG_SEPARATOR = ';';
+
+export function createLibrary(
+  history: LibraryHistoryItem[],
+  templates: LibraryTemplate[],
+): Library {
+  return {
+    format: LIBRARY_FORMAT,
+    version: LIBRARY_VERSION,
+    exportedAt: new Date().toISOString(),
+    history,
+    templates,
+  };
+}
+
+/** Guess from the file name, falling back to sniffing the contents */
+export function detectLibraryFormat(name: string, bytes: Uint8Array): LibraryFormat {
+  const extension = name.includes('.') ? name.split('.').pop()!.toLowerCase() : '';
+  if (extension === 'qrfoundry' || extension === 'zip') return 'qrfoundry';
+  if (extension === 'csv') return 'csv';
+  if (extension === 'json') return 'json';
+  if (bytes[0] === 0x50 && bytes[1] === 0x4b) return 'qrfoundry';
+  return new TextDecoder().decode(bytes.slice(0, 64)).trimStart().startsWith('{') ? 'json' : 'csv';
+}
+
+const REDACTED = '********';
+
+/** Mask WiFi passwords and OTP secrets, as the desktop app does */
+export function redactSecrets(content: string): string {
+  if (/^WIFI:/i.test(content)) {
+    return content.replace(/(^WIFI:|;)(P:)(?:\\.|[^;\\])*/i, `$1$2${REDACTED}`);
+  }
+  if (/^otpauth:\/\//i.test(content)) {
+    return content.replace(/([?&]secret=)[^&]*/i, `$1${REDACTED}`);
+  }
+  return content;
+}
+
+/** Whether an item holds a masked copy, flagged or (in older files) not */
+export function isRedacted(item: LibraryHistoryItem): boolean {
+  if (item.redacted) return true;
+  const sensitive = /^(WIFI:|otpauth:\/\/)/i.test(item.content);
+  return sensitive && item.content.includes(REDACTED) && redactSecrets(item.content) === item.content;
+}
+
+// --- JSON ---
+
+export function libraryToJson(library: Library): string {
+  return JSON.stringify(library, null, 2);
+}
+
+export function libraryFromJson(text: string): Library {
+  let parsed: Partial<Library>;
+  try {
+    parsed = JSON.parse(text);
+  } catch (error) {
+    throw new Error(`Invalid library file: ${error}`);
+  }
+  if (parsed.format !== LIBRARY_FORMAT) {
+    throw new Error('Not a QR Foundry library file');
+  }
+  if ((parsed.version ?? 0) > LIBRARY_VERSION) {
+    throw new Error(
+      `Library version ${parsed.version} is newer than this app supports (${LIBRARY_VERSION}); please update QR Foundry`,
+    );
+  }
+
+  // Fill in the fields the desktop app treats as optional
+  const library = createLibrary(
+    (parsed.history ?? []).map((item) => ({
+      content: item.content,
+      qrType: item.qrType,
+      label: item.label ?? null,
+      styleJson: item.styleJson ?? '{}',
+      thumbnail: item.thumbnail ?? null,
+      pinned: item.pinned ?? false,
+      tags: item.tags ?? [],
+      createdAt: item.createdAt ?? null,
+      updatedAt: item.updatedAt ?? null,
+      redacted: item.redacted ?? false,
+    })),
+    (parsed.templates ?? []).map((template) => ({
+      name: template.name,
+      styleJson: template.styleJson,
+      preview: template.preview ?? null,
+      isDefault: template.isDefault ?? false,
+      createdAt: template.createdAt ?? null,
+    })),
+  );
+  library.exportedAt = parsed.exportedAt ?? library.exportedAt;
+  return library;
+}
+
+// --- CSV ---
+
+function csvField(value: string): string {
+  return /[",\r\n]/.test(value) ? `"${value.replace(/"/g, '""')}"` : value;
+}
+
+/** Split CSV text into rows, honouring quoted fields */
+function parseCsvRows(text: string): string[][] {
+  const rows: string[][] = [];
+  let row: string[] = [];
+  let field = '';
+  let quoted = false;
+
+  for (let i = 0; i < text.length; i++) {
+    const char = text[i];
+    if (quoted) {
+      if (char === '"' && text[i + 1] === '"') {
+        field += '"';
+        i++;
+      } else if (char === '"') {
+        quoted = false;
+      } else {
+        field += char;
+      }
+    } else if (char === '"') {
+      quoted = true;
+    } else if (char === ',') {
+      row.push(field);
+      field = '';
+    } else if (char === '\n' || char === '\r') {
+      if (char === '\r' && text[i + 1] === '\n') i++;
+      row.push(field);
+      rows.push(row);
+      row = [];
+      field = '';
+    } else {
+      field += char;
+    }
+  }
+  if (field || row.length > 0) {
+    row.push(field);
+    rows.push(row);
+  }
+  return rows.filter((r) => r.some((f) => f !== ''));
+}
+
+export function libraryToCsv(library: Library): string {
+  const rows: string[][] = [Array.from(CSV_HEADERS)];
+  for (const item of library.history) {
+    rows.push([
+      'history',
+      item.label ?? '',
+      item.content,
+      item.qrType,
+      item.styleJson,
+      item.tags.join(CSV_TAG_SEPARATOR),
+      String(item.pinned),
+      '',
+      item.createdAt ?? '',
+      item.updatedAt ?? '',
+      String(item.redacted),
+    ]);
+  }
+  for (const template of library.templates) {
+    rows.push([
+      'template',
+      template.name,
+      '',
+      '',
+      template.styleJson,
+      '',
+      '',
+      String(template.isDefault),
+      template.createdAt ?? '',
+      '',
+      '',
+    ]);
+  }
+  return rows.map((row) => row.map(csvField).join(',')).join('\n') + '\n';
+}
+
+/**
+ * Read CSV rows written by `libraryToCsv`, or a hand-made subset.
+ * Only `content` is required; rows default to history.
+ */
+export function libraryFromCsv(text: string): Library {
+  const [headers, ...records] = parseCsvRows(text.replace(/^\uFEFF/, ''));
+  const columns = new Map<string, number>();
+  for (const name of CSV_HEADERS) {
+    const index = (headers ?? []).findIndex((h) => h.trim().toLowerCase() === name.toLowerCase());
+    if (index !== -1) columns.set(name, index);
+  }
+  if (!columns.has('content') && !columns.has('kind')) {
+    throw new Error("CSV must have a 'content' column");
+  }
+
+  const history: LibraryHistoryItem[] = [];
+  const templates: LibraryTemplate[] = [];
+  for (const record of records) {
+    const field = (name: string) => {
+      const index = columns.get(name);
+      return index === undefined ? '' : (record[index] ?? '');
+    };
+    const optional = (name: string) => field(name) || null;
+    const flag = (name: string) => ['true', '1', 'yes'].includes(field(name).toLowerCase());
+    const styleJson = optional('styleJson') ?? '{}';
+
+    if (field('kind').toLowerCase() === 'template') {
+      const name = optional('label');
+      if (!name) continue;
+      templates.push({
+        name,
+        styleJson,
+        preview: null,
+        isDefault: flag('isDefault'),
+        createdAt: optional('createdAt'),
+      });
+      continue;
+    }
+
+    const content = field('content');
+    if (!content) continue;
+    history.push({
+      content,
+      qrType: optional('qrType') ?? detectQrType(content),
+      label: optional('label'),
+      styleJson,
+      thumbnail: null,
+      pinned: flag('pinned'),
+      tags: field('tags')
+        .split(CSV_TAG_SEPARATOR)
+        .map((tag) => tag.trim())
+        .filter(Boolean),
+      createdAt: optional('createdAt'),
+      updatedAt: optional('updatedAt'),
+      redacted: flag('redacted'),
+    });
+  }
+
+  return createLibrary(history, templates);
+}
+
+// --- Bundle ---
+
+const IMAGE_EXTENSIONS: Record<string, string> = {
+  'image/png': 'png',
+  'image/jpeg': 'jpg',
+  'image/gif': 'gif',
+  'image/webp': 'webp',
+  'image/svg+xml': 'svg',
+};
+
+function imageMime(path: string): string {
+  const extension = path.split('.').pop();
+  if (extension === 'jpeg') return 'image/jpeg';
+  const match = Object.entries(IMAGE_EXTENSIONS).find(([, ext]) => ext === extension);
+  return match ? match[0] : 'application/octet-stream';
+}
+
+function parseDataUrl(dataUrl: string): { mime: string; bytes: Uint8Array } | null {
+  const match = /^data:([^;,]+);base64,(.*)$/s.exec(dataUrl);
+  if (!match) return null;
+  try {
+    const binary = atob(match[2]);
+    return { mime: match[1], bytes: Uint8Array.from(binary, (c) => c.charCodeAt(0)) };
+  } catch {
+    return null;
+  }
+}
+
+function toDataUrl(mime: string, bytes: Uint8Array): string {
+  let binary = '';
+  for (let i = 0; i < bytes.length; i += 0x8000) {
+    binary += String.fromCharCode(...bytes.subarray(i, i + 0x8000));
+  }
+  return `data:${mime};base64,${btoa(binary)}`;
+}
+
+/** The style's inline logo, if it has one */
+function styleLogo(styleJson: string): string | null {
+  try {
+    const logo = JSON.parse(styleJson)?.logo;
+    return typeof logo === 'string' ? logo : null;
+  } catch {
+    return null;
+  }
+}
+
+const CRC_TABLE = Array.from({ length: 256 }, (_, n) => {
+  let c = n;
+  for (let k = 0; k < 8; k++) c = c & 1 ? 0xedb88320 ^ (c >>> 1) : c >>> 1;
+  return c >>> 0;
+});
+
+export function crc32(bytes: Uint8Array): number {
+  let crc = 0xffffffff;
+  for (const byte of bytes) crc = CRC_TABLE[(crc ^ byte) & 0xff] ^ (crc >>> 8);
+  return (crc ^ 0xffffffff) >>> 0;
+}
+
+/** 1980-01-01, the earliest date ZIP can hold */
+const DOS_DATE = 0x21;
+
+/** Write an uncompressed ZIP archive */
+export function writeZip(files: { name: string; data: Uint8Array }[]): Uint8Array {
+  const encoder = new TextEncoder();
+  const locals: Uint8Array[] = [];
+  const centrals: Uint8Array[] = [];
+  let offset = 0;
+
+  for (const file of files) {
+    const name = encoder.encode(file.name);
+    const crc = crc32(file.data);
+
+    const local = new Uint8Array(30 + name.length);
+    const lv = new DataView(local.buffer);
+    lv.setUint32(0, 0x04034b50, true);
+    lv.setUint16(4, 20, true); // version needed
+    lv.setUint16(6, 0x0800, true); // UTF-8 names
+    lv.setUint16(12, DOS_DATE, true);
+    lv.setUint32(14, crc, true);
+    lv.setUint32(18, file.data.length, true);
+    lv.setUint32(22, file.data.length, true);
+    lv.setUint16(26, name.length, true);
+    local.set(name, 30);
+
+    const central = new Uint8Array(46 + name.length);
+    const cv = new DataView(central.buffer);
+    cv.setUint32(0, 0x02014b50, true);
+    cv.setUint16(4, 20, true); // version made by
+    cv.setUint16(6, 20, true);
+    cv.setUint16(8, 0x0800, true);
+    cv.setUint16(14, DOS_DATE, true);
+    cv.setUint32(16, crc, true);
+    cv.setUint32(20, file.data.length, true);
+    cv.setUint32(24, file.data.length, true);
+    cv.setUint16(28, name.length, true);
+    cv.setUint32(42, offset, true);
+    central.set(name, 46);
+
+    locals.push(local, file.data);
+    centrals.push(central);
+    offset += local.length + file.data.length;
+  }
+
+  const centralSize = centrals.reduce((sum, c) => sum + c.length, 0);
+  const end = new Uint8Array(22);
+  const ev = new DataView(end.buffer);
+  ev.setUint32(0, 0x06054b50, true);
+  ev.setUint16(8, files.length, true);
+  ev.setUint16(10, files.length, true);
+  ev.setUint32(12, centralSize, true);
+  ev.setUint32(16, offset, true);
+
+  const parts = [...locals, ...centrals, end];
+  const out = new Uint8Array(parts.reduce((sum, p) => sum + p.length, 0));
+  let position = 0;
+  for (const part of parts) {
+    out.set(part, position);
+    position += part.length;
+  }
+  return out;
+}
+
+async function inflateRaw(data: Uint8Array): Promise<Uint8Array> {
+  const stream = new Blob([data]).stream().pipeThrough(new DecompressionStream('deflate-raw'));
+  return new Uint8Array(await new Response(stream).arrayBuffer());
+}
+
+/** Read a ZIP archive's stored and deflated entries */
+export async function readZip(bytes: Uint8Array): Promise<Map<string, Uint8Array>> {
+  const view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+  let end = bytes.length - 22;
+  while (end >= 0 && view.getUint32(end, true) !== 0x06054b50) end--;
+  if (end < 0) throw new Error('Invalid bundle: not a ZIP archive');
+
+  const count = view.getUint16(end + 10, true);
+  let position = view.getUint32(end + 16, true);
+  const decoder = new TextDecoder();
+  const entries = new Map<string, Uint8Array>();
+
+  for (let i = 0; i < count; i++) {
+    if (view.getUint32(position, true) !== 0x02014b50) {
+      throw new Error('Invalid bundle: corrupt directory');
+    }
+    const method = view.getUint16(position + 10, true);
+    const compressedSize = view.getUint32(position + 20, true);
+    const nameLength = view.getUint16(position + 28, true);
+    const extraLength = view.getUint16(position + 30, true);
+    const commentLength = view.getUint16(position + 32, true);
+    const localOffset = view.getUint32(position + 42, true);
+    const name = decoder.decode(bytes.subarray(position + 46, position + 46 + nameLength));
+    position += 46 + nameLength + extraLength + commentLength;
+
+    const dataStart =
+      localOffset +
+      30 +
+      view.getUint16(localOffset + 26, true) +
+      view.getUint16(localOffset + 28, true);
+    const data = bytes.subarray(dataStart, dataStart + compressedSize);
+    if (method === 0) {
+      entries.set(name, data);
+    } else if (method === 8) {
+      entries.set(name, await inflateRaw(data));
+    }
+    // Other methods are skipped, like a missing image
+  }
+  return entries;
+}
+
+async function imagePath(mime: string, bytes: Uint8Array): Promise<string> {
+  const digest = new Uint8Array(await crypto.subtle.digest('SHA-256', bytes));
+  const name = Array.from(digest.subarray(0, 12), (b) => b.toString(16).padStart(2, '0')).join('');
+  return `${BUNDLE_IMAGES}${name}.${IMAGE_EXTENSIONS[mime] ?? 'bin'}`;
+}
+
+export async function libraryToBundle(library: Library): Promise<Uint8Array> {
+  const files = new Map<string, Uint8Array>();
+  const paths = new Map<string, string>(); // data URL -> archive path
+
+  const add = async (dataUrl: string | null): Promise<string | null> => {
+    if (!dataUrl) return null;
+    const known = paths.get(dataUrl);
+    if (known) return known;
+    const parsed = parseDataUrl(dataUrl);
+    if (!parsed) return null;
+    const path = await imagePath(parsed.mime, parsed.bytes);
+    paths.set(dataUrl, path);
+    files.set(path, parsed.bytes);
+    return path;
+  };
+  // Edit the JSON text so the style comes back byte-for-byte
+  const externalizeLogo = async (styleJson: string) => {
+    const logo = styleLogo(styleJson);
+    const path = await add(logo);
+    return logo && path ? styleJson.split(logo).join(path) : styleJson;
+  };
+
+  const bundled = createLibrary([], []);
+  bundled.exportedAt = library.exportedAt;
+  for (const item of library.history) {
+    bundled.history.push({
+      ...item,
+      thumbnail: (await add(item.thumbnail)) ?? item.thumbnail,
+      styleJson: await externalizeLogo(item.styleJson),
+    });
+  }
+  for (const template of library.templates) {
+    bundled.templates.push({
+      ...template,
+      preview: (await add(template.preview)) ?? template.preview,
+      styleJson: await externalizeLogo(template.styleJson),
+    });
+  }
+
+  return writeZip([
+    { name: BUNDLE_MANIFEST, data: new TextEncoder().encode(libraryToJson(bundled)) },
+    ...Array.from(files, ([name, data]) => ({ name, data })),
+  ]);
+}
+
+export async function libraryFromBundle(bytes: Uint8Array): Promise<Library> {
+  const entries = await readZip(bytes);
+  const manifest = entries.get(BUNDLE_MANIFEST);
+  if (!manifest) throw new Error(`Bundle has no ${BUNDLE_MANIFEST}`);
+  const library = libraryFromJson(new TextDecoder().decode(manifest));
+
+  // A missing image just leaves the item without one
+  const inline = (path: string | null): string | null => {
+    if (!path?.startsWith(BUNDLE_IMAGES)) return path;
+    const data = entries.get(path);
+    return data ? toDataUrl(imageMime(path), data) : null;
+  };
+  const restoreLogo = (styleJson: string) => {
+    const logo = styleLogo(styleJson);
+    const dataUrl = logo?.startsWith(BUNDLE_IMAGES) ? inline(logo) : null;
+    return logo && dataUrl ? styleJson.split(logo).join(dataUrl) : styleJson;
+  };
+
+  for (const item of library.history) {
+    item.thumbnail = inline(item.thumbnail);
+    item.styleJson = restoreLogo(item.styleJson);
+  }
+  for (const template of library.templates) {
+    template.preview = inline(template.preview);
+    template.styleJson = restoreLogo(template.styleJson);
+  }
+  return library;
+}
+
+// --- Any format ---
+
+export async function writeLibrary(library: Library, format: LibraryFormat): Promise<Blob> {
+  switch (format) {
+    case 'json':
+      return new Blob([libraryToJson(library)], { type: 'application/json' });
+    case 'csv':
+      return new Blob([libraryToCsv(library)], { type: 'text/csv' });
+    case 'qrfoundry':
+      return new Blob([await libraryToBundle(library)], { type: 'application/zip' });
+  }
+}
+
+export async function readLibrary(name: string, bytes: Uint8Array): Promise<Library> {
+  switch (detectLibraryFormat(name, bytes)) {
+    case 'json':
+      return libraryFromJson(new TextDecoder().decode(bytes));
+    case 'csv':
+      return libraryFromCsv(new TextDecoder().decode(bytes));
+    case 'qrfoundry':
+      return libraryFromBundle(bytes);
+  }
+}
@@ -0,0 +1,28 @@
+import { describe, it, expect } from 'vitest';
+import { QR_TYPES, QR_TYPE_ALIASES, normalizeQrType } from './qrTypes';
+
+describe('normalizeQrType', () => {
+  it('accepts known types in any case', () => {
+    expect(normalizeQrType('URL')).toBe('url');
+    expect(normalizeQrType(' wifi ')).toBe('wifi');
+    expect(normalizeQrType('payment')).toBe('payment');
+  });
+
+  it('maps aliases to their type', () => {
+    expect(normalizeQrType('tel')).toBe('phone');
+    expect(normalizeQrType('contact')).toBe('vcard');
+    expect(normalizeQrType('TOTP')).toBe('otp');
+  });
+
+  it('rejects unknown names', () => {
+    expect(normalizeQrType('barcode')).toBeNull();
+    expect(normalizeQrType('')).toBeNull();
+    expect(normalizeQrType('constructor')).toBeNull();
+  });
+
+  it('only aliases to known types', () => {
+    for (const type of Object.values(QR_TYPE_ALIASES)) {
+      expect(QR_TYPES).toContain(type);
+    }
+  });
+});
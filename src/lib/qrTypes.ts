@@ -0,0 +1,47 @@
+import type { QrType } from '../types/qr';
+
+/**
+ * Every QR type the app understands, shared by the scanner and CSV import.
+ * Mirrors `QR_TYPES` in src-tauri/src/qr_type.rs; a Rust test keeps them in step.
+ */
+export const QR_TYPES: readonly QrType[] = [
+  'url',
+  'text',
+  'wifi',
+  'vcard',
+  'email',
+  'sms',
+  'phone',
+  'geo',
+  'calendar',
+  'otp',
+  'crypto',
+  'payment',
+  'app',
+  'media',
+];
+
+/** Alternative spellings accepted in CSV `type` columns */
+export const QR_TYPE_ALIASES: Readonly<Record<string, QrType>> = {
+  link: 'url',
+  contact: 'vcard',
+  mecard: 'vcard',
+  mail: 'email',
+  mailto: 'email',
+  tel: 'phone',
+  location: 'geo',
+  event: 'calendar',
+  vevent: 'calendar',
+  totp: 'otp',
+};
+
+/**
+ * Map a user-supplied type name to a known QR type, accepting aliases
+ */
+export function normalizeQrType(value: string): QrType | null {
+  const lower = value.trim().toLowerCase();
+  const known = QR_TYPES.find((type) => type === lower);
+  if (known) return known;
+  // Own keys only, so names like "constructor" aren't mistaken for aliases
+  return Object.prototype.hasOwnProperty.call(QR_TYPE_ALIASES, lower) ? QR_TYPE_ALIASES[lower] : null;
+}
@@ -2,17 +2,19 @@ import { invoke } from '@tauri-apps/api/core';
 import type { ExportAdapter, ExportResult } from '../types';
 
 export const exportAdapter: ExportAdapter = {
-  async exportPng(imageDataUrl: string, suggestedName = 'qr-code.png'): Promise<ExportResult> {
+  async exportPng(imageDataUrl: string, suggestedName = 'qr-code.png', directory?: string | null): Promise<ExportResult> {
     return invoke<ExportResult>('export_png', {
       imageData: imageDataUrl,
       suggestedName,
+      directory: directory ?? null,
     });
   },
 
-  async exportSvg(svgData: string, suggestedName = 'qr-code.svg'): Promise<ExportResult> {
+  async exportSvg(svgData: string, suggestedName = 'qr-code.svg', directory?: string | null): Promise<ExportResult> {
     return invoke<ExportResult>('export_svg', {
       svgData,
       suggestedName,
+      directory: directory ?? null,
     });
   },
 };
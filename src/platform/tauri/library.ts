@@ -0,0 +1,32 @@
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  ConflictStrategy,
+  ExportResult,
+  LibraryAdapter,
+  LibraryFormat,
+  LibraryImportReport,
+  LibrarySelection,
+} from '../types';
+
+export const libraryAdapter: LibraryAdapter = {
+  async export(
+    format: LibraryFormat,
+    selection?: LibrarySelection,
+    options?: { includeSecrets?: boolean },
+  ): Promise<ExportResult> {
+    return invoke<ExportResult>('library_export', {
+      format,
+      selection: selection ?? null,
+      includeSecrets: options?.includeSecrets ?? false,
+    });
+  },
+
+  async import(options?: { conflict?: ConflictStrategy }): Promise<LibraryImportReport | null> {
+    const path = await invoke<string | null>('pick_library_file');
+    if (!path) return null;
+    return invoke<LibraryImportReport>('library_import', {
+      path,
+      conflict: options?.conflict ?? null,
+    });
+  },
+};
@@ -1,7 +1,7 @@
 import { invoke } from '@tauri-apps/api/core';
 import { decodeWithJsQr, fileDataToDataUrl } from '../../lib/scanHelpers';
 import { filesystemAdapter } from './filesystem';
-import type { ScannerAdapter, ScanResult, ValidationResult } from '../types';
+import type { ScannerAdapter, ScanResult, ScanSource, ValidationResult } from '../types';
 
 export const scannerAdapter: ScannerAdapter = {
   async validateQr(imageDataUrl: string, expectedContent: string): Promise<ValidationResult> {
@@ -90,4 +90,7 @@ export const scannerAdapter: ScannerAdapter = {
       };
     }
   },
+  async saveScan(source: ScanSource, sourcePath: string | null, result: ScanResult): Promise<number | null> {
+    return invoke<number>('scan_save', { source, sourcePath, result, thumbnail: null });
+  },
 };
@@ -0,0 +1,21 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { AppSettings, SettingsAdapter, SettingsChange, UnlistenFn } from '../types';
+
+export const settingsAdapter: SettingsAdapter = {
+  async get(): Promise<AppSettings> {
+    return invoke<AppSettings>('settings_get');
+  },
+
+  async update(patch: Partial<AppSettings>): Promise<SettingsChange> {
+    return invoke<SettingsChange>('settings_update', { patch });
+  },
+
+  async reset(keys?: (keyof AppSettings)[]): Promise<SettingsChange> {
+    return invoke<SettingsChange>('settings_reset', { keys: keys ?? null });
+  },
+
+  async listen(callback: (change: SettingsChange) => void): Promise<UnlistenFn> {
+    return listen<SettingsChange>('settings-changed', (event) => callback(event.payload));
+  },
+};
@@ -1,6 +1,8 @@
 import { invoke } from '@tauri-apps/api/core';
 import type {
+  DuplicateGroup,
   HistoryAdapter,
+  HistoryItem,
   HistoryListResult,
   NewHistoryItem,
   TemplateAdapter,
@@ -24,6 +26,45 @@ export const historyAdapter: HistoryAdapter = {
   async clear(): Promise<number> {
     return invoke<number>('history_clear');
   },
+
+  async listTrash(limit: number, offset: number): Promise<HistoryListResult> {
+    return invoke<HistoryListResult>('history_list', {
+      limit,
+      offset,
+      filter: { trashed: true },
+      sort: 'deletedDesc',
+    });
+  },
+
+  async restore(ids: number[]): Promise<number> {
+    return invoke<number>('history_restore', { ids });
+  },
+
+  async purge(ids: number[]): Promise<number> {
+    return invoke<number>('history_purge', { ids });
+  },
+
+  async emptyTrash(): Promise<number> {
+    return invoke<number>('history_empty_trash');
+  },
+
+  async getThumbnail(item: HistoryItem): Promise<string | null> {
+    if (!item.thumbnailRef) return item.thumbnail ?? null;
+    return invoke<string | null>('blob_get', { hash: item.thumbnailRef });
+  },
+
+  async getContent(item: HistoryItem): Promise<string> {
+    if (!item.sensitive && !item.encrypted) return item.content;
+    return invoke<string>('history_reveal', { id: item.id });
+  },
+
+  async findDuplicates(): Promise<DuplicateGroup[]> {
+    return invoke<DuplicateGroup[]>('history_duplicates');
+  },
+
+  async mergeDuplicates(keepIds?: number[]): Promise<number> {
+    return invoke<number>('history_merge_duplicates', { keepIds });
+  },
 };
 
 export const templateAdapter: TemplateAdapter = {
@@ -50,4 +91,20 @@ export const templateAdapter: TemplateAdapter = {
   async setDefault(id: number): Promise<boolean> {
     return invoke<boolean>('template_set_default', { id });
   },
+
+  async getPreview(template: Template): Promise<string | null> {
+    if (!template.previewRef) return template.preview ?? null;
+    return invoke<string | null>('blob_get', { hash: template.previewRef });
+  },
+  async listTrash(): Promise<Template[]> {
+    return invoke<Template[]>('template_trash_list');
+  },
+
+  async restore(id: number): Promise<boolean> {
+    return invoke<boolean>('template_restore', { id });
+  },
+
+  async purge(id: number): Promise<boolean> {
+    return invoke<boolean>('template_purge', { id });
+  },
 };
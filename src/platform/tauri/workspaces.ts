@@ -0,0 +1,33 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { DataDirResult, UnlistenFn, WorkspaceAdapter, WorkspaceInfo, WorkspaceList } from '../types';
+
+export const workspaceAdapter: WorkspaceAdapter = {
+  async list(): Promise<WorkspaceList> {
+    return invoke<WorkspaceList>('workspace_list');
+  },
+
+  async create(name: string): Promise<WorkspaceInfo> {
+    return invoke<WorkspaceInfo>('workspace_create', { name });
+  },
+
+  async switch(name: string): Promise<WorkspaceList> {
+    return invoke<WorkspaceList>('workspace_switch', { name });
+  },
+
+  async delete(name: string): Promise<boolean> {
+    return invoke<boolean>('workspace_delete', { name });
+  },
+
+  async setDataDir(path: string | null, copyExisting: boolean): Promise<DataDirResult> {
+    return invoke<DataDirResult>('data_dir_set', { path, copyExisting });
+  },
+
+  async pickDataDir(): Promise<string | null> {
+    return invoke<string | null>('pick_data_dir');
+  },
+
+  async listen(callback: (list: WorkspaceList) => void): Promise<UnlistenFn> {
+    return listen<WorkspaceList>('workspace-changed', (event) => callback(event.payload));
+  },
+};
@@ -9,8 +9,9 @@ export interface ExportResult {
 }
 
 export interface ExportAdapter {
-  exportPng(imageDataUrl: string, suggestedName?: string): Promise<ExportResult>;
-  exportSvg(svgData: string, suggestedName?: string): Promise<ExportResult>;
+  /** `directory` is where the save dialog opens, when the platform has one */
+  exportPng(imageDataUrl: string, suggestedName?: string, directory?: string | null): Promise<ExportResult>;
+  exportSvg(svgData: string, suggestedName?: string, directory?: string | null): Promise<ExportResult>;
 }
 
 // --- Clipboard ---
@@ -35,9 +36,24 @@ export interface HistoryItem {
   qrType: string;
   label: string | null;
   styleJson: string;
-  thumbnail: string | null;
+  /** Inline image data URL (web storage) */
+  thumbnail?: string | null;
+  /** Desktop blob hash; load the image with `historyAdapter.getThumbnail` */
+  thumbnailRef?: string | null;
   createdAt: string;
   updatedAt: string;
+  folderId?: number | null;
+  pinned?: boolean;
+  tags?: string[];
+  deletedAt?: string | null;
+  /** WiFi or OTP content; `content` has the secret redacted */
+  sensitive?: boolean;
+  /** Content is encrypted at rest; read it with `historyAdapter.getContent` */
+  encrypted?: boolean;
+  /** Stable across synced devices, unlike `id` (desktop) */
+  uuid?: string;
+  /** How many times the item was saved */
+  useCount?: number;
 }
 
 export interface NewHistoryItem {
@@ -48,17 +64,45 @@ export interface NewHistoryItem {
   thumbnail?: string;
 }
 
+export interface HistoryCursor {
+  key: string;
+  id: number;
+}
+
 export interface HistoryListResult {
   items: HistoryItem[];
   total: number;
   hasMore: boolean;
+  nextCursor?: HistoryCursor | null;
+}
+
+/** Live items with the same type, content and style */
+export interface DuplicateGroup {
+  /** The copy the others merge into */
+  keep: HistoryItem;
+  duplicateIds: number[];
 }
 
 export interface HistoryAdapter {
   list(limit: number, offset: number, search: string | null): Promise<HistoryListResult>;
   save(item: NewHistoryItem): Promise<number>;
+  /** Move an item to the trash */
   delete(id: number): Promise<boolean>;
+  /** Move every item to the trash */
   clear(): Promise<number>;
+  /** Trashed items, most recently deleted first */
+  listTrash(limit: number, offset: number): Promise<HistoryListResult>;
+  /** Bring items back from the trash; resolves how many were restored */
+  restore(ids: number[]): Promise<number>;
+  /** Permanently delete trashed items; resolves how many were deleted */
+  purge(ids: number[]): Promise<number>;
+  emptyTrash(): Promise<number>;
+  getThumbnail(item: HistoryItem): Promise<string | null>;
+  /** Full content, unredacted and decrypted */
+  getContent(item: HistoryItem): Promise<string>;
+  findDuplicates(): Promise<DuplicateGroup[]>;
+  /** Merge all groups, or those keeping one of `keepIds`; resolves how many were merged away */
+  mergeDuplicates(keepIds?: number[]): Promise<number>;
 }
 
 // --- Templates ---
@@ -67,9 +111,15 @@ export interface Template {
   id: number;
   name: string;
   styleJson: string;
-  preview: string | null;
+  /** Inline image data URL (web storage) */
+  preview?: string | null;
+  /** Desktop blob hash; load the image with `templateAdapter.getPreview` */
+  previewRef?: string | null;
   isDefault: boolean;
   createdAt: string;
+  deletedAt?: string | null;
+  /** Stable across synced devices, unlike `id` (desktop) */
+  uuid?: string;
 }
 
 export interface NewTemplate {
@@ -84,8 +134,139 @@ export interface TemplateAdapter {
   get(id: number): Promise<Template | null>;
   save(template: NewTemplate): Promise<number>;
   update(id: number, template: NewTemplate): Promise<boolean>;
+  /** Move a template to the trash */
   delete(id: number): Promise<boolean>;
   setDefault(id: number): Promise<boolean>;
+  getPreview(template: Template): Promise<string | null>;
+  /** Trashed templates, most recently deleted first */
+  listTrash(): Promise<Template[]>;
+  restore(id: number): Promise<boolean>;
+  /** Permanently delete a trashed template */
+  purge(id: number): Promise<boolean>;
+}
+
+// --- Library ---
+
+export type LibraryFormat = 'json' | 'csv' | 'qrfoundry';
+
+/** IDs to export; omit a list to export all of that kind */
+export interface LibrarySelection {
+  historyIds?: number[];
+  templateIds?: number[];
+}
+
+/** What to do when an imported item clashes with an existing one */
+export type ConflictStrategy = 'skip' | 'keepBoth' | 'replace';
+
+export interface LibraryImportCounts {
+  imported: number;
+  /** Identical to an existing item, so not imported again */
+  duplicates: number;
+  replaced: number;
+  /** Conflicts left as they were */
+  skipped: number;
+  /** Exported without their secret, so not imported */
+  redacted: number;
+}
+
+export interface LibraryImportConflict {
+  kind: 'history' | 'template';
+  name: string;
+  existingId: number;
+  resolution: ConflictStrategy;
+}
+
+export interface LibraryImportReport {
+  history: LibraryImportCounts;
+  templates: LibraryImportCounts;
+  conflicts: LibraryImportConflict[];
+}
+
+export interface LibraryAdapter {
+  /** WiFi passwords and OTP secrets are redacted unless `includeSecrets` */
+  export(
+    format: LibraryFormat,
+    selection?: LibrarySelection,
+    options?: { includeSecrets?: boolean },
+  ): Promise<ExportResult>;
+  /** Pick a JSON, CSV or `.qrfoundry` file and import it; null if cancelled */
+  import(options?: { conflict?: ConflictStrategy }): Promise<LibraryImportReport | null>;
+}
+
+// --- Settings ---
+
+export interface RetentionPolicy {
+  maxItems: number | null;
+  maxAgeDays: number | null;
+  maxDbBytes: number | null;
+}
+
+export interface AppSettings {
+  exportFormat: 'png' | 'svg' | 'pdf' | 'eps';
+  exportSize: number;
+  errorCorrection: 'L' | 'M' | 'Q' | 'H';
+  exportDirectory: string | null;
+  /** Supports {label}, {type}, {date}, {time} and {index} */
+  filenamePattern: string;
+  /** Days before trashed items are purged; 0 keeps them */
+  trashRetentionDays: number;
+  retentionPolicy: RetentionPolicy;
+  autoBackupKeep: number;
+  lastTemplateId: number | null;
+  /** Saving an item identical to a live one bumps it instead of adding a copy */
+  upsertHistory: boolean;
+}
+
+export interface SettingsChange {
+  settings: AppSettings;
+  /** Keys whose values changed */
+  changed: (keyof AppSettings)[];
+}
+
+export interface SettingsAdapter {
+  get(): Promise<AppSettings>;
+  update(patch: Partial<AppSettings>): Promise<SettingsChange>;
+  /** Restore the given keys, or every setting, to the defaults */
+  reset(keys?: (keyof AppSettings)[]): Promise<SettingsChange>;
+  /** Called after any change, including ones made in other windows */
+  listen(callback: (change: SettingsChange) => void): Promise<UnlistenFn>;
+}
+
+// --- Workspaces ---
+
+export interface WorkspaceInfo {
+  name: string;
+  /** Database file */
+  path: string;
+  bytes: number;
+}
+
+export interface WorkspaceList {
+  active: string;
+  dataDir: string;
+  /** Data kept next to the executable */
+  portable: boolean;
+  workspaces: WorkspaceInfo[];
+  /** Set when the configured data directory couldn't be opened at startup */
+  dataDirError: string | null;
+}
+
+export interface DataDirResult {
+  workspaces: WorkspaceList;
+  /** Workspaces copied into the new directory, when asked to */
+  copy: { copied: string[]; skipped: string[] } | null;
+}
+
+export interface WorkspaceAdapter {
+  list(): Promise<WorkspaceList>;
+  create(name: string): Promise<WorkspaceInfo>;
+  switch(name: string): Promise<WorkspaceList>;
+  delete(name: string): Promise<boolean>;
+  /** Move to `path`, or back to the default directory with null */
+  setDataDir(path: string | null, copyExisting: boolean): Promise<DataDirResult>;
+  pickDataDir(): Promise<string | null>;
+  /** Called after switching workspace or data directory */
+  listen(callback: (list: WorkspaceList) => void): Promise<UnlistenFn>;
 }
 
 // --- Scanner ---
@@ -94,9 +275,47 @@ export interface ScanResult {
   success: boolean;
   content: string | null;
   qrType: string | null;
+  /** More specific scheme within `qrType` (e.g. "mecard", "totp") */
+  subtype?: string | null;
+  /** Detection confidence between 0 and 1 */
+  confidence?: number | null;
+  /** Desktop preprocessing pass that decoded the code (e.g. "inverted") */
+  pass?: string | null;
+  /** Desktop-parsed payload fields (WiFi, contact, event...), keyed by `type` */
+  parsed?: ({ type: string } & Record<string, unknown>) | null;
+  /** Offline phishing/privacy analysis for link payloads */
+  urlRisk?: UrlRiskReport | null;
+  error: string | null;
+}
+
+/** Summary of a desktop folder scan; the per-code rows go to the saved report */
+export interface BulkScanResult {
+  success: boolean;
+  reportPath: string | null;
+  /** Images scanned, whether or not they held a code */
+  filesScanned: number;
+  codesFound: number;
+  /** Scanned images without a readable code */
+  filesFailed: number;
+  /** Entries that couldn't be read at all, like a locked subfolder */
+  filesSkipped: number;
   error: string | null;
 }
 
+export interface UrlWarning {
+  code: string;
+  severity: 'info' | 'low' | 'medium' | 'high';
+  message: string;
+}
+
+export interface UrlRiskReport {
+  url: string;
+  host: string | null;
+  unicodeHost: string | null;
+  risk: 'safe' | 'low' | 'medium' | 'high';
+  warnings: UrlWarning[];
+}
+
 export interface ValidationResult {
   state: 'pass' | 'warn' | 'fail';
   decodedContent: string | null;
@@ -105,10 +324,15 @@ export interface ValidationResult {
   suggestions: string[];
 }
 
+/** Where a scanned image came from */
+export type ScanSource = 'file' | 'clipboard' | 'data' | 'folder' | 'pdf';
+
 export interface ScannerAdapter {
   validateQr(imageDataUrl: string, expectedContent: string): Promise<ValidationResult>;
   scanFromFile(filePath: string): Promise<ScanResult>;
   scanFromData(imageData: string): Promise<ScanResult>;
+  /** Record a successful scan in scan history; returns its id, or null where there is none */
+  saveScan(source: ScanSource, sourcePath: string | null, result: ScanResult): Promise<number | null>;
 }
 
 // --- Batch ---
@@ -118,6 +342,8 @@ export interface BatchItem {
   content: string;
   qrType: string;
   label: string | null;
+  /** Present for URL rows; includes label/domain mismatch checks */
+  urlRisk?: UrlRiskReport | null;
 }
 
 export interface BatchGenerateItem {
@@ -0,0 +1,21 @@
+import { describe, it, expect } from 'vitest';
+import { batchAdapter } from './batch';
+
+describe('web batchAdapter.parseCsvContent', () => {
+  it('normalizes explicit types and detects blank ones', async () => {
+    const result = await batchAdapter.parseCsvContent(
+      'content,type\nhttps://example.com,LINK\ntel:+15551234567,\notpauth://totp/x?secret=ABC,otp'
+    );
+
+    expect(result.success).toBe(true);
+    expect(result.items.map((item) => item.qrType)).toEqual(['url', 'phone', 'otp']);
+  });
+
+  it('reports the row with an unknown type', async () => {
+    const result = await batchAdapter.parseCsvContent('content,type\nhello,text\nhttps://example.com,barcode');
+
+    expect(result.success).toBe(false);
+    expect(result.items).toEqual([]);
+    expect(result.error).toBe("Error at row 3: unknown type 'barcode'");
+  });
+});
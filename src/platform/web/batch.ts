@@ -1,4 +1,5 @@
 import { decodeWithJsQr, detectQrType } from '../../lib/scanHelpers';
+import { normalizeQrType } from '../../lib/qrTypes';
 import type {
   BatchAdapter,
   BatchParseResult,
@@ -79,7 +80,13 @@ function parseCsvToItems(text: string): BatchParseResult {
     const content = row[contentIdx] || '';
     if (!content) continue;
 
-    const qrType = typeIdx >= 0 && row[typeIdx] ? row[typeIdx].toLowerCase() : detectQrType(content);
+    // Explicit types must be ones the app knows; blank cells are detected
+    const typeValue = typeIdx >= 0 ? row[typeIdx] || '' : '';
+    const knownType = typeValue ? normalizeQrType(typeValue) : null;
+    if (typeValue && !knownType) {
+      return { success: false, items: [], error: `Error at row ${i + 1}: unknown type '${typeValue}'`, totalRows: 0 };
+    }
+    const qrType = knownType ?? detectQrType(content);
     const label = labelIdx >= 0 ? row[labelIdx] || null : null;
 
     items.push({
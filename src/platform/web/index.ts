@@ -3,7 +3,10 @@ export { exportAdapter } from './export';
 export { clipboardAdapter } from './clipboard';
 export { filesystemAdapter } from './filesystem';
 export { historyAdapter, templateAdapter } from './storage';
+export { settingsAdapter } from './settings';
+export { workspaceAdapter } from './workspaces';
 export { scannerAdapter } from './scanner';
 export { batchAdapter } from './batch';
 export { dragDropAdapter } from './dragdrop';
 export { authAdapter } from './auth';
+export { libraryAdapter } from './library';
@@ -0,0 +1,238 @@
+import type {
+  ConflictStrategy,
+  ExportResult,
+  HistoryItem,
+  LibraryAdapter,
+  LibraryFormat,
+  LibraryImportConflict,
+  LibraryImportReport,
+  LibrarySelection,
+  Template,
+} from '../types';
+import {
+  createLibrary,
+  isRedacted,
+  readLibrary,
+  redactSecrets,
+  writeLibrary,
+  type Library,
+  type LibraryHistoryItem,
+  type LibraryTemplate,
+} from '../../lib/library';
+import { HISTORY_KEY, TEMPLATES_KEY, getStoredItems, nextId, setStoredItems } from './storage';
+
+function downloadBlob(blob: Blob, filename: string): void {
+  const url = URL.createObjectURL(blob);
+  const a = document.createElement('a');
+  a.href = url;
+  a.download = filename;
+  document.body.appendChild(a);
+  a.click();
+  document.body.removeChild(a);
+  URL.revokeObjectURL(url);
+}
+
+/** Let the user choose a file; resolves null if they cancel */
+function pickFile(accept: string): Promise<File | null> {
+  return new Promise((resolve) => {
+    const input = document.createElement('input');
+    input.type = 'file';
+    input.accept = accept;
+    input.addEventListener('change', () => resolve(input.files?.[0] ?? null));
+    input.addEventListener('cancel', () => resolve(null));
+    input.click();
+  });
+}
+
+function selected(ids: number[] | undefined, id: number): boolean {
+  return ids === undefined || ids.includes(id);
+}
+
+function emptyReport(): LibraryImportReport {
+  const counts = () => ({ imported: 0, duplicates: 0, replaced: 0, skipped: 0, redacted: 0 });
+  return { history: counts(), templates: counts(), conflicts: [] };
+}
+
+function exportLibrary(selection: LibrarySelection, includeSecrets: boolean): Library {
+  const history = getStoredItems<HistoryItem>(HISTORY_KEY)
+    .filter((item) => !item.deletedAt && selected(selection.historyIds, item.id))
+    .map((item): LibraryHistoryItem => {
+      const content = includeSecrets ? item.content : redactSecrets(item.content);
+      return {
+        content,
+        qrType: item.qrType,
+        label: item.label,
+        styleJson: item.styleJson,
+        thumbnail: item.thumbnail ?? null,
+        pinned: item.pinned ?? false,
+        tags: item.tags ?? [],
+        createdAt: item.createdAt,
+        updatedAt: item.updatedAt,
+        redacted: content !== item.content,
+      };
+    });
+  const templates = getStoredItems<Template>(TEMPLATES_KEY)
+    .filter((template) => !template.deletedAt && selected(selection.templateIds, template.id))
+    .map(
+      (template): LibraryTemplate => ({
+        name: template.name,
+        styleJson: template.styleJson,
+        preview: template.preview ?? null,
+        isDefault: template.isDefault,
+        createdAt: template.createdAt,
+      }),
+    );
+  return createLibrary(history, templates);
+}
+
+function mergeTags(item: HistoryItem, tags: string[]): void {
+  const merged = new Set(item.tags ?? []);
+  for (const tag of tags.map((t) => t.trim()).filter(Boolean)) merged.add(tag);
+  item.tags = Array.from(merged);
+}
+
+/** Add a library's items to local storage, matching the desktop app's rules */
+function importLibrary(library: Library, strategy: ConflictStrategy): LibraryImportReport {
+  const report = emptyReport();
+  const history = getStoredItems<HistoryItem>(HISTORY_KEY);
+  const templates = getStoredItems<Template>(TEMPLATES_KEY);
+  const now = new Date().toISOString();
+
+  const addHistory = (item: LibraryHistoryItem) => {
+    history.push({
+      id: nextId(history),
+      content: item.content,
+      qrType: item.qrType,
+      label: item.label,
+      styleJson: item.styleJson,
+      thumbnail: item.thumbnail,
+      pinned: item.pinned,
+      tags: Array.from(new Set(item.tags)),
+      createdAt: item.createdAt ?? now,
+      updatedAt: item.updatedAt ?? item.createdAt ?? now,
+    });
+    report.history.imported++;
+  };
+
+  for (const item of library.history) {
+    // A masked copy would be a broken code, so never save one
+    if (isRedacted(item)) {
+      report.history.redacted++;
+      continue;
+    }
+
+    const matches = history.filter(
+      (h) => !h.deletedAt && h.content === item.content && h.qrType === item.qrType,
+    );
+    const identical = matches.find((h) => h.label === item.label && h.styleJson === item.styleJson);
+    if (matches.length === 0) {
+      addHistory(item);
+      continue;
+    }
+    if (identical) {
+      mergeTags(identical, item.tags);
+      report.history.duplicates++;
+      continue;
+    }
+
+    const existing = matches[0];
+    if (strategy === 'skip') {
+      report.history.skipped++;
+    } else if (strategy === 'keepBoth') {
+      addHistory(item);
+    } else {
+      existing.label = item.label;
+      existing.styleJson = item.styleJson;
+      existing.thumbnail = item.thumbnail ?? existing.thumbnail;
+      existing.updatedAt = now;
+      mergeTags(existing, item.tags);
+      report.history.replaced++;
+    }
+    report.conflicts.push(
+      conflict('history', item.label ?? redactSecrets(item.content), existing.id, strategy),
+    );
+  }
+
+  const findTemplate = (name: string) =>
+    templates.find((t) => !t.deletedAt && t.name.toLowerCase() === name.toLowerCase());
+  const addTemplate = (template: LibraryTemplate, name: string) => {
+    templates.push({
+      id: nextId(templates),
+      name,
+      styleJson: template.styleJson,
+      preview: template.preview,
+      // Keep the current default; only take over if there isn't one
+      isDefault: template.isDefault && !templates.some((t) => t.isDefault),
+      createdAt: template.createdAt ?? now,
+    });
+    report.templates.imported++;
+  };
+
+  for (const template of library.templates) {
+    const name = template.name.trim();
+    if (!name) {
+      report.templates.skipped++;
+      continue;
+    }
+
+    const existing = findTemplate(name);
+    if (!existing) {
+      addTemplate(template, name);
+      continue;
+    }
+    if (existing.styleJson === template.styleJson) {
+      report.templates.duplicates++;
+      continue;
+    }
+
+    if (strategy === 'skip') {
+      report.templates.skipped++;
+    } else if (strategy === 'keepBoth') {
+      let candidate = name;
+      for (let n = 2; findTemplate(candidate); n++) candidate = `${name} (${n})`;
+      addTemplate(template, candidate);
+    } else {
+      existing.styleJson = template.styleJson;
+      existing.preview = template.preview ?? existing.preview;
+      report.templates.replaced++;
+    }
+    report.conflicts.push(conflict('template', name, existing.id, strategy));
+  }
+
+  setStoredItems(HISTORY_KEY, history);
+  setStoredItems(TEMPLATES_KEY, templates);
+  return report;
+}
+
+function conflict(
+  kind: LibraryImportConflict['kind'],
+  name: string,
+  existingId: number,
+  resolution: ConflictStrategy,
+): LibraryImportConflict {
+  return { kind, name, existingId, resolution };
+}
+
+export const libraryAdapter: LibraryAdapter = {
+  async export(
+    format: LibraryFormat,
+    selection: LibrarySelection = {},
+    options?: { includeSecrets?: boolean },
+  ): Promise<ExportResult> {
+    try {
+      const library = exportLibrary(selection, options?.includeSecrets ?? false);
+      const filename = `qr-foundry-library.${format}`;
+      downloadBlob(await writeLibrary(library, format), filename);
+      return { success: true, path: filename, error: null };
+    } catch (error) {
+      return { success: false, path: null, error: `Export failed: ${error}` };
+    }
+  },
+
+  async import(options?: { conflict?: ConflictStrategy }): Promise<LibraryImportReport | null> {
+    const file = await pickFile('.qrfoundry,.zip,.json,.csv');
+    if (!file) return null;
+    const library = await readLibrary(file.name, new Uint8Array(await file.arrayBuffer()));
+    return importLibrary(library, options?.conflict ?? 'skip');
+  },
+};
@@ -1,5 +1,5 @@
 import { decodeWithJsQr } from '../../lib/scanHelpers';
-import type { ScannerAdapter, ScanResult, ValidationResult } from '../types';
+import type { ScannerAdapter, ScanResult, ScanSource, ValidationResult } from '../types';
 
 export const scannerAdapter: ScannerAdapter = {
   async validateQr(imageDataUrl: string, expectedContent: string): Promise<ValidationResult> {
@@ -44,4 +44,8 @@ export const scannerAdapter: ScannerAdapter = {
   async scanFromData(imageData: string): Promise<ScanResult> {
     return decodeWithJsQr(imageData);
   },
+  // Scan history is kept by the desktop app only
+  async saveScan(_source: ScanSource, _sourcePath: string | null, _result: ScanResult): Promise<number | null> {
+    return null;
+  },
 };
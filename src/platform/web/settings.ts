@@ -0,0 +1,66 @@
+import type { AppSettings, SettingsAdapter, SettingsChange, UnlistenFn } from '../types';
+
+const SETTINGS_KEY = 'qr-foundry-settings';
+const listeners = new Set<(change: SettingsChange) => void>();
+
+// Mirrors the desktop defaults in src-tauri/src/db/settings.rs
+const DEFAULT_SETTINGS: AppSettings = {
+  exportFormat: 'png',
+  exportSize: 1024,
+  errorCorrection: 'M',
+  exportDirectory: null,
+  filenamePattern: 'qr-{type}-{date}',
+  trashRetentionDays: 30,
+  retentionPolicy: { maxItems: null, maxAgeDays: null, maxDbBytes: null },
+  autoBackupKeep: 7,
+  lastTemplateId: null,
+  upsertHistory: true,
+};
+
+function load(): AppSettings {
+  try {
+    const raw = localStorage.getItem(SETTINGS_KEY);
+    return raw ? { ...DEFAULT_SETTINGS, ...JSON.parse(raw) } : { ...DEFAULT_SETTINGS };
+  } catch {
+    return { ...DEFAULT_SETTINGS };
+  }
+}
+
+function store(next: AppSettings): SettingsChange {
+  const previous = load();
+  const changed = (Object.keys(next) as (keyof AppSettings)[]).filter(
+    (key) => JSON.stringify(previous[key]) !== JSON.stringify(next[key])
+  );
+  localStorage.setItem(SETTINGS_KEY, JSON.stringify(next));
+
+  const change = { settings: next, changed };
+  if (changed.length > 0) listeners.forEach((listener) => listener(change));
+  return change;
+}
+
+// Web keeps settings in localStorage; validation happens on desktop only
+export const settingsAdapter: SettingsAdapter = {
+  async get(): Promise<AppSettings> {
+    return load();
+  },
+
+  async update(patch: Partial<AppSettings>): Promise<SettingsChange> {
+    return store({ ...load(), ...patch });
+  },
+
+  async reset(keys?: (keyof AppSettings)[]): Promise<SettingsChange> {
+    if (!keys) return store({ ...DEFAULT_SETTINGS });
+    const next = { ...load() };
+    for (const key of keys) {
+      Object.assign(next, { [key]: DEFAULT_SETTINGS[key] });
+    }
+    return store(next);
+  },
+
+  async listen(callback: (change: SettingsChange) => void): Promise<UnlistenFn> {
+    listeners.add(callback);
+    return () => {
+      listeners.delete(callback);
+    };
+  },
+};
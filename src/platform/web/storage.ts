@@ -1,4 +1,5 @@
 import type {
+  DuplicateGroup,
   HistoryAdapter,
   HistoryItem,
   HistoryListResult,
@@ -7,11 +8,12 @@ import type {
   Template,
   NewTemplate,
 } from '../types';
+import { settingsAdapter } from './settings';
 
-const HISTORY_KEY = 'qr-foundry-history';
-const TEMPLATES_KEY = 'qr-foundry-templates';
+export const HISTORY_KEY = 'qr-foundry-history';
+export const TEMPLATES_KEY = 'qr-foundry-templates';
 
-function getStoredItems<T>(key: string): T[] {
+export function getStoredItems<T>(key: string): T[] {
   try {
     const raw = localStorage.getItem(key);
     return raw ? JSON.parse(raw) : [];
@@ -20,17 +22,56 @@ function getStoredItems<T>(key: string): T[] {
   }
 }
 
-function setStoredItems<T>(key: string, items: T[]): void {
+export function setStoredItems<T>(key: string, items: T[]): void {
   localStorage.setItem(key, JSON.stringify(items));
 }
 
-function nextId(items: { id: number }[]): number {
+export function nextId(items: { id: number }[]): number {
   return items.length > 0 ? Math.max(...items.map((i) => i.id)) + 1 : 1;
 }
 
+/** Permanently delete trashed entries older than the trash retention setting */
+async function purgeExpired<T extends { deletedAt?: string | null }>(key: string): Promise<T[]> {
+  const items = getStoredItems<T>(key);
+  const { trashRetentionDays } = await settingsAdapter.get();
+  if (trashRetentionDays <= 0) return items;
+
+  const cutoff = Date.now() - trashRetentionDays * 86400000;
+  const kept = items.filter((i) => !i.deletedAt || new Date(i.deletedAt).getTime() >= cutoff);
+  if (kept.length !== items.length) setStoredItems(key, kept);
+  return kept;
+}
+
+/** Trashed entries, most recently deleted first */
+function byDeletedDesc<T extends { id: number; deletedAt?: string | null }>(a: T, b: T): number {
+  return (b.deletedAt ?? '').localeCompare(a.deletedAt ?? '') || b.id - a.id;
+}
+
+function isDuplicate(a: HistoryItem, b: HistoryItem | NewHistoryItem): boolean {
+  return a.content === b.content && a.qrType === b.qrType && a.styleJson === b.styleJson;
+}
+
+/** Same grouping and choice of copy to keep as the desktop app */
+function duplicateGroups(items: HistoryItem[]): DuplicateGroup[] {
+  const ordered = items.filter((i) => !i.deletedAt).sort(
+    (a, b) =>
+      Number(b.pinned ?? false) - Number(a.pinned ?? false) ||
+      Number(!a.label) - Number(!b.label) ||
+      a.createdAt.localeCompare(b.createdAt) ||
+      a.id - b.id
+  );
+  const groups: DuplicateGroup[] = [];
+  for (const item of ordered) {
+    const group = groups.find((g) => isDuplicate(g.keep, item));
+    if (group) group.duplicateIds.push(item.id);
+    else groups.push({ keep: item, duplicateIds: [] });
+  }
+  return groups.filter((g) => g.duplicateIds.length > 0);
+}
+
 export const historyAdapter: HistoryAdapter = {
   async list(limit: number, offset: number, search: string | null): Promise<HistoryListResult> {
-    let items = getStoredItems<HistoryItem>(HISTORY_KEY);
+    let items = getStoredItems<HistoryItem>(HISTORY_KEY).filter((i) => !i.deletedAt);
 
     // Sort by creation date descending (newest first)
     items.sort((a, b) => new Date(b.createdAt).getTime() - new Date(a.createdAt).getTime());
@@ -58,6 +99,19 @@ export const historyAdapter: HistoryAdapter = {
   async save(item: NewHistoryItem): Promise<number> {
     const items = getStoredItems<HistoryItem>(HISTORY_KEY);
     const now = new Date().toISOString();
+
+    const { upsertHistory } = await settingsAdapter.get();
+    const existing = upsertHistory
+      ? items.find((i) => !i.deletedAt && isDuplicate(i, item))
+      : undefined;
+    if (existing) {
+      existing.updatedAt = now;
+      existing.useCount = (existing.useCount ?? 1) + 1;
+      existing.label = item.label || existing.label;
+      setStoredItems(HISTORY_KEY, items);
+      return existing.id;
+    }
+
     const newItem: HistoryItem = {
       id: nextId(items),
       content: item.content,
@@ -67,6 +121,7 @@ export const historyAdapter: HistoryAdapter = {
       thumbnail: item.thumbnail || null,
       createdAt: now,
       updatedAt: now,
+      useCount: 1,
     };
     items.push(newItem);
     setStoredItems(HISTORY_KEY, items);
@@ -75,30 +130,110 @@ export const historyAdapter: HistoryAdapter = {
 
   async delete(id: number): Promise<boolean> {
     const items = getStoredItems<HistoryItem>(HISTORY_KEY);
-    const filtered = items.filter((i) => i.id !== id);
-    if (filtered.length === items.length) return false;
-    setStoredItems(HISTORY_KEY, filtered);
+    const item = items.find((i) => i.id === id && !i.deletedAt);
+    if (!item) return false;
+    item.deletedAt = new Date().toISOString();
+    setStoredItems(HISTORY_KEY, items);
     return true;
   },
 
   async clear(): Promise<number> {
     const items = getStoredItems<HistoryItem>(HISTORY_KEY);
-    const count = items.length;
-    localStorage.removeItem(HISTORY_KEY);
-    return count;
+    const now = new Date().toISOString();
+    const live = items.filter((i) => !i.deletedAt);
+    for (const item of live) item.deletedAt = now;
+    setStoredItems(HISTORY_KEY, items);
+    return live.length;
+  },
+
+  async listTrash(limit: number, offset: number): Promise<HistoryListResult> {
+    const items = (await purgeExpired<HistoryItem>(HISTORY_KEY))
+      .filter((i) => i.deletedAt)
+      .sort(byDeletedDesc);
+    return {
+      items: items.slice(offset, offset + limit),
+      total: items.length,
+      hasMore: offset + limit < items.length,
+    };
+  },
+
+  async restore(ids: number[]): Promise<number> {
+    const items = getStoredItems<HistoryItem>(HISTORY_KEY);
+    const restored = items.filter((i) => i.deletedAt && ids.includes(i.id));
+    for (const item of restored) item.deletedAt = null;
+    setStoredItems(HISTORY_KEY, items);
+    return restored.length;
+  },
+
+  async purge(ids: number[]): Promise<number> {
+    const items = getStoredItems<HistoryItem>(HISTORY_KEY);
+    const kept = items.filter((i) => !(i.deletedAt && ids.includes(i.id)));
+    setStoredItems(HISTORY_KEY, kept);
+    return items.length - kept.length;
+  },
+
+  async emptyTrash(): Promise<number> {
+    const items = getStoredItems<HistoryItem>(HISTORY_KEY);
+    const kept = items.filter((i) => !i.deletedAt);
+    setStoredItems(HISTORY_KEY, kept);
+    return items.length - kept.length;
+  },
+
+  async getThumbnail(item: HistoryItem): Promise<string | null> {
+    return item.thumbnail ?? null;
+  },
+
+  async getContent(item: HistoryItem): Promise<string> {
+    return item.content;
+  },
+
+  async findDuplicates(): Promise<DuplicateGroup[]> {
+    return duplicateGroups(getStoredItems<HistoryItem>(HISTORY_KEY));
+  },
+
+  // Merged copies go to the trash, as on desktop
+  async mergeDuplicates(keepIds?: number[]): Promise<number> {
+    const items = getStoredItems<HistoryItem>(HISTORY_KEY);
+    const groups = duplicateGroups(items).filter((g) => !keepIds || keepIds.includes(g.keep.id));
+
+    const byId = new Map(items.map((i) => [i.id, i]));
+    const removed = new Set<number>();
+    for (const group of groups) {
+      const keep = byId.get(group.keep.id);
+      if (!keep) continue;
+      for (const id of group.duplicateIds) {
+        const duplicate = byId.get(id);
+        if (!duplicate) continue;
+        keep.useCount = (keep.useCount ?? 1) + (duplicate.useCount ?? 1);
+        if (duplicate.updatedAt > keep.updatedAt) keep.updatedAt = duplicate.updatedAt;
+        if (duplicate.createdAt < keep.createdAt) keep.createdAt = duplicate.createdAt;
+        keep.pinned = keep.pinned || duplicate.pinned;
+        keep.label = keep.label ?? duplicate.label;
+        keep.thumbnail = keep.thumbnail ?? duplicate.thumbnail;
+        keep.tags = Array.from(new Set([...(keep.tags ?? []), ...(duplicate.tags ?? [])]));
+        removed.add(id);
+      }
+    }
+
+    const now = new Date().toISOString();
+    for (const item of items) {
+      if (removed.has(item.id)) item.deletedAt = now;
+    }
+    setStoredItems(HISTORY_KEY, items);
+    return removed.size;
   },
 };
 
 export const templateAdapter: TemplateAdapter = {
   async list(): Promise<Template[]> {
-    const items = getStoredItems<Template>(TEMPLATES_KEY);
+    const items = getStoredItems<Template>(TEMPLATES_KEY).filter((t) => !t.deletedAt);
     items.sort((a, b) => new Date(b.createdAt).getTime() - new Date(a.createdAt).getTime());
     return items;
   },
 
   async get(id: number): Promise<Template | null> {
     const items = getStoredItems<Template>(TEMPLATES_KEY);
-    return items.find((t) => t.id === id) || null;
+    return items.find((t) => t.id === id && !t.deletedAt) || null;
   },
 
   async save(template: NewTemplate): Promise<number> {
@@ -133,15 +268,18 @@ export const templateAdapter: TemplateAdapter = {
 
   async delete(id: number): Promise<boolean> {
     const items = getStoredItems<Template>(TEMPLATES_KEY);
-    const filtered = items.filter((t) => t.id !== id);
-    if (filtered.length === items.length) return false;
-    setStoredItems(TEMPLATES_KEY, filtered);
+    const template = items.find((t) => t.id === id && !t.deletedAt);
+    if (!template) return false;
+    template.deletedAt = new Date().toISOString();
+    // A trashed template can't stay the default
+    template.isDefault = false;
+    setStoredItems(TEMPLATES_KEY, items);
     return true;
   },
 
   async setDefault(id: number): Promise<boolean> {
     const items = getStoredItems<Template>(TEMPLATES_KEY);
-    const found = items.find((t) => t.id === id);
+    const found = items.find((t) => t.id === id && !t.deletedAt);
     if (!found) return false;
     for (const t of items) {
       t.isDefault = t.id === id;
@@ -149,4 +287,29 @@ export const templateAdapter: TemplateAdapter = {
     setStoredItems(TEMPLATES_KEY, items);
     return true;
   },
+
+  async getPreview(template: Template): Promise<string | null> {
+    return template.preview ?? null;
+  },
+  async listTrash(): Promise<Template[]> {
+    const items = await purgeExpired<Template>(TEMPLATES_KEY);
+    return items.filter((t) => t.deletedAt).sort(byDeletedDesc);
+  },
+
+  async restore(id: number): Promise<boolean> {
+    const items = getStoredItems<Template>(TEMPLATES_KEY);
+    const template = items.find((t) => t.id === id && t.deletedAt);
+    if (!template) return false;
+    template.deletedAt = null;
+    setStoredItems(TEMPLATES_KEY, items);
+    return true;
+  },
+
+  async purge(id: number): Promise<boolean> {
+    const items = getStoredItems<Template>(TEMPLATES_KEY);
+    const kept = items.filter((t) => !(t.id === id && t.deletedAt));
+    if (kept.length === items.length) return false;
+    setStoredItems(TEMPLATES_KEY, kept);
+    return true;
+  },
 };
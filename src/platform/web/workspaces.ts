@@ -0,0 +1,42 @@
+import type { DataDirResult, UnlistenFn, WorkspaceAdapter, WorkspaceInfo, WorkspaceList } from '../types';
+
+const UNSUPPORTED = 'Workspaces are only available in the desktop app';
+
+// Web keeps a single workspace in localStorage
+const WORKSPACES: WorkspaceList = {
+  active: 'default',
+  dataDir: '',
+  portable: false,
+  workspaces: [{ name: 'default', path: 'localStorage', bytes: 0 }],
+  dataDirError: null,
+};
+
+export const workspaceAdapter: WorkspaceAdapter = {
+  async list(): Promise<WorkspaceList> {
+    return WORKSPACES;
+  },
+
+  async create(_name: string): Promise<WorkspaceInfo> {
+    throw new Error(UNSUPPORTED);
+  },
+
+  async switch(_name: string): Promise<WorkspaceList> {
+    throw new Error(UNSUPPORTED);
+  },
+
+  async delete(_name: string): Promise<boolean> {
+    return false;
+  },
+
+  async setDataDir(_path: string | null, _copyExisting: boolean): Promise<DataDirResult> {
+    throw new Error(UNSUPPORTED);
+  },
+
+  async pickDataDir(): Promise<string | null> {
+    return null;
+  },
+
+  async listen(_callback: (list: WorkspaceList) => void): Promise<UnlistenFn> {
+    return () => {};
+  },
+};
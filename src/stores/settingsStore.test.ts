@@ -0,0 +1,96 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { settingsAdapter, workspaceAdapter } from '@platform';
+import type { AppSettings, SettingsChange } from '../platform/types';
+import { useSettingsStore, exportFilename } from './settingsStore';
+import { useQrStore } from './qrStore';
+import { useBatchStore } from './batchStore';
+
+const mockGet = vi.mocked(settingsAdapter.get);
+const mockUpdate = vi.mocked(settingsAdapter.update);
+const mockListen = vi.mocked(settingsAdapter.listen);
+
+const savedSettings: AppSettings = {
+  exportFormat: 'svg',
+  exportSize: 2048,
+  errorCorrection: 'H',
+  exportDirectory: '/exports',
+  filenamePattern: '{label}-{type}',
+  trashRetentionDays: 30,
+  retentionPolicy: { maxItems: null, maxAgeDays: null, maxDbBytes: null },
+  autoBackupKeep: 5,
+  lastTemplateId: null,
+  upsertHistory: false,
+};
+
+describe('settingsStore', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    useQrStore.getState().reset();
+    useBatchStore.getState().setExportFormat('png');
+    useSettingsStore.setState({ settings: null });
+  });
+
+  it('loads settings and applies them as generator defaults', async () => {
+    mockGet.mockResolvedValueOnce(savedSettings);
+    mockListen.mockResolvedValueOnce(() => {});
+
+    await useSettingsStore.getState().initialize();
+
+    expect(useSettingsStore.getState().settings).toEqual(savedSettings);
+    expect(useQrStore.getState().exportSize).toBe(2048);
+    expect(useQrStore.getState().errorCorrection).toBe('H');
+    expect(useBatchStore.getState().exportFormat).toBe('svg');
+    expect(mockListen).toHaveBeenCalled();
+    expect(workspaceAdapter.listen).toHaveBeenCalled();
+  });
+
+  it('applies only the changed keys from a settings event', () => {
+    useQrStore.getState().setExportSize(512);
+    const change: SettingsChange = {
+      settings: { ...savedSettings, errorCorrection: 'Q' },
+      changed: ['errorCorrection'],
+    };
+
+    useSettingsStore.getState().applyChange(change);
+
+    expect(useQrStore.getState().errorCorrection).toBe('Q');
+    expect(useQrStore.getState().exportSize).toBe(512);
+    expect(useSettingsStore.getState().settings?.errorCorrection).toBe('Q');
+  });
+
+  it('keeps the batch format for formats it cannot export', () => {
+    useSettingsStore.getState().applyChange({
+      settings: { ...savedSettings, exportFormat: 'pdf' },
+      changed: ['exportFormat'],
+    });
+
+    expect(useBatchStore.getState().exportFormat).toBe('png');
+  });
+
+  it('returns false when an update is rejected', async () => {
+    mockUpdate.mockRejectedValueOnce(new Error('Invalid value'));
+
+    const success = await useSettingsStore.getState().update({ exportSize: -1 });
+
+    expect(success).toBe(false);
+    expect(useSettingsStore.getState().settings).toBeNull();
+  });
+});
+
+describe('exportFilename', () => {
+  const now = new Date(2024, 2, 5, 9, 7, 3);
+
+  it('expands every placeholder', () => {
+    expect(exportFilename('{label}-{type}-{date}-{time}-{index}', { label: 'Menu', type: 'url', index: 3 }, 'png', now))
+      .toBe('Menu-url-2024-03-05-090703-3.png');
+  });
+
+  it('falls back when the label or pattern is missing', () => {
+    expect(exportFilename('{label}', { label: '', type: 'url' }, 'svg', now)).toBe('qr.svg');
+    expect(exportFilename(undefined, { type: 'url' }, 'png', now)).toBe('qr-code.png');
+  });
+
+  it('replaces characters that are not allowed in filenames', () => {
+    expect(exportFilename('{label}', { label: 'a/b:c', type: 'url' }, 'png', now)).toBe('a-b-c.png');
+  });
+});
@@ -0,0 +1,87 @@
+import { create } from 'zustand';
+import { settingsAdapter, workspaceAdapter } from '@platform';
+import type { AppSettings, SettingsChange } from '../platform/types';
+import { useQrStore } from './qrStore';
+import { useBatchStore } from './batchStore';
+
+interface SettingsState {
+  settings: AppSettings | null;
+  initialize: () => Promise<void>;
+  update: (patch: Partial<AppSettings>) => Promise<boolean>;
+  applyChange: (change: SettingsChange) => void;
+}
+
+let listening = false;
+
+// Push the saved defaults into the generator and batch stores
+function applyDefaults(settings: AppSettings, keys: (keyof AppSettings)[]) {
+  const qr = useQrStore.getState();
+  if (keys.includes('exportSize')) qr.setExportSize(settings.exportSize);
+  if (keys.includes('errorCorrection')) qr.setErrorCorrection(settings.errorCorrection);
+  if (keys.includes('exportFormat') && (settings.exportFormat === 'png' || settings.exportFormat === 'svg')) {
+    useBatchStore.getState().setExportFormat(settings.exportFormat);
+  }
+}
+
+export const useSettingsStore = create<SettingsState>((set, get) => ({
+  settings: null,
+
+  async initialize() {
+    try {
+      const settings = await settingsAdapter.get();
+      set({ settings });
+      applyDefaults(settings, ['exportSize', 'errorCorrection', 'exportFormat']);
+    } catch (error) {
+      console.error('Failed to load settings:', error);
+    }
+
+    if (!listening) {
+      listening = true;
+      try {
+        await settingsAdapter.listen((change) => get().applyChange(change));
+        // Each workspace keeps its own settings
+        await workspaceAdapter.listen(() => get().initialize());
+      } catch (error) {
+        console.error('Failed to listen for settings changes:', error);
+      }
+    }
+  },
+
+  async update(patch) {
+    try {
+      get().applyChange(await settingsAdapter.update(patch));
+      return true;
+    } catch (error) {
+      console.error('Failed to update settings:', error);
+      return false;
+    }
+  },
+
+  applyChange(change) {
+    set({ settings: change.settings });
+    applyDefaults(change.settings, change.changed);
+  },
+}));
+
+/** Expand the configured filename pattern, e.g. `qr-{type}-{date}` */
+export function exportFilename(
+  pattern: string | undefined,
+  values: { label?: string | null; type: string; index?: number },
+  extension: string,
+  now = new Date()
+): string {
+  const pad = (n: number) => String(n).padStart(2, '0');
+  const date = `${now.getFullYear()}-${pad(now.getMonth() + 1)}-${pad(now.getDate())}`;
+  const time = `${pad(now.getHours())}${pad(now.getMinutes())}${pad(now.getSeconds())}`;
+
+  const name = (pattern || 'qr-code')
+    .replace(/\{label\}/g, values.label || 'qr')
+    .replace(/\{type\}/g, values.type)
+    .replace(/\{date\}/g, date)
+    .replace(/\{time\}/g, time)
+    .replace(/\{index\}/g, String(values.index ?? 1))
+    .replace(/[\\/:*?"<>|]/g, '-')
+    .trim();
+
+  return `${name || 'qr-code'}.${extension}`;
+}
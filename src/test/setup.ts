@@ -20,6 +20,14 @@ vi.mock('@platform', () => ({
     save: vi.fn(),
     delete: vi.fn(),
     clear: vi.fn(),
+    getThumbnail: vi.fn(),
+    getContent: vi.fn(),
+    findDuplicates: vi.fn(),
+    mergeDuplicates: vi.fn(),
+    listTrash: vi.fn(),
+    restore: vi.fn(),
+    purge: vi.fn(),
+    emptyTrash: vi.fn(),
   },
   templateAdapter: {
     list: vi.fn(),
@@ -28,11 +36,35 @@ vi.mock('@platform', () => ({
     update: vi.fn(),
     delete: vi.fn(),
     setDefault: vi.fn(),
+    getPreview: vi.fn(),
+    listTrash: vi.fn(),
+    restore: vi.fn(),
+    purge: vi.fn(),
+  },
+  libraryAdapter: {
+    export: vi.fn(),
+    import: vi.fn(),
+  },
+  settingsAdapter: {
+    get: vi.fn(),
+    update: vi.fn(),
+    reset: vi.fn(),
+    listen: vi.fn(),
+  },
+  workspaceAdapter: {
+    list: vi.fn(),
+    create: vi.fn(),
+    switch: vi.fn(),
+    delete: vi.fn(),
+    setDataDir: vi.fn(),
+    pickDataDir: vi.fn(),
+    listen: vi.fn(() => Promise.resolve(() => {})),
   },
   scannerAdapter: {
     validateQr: vi.fn(),
     scanFromFile: vi.fn(),
     scanFromData: vi.fn(),
+    saveScan: vi.fn(),
   },
   batchAdapter: {
     parseCsvFile: vi.fn(),
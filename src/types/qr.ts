@@ -8,7 +8,12 @@ export type QrType =
   | 'sms'
   | 'phone'
   | 'geo'
-  | 'calendar';
+  | 'calendar'
+  | 'otp'
+  | 'crypto'
+  | 'payment'
+  | 'app'
+  | 'media';
 
 // Dot style options
 export type DotStyle = 'square' | 'rounded' | 'dots' | 'classy' | 'classy-rounded' | 'extra-rounded';